@@ -0,0 +1,257 @@
+//! Achievements, unlocked once and remembered in [`ACHIEVEMENTS_PATH`]: stacking ten boxes,
+//! surviving a long fall and breaking a joint.
+//!
+//! Each is watched for by a system of its own, from gameplay events or, for stacks, from the
+//! contacts of the boxes at rest, and sends an [`UnlockAchievement`]. The first time one is
+//! unlocked, a toast shows it and the file is saved right away, so a crash doesn't lose it.
+//!
+//! `achievements` in the console lists them.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::console::ConsoleAppExt;
+use crate::death::Dead;
+use crate::fall_damage::Landed;
+use crate::health::Health;
+use crate::joints::JointBroken;
+use crate::localization::Localization;
+use crate::toast::ToastEvent;
+use crate::Player;
+
+pub const ACHIEVEMENTS_PATH: &str = "saves/achievements.ron";
+/// Boxes a stack must have for [`Achievement::StackBoxes`].
+const STACK_HEIGHT: u32 = 10;
+/// Boxes moving slower than this, in meters per second, count as resting in a stack.
+const RESTING_SPEED: f32 = 0.2;
+/// A box rests on another touching it whose center is at least this much lower, in meters.
+const STACK_STEP: f32 = 0.3;
+/// Meters the player has to fall for [`Achievement::SurviveFall`], and seconds to stay alive after.
+const LONG_FALL: f32 = 50.0;
+const SURVIVAL_DELAY: f32 = 1.0;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Achievements::load())
+            .add_event::<UnlockAchievement>()
+            .add_console_command(
+                "achievements",
+                "achievements - list the achievements and which are unlocked",
+                achievements_command,
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        find_stacks.run_if(on_timer(Duration::from_millis(500))),
+                        survive_falls,
+                        break_joints,
+                    ),
+                    unlock_achievements,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Achievement {
+    StackBoxes,
+    SurviveFall,
+    BreakJoint,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 3] = [
+        Achievement::StackBoxes,
+        Achievement::SurviveFall,
+        Achievement::BreakJoint,
+    ];
+
+    /// Localization key of the achievement's title.
+    pub fn label(self) -> &'static str {
+        match self {
+            Achievement::StackBoxes => "achievement.stack_boxes",
+            Achievement::SurviveFall => "achievement.survive_fall",
+            Achievement::BreakJoint => "achievement.break_joint",
+        }
+    }
+}
+
+/// Sent when the conditions of an achievement are met; it's unlocked if it wasn't already.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UnlockAchievement(pub Achievement);
+
+/// The achievements unlocked so far, in any session.
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: BTreeSet<Achievement>,
+}
+
+impl Achievements {
+    /// Reads the achievements file, starting over if it is missing or invalid.
+    pub fn load() -> Self {
+        match fs::read_to_string(ACHIEVEMENTS_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("ignoring invalid {ACHIEVEMENTS_PATH}: {error}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = Path::new(ACHIEVEMENTS_PATH).parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                fs::write(ACHIEVEMENTS_PATH, contents).map_err(|error| error.to_string())
+            });
+        match result {
+            Ok(()) => info!("saved achievements to {ACHIEVEMENTS_PATH}"),
+            Err(error) => error!("could not save {ACHIEVEMENTS_PATH}: {error}"),
+        }
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+}
+
+/// Looks for a stack of boxes at rest, each lying on the one below. Boxes are counted from the
+/// lowest up, so each one's place in its stack is known when the boxes above it get to it.
+fn find_stacks(
+    achievements: Res<Achievements>,
+    rapier_context: Res<RapierContext>,
+    mut unlocks: EventWriter<UnlockAchievement>,
+    bodies: Query<(Entity, &RigidBody, &Collider, &Velocity, &GlobalTransform)>,
+) {
+    if achievements.is_unlocked(Achievement::StackBoxes) {
+        return;
+    }
+    let mut boxes: Vec<(Entity, Vec3)> = bodies
+        .iter()
+        .filter(|(_, body, collider, velocity, _)| {
+            **body == RigidBody::Dynamic
+                && collider.as_cuboid().is_some()
+                && velocity.linvel.length() < RESTING_SPEED
+        })
+        .map(|(entity, .., transform)| (entity, transform.translation()))
+        .collect();
+    boxes.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y));
+
+    let mut levels: HashMap<Entity, (u32, f32)> = HashMap::new();
+    for (entity, position) in boxes {
+        let below = rapier_context
+            .contacts_with(entity)
+            .filter(|pair| pair.has_any_active_contacts())
+            .filter_map(|pair| {
+                let other = if pair.collider1() == entity {
+                    pair.collider2()
+                } else {
+                    pair.collider1()
+                };
+                let &(level, height) = levels.get(&other)?;
+                (height < position.y - STACK_STEP).then_some(level)
+            })
+            .max()
+            .unwrap_or(0);
+        let level = below + 1;
+        if level >= STACK_HEIGHT {
+            unlocks.send(UnlockAchievement(Achievement::StackBoxes));
+            return;
+        }
+        levels.insert(entity, (level, position.y));
+    }
+}
+
+/// Unlocks [`Achievement::SurviveFall`] once the player is still alive a moment after landing from
+/// a long fall, when its damage has been dealt.
+fn survive_falls(
+    time: Res<Time>,
+    mut landings: EventReader<Landed>,
+    mut unlocks: EventWriter<UnlockAchievement>,
+    mut since_fall: Local<Option<f32>>,
+    player: Query<(Entity, &Health, Has<Dead>), With<Player>>,
+) {
+    let Ok((entity, health, dead)) = player.get_single() else {
+        return;
+    };
+    if landings
+        .read()
+        .any(|landed| landed.character == entity && landed.height >= LONG_FALL)
+    {
+        *since_fall = Some(0.0);
+    }
+    let Some(elapsed) = since_fall.as_mut() else {
+        return;
+    };
+    if dead || health.current <= 0.0 {
+        *since_fall = None;
+        return;
+    }
+    *elapsed += time.delta_seconds();
+    if *elapsed >= SURVIVAL_DELAY {
+        *since_fall = None;
+        unlocks.send(UnlockAchievement(Achievement::SurviveFall));
+    }
+}
+
+fn break_joints(mut broken: EventReader<JointBroken>, mut unlocks: EventWriter<UnlockAchievement>) {
+    if broken.read().count() > 0 {
+        unlocks.send(UnlockAchievement(Achievement::BreakJoint));
+    }
+}
+
+fn unlock_achievements(
+    localization: Res<Localization>,
+    mut achievements: ResMut<Achievements>,
+    mut unlocks: EventReader<UnlockAchievement>,
+    mut toasts: EventWriter<ToastEvent>,
+) {
+    let mut unlocked_any = false;
+    for &UnlockAchievement(achievement) in unlocks.read() {
+        if !achievements.unlocked.insert(achievement) {
+            continue;
+        }
+        unlocked_any = true;
+        info!("unlocked achievement {achievement:?}");
+        toasts.send(ToastEvent(localization.format(
+            "achievement.unlocked",
+            &[("name", &localization.get(achievement.label()))],
+        )));
+    }
+    if unlocked_any {
+        achievements.save();
+    }
+}
+
+fn achievements_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let achievements = world.resource::<Achievements>();
+    let localization = world.resource::<Localization>();
+    let lines: Vec<String> = Achievement::ALL
+        .into_iter()
+        .map(|achievement| {
+            let mark = if achievements.is_unlocked(achievement) {
+                "x"
+            } else {
+                " "
+            };
+            format!("[{mark}] {}", localization.get(achievement.label()))
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
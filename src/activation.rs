@@ -0,0 +1,64 @@
+//! Distance-based physics activation: dynamic bodies farther from the player than
+//! [`PhysicsSettings::activation_radius`](crate::settings::PhysicsSettings::activation_radius)
+//! have their body and collider disabled, and are enabled again when the player comes back, so
+//! only the part of a large level around the player is simulated.
+//!
+//! Fixed bodies are left alone: the level, its streamed chunks and the terrain have their origin
+//! far from most of their geometry, and the player would fall through them.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_set::GameSet;
+use crate::pool::Pooled;
+use crate::settings::Settings;
+use crate::Player;
+
+/// Bodies are woken this fraction of the radius away, so one sitting on the edge doesn't toggle
+/// every frame.
+const REACTIVATION_FACTOR: f32 = 0.9;
+
+pub struct PhysicsActivationPlugin;
+
+impl Plugin for PhysicsActivationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_physics_activation.after(GameSet::Movement));
+    }
+}
+
+/// A body taken out of the simulation for being far from the player.
+#[derive(Component)]
+pub struct Inactive;
+
+fn update_physics_activation(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    player: Query<&GlobalTransform, With<Player>>,
+    bodies: Query<
+        (Entity, &RigidBody, &GlobalTransform, Has<Inactive>),
+        (Without<Player>, Without<Pooled>),
+    >,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let center = player.translation();
+    let radius = settings.physics.activation_radius;
+    let wake_radius = radius * REACTIVATION_FACTOR;
+
+    for (entity, body, transform, inactive) in &bodies {
+        if *body != RigidBody::Dynamic {
+            continue;
+        }
+        let distance_squared = transform.translation().distance_squared(center);
+        if !inactive && distance_squared > radius * radius {
+            commands
+                .entity(entity)
+                .insert((Inactive, RigidBodyDisabled, ColliderDisabled));
+        } else if inactive && distance_squared < wake_radius * wake_radius {
+            commands
+                .entity(entity)
+                .remove::<(Inactive, RigidBodyDisabled, ColliderDisabled)>();
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! The AI clock: [`AiTick`] is a schedule run [`AI_TICK_RATE`] times per second of game time,
+//! independent of the frame rate, for decisions and steering that don't need to be made every
+//! frame.
+//!
+//! AI systems don't move entities directly. They set a [`MoveTarget`] each tick, and every frame
+//! the entity is moved towards the target interpolated between the last two ticks, so movement
+//! stays smooth at any frame rate while the AI cost only scales with the tick rate.
+
+use std::time::Duration;
+
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_set::GameSet;
+
+/// AI ticks per second.
+pub const AI_TICK_RATE: f64 = 15.0;
+/// Ticks run in one frame at most, so a long frame doesn't stall the next one catching up.
+const MAX_TICKS_PER_FRAME: u32 = 3;
+/// Downward pull on AI-driven character controllers, in meters per second.
+const FALL_SPEED: f32 = 5.0;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_schedule(AiTick)
+            .init_resource::<AiClock>()
+            .add_systems(
+                Update,
+                (
+                    run_ai_ticks.after(GameSet::Input).before(GameSet::Movement),
+                    follow_move_targets.in_set(GameSet::Movement),
+                ),
+            );
+    }
+}
+
+/// Runs at [`AI_TICK_RATE`]; see [`AiClock`] for the tick length.
+#[derive(ScheduleLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AiTick;
+
+/// Time accumulated towards the next [`AiTick`].
+#[derive(Resource)]
+pub struct AiClock {
+    step: Duration,
+    accumulated: Duration,
+}
+
+impl Default for AiClock {
+    fn default() -> Self {
+        Self {
+            step: Duration::from_secs_f64(1.0 / AI_TICK_RATE),
+            accumulated: Duration::ZERO,
+        }
+    }
+}
+
+impl AiClock {
+    /// Length of a tick in seconds, the time step of systems in [`AiTick`].
+    pub fn step(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /// How far the current frame is between the last tick and the next, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulated.as_secs_f32() / self.step()).min(1.0)
+    }
+}
+
+/// Where an AI wants an entity to be, set once per [`AiTick`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MoveTarget {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl MoveTarget {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            previous: position,
+            current: position,
+        }
+    }
+
+    /// The target of this tick; the entity reaches it by the next tick.
+    pub fn set(&mut self, target: Vec3) {
+        self.previous = self.current;
+        self.current = target;
+    }
+
+    pub fn current(&self) -> Vec3 {
+        self.current
+    }
+
+    /// Moves the targets by `offset`, for when something else moved the entity.
+    pub fn displace(&mut self, offset: Vec3) {
+        self.previous += offset;
+        self.current += offset;
+    }
+
+    /// The target `alpha` of the way from the previous tick's to this tick's.
+    pub fn at(&self, alpha: f32) -> Vec3 {
+        self.previous.lerp(self.current, alpha)
+    }
+}
+
+/// Runs the [`AiTick`]s due this frame. Game time stops while paused, and so do the ticks.
+fn run_ai_ticks(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    let mut ticks = 0;
+    {
+        let mut clock = world.resource_mut::<AiClock>();
+        let step = clock.step;
+        clock.accumulated += delta;
+        while clock.accumulated >= step && ticks < MAX_TICKS_PER_FRAME {
+            clock.accumulated -= step;
+            ticks += 1;
+        }
+        if ticks == MAX_TICKS_PER_FRAME {
+            clock.accumulated = clock.accumulated.min(step);
+        }
+    }
+    for _ in 0..ticks {
+        world.run_schedule(AiTick);
+    }
+}
+
+fn follow_move_targets(
+    time: Res<Time>,
+    clock: Res<AiClock>,
+    mut movers: Query<(&MoveTarget, &Transform, &mut KinematicCharacterController)>,
+) {
+    let alpha = clock.alpha();
+    for (target, transform, mut controller) in &mut movers {
+        let mut translation = target.at(alpha) - transform.translation;
+        translation.y = -FALL_SPEED * time.delta_seconds();
+        controller.translation = Some(translation);
+    }
+}
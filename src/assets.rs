@@ -0,0 +1,92 @@
+//! Asset collections loaded in [`MyStates::AssetLoading`](crate::MyStates::AssetLoading).
+//!
+//! Fields with a `key` are looked up in [`MANIFEST_PATH`], so which models, sounds and textures
+//! are loaded can be changed without recompiling.
+
+use std::path::PathBuf;
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+
+use crate::level_data::{LevelData, Prefabs};
+
+/// Dynamic asset file mapping the collections' keys to asset paths.
+pub const MANIFEST_PATH: &str = "manifest.assets.ron";
+
+/// The directory the asset server loads from, for writing files it should load later.
+pub fn asset_root() -> PathBuf {
+    FileAssetReader::get_base_path().join(AssetPlugin::default().file_path)
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct Models {
+    /// Scene spawned as the playable level.
+    #[asset(key = "levels.playground")]
+    pub level: Handle<Scene>,
+    /// Props placed in the playground, see [`LevelData`](crate::level_data::LevelData).
+    #[asset(key = "levels.playground_entities")]
+    pub level_entities: Handle<LevelData>,
+    #[asset(key = "prefabs")]
+    pub prefabs: Handle<Prefabs>,
+    /// Meshes and materials of spawnable props, looked up by name with
+    /// [`GltfAssets`](crate::gltf_lookup::GltfAssets).
+    #[asset(key = "models.props")]
+    pub props: Handle<Gltf>,
+    /// The rigged player model and its animations, see
+    /// [`CharacterModel`](crate::character_animation::CharacterModel).
+    #[asset(key = "models.character")]
+    pub character: Handle<Gltf>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct AudioAssets {
+    #[asset(key = "audio.music.menu")]
+    pub menu_music: Handle<AudioSource>,
+    #[asset(key = "audio.music.gameplay")]
+    pub gameplay_music: Handle<AudioSource>,
+    #[asset(key = "audio.music.game_over")]
+    pub game_over_music: Handle<AudioSource>,
+    #[asset(key = "audio.impacts.concrete")]
+    pub impact_concrete: Handle<AudioSource>,
+    #[asset(key = "audio.impacts.wood")]
+    pub impact_wood: Handle<AudioSource>,
+    #[asset(key = "audio.impacts.rubber")]
+    pub impact_rubber: Handle<AudioSource>,
+    #[asset(key = "audio.impacts.glass")]
+    pub impact_glass: Handle<AudioSource>,
+    #[asset(key = "audio.footsteps.concrete", collection(typed))]
+    pub footsteps_concrete: Vec<Handle<AudioSource>>,
+    #[asset(key = "audio.footsteps.wood", collection(typed))]
+    pub footsteps_wood: Vec<Handle<AudioSource>>,
+    #[asset(key = "audio.loops.rolling")]
+    pub rolling_loop: Handle<AudioSource>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct TextureAssets {
+    /// Neutral grid for prototype surfaces.
+    #[asset(key = "textures.checker")]
+    pub checker: Handle<Image>,
+    #[asset(key = "textures.floor.base_color")]
+    pub floor_base_color: Handle<Image>,
+    #[asset(key = "textures.floor.normal")]
+    pub floor_normal: Handle<Image>,
+    #[asset(key = "textures.floor.metallic_roughness")]
+    pub floor_metallic_roughness: Handle<Image>,
+    #[asset(key = "textures.crate.base_color")]
+    pub crate_base_color: Handle<Image>,
+    #[asset(key = "textures.crate.normal")]
+    pub crate_normal: Handle<Image>,
+    #[asset(key = "textures.crate.metallic_roughness")]
+    pub crate_metallic_roughness: Handle<Image>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct UiAssets {
+    #[asset(key = "ui.font")]
+    pub font: Handle<Font>,
+    #[asset(key = "ui.player_marker")]
+    pub player_marker: Handle<Image>,
+}
@@ -0,0 +1,72 @@
+//! Volume channels applied to all playback.
+//!
+//! The levels come from [`Settings`]; sounds take their volume from [`AudioMixer::volume`] when
+//! spawned, and long-running sinks such as music re-apply it every frame.
+
+use bevy::audio::{Volume, VolumeLevel};
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+
+pub struct AudioMixerPlugin;
+
+impl Plugin for AudioMixerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioMixer>()
+            .add_systems(Update, sync_mixer.run_if(resource_changed::<Settings>()));
+    }
+}
+
+/// What a sound is mixed as.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioChannel {
+    Music,
+    Sfx,
+}
+
+/// Volume levels, each between 0 and 1.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AudioMixer {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl AudioMixer {
+    fn from_settings(settings: &Settings) -> Self {
+        Self {
+            master: settings.audio.master_volume,
+            music: settings.audio.music_volume,
+            sfx: settings.audio.sfx_volume,
+        }
+    }
+
+    /// The sink volume of a sound at `level` on `channel`.
+    pub fn volume(&self, channel: AudioChannel, level: f32) -> f32 {
+        let channel = match channel {
+            AudioChannel::Music => self.music,
+            AudioChannel::Sfx => self.sfx,
+        };
+        level * channel * self.master
+    }
+
+    /// [`AudioMixer::volume`] for a sound's [`PlaybackSettings`].
+    pub fn playback_volume(&self, channel: AudioChannel, level: f32) -> Volume {
+        Volume::Absolute(VolumeLevel::new(self.volume(channel, level)))
+    }
+}
+
+impl FromWorld for AudioMixer {
+    fn from_world(world: &mut World) -> Self {
+        Self::from_settings(
+            &world
+                .get_resource::<Settings>()
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+}
+
+fn sync_mixer(settings: Res<Settings>, mut mixer: ResMut<AudioMixer>) {
+    *mixer = AudioMixer::from_settings(&settings);
+}
@@ -0,0 +1,70 @@
+//! Benchmark mode, enabled with `--bench [frames]`.
+//!
+//! Once loading finishes, the frame times of the given number of frames are recorded with vsync
+//! off; their average, minimum, maximum and 99th percentile are then printed and the game quits.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy::window::{PresentMode, PrimaryWindow};
+
+use crate::MyStates;
+
+pub struct BenchPlugin {
+    /// Frames to record before quitting.
+    pub frames: u32,
+}
+
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FrameTimes {
+            times: Vec::with_capacity(self.frames as usize),
+            frames: self.frames,
+        })
+        .add_systems(
+            Update,
+            (unlock_frame_rate, record_frame_time).run_if(not(in_state(MyStates::AssetLoading))),
+        );
+    }
+}
+
+/// Frame times recorded so far, in milliseconds.
+#[derive(Resource)]
+struct FrameTimes {
+    times: Vec<f32>,
+    frames: u32,
+}
+
+/// Turns vsync off, also when the settings turn it back on.
+fn unlock_frame_rate(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in &mut windows {
+        if window.present_mode != PresentMode::AutoNoVsync {
+            window.present_mode = PresentMode::AutoNoVsync;
+        }
+    }
+}
+
+fn record_frame_time(
+    time: Res<Time<Real>>,
+    mut frame_times: ResMut<FrameTimes>,
+    mut exit: EventWriter<AppExit>,
+) {
+    frame_times.times.push(time.delta_seconds() * 1000.0);
+    if frame_times.times.len() < frame_times.frames as usize {
+        return;
+    }
+
+    let mut times = std::mem::take(&mut frame_times.times);
+    times.sort_by(f32::total_cmp);
+    let average = times.iter().sum::<f32>() / times.len() as f32;
+    let percentile = times[(times.len() - 1) * 99 / 100];
+    // Printed rather than logged so scripts can read it regardless of the log level.
+    println!(
+        "bench: {} frames, average {average:.2} ms ({:.1} fps), min {:.2} ms, max {:.2} ms, 99th percentile {percentile:.2} ms",
+        times.len(),
+        1000.0 / average,
+        times[0],
+        times[times.len() - 1],
+    );
+    exit.send(AppExit);
+}
@@ -0,0 +1,288 @@
+//! Block maps: levels drawn as text, one character per [`CELL_SIZE`] cell, for prototyping parkour
+//! courses without a modelling tool. `--level` loads one when its path ends with `.blocks.txt`.
+//!
+//! Rows run along +z and columns along +x, starting at the origin:
+//!
+//! - `.` or a space: nothing
+//! - `_`: a floor tile
+//! - `1` to `9`: a block that many [`BLOCK_HEIGHT`]s tall
+//! - `#`: a wall, [`WALL_HEIGHT`] tall
+//! - `>`, `<`, `^`, `v`: a ramp rising one block towards +x, -x, -z or +z
+//!
+//! Lines starting with `;` are comments. Neighbouring cells of the same kind are merged into one
+//! box collider, so a large floor is a single collider rather than hundreds. The map loads as
+//! [`LevelData`] through [`BlockMapLoader`].
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+
+use crate::level_data::{BodyKind, ColliderShape, EntityData, LevelData, LevelDataError, MeshRef};
+
+/// Side of a cell, in meters.
+pub const CELL_SIZE: f32 = 2.0;
+/// Height of one block level, in meters.
+pub const BLOCK_HEIGHT: f32 = 1.0;
+pub const WALL_HEIGHT: f32 = 3.0;
+/// Thickness of floor tiles and ramps; floors have their top at y = 0.
+const SLAB_THICKNESS: f32 = 0.2;
+
+/// Suffix of level paths read as block maps.
+pub const BLOCK_MAP_SUFFIX: &str = ".blocks.txt";
+
+pub fn is_block_map(level: &str) -> bool {
+    level.ends_with(BLOCK_MAP_SUFFIX)
+}
+
+/// What a cell holds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Cell {
+    Empty,
+    Floor,
+    Block(u8),
+    Wall,
+    /// A ramp rising towards the given ground plane direction.
+    Ramp(IVec2),
+}
+
+impl Cell {
+    fn parse(character: char) -> Result<Self, String> {
+        Ok(match character {
+            '.' | ' ' => Cell::Empty,
+            '_' => Cell::Floor,
+            '1'..='9' => Cell::Block(character as u8 - b'0'),
+            '#' => Cell::Wall,
+            '>' => Cell::Ramp(IVec2::X),
+            '<' => Cell::Ramp(IVec2::NEG_X),
+            '^' => Cell::Ramp(IVec2::NEG_Y),
+            'v' => Cell::Ramp(IVec2::Y),
+            _ => return Err(format!("unknown block `{character}`")),
+        })
+    }
+
+    /// Top of the cell's box, if it is one.
+    fn height(self) -> Option<f32> {
+        match self {
+            Cell::Floor => Some(0.0),
+            Cell::Block(levels) => Some(levels as f32 * BLOCK_HEIGHT),
+            Cell::Wall => Some(WALL_HEIGHT),
+            Cell::Empty | Cell::Ramp(_) => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Cell::Empty => "Empty",
+            Cell::Floor => "Floor",
+            Cell::Block(_) => "Block",
+            Cell::Wall => "Wall",
+            Cell::Ramp(_) => "Ramp",
+        }
+    }
+}
+
+/// Turns the text of a block map into level entities.
+pub fn parse(text: &str) -> Result<LevelData, String> {
+    let mut grid: Vec<Vec<Cell>> = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        if line.starts_with(';') {
+            continue;
+        }
+        let row = line
+            .chars()
+            .map(Cell::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| format!("line {}: {error}", line_number + 1))?;
+        grid.push(row);
+    }
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut grid {
+        row.resize(width, Cell::Empty);
+    }
+
+    let mut entities = Vec::new();
+    let mut merged = vec![vec![false; width]; grid.len()];
+    for z in 0..grid.len() {
+        for x in 0..width {
+            let cell = grid[z][x];
+            if merged[z][x] {
+                continue;
+            }
+            if let Cell::Ramp(direction) = cell {
+                entities.push(ramp(x, z, direction));
+                continue;
+            }
+            let Some(height) = cell.height() else {
+                continue;
+            };
+
+            // Grow the box along x, then along z while whole rows of the same cells follow.
+            let mut end_x = x + 1;
+            while end_x < width && grid[z][end_x] == cell && !merged[z][end_x] {
+                end_x += 1;
+            }
+            let mut end_z = z + 1;
+            while end_z < grid.len()
+                && (x..end_x).all(|x| grid[end_z][x] == cell && !merged[end_z][x])
+            {
+                end_z += 1;
+            }
+            for row in &mut merged[z..end_z] {
+                row[x..end_x].fill(true);
+            }
+
+            let bottom = -SLAB_THICKNESS;
+            let size = Vec3::new(
+                (end_x - x) as f32 * CELL_SIZE,
+                height - bottom,
+                (end_z - z) as f32 * CELL_SIZE,
+            );
+            let corner = Vec3::new(x as f32 * CELL_SIZE, bottom, z as f32 * CELL_SIZE);
+            entities.push(EntityData {
+                name: cell.name().to_string(),
+                translation: corner + size / 2.0,
+                ..solid_box(size)
+            });
+        }
+    }
+    Ok(LevelData { entities })
+}
+
+/// A fixed box of `size`, centered on the entity.
+fn solid_box(size: Vec3) -> EntityData {
+    EntityData {
+        mesh: Some(MeshRef::Box(size)),
+        body: Some(BodyKind::Fixed),
+        collider: Some(ColliderShape::Cuboid(size / 2.0)),
+        ..default()
+    }
+}
+
+/// A slab sloping up one block across the cell, towards `direction`.
+fn ramp(x: usize, z: usize, direction: IVec2) -> EntityData {
+    let slope = BLOCK_HEIGHT.atan2(CELL_SIZE);
+    let yaw = Quat::from_rotation_y(-(direction.y as f32).atan2(direction.x as f32));
+    let rotation = yaw * Quat::from_rotation_z(slope);
+    // Center of the top surface, then down half the thickness so the top is the walkable slope.
+    let top_center = Vec3::new(
+        (x as f32 + 0.5) * CELL_SIZE,
+        BLOCK_HEIGHT / 2.0,
+        (z as f32 + 0.5) * CELL_SIZE,
+    );
+    let size = Vec3::new(CELL_SIZE.hypot(BLOCK_HEIGHT), SLAB_THICKNESS, CELL_SIZE);
+    EntityData {
+        name: Cell::Ramp(direction).name().to_string(),
+        translation: top_center - rotation * Vec3::Y * SLAB_THICKNESS / 2.0,
+        rotation,
+        ..solid_box(size)
+    }
+}
+
+/// Loads `.blocks.txt` files as [`LevelData`].
+#[derive(Default)]
+pub struct BlockMapLoader;
+
+impl AssetLoader for BlockMapLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelDataError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<LevelData, LevelDataError>> {
+        Box::pin(async move {
+            let mut text = String::new();
+            reader
+                .read_to_string(&mut text)
+                .await
+                .map_err(LevelDataError::Io)?;
+            parse(&text).map_err(LevelDataError::BlockMap)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["blocks.txt"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn half_extents(entity: &EntityData) -> Vec3 {
+        match entity.collider {
+            Some(ColliderShape::Cuboid(half_extents)) => half_extents,
+            other => panic!("expected a box collider, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn same_cells_merge_into_one_box() {
+        let level = parse("; a 3x2 floor\n___\n___\n").unwrap();
+        assert_eq!(level.entities.len(), 1);
+        let floor = &level.entities[0];
+        assert_eq!(floor.name, "Floor");
+        assert_eq!(
+            half_extents(floor) * 2.0,
+            Vec3::new(3.0 * CELL_SIZE, SLAB_THICKNESS, 2.0 * CELL_SIZE)
+        );
+        assert_eq!(
+            floor.translation,
+            Vec3::new(1.5 * CELL_SIZE, -SLAB_THICKNESS / 2.0, CELL_SIZE)
+        );
+    }
+
+    #[test]
+    fn different_cells_stay_apart() {
+        let level = parse("__2\n__#\n").unwrap();
+        let names: Vec<&str> = level.entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Floor", "Block", "Wall"]);
+        let block = &level.entities[1];
+        assert_eq!(
+            half_extents(block).y * 2.0,
+            2.0 * BLOCK_HEIGHT + SLAB_THICKNESS
+        );
+    }
+
+    #[test]
+    fn rows_of_different_width_are_padded() {
+        let level = parse("_\n__\n").unwrap();
+        assert_eq!(level.entities.len(), 2);
+    }
+
+    #[test]
+    fn ramps_rise_towards_their_arrow() {
+        for (arrow, direction) in [
+            ('>', Vec3::X),
+            ('<', Vec3::NEG_X),
+            ('^', Vec3::NEG_Z),
+            ('v', Vec3::Z),
+        ] {
+            let level = parse(&arrow.to_string()).unwrap();
+            let [ramp] = &level.entities[..] else {
+                panic!("one ramp expected for `{arrow}`");
+            };
+            assert_eq!(ramp.name, "Ramp");
+            let half = half_extents(ramp);
+            let center = Vec3::new(CELL_SIZE / 2.0, 0.0, CELL_SIZE / 2.0);
+            // The ends of the top surface, along the slope.
+            let top = ramp.translation + ramp.rotation * Vec3::new(0.0, half.y, 0.0);
+            let high = top + ramp.rotation * Vec3::new(half.x, 0.0, 0.0);
+            let low = top - ramp.rotation * Vec3::new(half.x, 0.0, 0.0);
+            let expected_high = center + direction * CELL_SIZE / 2.0 + Vec3::Y * BLOCK_HEIGHT;
+            let expected_low = center - direction * CELL_SIZE / 2.0;
+            assert!(high.abs_diff_eq(expected_high, 1e-4), "`{arrow}`: {high}");
+            assert!(low.abs_diff_eq(expected_low, 1e-4), "`{arrow}`: {low}");
+        }
+    }
+
+    #[test]
+    fn unknown_blocks_name_their_line() {
+        let error = parse("__\n_x\n").unwrap_err();
+        assert!(error.starts_with("line 2"), "{error}");
+    }
+}
@@ -0,0 +1,254 @@
+//! Water: a flat surface at the [`Water`] level, off by default, that dynamic bodies float in and
+//! characters swim in. `water <height|off>` in the console floods the playground or drains it.
+//!
+//! Every dynamic collider reaching below the surface is sampled on a grid through its volume. The
+//! submerged samples push up at their middle, so bodies lighter than water float and turn to float
+//! steadily, and slow the body down in proportion to the water it displaces. Characters swim
+//! instead of falling: they rise or sink until the share of them under water matches their density.
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy_rapier3d::parry::shape::Shape;
+use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::rapier::math::{Isometry, Point};
+
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::game_set::GameSet;
+
+/// Samples along each axis of a collider's bounding box.
+const SAMPLES_PER_AXIS: usize = 4;
+/// Upthrust, and the drag growing with it, are capped at this many times the body's weight, so
+/// very light bodies bob on the surface instead of being flung off it or stopped dead.
+const MAX_UPTHRUST: f32 = 3.0;
+/// How much slower than walking characters swim.
+const SWIM_SPEED: f32 = 0.6;
+/// How fast characters rise or sink towards floating, in meters per second per meter off.
+const FLOAT_RATE: f32 = 2.0;
+/// Share of a character's height its bobbing moves it by, and how fast it bobs, in radians per
+/// second.
+const BOB_AMPLITUDE: f32 = 0.04;
+const BOB_RATE: f32 = 2.0;
+/// Density of characters without their own, in kilograms per cubic meter.
+const CHARACTER_DENSITY: f32 = 500.0;
+/// Width of the water surface, in meters.
+const SURFACE_SIZE: f32 = 500.0;
+
+pub struct BuoyancyPlugin;
+
+impl Plugin for BuoyancyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Water>()
+            .add_console_command(
+                "water",
+                "water <height|off> - flood the playground up to a height, or drain it",
+                water_command,
+            )
+            .add_systems(
+                Update,
+                (
+                    swim.in_set(GameSet::MovementScaling),
+                    update_water_surface.run_if(resource_changed::<Water>()),
+                ),
+            )
+            // Between the sync and the step, so the bodies' velocity components, synced first,
+            // don't overwrite the change.
+            .add_systems(
+                PostUpdate,
+                apply_buoyancy
+                    .after(PhysicsSet::SyncBackend)
+                    .before(PhysicsSet::StepSimulation),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Water {
+    /// Height of the surface, or `None` when there is no water.
+    pub level: Option<f32>,
+    /// In kilograms per cubic meter.
+    pub density: f32,
+    /// How quickly floating bodies slow down, as a share of their velocity per second. Bodies
+    /// displacing more water for their mass slow down faster.
+    pub drag: f32,
+    pub angular_drag: f32,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self {
+            level: None,
+            density: 1000.0,
+            drag: 1.5,
+            angular_drag: 1.0,
+        }
+    }
+}
+
+/// Marks the mesh of the water surface.
+#[derive(Component)]
+struct WaterSurface;
+
+/// The volume of `shape` at `position` under `level`, in cubic meters, and its middle in world
+/// space. Samples near the surface count partly, so the upthrust changes
+/// smoothly as a body sinks.
+fn submerged_volume(shape: &dyn Shape, position: &Isometry<f32>, level: f32) -> (f32, Vec3) {
+    let aabb = shape.compute_local_aabb();
+    let mins: Vec3 = aabb.mins.coords.into();
+    let cell = Vec3::from(aabb.extents()) / SAMPLES_PER_AXIS as f32;
+    let cell_volume = cell.x * cell.y * cell.z;
+    // How tall a cell stands in the world, so a cell counts fully once it's all under.
+    let up: Vec3 = position
+        .rotation
+        .inverse_transform_vector(&Vec3::Y.into())
+        .into();
+    let cell_height = (up.abs() * cell).dot(Vec3::ONE).max(f32::EPSILON);
+
+    let (mut submerged, mut center) = (0.0, Vec3::ZERO);
+    for index in 0..SAMPLES_PER_AXIS.pow(3) {
+        let step = UVec3::new(
+            (index % SAMPLES_PER_AXIS) as u32,
+            (index / SAMPLES_PER_AXIS % SAMPLES_PER_AXIS) as u32,
+            (index / SAMPLES_PER_AXIS.pow(2)) as u32,
+        );
+        let sample = mins + (step.as_vec3() + 0.5) * cell;
+        if !shape.contains_local_point(&Point::from(sample)) {
+            continue;
+        }
+        let world: Vec3 = (position * Point::from(sample)).coords.into();
+        let under = ((level - world.y) / cell_height + 0.5).clamp(0.0, 1.0) * cell_volume;
+        submerged += under;
+        center += world * under;
+    }
+    let center = if submerged > 0.0 {
+        center / submerged
+    } else {
+        center
+    };
+    (submerged, center)
+}
+
+fn apply_buoyancy(
+    water: Res<Water>,
+    rapier_config: Res<RapierConfiguration>,
+    mut rapier_context: ResMut<RapierContext>,
+) {
+    let Some(level) = water.level else {
+        return;
+    };
+    // Bodies asleep at the bottom, or floating still, are left alone unless the water changed.
+    let wake = water.is_changed();
+    let gravity = -rapier_config.gravity.y;
+    let context = &mut *rapier_context;
+    let dt = context.integration_parameters.dt;
+    for (_, collider) in context.colliders.iter() {
+        if collider.is_sensor() || collider.compute_aabb().mins.y >= level {
+            continue;
+        }
+        let Some(body) = collider
+            .parent()
+            .and_then(|handle| context.bodies.get_mut(handle))
+        else {
+            continue;
+        };
+        if !body.is_dynamic() || (body.is_sleeping() && !wake) || body.mass() <= 0.0 {
+            continue;
+        }
+        let (submerged, center) = submerged_volume(collider.shape(), collider.position(), level);
+        if submerged <= 0.0 {
+            continue;
+        }
+
+        // Upthrust and drag grow with the water displaced for the body's mass, so light bodies
+        // settle quickly like a cork. Slowing the velocities themselves rather than pushing back
+        // keeps them steady.
+        let displaced = (water.density * submerged / body.mass()).min(MAX_UPTHRUST);
+        let upthrust = Vec3::Y * displaced * body.mass() * gravity;
+        body.apply_impulse_at_point((upthrust * dt).into(), center.into(), wake);
+        let linvel = *body.linvel() * (-water.drag * displaced * dt).exp();
+        let angvel = *body.angvel() * (-water.angular_drag * displaced * dt).exp();
+        body.set_linvel(linvel, wake);
+        body.set_angvel(angvel, wake);
+    }
+}
+
+/// Replaces the pull downwards of characters in the water with swimming: slower, and rising or
+/// sinking until they float, bobbing on the surface.
+fn swim(
+    time: Res<Time>,
+    water: Res<Water>,
+    mut characters: Query<(
+        &mut KinematicCharacterController,
+        &GlobalTransform,
+        &Collider,
+        Option<&ColliderMassProperties>,
+    )>,
+) {
+    let Some(level) = water.level else {
+        return;
+    };
+    let dt = time.delta_seconds();
+    for (mut controller, transform, collider, mass_properties) in &mut characters {
+        let half_height = collider.raw.compute_local_aabb().half_extents().y;
+        let bottom = transform.translation().y - half_height;
+        let depth = ((level - bottom) / (half_height * 2.0)).clamp(0.0, 1.0);
+        if depth <= 0.0 {
+            continue;
+        }
+        let density = match mass_properties {
+            Some(ColliderMassProperties::Density(density)) => *density,
+            _ => CHARACTER_DENSITY,
+        };
+        let bob = BOB_AMPLITUDE * (time.elapsed_seconds() * BOB_RATE).sin();
+        let floating = (density / water.density).min(1.0) + bob;
+        let rise = FLOAT_RATE * (depth - floating) * half_height * 2.0;
+        let walk = controller.translation.unwrap_or_default() * Vec3::new(1.0, 0.0, 1.0);
+        controller.translation = Some(walk * SWIM_SPEED + Vec3::Y * rise * dt);
+    }
+}
+
+fn update_water_surface(
+    mut commands: Commands,
+    water: Res<Water>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut surfaces: Query<(Entity, &mut Transform), With<WaterSurface>>,
+) {
+    match (water.level, surfaces.get_single_mut()) {
+        (Some(level), Ok((_, mut transform))) => transform.translation.y = level,
+        (Some(level), Err(_)) => {
+            commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(shape::Plane::from_size(SURFACE_SIZE).into()),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::rgba(0.1, 0.35, 0.6, 0.6),
+                        alpha_mode: AlphaMode::Blend,
+                        perceptual_roughness: 0.05,
+                        reflectance: 0.7,
+                        double_sided: true,
+                        cull_mode: None,
+                        ..default()
+                    }),
+                    transform: Transform::from_xyz(0.0, level, 0.0),
+                    ..default()
+                },
+                NotShadowCaster,
+                WaterSurface,
+                Name::new("Water"),
+            ));
+        }
+        (None, Ok((surface, _))) => commands.entity(surface).despawn_recursive(),
+        (None, Err(_)) => {}
+    }
+}
+
+fn water_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let level = match args.first() {
+        Some(&"off") => None,
+        _ => Some(parse_arg::<f32>(args, 0, "height")?),
+    };
+    world.resource_mut::<Water>().level = level;
+    Ok(match level {
+        Some(level) => format!("water level set to {level}"),
+        None => "water drained".to_string(),
+    })
+}
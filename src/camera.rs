@@ -0,0 +1,139 @@
+//! Camera modes: the fixed overview camera looking at the playground, and a first-person camera
+//! following the player's head with mouse look. V switches between them.
+
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+
+use crate::conditions::text_input_closed;
+use crate::game_set::GameSet;
+use crate::player::CharacterDimensions;
+use crate::settings::Settings;
+use crate::{MyStates, Player};
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraMode>()
+            .init_resource::<FirstPersonLook>()
+            .add_systems(
+                OnEnter(MyStates::Next),
+                spawn_main_camera.run_if(run_once()),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_camera_mode
+                        .run_if(text_input_closed().and_then(not(in_state(MyStates::Editor)))),
+                    first_person_look.run_if(resource_equals(CameraMode::FirstPerson)),
+                )
+                    .chain()
+                    .in_set(GameSet::Input),
+            )
+            .add_systems(
+                PostUpdate,
+                follow_player_head
+                    .run_if(resource_equals(CameraMode::FirstPerson))
+                    .in_set(GameSet::Camera),
+            );
+    }
+}
+
+/// The camera rendering the 3D scene.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// How [`MainCamera`] is placed.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    /// Fixed camera looking at the middle of the playground.
+    #[default]
+    Overview,
+    /// Camera at the player's eyes, rotated with the mouse.
+    FirstPerson,
+}
+
+/// Orientation of the first-person camera.
+#[derive(Resource, Default)]
+struct FirstPersonLook {
+    yaw: f32,
+    pitch: f32,
+}
+
+/// Transform of the overview camera.
+pub fn overview_transform() -> Transform {
+    Transform::from_xyz(0.0, 3.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y)
+}
+
+fn spawn_main_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera3dBundle {
+            transform: overview_transform(),
+            ..default()
+        },
+        MainCamera,
+    ));
+}
+
+/// Run condition for aim-related systems: always in first person, or while the right mouse button
+/// is held in the overview.
+pub fn aiming(mode: Res<CameraMode>, mouse: Res<Input<MouseButton>>) -> bool {
+    *mode == CameraMode::FirstPerson || mouse.pressed(MouseButton::Right)
+}
+
+fn toggle_camera_mode(
+    input: Res<Input<KeyCode>>,
+    mut mode: ResMut<CameraMode>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    if !input.just_pressed(KeyCode::V) {
+        return;
+    }
+    *mode = match *mode {
+        CameraMode::Overview => CameraMode::FirstPerson,
+        CameraMode::FirstPerson => CameraMode::Overview,
+    };
+
+    let first_person = *mode == CameraMode::FirstPerson;
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.cursor.visible = !first_person;
+        window.cursor.grab_mode = if first_person {
+            CursorGrabMode::Locked
+        } else {
+            CursorGrabMode::None
+        };
+    }
+    if !first_person {
+        for mut transform in &mut cameras {
+            *transform = overview_transform();
+        }
+    }
+}
+
+fn first_person_look(
+    settings: Res<Settings>,
+    mut motion: EventReader<MouseMotion>,
+    mut look: ResMut<FirstPersonLook>,
+) {
+    let sensitivity = settings.input.mouse_sensitivity;
+    for event in motion.read() {
+        look.yaw -= event.delta.x * sensitivity;
+        look.pitch = (look.pitch - event.delta.y * sensitivity).clamp(-1.5, 1.5);
+    }
+}
+
+fn follow_player_head(
+    look: Res<FirstPersonLook>,
+    player: Query<(&Transform, &CharacterDimensions), (With<Player>, Without<MainCamera>)>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Ok((player, dimensions)) = player.get_single() else {
+        return;
+    };
+    for mut transform in &mut cameras {
+        transform.translation = player.translation + Vec3::Y * dimensions.eye_height();
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, look.yaw, look.pitch, 0.0);
+    }
+}
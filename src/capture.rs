@@ -0,0 +1,83 @@
+//! Deterministic frame capture, enabled with `--capture` on the command line.
+//!
+//! Time and physics advance by exactly [`FRAME_TIME`] per frame regardless of how long rendering
+//! takes, and every frame after loading is saved as a numbered PNG in [`CAPTURE_DIR`]. Pass
+//! `--capture-frames <n>` to quit after `n` frames. The frames can be turned into a video with,
+//! for example, `ffmpeg -framerate 60 -i captures/frame_%05d.png demo.mp4`.
+
+use std::fs;
+use std::time::Duration;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::PrimaryWindow;
+use bevy_rapier3d::prelude::*;
+
+use crate::MyStates;
+
+pub const CAPTURE_DIR: &str = "captures";
+/// Simulated seconds per captured frame.
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+pub struct CapturePlugin {
+    /// Frames to capture before quitting, or `None` to capture until the window is closed.
+    pub frame_limit: Option<u32>,
+}
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            FRAME_TIME,
+        )))
+        .insert_resource(CaptureProgress {
+            frame: 0,
+            limit: self.frame_limit,
+        })
+        .add_systems(Startup, (fix_physics_timestep, create_capture_dir))
+        .add_systems(
+            Last,
+            capture_frame.run_if(not(in_state(MyStates::AssetLoading))),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct CaptureProgress {
+    frame: u32,
+    limit: Option<u32>,
+}
+
+fn fix_physics_timestep(mut rapier: ResMut<RapierConfiguration>) {
+    rapier.timestep_mode = TimestepMode::Fixed {
+        dt: FRAME_TIME,
+        substeps: 1,
+    };
+}
+
+fn create_capture_dir() {
+    if let Err(error) = fs::create_dir_all(CAPTURE_DIR) {
+        error!("could not create {CAPTURE_DIR}: {error}");
+    }
+}
+
+fn capture_frame(
+    mut progress: ResMut<CaptureProgress>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut exit: EventWriter<AppExit>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+) {
+    if progress.limit.is_some_and(|limit| progress.frame >= limit) {
+        info!("captured {} frames to {CAPTURE_DIR}", progress.frame);
+        exit.send(AppExit);
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let path = format!("{CAPTURE_DIR}/frame_{:05}.png", progress.frame);
+    if screenshots.save_screenshot_to_disk(window, path).is_ok() {
+        progress.frame += 1;
+    }
+}
@@ -0,0 +1,674 @@
+//! The animated model of characters, in place of a bare capsule: the rigged character from
+//! [`Models::character`], playing an idle, walk, run, jump or fall clip chosen from how its
+//! character controller moved during the last physics step. Actions such as attacking play their
+//! clip once over that, see [`CharacterModel::play_action`].
+//!
+//! On the ground, the idle, walk and run clips blend by horizontal speed between the thresholds of
+//! the [`PlayerSettings`], walking and running in step. The weights follow the speed over the
+//! settings' blend time, which is also how long the other clips take to fade in.
+//!
+//! The model is a child of the character, turned towards where it walks and lowered to the
+//! bottom of the capsule. It squashes when the character crouches, and is hidden in first person
+//! so it doesn't get in front of the camera.
+//!
+//! The walk and run clips carry the hips forward. Every state's clip plays in place, but states
+//! set to use [`RootMotion`] add that travel to the character's movement, after its input moved it
+//! and before knockback and the like are added. `root_motion <state|all> <on|off>` in the console
+//! switches a state.
+
+use std::time::Duration;
+
+use bevy::animation::animation_player;
+use bevy::animation::VariableCurve;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy::utils::HashSet;
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::Models;
+use crate::camera::CameraMode;
+use crate::console::ConsoleAppExt;
+use crate::game_set::GameSet;
+use crate::player::CharacterDimensions;
+use crate::settings::{PlayerSettings, Settings};
+
+/// Below this horizontal speed, in meters per second, the model keeps facing the same way.
+const FACING_SPEED: f32 = 0.3;
+/// How long a character must be off the ground before it jumps or falls, in seconds, so stepping
+/// down a ledge doesn't flicker between clips.
+const AIRBORNE_DELAY: f32 = 0.1;
+/// How quickly the model turns towards where it walks, as a share of the angle left per second.
+const TURN_RATE: f32 = 10.0;
+/// Names of the scene's root node and of the bone below it carrying the root motion.
+const ROOT_BONE_PATH: [&str; 2] = ["Character", "Hips"];
+
+pub struct CharacterAnimationPlugin;
+
+impl Plugin for CharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RootMotion>()
+            .add_console_command(
+                "root_motion",
+                "root_motion [state|all] [on|off] - move characters by their clip, or list the states that do",
+                root_motion_command,
+            )
+            .add_systems(
+                Update,
+                (
+                    link_animation_players,
+                    animate_characters,
+                    apply_root_motion.in_set(GameSet::RootMotion),
+                )
+                    .chain()
+                    .after(GameSet::Movement)
+                    .run_if(resource_exists::<Models>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    blend_locomotion.run_if(resource_exists::<Models>()),
+                    keep_root_bones_in_place,
+                )
+                    .chain()
+                    .after(animation_player)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// What a character's model is doing.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+    Run,
+    Jump,
+    Fall,
+    Attack,
+}
+
+impl AnimationState {
+    pub const ALL: [AnimationState; 6] = [
+        AnimationState::Idle,
+        AnimationState::Walk,
+        AnimationState::Run,
+        AnimationState::Jump,
+        AnimationState::Fall,
+        AnimationState::Attack,
+    ];
+
+    /// Name of the clip in the character's glTF.
+    pub fn clip_name(self) -> &'static str {
+        match self {
+            AnimationState::Idle => "Idle",
+            AnimationState::Walk => "Walk",
+            AnimationState::Run => "Run",
+            AnimationState::Jump => "Jump",
+            AnimationState::Fall => "Fall",
+            AnimationState::Attack => "Attack",
+        }
+    }
+
+    /// Jumping and attacking play once and hold their last pose; the other clips loop.
+    fn repeats(self) -> bool {
+        !matches!(self, AnimationState::Jump | AnimationState::Attack)
+    }
+
+    /// The state named `name`, in any case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|state| state.clip_name().eq_ignore_ascii_case(name))
+    }
+
+    /// The state of a character moving at `velocity`, after `airborne` seconds off the ground. On
+    /// the ground, it's the locomotion clip weighing the most.
+    pub fn from_movement(velocity: Vec3, airborne: f32, settings: &PlayerSettings) -> Self {
+        let speed = Vec2::new(velocity.x, velocity.z).length();
+        if airborne > AIRBORNE_DELAY {
+            if velocity.y > 0.0 {
+                AnimationState::Jump
+            } else {
+                AnimationState::Fall
+            }
+        } else {
+            let weights = locomotion_weights(speed, settings);
+            let heaviest = (0..LOCOMOTION.len())
+                .max_by(|&a, &b| weights[a].total_cmp(&weights[b]))
+                .unwrap_or(0);
+            LOCOMOTION[heaviest]
+        }
+    }
+}
+
+/// The states whose clips blend by speed, in the order of [`locomotion_weights`].
+const LOCOMOTION: [AnimationState; 3] = [
+    AnimationState::Idle,
+    AnimationState::Walk,
+    AnimationState::Run,
+];
+
+/// How much of the idle, walk and run clips a character moving at `speed` plays, adding up to 1.
+pub fn locomotion_weights(speed: f32, settings: &PlayerSettings) -> [f32; 3] {
+    if speed < settings.walk_speed {
+        let walk = speed / settings.walk_speed.max(f32::EPSILON);
+        [1.0 - walk, walk, 0.0]
+    } else {
+        let run = ((speed - settings.walk_speed)
+            / (settings.run_speed - settings.walk_speed).max(f32::EPSILON))
+        .min(1.0);
+        [0.0, 1.0 - run, run]
+    }
+}
+
+/// The states whose clips move characters, on top of their input. None do by default.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct RootMotion {
+    states: HashSet<AnimationState>,
+}
+
+impl RootMotion {
+    pub fn enabled(&self, state: AnimationState) -> bool {
+        self.states.contains(&state)
+    }
+
+    pub fn set(&mut self, state: AnimationState, enabled: bool) {
+        if enabled {
+            self.states.insert(state);
+        } else {
+            self.states.remove(&state);
+        }
+    }
+}
+
+/// The model of the character it's a child of.
+#[derive(Component)]
+pub struct CharacterModel {
+    pub state: AnimationState,
+    /// The entity of the model's scene with its [`AnimationPlayer`], once the scene is spawned.
+    animator: Option<Entity>,
+    /// The state whose clip the animator plays.
+    playing: Option<AnimationState>,
+    /// The action played over the movement's state, and how far into its clip the animator is.
+    action: Option<AnimationState>,
+    action_time: Option<f32>,
+    /// The bone whose travel is the root motion, and its place in the clip last frame.
+    root_bone: Option<Entity>,
+    root_seek_time: f32,
+    /// Seconds since the character was last on the ground.
+    airborne: f32,
+    /// Horizontal speed the character means to move at, in meters per second.
+    speed: f32,
+    /// The animated bones, with their paths from the animator.
+    bones: Vec<(Entity, EntityPath)>,
+    blend: LocomotionBlend,
+}
+
+/// The blend of the locomotion clips on a model.
+#[derive(Default)]
+struct LocomotionBlend {
+    /// Weights of the clips of [`LOCOMOTION`], following the character's speed.
+    weights: [f32; 3],
+    /// How much the blend replaces the animator's clip, from 0 in the air to 1 on the ground.
+    amount: f32,
+    /// How far through their cycle the walk and run clips are, from 0 to 1.
+    phase: f32,
+    idle_time: f32,
+}
+
+/// A bone's pose, blended from several clips.
+#[derive(Default)]
+struct BlendedPose {
+    translation: Vec3,
+    translation_weight: f32,
+    rotation: Vec4,
+    rotation_weight: f32,
+    scale: Vec3,
+    scale_weight: f32,
+}
+
+impl BlendedPose {
+    /// Adds `curve` at `time`, weighing `weight`.
+    fn add(&mut self, curve: &VariableCurve, time: f32, weight: f32) {
+        let (a, b, t) = keyframe_span(&curve.keyframe_timestamps, time);
+        match &curve.keyframes {
+            Keyframes::Translation(keyframes) => {
+                self.translation += keyframes[a].lerp(keyframes[b], t) * weight;
+                self.translation_weight += weight;
+            }
+            Keyframes::Rotation(keyframes) => {
+                let rotation = Vec4::from(keyframes[a].slerp(keyframes[b], t));
+                // The same rotation either way round, taken the way the others go.
+                let rotation = if rotation.dot(self.rotation) < 0.0 {
+                    -rotation
+                } else {
+                    rotation
+                };
+                self.rotation += rotation * weight;
+                self.rotation_weight += weight;
+            }
+            Keyframes::Scale(keyframes) => {
+                self.scale += keyframes[a].lerp(keyframes[b], t) * weight;
+                self.scale_weight += weight;
+            }
+            Keyframes::Weights(_) => {}
+        }
+    }
+
+    /// Moves `transform` `amount` of the way to the pose.
+    fn apply(&self, transform: &mut Transform, amount: f32) {
+        if self.translation_weight > 0.0 {
+            let translation = self.translation / self.translation_weight;
+            transform.translation = transform.translation.lerp(translation, amount);
+        }
+        if self.rotation_weight > 0.0 {
+            let rotation = Quat::from_vec4(self.rotation).normalize();
+            transform.rotation = transform.rotation.slerp(rotation, amount);
+        }
+        if self.scale_weight > 0.0 {
+            let scale = self.scale / self.scale_weight;
+            transform.scale = transform.scale.lerp(scale, amount);
+        }
+    }
+}
+
+impl CharacterModel {
+    /// The model, to spawn as a child of a character.
+    pub fn bundle(scene: Handle<Scene>) -> impl Bundle {
+        (
+            SceneBundle { scene, ..default() },
+            CharacterModel {
+                state: AnimationState::Idle,
+                animator: None,
+                playing: None,
+                action: None,
+                action_time: None,
+                root_bone: None,
+                root_seek_time: 0.0,
+                airborne: 0.0,
+                speed: 0.0,
+                bones: Vec::new(),
+                blend: LocomotionBlend::default(),
+            },
+            Name::new("Character model"),
+        )
+    }
+
+    /// Plays `action`'s clip once, in place of the state the character's movement calls for.
+    pub fn play_action(&mut self, action: AnimationState) {
+        self.action = Some(action);
+        self.action_time = None;
+        self.playing = None;
+    }
+
+    /// How far into the clip of the action being played the model is, in seconds, once it has
+    /// started playing.
+    pub fn action_time(&self) -> Option<f32> {
+        self.action_time
+    }
+
+    /// Whether an action is being played.
+    pub fn in_action(&self) -> bool {
+        self.action.is_some()
+    }
+}
+
+/// The character's scene from `models`, once loaded.
+pub fn character_scene(models: &Models, gltfs: &Assets<Gltf>) -> Option<Handle<Scene>> {
+    let gltf = gltfs.get(&models.character)?;
+    gltf.default_scene
+        .clone()
+        .or_else(|| gltf.scenes.first().cloned())
+}
+
+/// The keyframes in `timestamps` either side of `time`, and how far from the first to the second
+/// it is.
+fn keyframe_span(timestamps: &[f32], time: f32) -> (usize, usize, f32) {
+    let last = timestamps.len().saturating_sub(1);
+    match timestamps.partition_point(|&keyframe| keyframe <= time) {
+        0 => (0, 0, 0.0),
+        next if next > last => (last, last, 0.0),
+        next => (
+            next - 1,
+            next,
+            (time - timestamps[next - 1]) / (timestamps[next] - timestamps[next - 1]),
+        ),
+    }
+}
+
+/// Where `clip` puts the root bone at `time`, or `None` without a track moving it.
+fn root_translation(clip: &AnimationClip, time: f32) -> Option<Vec3> {
+    let path = EntityPath {
+        parts: ROOT_BONE_PATH.map(Name::new).to_vec(),
+    };
+    let curve = clip
+        .get_curves_by_path(&path)?
+        .iter()
+        .find(|curve| matches!(curve.keyframes, Keyframes::Translation(_)))?;
+    let Keyframes::Translation(keyframes) = &curve.keyframes else {
+        return None;
+    };
+    let (a, b, t) = keyframe_span(&curve.keyframe_timestamps, time);
+    Some(keyframes[a].lerp(keyframes[b], t))
+}
+
+/// Gives each model the animation player its scene spawned with, which sits on the scene's root
+/// node below the model, and the bones below it.
+fn link_animation_players(
+    players: Query<Entity, Added<AnimationPlayer>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    mut models: Query<&mut CharacterModel>,
+) {
+    for player in &players {
+        let Some(model) = parents
+            .iter_ancestors(player)
+            .find(|&ancestor| models.contains(ancestor))
+        else {
+            continue;
+        };
+        if let Ok(mut model) = models.get_mut(model) {
+            model.animator = Some(player);
+            model.playing = None;
+            model.root_bone = children.iter_descendants(player).find(|&bone| {
+                names
+                    .get(bone)
+                    .is_ok_and(|name| name.as_str() == ROOT_BONE_PATH[1])
+            });
+            // Paths as the clips name them: from the animator down, every part named.
+            model.bones = children
+                .iter_descendants(player)
+                .filter_map(|bone| {
+                    let mut parts = vec![names.get(bone).ok()?.clone()];
+                    for ancestor in parents.iter_ancestors(bone) {
+                        parts.push(names.get(ancestor).ok()?.clone());
+                        if ancestor == player {
+                            parts.reverse();
+                            return Some((bone, EntityPath { parts }));
+                        }
+                    }
+                    None
+                })
+                .collect();
+        }
+    }
+}
+
+fn animate_characters(
+    time: Res<Time>,
+    models: Res<Models>,
+    gltfs: Res<Assets<Gltf>>,
+    settings: Res<Settings>,
+    camera_mode: Res<CameraMode>,
+    characters: Query<(
+        &CharacterDimensions,
+        Option<&KinematicCharacterController>,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+    mut model_query: Query<(
+        &mut CharacterModel,
+        &mut Transform,
+        &mut Visibility,
+        &Parent,
+    )>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+) {
+    let dt = time.delta_seconds();
+    let clips = gltfs
+        .get(&models.character)
+        .map(|gltf| &gltf.named_animations);
+    for (mut model, mut transform, mut visibility, parent) in &mut model_query {
+        let Ok((dimensions, controller, output)) = characters.get(parent.get()) else {
+            continue;
+        };
+
+        // Standing on the bottom of the capsule, or the controller's offset below it.
+        let offset = match controller.map(|controller| controller.offset) {
+            Some(CharacterLength::Absolute(offset)) => offset,
+            _ => 0.0,
+        };
+        transform.translation.y = -dimensions.current_height() / 2.0 - offset;
+        transform.scale.y = dimensions.current_height() / dimensions.height;
+        *visibility = if *camera_mode == CameraMode::FirstPerson {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        if let Some(output) = output.filter(|_| dt > 0.0) {
+            // Walking and facing follow where the character means to go, since with root motion
+            // it only goes there once the clip plays. Rising or falling is what it really did.
+            let intended = controller
+                .and_then(|controller| controller.translation)
+                .unwrap_or(output.effective_translation)
+                / dt;
+            let velocity = Vec3::new(intended.x, output.effective_translation.y / dt, intended.z);
+            model.airborne = if output.grounded {
+                0.0
+            } else {
+                model.airborne + dt
+            };
+            let heading = Vec2::new(velocity.x, velocity.z);
+            model.speed = heading.length();
+            model.state = model.action.unwrap_or_else(|| {
+                AnimationState::from_movement(velocity, model.airborne, &settings.player)
+            });
+            if model.speed >= FACING_SPEED {
+                let facing = Quat::from_rotation_y(heading.x.atan2(heading.y));
+                transform.rotation = transform.rotation.slerp(facing, (TURN_RATE * dt).min(1.0));
+            }
+        }
+
+        let Some(mut player) = model
+            .animator
+            .and_then(|animator| animation_players.get_mut(animator).ok())
+        else {
+            continue;
+        };
+        if model.action.is_some() && model.playing == model.action {
+            if player.is_finished() {
+                model.action = None;
+                model.action_time = None;
+            } else {
+                model.action_time = Some(player.seek_time());
+            }
+        }
+        let state = model.action.unwrap_or(model.state);
+        model.state = state;
+        if model.playing == Some(state) {
+            continue;
+        }
+        let Some(clip) = clips.and_then(|clips| clips.get(state.clip_name())) else {
+            continue;
+        };
+        player.play_with_transition(
+            clip.clone(),
+            Duration::from_secs_f32(settings.player.blend_time),
+        );
+        if state.repeats() {
+            player.repeat();
+        } else {
+            // From the start, should it be the clip that was playing.
+            player.replay();
+        }
+        model.playing = Some(state);
+        model.root_seek_time = 0.0;
+    }
+}
+
+/// Adds how far the root bone traveled in their clip since last frame, turned the way the model
+/// faces, to the movement of characters whose state uses [`RootMotion`].
+fn apply_root_motion(
+    root_motion: Res<RootMotion>,
+    clips: Res<Assets<AnimationClip>>,
+    mut characters: Query<(&mut KinematicCharacterController, &Transform)>,
+    mut models: Query<(&mut CharacterModel, &Transform, &Parent)>,
+    animation_players: Query<&AnimationPlayer>,
+) {
+    for (mut model, model_transform, parent) in &mut models {
+        let Some(player) = model
+            .animator
+            .and_then(|animator| animation_players.get(animator).ok())
+        else {
+            continue;
+        };
+        let (previous, now) = (model.root_seek_time, player.seek_time());
+        model.root_seek_time = now;
+        if !model
+            .playing
+            .is_some_and(|state| root_motion.enabled(state))
+        {
+            continue;
+        }
+        let Ok((mut controller, transform)) = characters.get_mut(parent.get()) else {
+            continue;
+        };
+        let Some(clip) = clips.get(player.animation_clip()) else {
+            continue;
+        };
+        let at = |time| root_translation(clip, time).unwrap_or_default();
+        let mut travel = at(now) - at(previous);
+        // The clip looped since last frame.
+        if now < previous {
+            travel += at(clip.duration()) - at(0.0);
+        }
+        let travel = transform.rotation * model_transform.rotation * travel;
+        let travel = Vec3::new(travel.x, 0.0, travel.z);
+        controller.translation = Some(controller.translation.unwrap_or_default() + travel);
+    }
+}
+
+/// Poses models on the ground with the locomotion clips blended by their speed, in place of their
+/// animator's single clip.
+fn blend_locomotion(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    models: Res<Models>,
+    gltfs: Res<Assets<Gltf>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut model_query: Query<&mut CharacterModel>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+    mut bones: Query<&mut Transform>,
+) {
+    let Some(gltf) = gltfs.get(&models.character) else {
+        return;
+    };
+    let [Some(idle), Some(walk), Some(run)] = LOCOMOTION.map(|state| {
+        gltf.named_animations
+            .get(state.clip_name())
+            .and_then(|clip| clips.get(clip))
+    }) else {
+        return;
+    };
+    for mut model in &mut model_query {
+        // Frozen along with the animator, such as by a hitstop.
+        let paused = model
+            .animator
+            .and_then(|animator| animation_players.get(animator).ok())
+            .is_some_and(|player| player.is_paused());
+        let dt = if paused { 0.0 } else { time.delta_seconds() };
+        let follow = if settings.player.blend_time > 0.0 {
+            (dt / settings.player.blend_time).min(1.0)
+        } else {
+            1.0
+        };
+        let targets = locomotion_weights(model.speed, &settings.player);
+        let on_ground = LOCOMOTION.contains(&model.state);
+        let blend = &mut model.blend;
+        for (weight, target) in blend.weights.iter_mut().zip(targets) {
+            *weight += (target - *weight) * follow;
+        }
+        blend.amount += (on_ground as i32 as f32 - blend.amount) * follow;
+        // Walking and running share a cycle, so the feet stay in step as they blend.
+        let [_, walk_weight, run_weight] = blend.weights;
+        let cycle = if walk_weight + run_weight > 0.0 {
+            (walk_weight * walk.duration() + run_weight * run.duration())
+                / (walk_weight + run_weight)
+        } else {
+            walk.duration()
+        };
+        blend.phase = (blend.phase + dt / cycle).fract();
+        blend.idle_time = (blend.idle_time + dt) % idle.duration();
+        let (weights, amount, phase) = (blend.weights, blend.amount, blend.phase);
+        let times = [
+            blend.idle_time,
+            phase * walk.duration(),
+            phase * run.duration(),
+        ];
+        if amount < 0.001 {
+            continue;
+        }
+
+        // The animator's clip keeps to the shared cycle too, for its root motion.
+        if let Some(mut player) = model
+            .animator
+            .and_then(|animator| animation_players.get_mut(animator).ok())
+        {
+            match model.playing {
+                Some(AnimationState::Walk) => {
+                    player.seek_to(times[1]);
+                }
+                Some(AnimationState::Run) => {
+                    player.seek_to(times[2]);
+                }
+                _ => {}
+            }
+        }
+        for (bone, path) in &model.bones {
+            let mut pose = BlendedPose::default();
+            for ((clip, weight), time) in [idle, walk, run].into_iter().zip(weights).zip(times) {
+                if weight <= 0.0 {
+                    continue;
+                }
+                for curve in clip.get_curves_by_path(path).into_iter().flatten() {
+                    pose.add(curve, time, weight);
+                }
+            }
+            if let Ok(mut transform) = bones.get_mut(*bone) {
+                pose.apply(&mut transform, amount);
+            }
+        }
+    }
+}
+
+/// Holds the root bones of models over their feet: the clips' travel moves the character, if
+/// anything, not the model away from it.
+pub fn keep_root_bones_in_place(models: Query<&CharacterModel>, mut bones: Query<&mut Transform>) {
+    for model in &models {
+        if let Some(mut bone) = model.root_bone.and_then(|bone| bones.get_mut(bone).ok()) {
+            bone.translation.x = 0.0;
+            bone.translation.z = 0.0;
+        }
+    }
+}
+
+fn root_motion_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut root_motion = world.resource_mut::<RootMotion>();
+    if let Some(&name) = args.first() {
+        let states: Vec<AnimationState> = if name == "all" {
+            AnimationState::ALL.to_vec()
+        } else {
+            vec![AnimationState::from_name(name).ok_or_else(|| format!("unknown state {name}"))?]
+        };
+        let enabled = match args.get(1) {
+            Some(&"on") | None => true,
+            Some(&"off") => false,
+            Some(other) => return Err(format!("expected on or off, got {other}")),
+        };
+        for state in states {
+            root_motion.set(state, enabled);
+        }
+    }
+    let states: Vec<&str> = AnimationState::ALL
+        .into_iter()
+        .filter(|&state| root_motion.enabled(state))
+        .map(AnimationState::clip_name)
+        .collect();
+    Ok(if states.is_empty() {
+        "no state uses root motion".to_string()
+    } else {
+        format!("root motion for: {}", states.join(", "))
+    })
+}
@@ -0,0 +1,76 @@
+//! [`CharacterHit`] events: what the character controllers ran into during the frame's physics
+//! step, for gameplay to react to without reading [`KinematicCharacterControllerOutput`] itself.
+//!
+//! Each controller sends at most one hit per collider a frame. Characters pressing into the
+//! ground hit it every frame, so consumers interested in bumps look at the normal.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_set::GameSet;
+
+pub struct CharacterHitsPlugin;
+
+impl Plugin for CharacterHitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CharacterHit>().add_systems(
+            PostUpdate,
+            send_character_hits.in_set(GameSet::PhysicsWriteback),
+        );
+    }
+}
+
+/// Sent when a character controller's movement runs into a collider.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CharacterHit {
+    pub character: Entity,
+    /// The collider hit.
+    pub entity: Entity,
+    /// World-space position on the collider's surface.
+    pub point: Vec3,
+    /// The collider's surface normal at `point`, towards the character.
+    pub normal: Vec3,
+    /// The character's velocity when it hit, in meters per second, including the pull downwards.
+    pub velocity: Vec3,
+}
+
+impl CharacterHit {
+    /// How fast the character was moving into the surface, in meters per second.
+    pub fn speed_into(&self) -> f32 {
+        (-self.velocity.dot(self.normal)).max(0.0)
+    }
+}
+
+fn send_character_hits(
+    time: Res<Time>,
+    mut hits: EventWriter<CharacterHit>,
+    characters: Query<
+        (Entity, &KinematicCharacterControllerOutput),
+        Changed<KinematicCharacterControllerOutput>,
+    >,
+) {
+    let dt = time.delta_seconds();
+    if dt <= 0.0 {
+        return;
+    }
+    for (character, output) in &characters {
+        let mut hit_entities = Vec::with_capacity(output.collisions.len());
+        for collision in &output.collisions {
+            // Without details, the character started out overlapping the collider.
+            let Some(details) = collision.toi.details else {
+                continue;
+            };
+            if hit_entities.contains(&collision.entity) {
+                continue;
+            }
+            hit_entities.push(collision.entity);
+            hits.send(CharacterHit {
+                character,
+                entity: collision.entity,
+                point: details.witness1,
+                normal: details.normal1,
+                velocity: (collision.translation_applied + collision.translation_remaining) / dt,
+            });
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! The player's movement input, sampled once per frame.
+//!
+//! Kept apart from the character controller so the same input can be replayed, for example by
+//! client-side prediction.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Horizontal speed of the character, in meters per second.
+const WALK_SPEED: f32 = 5.0;
+/// Share of the walking speed kept while crouching.
+const CROUCH_SPEED: f32 = 0.5;
+
+/// One frame of movement input.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, Debug)]
+pub struct CharacterInput {
+    /// Numbers inputs so a server can acknowledge them.
+    pub sequence: u32,
+    /// Frame time the input was applied over, in seconds.
+    pub dt: f32,
+    /// Walk direction on the ground plane, each axis between -1 and 1.
+    pub direction: Vec2,
+    /// 1 on the frame W is pressed, -1 on the frame S is pressed.
+    pub vertical: f32,
+    /// Whether C is held.
+    pub crouch: bool,
+}
+
+impl CharacterInput {
+    pub fn read(keys: &Input<KeyCode>, dt: f32) -> Self {
+        let axis =
+            |positive, negative| keys.pressed(positive) as i32 - keys.pressed(negative) as i32;
+        Self {
+            sequence: 0,
+            dt,
+            direction: Vec2::new(
+                axis(KeyCode::Right, KeyCode::Left) as f32,
+                axis(KeyCode::Down, KeyCode::Up) as f32,
+            ),
+            vertical: keys.just_pressed(KeyCode::W) as i32 as f32
+                - keys.just_pressed(KeyCode::S) as i32 as f32,
+            crouch: keys.pressed(KeyCode::C),
+        }
+    }
+
+    /// Horizontal part of [`CharacterInput::translation`].
+    pub fn walk(&self) -> Vec3 {
+        let speed = if self.crouch {
+            WALK_SPEED * CROUCH_SPEED
+        } else {
+            WALK_SPEED
+        };
+        Vec3::new(self.direction.x, 0.0, self.direction.y) * speed * self.dt
+    }
+
+    /// The character controller translation for this frame, including the pull downwards.
+    pub fn translation(&self) -> Vec3 {
+        let vertical = self.dt * 10.0 * (self.vertical * self.dt * 10.0 - 10.0);
+        self.walk() + Vec3::Y * vertical
+    }
+}
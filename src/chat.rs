@@ -0,0 +1,252 @@
+//! Text chat, opened with Enter while playing.
+//!
+//! Enter opens the chat and sends the typed line; Escape closes it without sending. Connected
+//! instances send lines as [`NetMessage::Chat`], which the server shows and relays to its other
+//! clients. Offline, or when a line starts with `/`, the line is run as a console command instead
+//! and its result is shown in the chat.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+use crate::conditions::in_gameplay;
+use crate::console::{self, console_closed};
+use crate::localization::Localization;
+use crate::network::{NetMessage, NetReceived, Network, NetworkRole};
+use crate::pause_menu::PauseState;
+use crate::text_theme::TextTheme;
+
+/// Number of lines kept in the chat history.
+const LOG_LEN: usize = 32;
+/// Number of history lines shown while the chat is open.
+const VISIBLE_LINES: usize = 8;
+/// Seconds a line stays visible while the chat is closed.
+const LINE_SECONDS: f32 = 10.0;
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Chat>()
+            .add_systems(Startup, spawn_chat)
+            .add_systems(OnEnter(PauseState::Paused), close_chat)
+            .add_systems(
+                Update,
+                (
+                    chat_input.run_if(in_gameplay()).run_if(console_closed),
+                    run_chat_commands,
+                    receive_chat.run_if(resource_exists::<Network>()),
+                    update_chat_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A line in the chat history.
+struct ChatLine {
+    text: String,
+    /// Seconds since the line arrived.
+    age: f32,
+}
+
+/// Chat visibility, the line being typed and the history.
+#[derive(Resource, Default)]
+pub struct Chat {
+    pub open: bool,
+    input: String,
+    log: VecDeque<ChatLine>,
+    /// Lines to run as console commands.
+    commands: Vec<String>,
+}
+
+impl Chat {
+    /// Appends a line to the chat history.
+    pub fn print(&mut self, text: impl Into<String>) {
+        if self.log.len() == LOG_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back(ChatLine {
+            text: text.into(),
+            age: 0.0,
+        });
+    }
+}
+
+/// Run condition for systems that read gameplay input, which the chat swallows while open.
+pub fn chat_closed(chat: Res<Chat>) -> bool {
+    !chat.open
+}
+
+#[derive(Component)]
+struct ChatText;
+
+fn spawn_chat(mut commands: Commands, theme: Res<TextTheme>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(90.0),
+                left: Val::Px(15.0),
+                width: Val::Percent(40.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(9),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new("", theme.small()),
+                    TextSection::new("", theme.body()),
+                ]),
+                ChatText,
+            ));
+        });
+}
+
+fn close_chat(mut chat: ResMut<Chat>) {
+    chat.open = false;
+    chat.input.clear();
+}
+
+fn chat_input(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut chat: ResMut<Chat>,
+    network: Option<Res<Network>>,
+    localization: Res<Localization>,
+) {
+    if !chat.open {
+        characters.clear();
+        if keys.just_pressed(KeyCode::Return) {
+            chat.open = true;
+        }
+        return;
+    }
+
+    for event in characters.read() {
+        if !event.char.is_control() {
+            chat.input.push(event.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        chat.input.pop();
+    }
+    // Escape would otherwise open the pause menu in the same frame.
+    if keys.clear_just_pressed(KeyCode::Escape) {
+        chat.open = false;
+        chat.input.clear();
+        return;
+    }
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    let line = std::mem::take(&mut chat.input);
+    chat.open = false;
+    let line = line.trim();
+    if line.is_empty() {
+        return;
+    }
+    match network.filter(|network| network.connected()) {
+        Some(network) if !line.starts_with('/') => {
+            let sender = match network.role {
+                NetworkRole::Server(_) => localization.get("chat.host"),
+                NetworkRole::Client(_) => localization.get("chat.you"),
+            };
+            chat.print(format!("{sender}: {line}"));
+            network.broadcast(&NetMessage::Chat {
+                sender: sender.to_string(),
+                text: line.to_string(),
+            });
+        }
+        _ => {
+            let command = line.strip_prefix('/').unwrap_or(line).to_string();
+            chat.commands.push(command);
+        }
+    }
+}
+
+fn run_chat_commands(world: &mut World) {
+    if world.resource::<Chat>().commands.is_empty() {
+        return;
+    }
+    let commands = std::mem::take(&mut world.resource_mut::<Chat>().commands);
+
+    for line in commands {
+        let result = console::run_command(world, &line);
+
+        let mut chat = world.resource_mut::<Chat>();
+        chat.print(format!("> {line}"));
+        match result {
+            Ok(message) if message.is_empty() => {}
+            Ok(message) => chat.print(message),
+            Err(error) => chat.print(format!("error: {error}")),
+        }
+    }
+}
+
+/// Shows chat lines from the network; a server also relays them to its other clients.
+fn receive_chat(
+    network: Res<Network>,
+    mut received: EventReader<NetReceived>,
+    mut chat: ResMut<Chat>,
+) {
+    for event in received.read() {
+        let NetMessage::Chat { sender, text } = &event.message else {
+            continue;
+        };
+        match network.role {
+            NetworkRole::Server(_) => {
+                if !network.has_client(event.from) {
+                    continue;
+                }
+                // Clients are named by their address rather than what they call themselves.
+                let sender = event.from.to_string();
+                chat.print(format!("{sender}: {text}"));
+                let message = NetMessage::Chat {
+                    sender,
+                    text: text.clone(),
+                };
+                for client in network.clients().filter(|client| *client != event.from) {
+                    network.send(client, &message);
+                }
+            }
+            NetworkRole::Client(_) => chat.print(format!("{sender}: {text}")),
+        }
+    }
+}
+
+fn update_chat_text(
+    time: Res<Time<Real>>,
+    mut chat: ResMut<Chat>,
+    mut texts: Query<&mut Text, With<ChatText>>,
+) {
+    let delta = time.delta_seconds();
+    for line in &mut chat.bypass_change_detection().log {
+        line.age += delta;
+    }
+
+    let skip = chat.log.len().saturating_sub(VISIBLE_LINES);
+    let log = chat
+        .log
+        .iter()
+        .skip(skip)
+        .filter(|line| chat.open || line.age < LINE_SECONDS)
+        .fold(String::new(), |text, line| text + &line.text + "\n");
+    let input = if chat.open {
+        format!("> {}_", chat.input)
+    } else {
+        String::new()
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != log {
+            text.sections[0].value = log.clone();
+        }
+        if text.sections[1].value != input {
+            text.sections[1].value = input.clone();
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Cloth: a banner hanging from a bar, made of a grid of small bodies joined to their neighbors
+//! with ball joints. `cloth [x y z]` in the console spawns one, in front of the camera by default.
+//!
+//! The top row of the grid is pinned to the bar, and the rest sways in the
+//! [`Wind`](crate::wind::Wind) through a [`WindDrag`] on every body. A mesh through the bodies is
+//! rebuilt every frame after the physics step. The joints hold each body at a fixed distance from
+//! its neighbors above and to the left, so the cloth keeps its size but folds and shears like a
+//! loose weave.
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_resource::Face;
+use bevy::render::view::NoFrustumCulling;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::game_set::GameSet;
+use crate::materials::MaterialLibrary;
+use crate::wind::WindDrag;
+
+/// Bodies across and down the banner, and the distance between neighbors, in meters.
+const CLOTH_COLUMNS: usize = 10;
+const CLOTH_ROWS: usize = 8;
+const CLOTH_SPACING: f32 = 0.2;
+const PARTICLE_RADIUS: f32 = 0.05;
+/// Mass of each body, in kilograms.
+const PARTICLE_MASS: f32 = 0.05;
+/// Wind drag of each body; at 3 m/s of wind, a little less than its weight.
+const PARTICLE_DRAG: f32 = 0.12;
+/// How far in front of the camera the banner's bar appears, in meters.
+const SPAWN_DISTANCE: f32 = 4.0;
+/// Height of the bar when spawned in front of the camera, in meters.
+const BAR_HEIGHT: f32 = 3.0;
+
+pub struct ClothPlugin;
+
+impl Plugin for ClothPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command(
+            "cloth",
+            "cloth [x y z] - hang a banner from a bar, in front of the camera by default",
+            cloth_command,
+        )
+        .add_systems(
+            PostUpdate,
+            update_cloth_meshes.in_set(GameSet::PhysicsWriteback),
+        );
+    }
+}
+
+/// The mesh of a cloth, following its bodies. The bodies are children of the cloth, which stays
+/// at the origin, so their transforms are in world space.
+#[derive(Component)]
+pub struct Cloth {
+    /// The bodies of the grid, row by row from the top.
+    pub particles: Vec<Entity>,
+    pub columns: usize,
+}
+
+/// A banner whose bar is centered on `position`. Returns the cloth.
+pub fn spawn_cloth(world: &mut World, position: Vec3) -> Entity {
+    let (columns, rows) = (CLOTH_COLUMNS, CLOTH_ROWS);
+    let width = (columns - 1) as f32 * CLOTH_SPACING;
+    let grid_position = |row: usize, column: usize| {
+        position + Vec3::new(column as f32 * CLOTH_SPACING - width / 2.0, 0.0, 0.0)
+            - Vec3::Y * row as f32 * CLOTH_SPACING
+    };
+    let positions: Vec<Vec3> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .map(|(row, column)| grid_position(row, column))
+        .collect();
+
+    let mesh = world
+        .resource_mut::<Assets<Mesh>>()
+        .add(cloth_mesh(&positions, columns));
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial {
+            base_color: Color::rgb(0.75, 0.1, 0.1),
+            perceptual_roughness: 0.9,
+            double_sided: true,
+            cull_mode: None::<Face>,
+            ..default()
+        });
+    let bar_material = match world.get_resource::<MaterialLibrary>() {
+        Some(library) => library.prototype.clone(),
+        None => world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::SILVER.into()),
+    };
+    let bar_length = width + 0.4;
+    let bar_mesh = world.resource_mut::<Assets<Mesh>>().add(
+        shape::Cylinder {
+            radius: 0.04,
+            height: bar_length,
+            ..default()
+        }
+        .into(),
+    );
+
+    let cloth = world
+        .spawn((
+            PbrBundle {
+                mesh,
+                material,
+                ..default()
+            },
+            // The mesh moves without its entity, so its bounds from spawning can't be trusted.
+            NoFrustumCulling,
+            Name::new("Cloth"),
+        ))
+        .id();
+    let bar = world
+        .spawn((
+            PbrBundle {
+                mesh: bar_mesh,
+                material: bar_material,
+                transform: Transform::from_translation(position + Vec3::Y * PARTICLE_RADIUS)
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+                ..default()
+            },
+            RigidBody::Fixed,
+            Collider::cylinder(bar_length / 2.0, 0.04),
+            Name::new("Cloth bar"),
+        ))
+        .id();
+
+    let particles: Vec<Entity> = positions
+        .iter()
+        .enumerate()
+        .map(|(index, &translation)| {
+            let mut particle = world.spawn((
+                TransformBundle::from_transform(Transform::from_translation(translation)),
+                Collider::ball(PARTICLE_RADIUS),
+                Name::new("Cloth particle"),
+            ));
+            // The top row is pinned to the bar.
+            if index < columns {
+                particle.insert(RigidBody::Fixed);
+            } else {
+                particle.insert((
+                    RigidBody::Dynamic,
+                    ColliderMassProperties::Mass(PARTICLE_MASS),
+                    Velocity::default(),
+                    ExternalForce::default(),
+                    WindDrag(PARTICLE_DRAG),
+                ));
+            }
+            particle.id()
+        })
+        .collect();
+
+    // Each body below the top row is joined to the one above it, and to the one on its left
+    // through a child, since an entity carries one joint.
+    let joint = |offset: Vec3| {
+        SphericalJointBuilder::new()
+            .local_anchor1(offset)
+            .local_anchor2(Vec3::ZERO)
+    };
+    for row in 1..rows {
+        for column in 0..columns {
+            let particle = particles[row * columns + column];
+            let above = particles[(row - 1) * columns + column];
+            world
+                .entity_mut(particle)
+                .insert(ImpulseJoint::new(above, joint(Vec3::NEG_Y * CLOTH_SPACING)));
+            if column > 0 {
+                let left = particles[row * columns + column - 1];
+                let link = world
+                    .spawn(ImpulseJoint::new(left, joint(Vec3::X * CLOTH_SPACING)))
+                    .id();
+                world.entity_mut(particle).add_child(link);
+            }
+        }
+    }
+
+    world
+        .entity_mut(cloth)
+        .push_children(&particles)
+        .add_child(bar)
+        .insert(Cloth { particles, columns });
+    cloth
+}
+
+/// A grid mesh through `positions`, `columns` to a row, with smooth normals.
+fn cloth_mesh(positions: &[Vec3], columns: usize) -> Mesh {
+    let rows = positions.len() / columns;
+    let at = |row: usize, column: usize| positions[row * columns + column];
+    let grid = || (0..rows).flat_map(|row| (0..columns).map(move |column| (row, column)));
+
+    let normals: Vec<Vec3> = grid()
+        .map(|(row, column)| {
+            let along = at(row, (column + 1).min(columns - 1)) - at(row, column.saturating_sub(1));
+            let up = at(row.saturating_sub(1), column) - at((row + 1).min(rows - 1), column);
+            along.cross(up).normalize_or_zero()
+        })
+        .collect();
+    let uvs: Vec<[f32; 2]> = grid()
+        .map(|(row, column)| {
+            [
+                column as f32 / (columns - 1) as f32,
+                row as f32 / (rows - 1) as f32,
+            ]
+        })
+        .collect();
+    let index = |row: usize, column: usize| (row * columns + column) as u32;
+    let indices: Vec<u32> = (0..rows - 1)
+        .flat_map(|row| (0..columns - 1).map(move |column| (row, column)))
+        .flat_map(|(row, column)| {
+            [
+                index(row, column),
+                index(row + 1, column),
+                index(row, column + 1),
+                index(row + 1, column),
+                index(row + 1, column + 1),
+                index(row, column + 1),
+            ]
+        })
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions.to_vec());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn update_cloth_meshes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    cloths: Query<(&Cloth, &Handle<Mesh>)>,
+    particles: Query<&Transform>,
+) {
+    for (cloth, handle) in &cloths {
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+        // Transforms rather than global transforms, which aren't propagated yet.
+        let positions: Vec<Vec3> = cloth
+            .particles
+            .iter()
+            .filter_map(|&particle| particles.get(particle).ok())
+            .map(|transform| transform.translation)
+            .collect();
+        if positions.len() == cloth.particles.len() {
+            *mesh = cloth_mesh(&positions, cloth.columns);
+        }
+    }
+}
+
+fn cloth_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let position = if args.is_empty() {
+        world
+            .query_filtered::<&GlobalTransform, With<MainCamera>>()
+            .get_single(world)
+            .map_or(Vec3::Y * BAR_HEIGHT, |camera| {
+                let ahead = camera.translation() + camera.forward() * SPAWN_DISTANCE;
+                Vec3::new(ahead.x, BAR_HEIGHT, ahead.z)
+            })
+    } else {
+        Vec3::new(
+            parse_arg(args, 0, "x")?,
+            parse_arg(args, 1, "y")?,
+            parse_arg(args, 2, "z")?,
+        )
+    };
+    spawn_cloth(world, position);
+    Ok(format!(
+        "hung a {CLOTH_COLUMNS}x{CLOTH_ROWS} banner at {position}"
+    ))
+}
@@ -0,0 +1,33 @@
+//! Run conditions shared by the game's plugins.
+
+use bevy::prelude::*;
+
+use crate::chat::chat_closed;
+use crate::console::console_closed;
+use crate::death::Dead;
+use crate::pause_menu::PauseState;
+use crate::{MyStates, Player};
+
+/// In the sandbox or a networked game, paused or not.
+pub fn in_play_state() -> impl Condition<()> {
+    in_state(MyStates::Next).or_else(in_state(MyStates::InGame))
+}
+
+/// In the sandbox or a networked game and not paused.
+pub fn in_gameplay() -> impl Condition<()> {
+    in_play_state().and_then(in_state(PauseState::Running))
+}
+
+/// Neither the console nor the chat takes the keyboard.
+pub fn text_input_closed() -> impl Condition<()> {
+    IntoSystem::into_system(console_closed).and_then(chat_closed)
+}
+
+pub fn player_exists(player: Query<(), With<Player>>) -> bool {
+    !player.is_empty()
+}
+
+/// The player exists and hasn't died.
+pub fn player_alive(player: Query<(), (With<Player>, Without<Dead>)>) -> bool {
+    !player.is_empty()
+}
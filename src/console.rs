@@ -0,0 +1,492 @@
+//! An in-game developer console, toggled with the `` ` `` key.
+//!
+//! Each line typed into the console is split on whitespace; the first word selects a command
+//! registered through [`ConsoleAppExt::add_console_command`] and the rest are passed to its
+//! handler, which runs with exclusive [`World`] access.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice};
+use bevy::window::ReceivedCharacter;
+use bevy_rapier3d::prelude::*;
+
+use crate::launch_options::LaunchOptions;
+use crate::lod::{LodMeshes, MeshLod};
+use crate::materials::{MaterialLibrary, MaterialPreset};
+use crate::settings::Settings;
+use crate::surface::Surface;
+use crate::text_theme::TextTheme;
+use crate::{MyStates, Player};
+
+/// Number of lines kept in the console history.
+const LOG_LEN: usize = 64;
+/// Number of history lines shown above the input line.
+const VISIBLE_LINES: usize = 12;
+/// Most bodies dropped by one `spawn` command, stacked in a single column.
+const MAX_SPAWN_COUNT: u32 = 1_000;
+/// Distance between the centers of neighbouring crates spawned by `grid`.
+const GRID_SPACING: f32 = 1.1;
+/// Most crates spawned by one `grid` command.
+const MAX_GRID_CELLS: usize = 50_000;
+/// Grid cells computed per task.
+const GRID_CHUNK_SIZE: usize = 256;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleCommands>()
+            .init_resource::<Console>()
+            .add_console_command("help", "help - list the available commands", help)
+            .add_console_command(
+                "spawn",
+                "spawn <cube|ball|torus> [count] [glass|pickup|hologram] - drop dynamic bodies above the origin",
+                spawn,
+            )
+            .add_console_command(
+                "grid",
+                "grid <columns> <rows> [layers] - stack a grid of crates around the origin",
+                grid,
+            )
+            .add_console_command("tp", "tp <x> <y> <z> - teleport the player", teleport)
+            .add_console_command("gravity", "gravity <y> - set the vertical gravity", gravity)
+            .add_console_command(
+                "state",
+                "state <assetloading|next|lobby|ingame|gameover|editor> - switch the game state",
+                state,
+            )
+            .add_systems(Startup, spawn_console)
+            .add_systems(OnExit(MyStates::AssetLoading), submit_launch_commands)
+            .add_systems(
+                Update,
+                (
+                    toggle_console,
+                    console_input.run_if(console_open),
+                    run_console_commands,
+                    update_console_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Handler of a console command, called with the arguments following the command name.
+///
+/// The returned message (or error) is printed to the console.
+pub type ConsoleHandler = fn(&mut World, &[&str]) -> Result<String, String>;
+
+struct ConsoleCommand {
+    help: &'static str,
+    handler: ConsoleHandler,
+}
+
+/// Registered console commands, by name.
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, ConsoleCommand>);
+
+pub trait ConsoleAppExt {
+    /// Registers `handler` to run when a console line starts with `name`.
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        handler: ConsoleHandler,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        handler: ConsoleHandler,
+    ) -> &mut Self {
+        self.init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .0
+            .insert(name, ConsoleCommand { help, handler });
+        self
+    }
+}
+
+/// Console visibility, the line being typed and the output history.
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: VecDeque<String>,
+    pending: Vec<String>,
+}
+
+impl Console {
+    /// Queues `line` to be run as if it had been typed into the console.
+    pub fn submit(&mut self, line: impl Into<String>) {
+        self.pending.push(line.into());
+    }
+
+    /// Appends a line to the console history.
+    pub fn print(&mut self, line: impl Into<String>) {
+        if self.log.len() == LOG_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back(line.into());
+    }
+}
+
+/// Run condition for systems that read gameplay input, which the console swallows while open.
+pub fn console_closed(console: Res<Console>) -> bool {
+    !console.open
+}
+
+fn console_open(console: Res<Console>) -> bool {
+    console.open
+}
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands, theme: Res<TextTheme>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.05, 0.05, 0.08, 0.85).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ConsolePanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new("", theme.muted()),
+                    TextSection::new("> ", theme.small()),
+                ]),
+                ConsoleText,
+            ));
+        });
+}
+
+/// Queues the commands given with `--exec`.
+fn submit_launch_commands(options: Option<Res<LaunchOptions>>, mut console: ResMut<Console>) {
+    for line in options.iter().flat_map(|options| &options.exec) {
+        console.submit(line.clone());
+    }
+}
+
+fn toggle_console(
+    input: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut panel: Query<&mut Visibility, With<ConsolePanel>>,
+) {
+    if !input.just_pressed(KeyCode::Grave) {
+        return;
+    }
+    console.open = !console.open;
+    for mut visibility in &mut panel {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn console_input(
+    input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+) {
+    for event in characters.read() {
+        if event.char != '`' && !event.char.is_control() {
+            console.input.push(event.char);
+        }
+    }
+
+    if input.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if input.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.submit(line);
+        }
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    if world.resource::<Console>().pending.is_empty() {
+        return;
+    }
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+
+    for line in pending {
+        if line.split_whitespace().next().is_none() {
+            continue;
+        }
+        let result = run_command(world, &line);
+
+        let mut console = world.resource_mut::<Console>();
+        console.print(format!("> {line}"));
+        match result {
+            Ok(message) if message.is_empty() => {}
+            Ok(message) => console.print(message),
+            Err(error) => console.print(format!("error: {error}")),
+        }
+    }
+}
+
+/// Runs a console line right away, returning the command's message or error.
+pub fn run_command(world: &mut World, line: &str) -> Result<String, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().ok_or("empty command")?;
+    let args: Vec<&str> = words.collect();
+    let _span = info_span!("console_command", name, ?args).entered();
+
+    let handler = world
+        .resource::<ConsoleCommands>()
+        .0
+        .get(name)
+        .map(|command| command.handler);
+    let result = match handler {
+        Some(handler) => handler(world, &args),
+        None => Err(format!("unknown command `{name}`, try `help`")),
+    };
+    match &result {
+        Ok(message) => info!("{message}"),
+        Err(error) => warn!("{error}"),
+    }
+    result
+}
+
+fn update_console_text(console: Res<Console>, mut text: Query<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut text in &mut text {
+        let skip = console.log.len().saturating_sub(VISIBLE_LINES);
+        text.sections[0].value = console
+            .log
+            .iter()
+            .skip(skip)
+            .fold(String::new(), |text, line| text + line + "\n");
+        text.sections[1].value = format!("> {}_", console.input);
+    }
+}
+
+/// Parses the argument at `index`, naming it in the error message.
+pub fn parse_arg<T: std::str::FromStr>(
+    args: &[&str],
+    index: usize,
+    name: &str,
+) -> Result<T, String> {
+    let arg = args
+        .get(index)
+        .ok_or_else(|| format!("missing argument <{name}>"))?;
+    arg.parse()
+        .map_err(|_| format!("invalid <{name}>: `{arg}`"))
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands
+        .0
+        .values()
+        .map(|command| command.help)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let kind = *args.first().ok_or("missing argument <cube|ball|torus>")?;
+    let count: u32 = if args.len() > 1 {
+        parse_arg(args, 1, "count")?
+    } else {
+        1
+    };
+    if count > MAX_SPAWN_COUNT {
+        return Err(format!("at most {MAX_SPAWN_COUNT} bodies at a time"));
+    }
+    let preset = match args.get(2) {
+        Some(name) => Some(
+            MaterialPreset::from_name(name).ok_or_else(|| format!("unknown material `{name}`"))?,
+        ),
+        None => None,
+    };
+
+    let lod_meshes = world.resource::<LodMeshes>();
+    let (mesh_lod, collider, collider_lod, surface) = match kind {
+        "cube" => (None, Collider::cuboid(0.5, 0.5, 0.5), None, Surface::Wood),
+        "ball" => (
+            Some(MeshLod::new(lod_meshes.ball.clone())),
+            Collider::ball(0.5),
+            None,
+            Surface::Rubber,
+        ),
+        "torus" => (
+            Some(MeshLod::new(lod_meshes.torus.clone())),
+            lod_meshes.torus_collider.detailed.clone(),
+            Some(lod_meshes.torus_collider.clone()),
+            Surface::Rubber,
+        ),
+        _ => return Err(format!("unknown shape `{kind}`")),
+    };
+    let mesh = match &mesh_lod {
+        Some(lod) => lod.full(),
+        None => world
+            .resource_mut::<Assets<Mesh>>()
+            .add(shape::Cube::new(1.0).into()),
+    };
+    let material = match preset {
+        Some(preset) => world
+            .get_resource::<MaterialLibrary>()
+            .ok_or("materials are still loading")?
+            .preset(preset),
+        None => world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::SILVER.into()),
+    };
+
+    for i in 0..count {
+        let mut entity = world.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(0.0, 5.0 + i as f32 * 1.2, 0.0),
+                ..default()
+            },
+            RigidBody::Dynamic,
+            collider.clone(),
+            if preset == Some(MaterialPreset::Glass) {
+                Surface::Glass
+            } else {
+                surface
+            },
+        ));
+        if let Some(lod) = &mesh_lod {
+            entity.insert(lod.clone());
+        }
+        if let Some(lod) = &collider_lod {
+            entity.insert(lod.clone());
+        }
+    }
+    Ok(format!("spawned {count} {kind}(s)"))
+}
+
+/// Spawns crates in a `columns` by `rows` grid, `layers` high. The transforms and colliders are
+/// computed in parallel chunks and the entities spawned in one batch, which matters at the
+/// tens of thousands of crates large test scenes use.
+fn grid(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let columns: usize = parse_arg(args, 0, "columns")?;
+    let rows: usize = parse_arg(args, 1, "rows")?;
+    let layers: usize = if args.len() > 2 {
+        parse_arg(args, 2, "layers")?
+    } else {
+        1
+    };
+    let count = columns
+        .checked_mul(rows)
+        .and_then(|count| count.checked_mul(layers))
+        .filter(|&count| count <= MAX_GRID_CELLS)
+        .ok_or_else(|| format!("at most {MAX_GRID_CELLS} crates at a time"))?;
+
+    let center = Vec3::new(columns as f32 - 1.0, 0.0, rows as f32 - 1.0) * GRID_SPACING / 2.0;
+    let cells: Vec<usize> = (0..count).collect();
+    let bodies = cells.par_chunk_map(ComputeTaskPool::get(), GRID_CHUNK_SIZE, |chunk| {
+        chunk
+            .iter()
+            .map(|&index| {
+                let column = index % columns;
+                let row = index / columns % rows;
+                let layer = index / (columns * rows);
+                let position = Vec3::new(column as f32, layer as f32 + 0.5, row as f32)
+                    * GRID_SPACING
+                    - center;
+                (
+                    Transform::from_translation(position),
+                    Collider::cuboid(0.5, 0.5, 0.5),
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mesh = world
+        .resource_mut::<Assets<Mesh>>()
+        .add(shape::Cube::new(1.0).into());
+    let material = world
+        .get_resource::<MaterialLibrary>()
+        .ok_or("materials are still loading")?
+        .wood_crate
+        .clone();
+    world.spawn_batch(
+        bodies
+            .into_iter()
+            .flatten()
+            .map(move |(transform, collider)| {
+                (
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        transform,
+                        ..default()
+                    },
+                    RigidBody::Dynamic,
+                    collider,
+                    Surface::Wood,
+                )
+            }),
+    );
+    Ok(format!("spawned {count} crates"))
+}
+
+fn teleport(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let target = Vec3::new(
+        parse_arg(args, 0, "x")?,
+        parse_arg(args, 1, "y")?,
+        parse_arg(args, 2, "z")?,
+    );
+    let mut player = world.query_filtered::<&mut Transform, With<Player>>();
+    let mut transform = player
+        .get_single_mut(world)
+        .map_err(|_| "no player to teleport")?;
+    transform.translation = target;
+    Ok(format!("teleported to {target}"))
+}
+
+fn gravity(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let y: f32 = parse_arg(args, 0, "y")?;
+    // Applied through the settings so a later settings change doesn't undo it.
+    world.resource_mut::<Settings>().physics.gravity = y;
+    Ok(format!("gravity set to {y}"))
+}
+
+fn state(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = *args.first().ok_or("missing argument <state>")?;
+    let state = match name.to_lowercase().as_str() {
+        "assetloading" => MyStates::AssetLoading,
+        "next" => MyStates::Next,
+        "lobby" => MyStates::Lobby,
+        "ingame" => MyStates::InGame,
+        "gameover" => MyStates::GameOver,
+        "editor" => MyStates::Editor,
+        _ => return Err(format!("unknown state `{name}`")),
+    };
+    world
+        .resource_mut::<NextState<MyStates>>()
+        .set(state.clone());
+    Ok(format!("switching to {state:?}"))
+}
@@ -0,0 +1,282 @@
+//! Crash reports and the crash screen.
+//!
+//! A panic hook writes a report to [`CRASH_DIR`] with the panic message, the game state at the
+//! time, the end of the log and some system information. Panics in the main world's schedules are
+//! caught by [`run_main_guarded`], which replaces Bevy's main schedule runner: the game then
+//! switches to [`MyStates::Crashed`] and shows the crash screen instead of closing. The schedule
+//! that panicked is lost, so the screen is driven by [`CrashScreenSchedule`]. Panics in the render
+//! world still end the process, with a report.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use bevy::app::{AppExit, MainScheduleOrder};
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::render::renderer::RenderAdapterInfo;
+use bevy::time::Real;
+use bevy_rapier3d::prelude::*;
+
+use crate::localization::Localization;
+use crate::logging::LOG_PATH;
+use crate::menu::{self, MenuInput, FOCUSED_BUTTON_COLOR};
+use crate::pause_menu::PauseState;
+use crate::screenshot::timestamp;
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+pub const CRASH_DIR: &str = "crashes";
+/// Lines from the end of the log included in a report.
+const LOG_TAIL_LINES: usize = 40;
+
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        install_panic_hook();
+        app.init_schedule(CrashGuard)
+            .init_schedule(CrashScreenSchedule)
+            .add_systems(CrashGuard, run_main_guarded)
+            .add_systems(Last, record_crash_context)
+            .add_systems(CrashScreenSchedule, navigate_crash_screen);
+        app.main_schedule_label = CrashGuard.intern();
+    }
+}
+
+/// Runs [`Main`]'s schedules, catching their panics.
+#[derive(ScheduleLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct CrashGuard;
+
+/// Runs every frame after a crash, after the schedules that survived it.
+#[derive(ScheduleLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct CrashScreenSchedule;
+
+/// What the report says about the game, updated every frame.
+#[derive(Default)]
+struct CrashContext {
+    state: String,
+    pause: String,
+    frame: u64,
+    elapsed: f32,
+    adapter: String,
+}
+
+static CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+/// The message and report path of the first panic.
+static CRASH: Mutex<Option<(String, String)>> = Mutex::new(None);
+static CRASHED: AtomicBool = AtomicBool::new(false);
+
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        // Later panics are usually consequences of the first one.
+        if CRASHED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let message = panic_message(info);
+        let path = format!("{CRASH_DIR}/crash-{}.txt", timestamp());
+        let written = fs::create_dir_all(CRASH_DIR)
+            .and_then(|()| fs::write(&path, crash_report(&message)))
+            .map_err(|error| eprintln!("could not write {path}: {error}"))
+            .is_ok();
+        if written {
+            eprintln!("crash report written to {path}");
+        }
+        if let Ok(mut crash) = CRASH.lock() {
+            *crash = Some((message, if written { path } else { String::new() }));
+        }
+    }));
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    match info.location() {
+        Some(location) => format!("{payload} ({location})"),
+        None => payload,
+    }
+}
+
+fn crash_report(message: &str) -> String {
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "{} {} crashed",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(report, "panic: {message}\n");
+
+    // Not waited for, in case the panic happened while the lock was held.
+    match CONTEXT.try_lock().ok().as_deref().and_then(Option::as_ref) {
+        Some(context) => {
+            let _ = writeln!(report, "state: {}", context.state);
+            let _ = writeln!(report, "pause: {}", context.pause);
+            let _ = writeln!(
+                report,
+                "frame: {} ({:.1} s)",
+                context.frame, context.elapsed
+            );
+            let _ = writeln!(report, "graphics adapter: {}", context.adapter);
+        }
+        None => {
+            let _ = writeln!(report, "crashed before the first frame");
+        }
+    }
+    let _ = writeln!(
+        report,
+        "system: {} {}, {} threads\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::thread::available_parallelism().map_or(0, |threads| threads.get())
+    );
+
+    let _ = writeln!(report, "last {LOG_TAIL_LINES} lines of {LOG_PATH}:");
+    match fs::read_to_string(LOG_PATH) {
+        Ok(log) => {
+            let lines: Vec<&str> = log.lines().collect();
+            for line in &lines[lines.len().saturating_sub(LOG_TAIL_LINES)..] {
+                let _ = writeln!(report, "{line}");
+            }
+        }
+        Err(error) => {
+            let _ = writeln!(report, "(could not read the log: {error})");
+        }
+    }
+    report
+}
+
+fn record_crash_context(
+    time: Res<Time<Real>>,
+    state: Res<State<MyStates>>,
+    pause: Res<State<PauseState>>,
+    adapter: Option<Res<RenderAdapterInfo>>,
+) {
+    let Ok(mut context) = CONTEXT.lock() else {
+        return;
+    };
+    let context = context.get_or_insert_with(|| CrashContext {
+        adapter: adapter.map_or_else(
+            || "unknown".to_string(),
+            |adapter| format!("{} ({:?})", adapter.name, adapter.backend),
+        ),
+        ..default()
+    });
+    if state.is_changed() || context.state.is_empty() {
+        context.state = format!("{:?}", state.get());
+    }
+    if pause.is_changed() || context.pause.is_empty() {
+        context.pause = format!("{:?}", pause.get());
+    }
+    context.frame += 1;
+    context.elapsed = time.elapsed_seconds();
+}
+
+/// Runs the startup schedules once and then the schedules of [`MainScheduleOrder`], like
+/// [`Main::run_main`], but switches to the crash screen when one of them panics.
+fn run_main_guarded(world: &mut World, mut started: Local<bool>) {
+    if !*started {
+        *started = true;
+        for label in [PreStartup.intern(), Startup.intern(), PostStartup.intern()] {
+            run_guarded(world, label);
+        }
+    }
+    world.resource_scope(|world, order: Mut<MainScheduleOrder>| {
+        for &label in &order.labels {
+            run_guarded(world, label);
+        }
+    });
+    if world.contains_resource::<CrashScreen>() {
+        run_guarded(world, CrashScreenSchedule.intern());
+    }
+}
+
+fn run_guarded(world: &mut World, label: bevy::ecs::schedule::InternedScheduleLabel) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = world.try_run_schedule(label);
+    }));
+    if result.is_err() {
+        error!("{label:?} panicked and was removed; showing the crash screen");
+        if !world.contains_resource::<CrashScreen>() {
+            world.run_system_once(enter_crash_screen);
+        }
+    }
+}
+
+/// Whether the crash screen is shown. Inserted on the first crash.
+#[derive(Resource)]
+struct CrashScreen;
+
+#[derive(Component)]
+struct QuitButton;
+
+fn enter_crash_screen(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    localization: Res<Localization>,
+    mut next_state: ResMut<NextState<MyStates>>,
+    mut rapier: ResMut<RapierConfiguration>,
+) {
+    let (message, path) = CRASH
+        .lock()
+        .ok()
+        .and_then(|crash| crash.clone())
+        .unwrap_or_default();
+    commands.insert_resource(CrashScreen);
+    next_state.set(MyStates::Crashed);
+    rapier.physics_pipeline_active = false;
+
+    // Localized through the lookup rather than `Localized`, whose system may be gone.
+    let report = if path.is_empty() {
+        localization.get("crash.no_report").to_string()
+    } else {
+        localization.format("crash.report", &[("path", &path)])
+    };
+    commands
+        .spawn(NodeBundle {
+            z_index: ZIndex::Global(100),
+            ..menu::overlay()
+        })
+        .with_children(|parent| {
+            parent.spawn(menu::label(localization.get("crash.title"), theme.title()));
+            parent.spawn(menu::label(message, theme.body()));
+            parent.spawn(menu::label(report, theme.muted()));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        background_color: FOCUSED_BUTTON_COLOR.into(),
+                        ..menu::button()
+                    },
+                    QuitButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(menu::label(
+                        localization.get("failed.quit"),
+                        theme.heading(),
+                    ));
+                });
+        });
+}
+
+/// Quit is the only button, so it always has focus.
+fn navigate_crash_screen(
+    menu_input: MenuInput,
+    buttons: Query<&Interaction, With<QuitButton>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let pressed = buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if pressed || menu_input.activate() || menu_input.pause() {
+        exit.send(AppExit);
+    }
+}
@@ -0,0 +1,160 @@
+//! Center-screen crosshair shown while aiming, flashing when an aim ray hits something.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::{aiming, CameraMode, MainCamera};
+use crate::Player;
+
+/// How long the crosshair stays tinted after a hit, in seconds.
+const FLASH_DURATION: f32 = 0.15;
+/// Maximum distance of the aim ray.
+const AIM_RANGE: f32 = 100.0;
+
+const IDLE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.8);
+const HIT_COLOR: Color = Color::RED;
+
+pub struct CrosshairPlugin;
+
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReticleHit>()
+            .init_resource::<CrosshairFlash>()
+            .add_systems(Startup, spawn_crosshair)
+            .add_systems(
+                Update,
+                (show_crosshair, fire_aim_ray.run_if(aiming), flash_crosshair).chain(),
+            );
+    }
+}
+
+/// Sent when something aimed with the crosshair (an aim ray, a projectile, an interaction) hits
+/// an entity, to flash the crosshair.
+#[derive(Event)]
+pub struct ReticleHit {
+    pub entity: Entity,
+}
+
+#[derive(Resource)]
+struct CrosshairFlash(Timer);
+
+impl Default for CrosshairFlash {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(FLASH_DURATION, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+#[derive(Component)]
+struct Crosshair;
+
+#[derive(Component)]
+struct CrosshairBar;
+
+fn spawn_crosshair(mut commands: Commands) {
+    let bar = |width: f32, height: f32| {
+        (
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(width),
+                    height: Val::Px(height),
+                    ..default()
+                },
+                background_color: IDLE_COLOR.into(),
+                ..default()
+            },
+            CrosshairBar,
+        )
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Crosshair,
+        ))
+        .with_children(|parent| {
+            parent.spawn(bar(16.0, 2.0));
+            parent.spawn(bar(2.0, 16.0));
+        });
+}
+
+fn show_crosshair(
+    mode: Res<CameraMode>,
+    mouse: Res<Input<MouseButton>>,
+    mut crosshair: Query<&mut Visibility, With<Crosshair>>,
+) {
+    let visible = aiming(mode, mouse);
+    for mut visibility in &mut crosshair {
+        *visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Casts a ray through the center of the screen on left click.
+fn fire_aim_ray(
+    mouse: Res<Input<MouseButton>>,
+    rapier_context: Res<RapierContext>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    player: Query<Entity, With<Player>>,
+    mut hits: EventWriter<ReticleHit>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let mut filter = QueryFilter::default();
+    if let Ok(player) = player.get_single() {
+        filter = filter.exclude_collider(player);
+    }
+
+    if let Some((entity, _)) = rapier_context.cast_ray(
+        camera.translation(),
+        camera.forward(),
+        AIM_RANGE,
+        true,
+        filter,
+    ) {
+        hits.send(ReticleHit { entity });
+    }
+}
+
+fn flash_crosshair(
+    time: Res<Time>,
+    mut hits: EventReader<ReticleHit>,
+    mut flash: ResMut<CrosshairFlash>,
+    mut bars: Query<&mut BackgroundColor, With<CrosshairBar>>,
+) {
+    for hit in hits.read() {
+        debug!("crosshair hit {:?}", hit.entity);
+        flash.0.reset();
+    }
+    let flashing = !flash.0.finished();
+    flash.0.tick(time.delta());
+    if !flashing {
+        return;
+    }
+
+    let t = flash.0.percent();
+    let color = Vec4::from(HIT_COLOR).lerp(Vec4::from(IDLE_COLOR), t);
+    for mut bar in &mut bars {
+        bar.0 = Color::from(color);
+    }
+}
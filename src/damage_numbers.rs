@@ -0,0 +1,121 @@
+//! Floating damage numbers.
+//!
+//! Every [`DamageEvent`] shows its amount at the damage location, drifting upward and fading out.
+//! The text entities come from a fixed pool spawned at startup and are reused, so a hit doesn't
+//! spawn or allocate anything.
+
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::game_set::GameSet;
+use crate::health::DamageEvent;
+use crate::text_theme::TextTheme;
+
+const POOL_SIZE: usize = 32;
+/// Seconds a number stays visible.
+const LIFETIME: f32 = 1.0;
+/// Upward drift in meters per second.
+const RISE_SPEED: f32 = 1.0;
+
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_damage_number_pool)
+            .add_systems(
+                PostUpdate,
+                (show_damage_numbers, animate_damage_numbers)
+                    .chain()
+                    .in_set(GameSet::Ui),
+            );
+    }
+}
+
+/// A pooled damage number; inactive ones are hidden and free for reuse.
+#[derive(Component, Default)]
+struct DamageNumber {
+    active: bool,
+    age: f32,
+    position: Vec3,
+    color: Color,
+}
+
+fn spawn_damage_number_pool(mut commands: Commands, theme: Res<TextTheme>) {
+    for _ in 0..POOL_SIZE {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section(String::with_capacity(8), theme.heading()),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DamageNumber::default(),
+        ));
+    }
+}
+
+fn show_damage_numbers(
+    mut events: EventReader<DamageEvent>,
+    mut numbers: Query<(&mut DamageNumber, &mut Text, &mut Visibility)>,
+) {
+    for event in events.read() {
+        // Reuse a free number, or the oldest one when the pool is exhausted.
+        let Some((mut number, mut text, mut visibility)) =
+            numbers.iter_mut().max_by(|(a, ..), (b, ..)| {
+                let age = |number: &DamageNumber| if number.active { number.age } else { f32::MAX };
+                age(a).total_cmp(&age(b))
+            })
+        else {
+            return;
+        };
+
+        number.active = true;
+        number.age = 0.0;
+        number.position = event.point + Vec3::Y;
+        number.color = if event.amount >= 0.0 {
+            Color::rgb(1.0, 0.3, 0.2)
+        } else {
+            Color::rgb(0.3, 1.0, 0.4)
+        };
+
+        let section = &mut text.sections[0];
+        section.value.clear();
+        let _ = write!(section.value, "{:.0}", event.amount.abs());
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn animate_damage_numbers(
+    time: Res<Time>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut numbers: Query<(&mut DamageNumber, &mut Text, &mut Style, &mut Visibility)>,
+) {
+    let camera = cameras.get_single().ok();
+
+    for (mut number, mut text, mut style, mut visibility) in &mut numbers {
+        if !number.active {
+            continue;
+        }
+        number.age += time.delta_seconds();
+        number.position.y += RISE_SPEED * time.delta_seconds();
+
+        let screen_position = camera
+            .and_then(|(camera, transform)| camera.world_to_viewport(transform, number.position));
+        match screen_position {
+            Some(screen_position) if number.age < LIFETIME => {
+                style.left = Val::Px(screen_position.x);
+                style.top = Val::Px(screen_position.y);
+                text.sections[0].style.color = number.color.with_a(1.0 - number.age / LIFETIME);
+            }
+            _ => {
+                number.active = false;
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
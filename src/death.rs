@@ -0,0 +1,247 @@
+//! The player's death, reacting to [`PlayerDied`]: the character stops taking input and goes limp
+//! as a dynamic body knocked over by an impulse, the camera pulls back to look at it, and the game
+//! switches to [`MyStates::GameOver`] after [`GAME_OVER_DELAY`] seconds.
+//!
+//! The game over screen offers Restart, going back to [`MyStates::Next`] with a new player, and
+//! Quit to menu, going to the [`MyStates::Lobby`] menu. Leaving the state removes the body.
+
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::{overview_transform, CameraMode, MainCamera};
+use crate::game_set::GameSet;
+use crate::health::PlayerDied;
+use crate::menu::{self, MenuInput, BUTTON_COLOR, FOCUSED_BUTTON_COLOR};
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+/// Seconds between the death and the game over screen.
+pub const GAME_OVER_DELAY: f32 = 3.0;
+/// Impulse knocking the body over, for the player's mass of about 1200 kg.
+const FALL_IMPULSE: Vec3 = Vec3::new(0.0, 1500.0, 0.0);
+const FALL_TORQUE_IMPULSE: Vec3 = Vec3::new(2000.0, 0.0, 800.0);
+/// Where the camera ends up, relative to the body.
+const DEATH_CAMERA_OFFSET: Vec3 = Vec3::new(0.0, 4.0, 6.0);
+/// How quickly the camera moves there, as a fraction of the remaining distance per second.
+const DEATH_CAMERA_SPEED: f32 = 1.5;
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameOverFocus>()
+            .add_systems(Update, (start_dying, count_down_to_game_over).chain())
+            .add_systems(
+                Update,
+                (navigate_game_over, highlight_game_over_focus)
+                    .chain()
+                    .run_if(in_state(MyStates::GameOver)),
+            )
+            .add_systems(
+                PostUpdate,
+                look_at_body
+                    .run_if(resource_exists::<Dying>())
+                    .in_set(GameSet::Camera),
+            )
+            .add_systems(OnEnter(MyStates::GameOver), spawn_game_over_screen)
+            .add_systems(
+                OnExit(MyStates::GameOver),
+                (despawn_game_over_screen, stop_dying),
+            );
+    }
+}
+
+/// Marks a player that died. Its character controller is replaced with a dynamic rigid body.
+#[derive(Component)]
+pub struct Dead;
+
+/// The body the death camera looks at, and the time left until the game over screen.
+#[derive(Resource)]
+struct Dying {
+    body: Entity,
+    timer: Timer,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum GameOverButton {
+    Restart,
+    QuitToMenu,
+}
+
+impl GameOverButton {
+    const ALL: [GameOverButton; 2] = [GameOverButton::Restart, GameOverButton::QuitToMenu];
+
+    /// Localization key of the button's label.
+    fn label(self) -> &'static str {
+        match self {
+            GameOverButton::Restart => "gameover.restart",
+            GameOverButton::QuitToMenu => "gameover.quit_to_menu",
+        }
+    }
+}
+
+/// Index into [`GameOverButton::ALL`] of the focused button.
+#[derive(Resource, Default)]
+struct GameOverFocus(usize);
+
+#[derive(Component)]
+struct GameOverScreen;
+
+fn start_dying(
+    mut commands: Commands,
+    mut died: EventReader<PlayerDied>,
+    dying: Option<Res<Dying>>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Some(event) = died.read().last() else {
+        return;
+    };
+    if dying.is_some() {
+        return;
+    }
+    info!(player = ?event.entity, "player died");
+
+    commands
+        .entity(event.entity)
+        .remove::<KinematicCharacterController>()
+        .insert((
+            Dead,
+            RigidBody::Dynamic,
+            Velocity::default(),
+            ExternalImpulse {
+                impulse: FALL_IMPULSE,
+                torque_impulse: FALL_TORQUE_IMPULSE,
+            },
+        ));
+    commands.insert_resource(Dying {
+        body: event.entity,
+        timer: Timer::from_seconds(GAME_OVER_DELAY, TimerMode::Once),
+    });
+
+    // The first-person camera would roll with the body.
+    *camera_mode = CameraMode::Overview;
+    for mut window in &mut windows {
+        window.cursor.visible = true;
+        window.cursor.grab_mode = CursorGrabMode::None;
+    }
+}
+
+fn count_down_to_game_over(
+    time: Res<Time>,
+    dying: Option<ResMut<Dying>>,
+    mut next_state: ResMut<NextState<MyStates>>,
+) {
+    let Some(mut dying) = dying else {
+        return;
+    };
+    if dying.timer.tick(time.delta()).just_finished() {
+        next_state.set(MyStates::GameOver);
+    }
+}
+
+fn look_at_body(
+    time: Res<Time>,
+    dying: Res<Dying>,
+    bodies: Query<&Transform, Without<MainCamera>>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Ok(body) = bodies.get(dying.body) else {
+        return;
+    };
+    let amount = (DEATH_CAMERA_SPEED * time.delta_seconds()).min(1.0);
+    for mut transform in &mut cameras {
+        let target = body.translation + DEATH_CAMERA_OFFSET;
+        transform.translation = transform.translation.lerp(target, amount);
+        transform.look_at(body.translation, Vec3::Y);
+    }
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    mut focus: ResMut<GameOverFocus>,
+) {
+    focus.0 = 0;
+    commands
+        .spawn((menu::overlay(), GameOverScreen))
+        .with_children(|parent| {
+            parent.spawn(menu::localized_label("gameover.title", theme.title()));
+            for button in GameOverButton::ALL {
+                parent
+                    .spawn((menu::button(), button))
+                    .with_children(|parent| {
+                        parent.spawn(menu::localized_label(button.label(), theme.heading()));
+                    });
+            }
+        });
+}
+
+fn despawn_game_over_screen(mut commands: Commands, screens: Query<Entity, With<GameOverScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_game_over(
+    menu_input: MenuInput,
+    mut focus: ResMut<GameOverFocus>,
+    buttons: Query<(&Interaction, &GameOverButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<MyStates>>,
+) {
+    menu_input.navigate(&mut focus.0, GameOverButton::ALL.len());
+    let mut activated = menu_input
+        .activate()
+        .then_some(GameOverButton::ALL[focus.0]);
+
+    for (interaction, button) in &buttons {
+        match interaction {
+            Interaction::Pressed => activated = Some(*button),
+            Interaction::Hovered => {
+                focus.0 = GameOverButton::ALL
+                    .iter()
+                    .position(|candidate| candidate == button)
+                    .unwrap_or(focus.0);
+            }
+            Interaction::None => {}
+        }
+    }
+
+    match activated {
+        Some(GameOverButton::Restart) => next_state.set(MyStates::Next),
+        Some(GameOverButton::QuitToMenu) => next_state.set(MyStates::Lobby),
+        None => {}
+    }
+}
+
+fn highlight_game_over_focus(
+    focus: Res<GameOverFocus>,
+    mut buttons: Query<(&GameOverButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in &mut buttons {
+        color.0 = if *button == GameOverButton::ALL[focus.0] {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
+
+/// Removes the body, so that a new player is spawned when the game is entered again, and moves the
+/// camera back from the body.
+fn stop_dying(
+    mut commands: Commands,
+    dying: Option<Res<Dying>>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    if let Some(dying) = dying {
+        if let Some(body) = commands.get_entity(dying.body) {
+            body.despawn_recursive();
+        }
+    }
+    commands.remove_resource::<Dying>();
+    for mut transform in &mut cameras {
+        *transform = overview_transform();
+    }
+}
@@ -0,0 +1,186 @@
+//! Gizmo arrows for velocities, external forces and contact normals of dynamic bodies, and
+//! markers for every active contact point.
+//!
+//! Drawn only while the debug renderer is enabled (F4), alongside Rapier's collider wireframes,
+//! which are tinted by the collider's [`CollisionGroups`] membership.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::physics_setup::DebugRenderSetting;
+
+/// Scale from newtons to arrow length, so the demo forces fit on screen.
+const FORCE_SCALE: f32 = 0.05;
+/// Length of the contact normal arrows.
+const NORMAL_LENGTH: f32 = 1.0;
+/// Radius of the contact point markers.
+const CONTACT_POINT_RADIUS: f32 = 0.04;
+/// Length of the per-point contact normals.
+const CONTACT_NORMAL_LENGTH: f32 = 0.3;
+
+/// Wireframe colors for collision groups, indexed by the lowest membership bit.
+const GROUP_COLORS: [Color; 8] = [
+    Color::ORANGE,
+    Color::LIME_GREEN,
+    Color::FUCHSIA,
+    Color::TURQUOISE,
+    Color::GOLD,
+    Color::TOMATO,
+    Color::VIOLET,
+    Color::AQUAMARINE,
+];
+
+pub struct DebugGizmosPlugin;
+
+impl Plugin for DebugGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (draw_body_vectors, draw_contact_normals, draw_contact_points)
+                    .run_if(debug_render_enabled),
+                tint_colliders_by_group,
+            ),
+        );
+    }
+}
+
+pub fn debug_render_enabled(setting: Res<DebugRenderSetting>) -> bool {
+    setting.enabled
+}
+
+/// Draws `vector` from `start` with a small arrow head.
+pub fn arrow(gizmos: &mut Gizmos, start: Vec3, vector: Vec3, color: Color) {
+    let length = vector.length();
+    if length < f32::EPSILON {
+        return;
+    }
+    let end = start + vector;
+    let direction = vector / length;
+    let side = direction.any_orthonormal_vector();
+    let head = (length * 0.2).min(0.25);
+
+    gizmos.ray(start, vector, color);
+    gizmos.line(end, end - direction * head + side * head * 0.5, color);
+    gizmos.line(end, end - direction * head - side * head * 0.5, color);
+}
+
+fn draw_body_vectors(
+    mut gizmos: Gizmos,
+    bodies: Query<(
+        &GlobalTransform,
+        &RigidBody,
+        Option<&Velocity>,
+        Option<&ExternalForce>,
+    )>,
+) {
+    for (transform, body, velocity, force) in &bodies {
+        if *body != RigidBody::Dynamic {
+            continue;
+        }
+        let center = transform.translation();
+        if let Some(velocity) = velocity {
+            arrow(&mut gizmos, center, velocity.linvel, Color::CYAN);
+        }
+        if let Some(force) = force {
+            arrow(
+                &mut gizmos,
+                center,
+                force.force * FORCE_SCALE,
+                Color::ORANGE_RED,
+            );
+        }
+    }
+}
+
+/// Draws the average contact normal pushing on each dynamic body, from the body's center.
+fn draw_contact_normals(
+    mut gizmos: Gizmos,
+    rapier_context: Res<RapierContext>,
+    bodies: Query<(&GlobalTransform, &RigidBody)>,
+) {
+    for pair in rapier_context.contact_pairs() {
+        if !pair.has_any_active_contacts() {
+            continue;
+        }
+        let normal: Vec3 = pair.manifolds().map(|manifold| manifold.normal()).sum();
+        let normal = normal.normalize_or_zero();
+
+        // The manifold normal points from the first collider towards the second one, so the
+        // second body is pushed along it and the first one against it.
+        for (collider, normal) in [(pair.collider1(), -normal), (pair.collider2(), normal)] {
+            let body = rapier_context.collider_parent(collider).unwrap_or(collider);
+            if let Ok((transform, RigidBody::Dynamic)) = bodies.get(body) {
+                arrow(
+                    &mut gizmos,
+                    transform.translation(),
+                    normal * NORMAL_LENGTH,
+                    Color::YELLOW,
+                );
+            }
+        }
+    }
+}
+
+/// Draws a marker and a short normal at each solver contact of the narrow phase. Penetrating
+/// contacts are red, speculative (not yet touching) ones green.
+fn draw_contact_points(mut gizmos: Gizmos, rapier_context: Res<RapierContext>) {
+    for pair in rapier_context.contact_pairs() {
+        if !pair.has_any_active_contacts() {
+            continue;
+        }
+        for manifold in pair.manifolds() {
+            let normal = manifold.normal();
+            for contact in manifold.solver_contacts() {
+                let color = if contact.dist() < 0.0 {
+                    Color::RED
+                } else {
+                    Color::GREEN
+                };
+                gizmos
+                    .sphere(contact.point(), Quat::IDENTITY, CONTACT_POINT_RADIUS, color)
+                    .circle_segments(8);
+                arrow(
+                    &mut gizmos,
+                    contact.point(),
+                    normal * CONTACT_NORMAL_LENGTH,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Colors collider wireframes by their first collision group so colliders in the same group share
+/// a color. Colliders in every group (the default) keep Rapier's own coloring.
+fn tint_colliders_by_group(
+    mut commands: Commands,
+    colliders: Query<
+        (Entity, Option<&CollisionGroups>),
+        (
+            With<Collider>,
+            Or<(Added<Collider>, Changed<CollisionGroups>)>,
+        ),
+    >,
+    mut removed_groups: RemovedComponents<CollisionGroups>,
+) {
+    for (entity, groups) in &colliders {
+        match groups.filter(|groups| groups.memberships != Group::ALL) {
+            Some(groups) => {
+                let index = groups.memberships.bits().trailing_zeros() as usize;
+                commands
+                    .entity(entity)
+                    .insert(ColliderDebugColor(GROUP_COLORS[index % GROUP_COLORS.len()]));
+            }
+            None => {
+                commands.entity(entity).remove::<ColliderDebugColor>();
+            }
+        }
+    }
+
+    for entity in removed_groups.read() {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<ColliderDebugColor>();
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! An F3-style debug overlay showing the player, the current state and simulation statistics.
+//!
+//! The panel also lists the [`PhysicsTuning`] and the settings of the player's character
+//! controller: while it is shown, `[` and `]` select a value and `-` and `=` change it, live.
+//!
+//! The panel is hidden by default and independent from the bottom-right [`crate::hud::TextChanges`]
+//! text, which always stays on screen.
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::conditions::text_input_closed;
+use crate::game_set::GameSet;
+use crate::launch_options::LaunchOptions;
+use crate::physics_setup::{
+    ControllerParameter, PhysicsDiagnosticsPlugin, PhysicsTuning, TuningParameter,
+};
+use crate::text_theme::TextTheme;
+use crate::{MyStates, Player};
+
+pub struct DebugUiPlugin;
+
+impl Plugin for DebugUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TuningFocus>()
+            .add_systems(Startup, spawn_debug_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_panel,
+                    // The editor uses the same keys for its property panel.
+                    adjust_physics_tuning.run_if(
+                        debug_panel_visible
+                            .and_then(text_input_closed())
+                            .and_then(not(in_state(MyStates::Editor))),
+                    ),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                (update_debug_panel, update_tuning_text)
+                    .run_if(debug_panel_visible)
+                    .in_set(GameSet::Ui),
+            );
+    }
+}
+
+/// Root node of the debug panel, toggled with F3.
+#[derive(Component)]
+struct DebugPanel;
+
+/// The text listing the [`PhysicsTuning`] and the player's controller settings.
+#[derive(Component)]
+struct TuningText;
+
+/// Index of the value `-` and `=` change, into [`TuningParameter::ALL`] followed by
+/// [`ControllerParameter::ALL`].
+#[derive(Resource, Default)]
+struct TuningFocus(usize);
+
+/// A value listed under the simulation statistics.
+#[derive(Clone, Copy)]
+enum TuningLine {
+    Physics(TuningParameter),
+    Controller(ControllerParameter),
+}
+
+impl TuningLine {
+    fn all() -> impl Iterator<Item = TuningLine> {
+        TuningParameter::ALL
+            .into_iter()
+            .map(TuningLine::Physics)
+            .chain(
+                ControllerParameter::ALL
+                    .into_iter()
+                    .map(TuningLine::Controller),
+            )
+    }
+}
+
+/// Which line of the debug panel a text section shows.
+#[derive(Clone, Copy)]
+enum DebugLine {
+    Position,
+    Velocity,
+    Grounded,
+    State,
+    Fps,
+    Bodies,
+    Seed,
+}
+
+impl DebugLine {
+    const ALL: [DebugLine; 7] = [
+        DebugLine::Position,
+        DebugLine::Velocity,
+        DebugLine::Grounded,
+        DebugLine::State,
+        DebugLine::Fps,
+        DebugLine::Bodies,
+        DebugLine::Seed,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            DebugLine::Position => "position: ",
+            DebugLine::Velocity => "\nvelocity: ",
+            DebugLine::Grounded => "\ngrounded: ",
+            DebugLine::State => "\nstate: ",
+            DebugLine::Fps => "\nfps: ",
+            DebugLine::Bodies => "\nbodies: ",
+            DebugLine::Seed => "\nseed: ",
+        }
+    }
+
+    /// Index of the value section following this line's label.
+    fn section(self) -> usize {
+        self as usize * 2 + 1
+    }
+}
+
+fn spawn_debug_panel(mut commands: Commands, theme: Res<TextTheme>, options: Res<LaunchOptions>) {
+    let label_style = theme.muted();
+    let value_style = theme.small();
+
+    let sections = DebugLine::ALL.into_iter().flat_map(|line| {
+        [
+            TextSection::new(line.label(), label_style.clone()),
+            TextSection::new("-", value_style.clone()),
+        ]
+    });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(5.0),
+                    left: Val::Px(5.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                visibility: if options.debug.panel {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                },
+                ..default()
+            },
+            DebugPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections(sections));
+            parent.spawn((
+                TextBundle::from_section("", theme.small()).with_style(Style {
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..default()
+                }),
+                TuningText,
+            ));
+        });
+}
+
+fn toggle_debug_panel(
+    input: Res<Input<KeyCode>>,
+    mut panel: Query<&mut Visibility, With<DebugPanel>>,
+) {
+    if !input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    for mut visibility in &mut panel {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn debug_panel_visible(panel: Query<&Visibility, With<DebugPanel>>) -> bool {
+    panel
+        .iter()
+        .any(|visibility| *visibility != Visibility::Hidden)
+}
+
+fn update_debug_panel(
+    time: Res<Time>,
+    state: Res<State<MyStates>>,
+    options: Res<LaunchOptions>,
+    diagnostics: Res<DiagnosticsStore>,
+    player: Query<(&Transform, Option<&KinematicCharacterControllerOutput>), With<Player>>,
+    panel: Query<&Children, With<DebugPanel>>,
+    mut texts: Query<&mut Text, Without<TuningText>>,
+) {
+    let smoothed = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or(0.0)
+    };
+
+    let (position, velocity, grounded) = match player.get_single() {
+        Ok((transform, output)) => {
+            let velocity = output
+                .filter(|_| time.delta_seconds() > 0.0)
+                .map(|output| output.effective_translation / time.delta_seconds())
+                .unwrap_or(Vec3::ZERO);
+            let grounded = output.is_some_and(|output| output.grounded);
+            (
+                format!("{:.2}", transform.translation),
+                format!("{velocity:.2} ({:.2} m/s)", velocity.length()),
+                grounded.to_string(),
+            )
+        }
+        Err(_) => ("-".to_string(), "-".to_string(), "-".to_string()),
+    };
+
+    for children in &panel {
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            for line in DebugLine::ALL {
+                text.sections[line.section()].value = match line {
+                    DebugLine::Position => position.clone(),
+                    DebugLine::Velocity => velocity.clone(),
+                    DebugLine::Grounded => grounded.clone(),
+                    DebugLine::State => format!("{:?}", state.get()),
+                    DebugLine::Fps => format!("{:.1}", smoothed(FrameTimeDiagnosticsPlugin::FPS)),
+                    DebugLine::Bodies => format!(
+                        "{:.0} ({:.0} active, {:.0} contacts)",
+                        smoothed(PhysicsDiagnosticsPlugin::RIGID_BODY_COUNT),
+                        smoothed(PhysicsDiagnosticsPlugin::ACTIVE_BODY_COUNT),
+                        smoothed(PhysicsDiagnosticsPlugin::CONTACT_PAIR_COUNT),
+                    ),
+                    DebugLine::Seed => options.seed.to_string(),
+                };
+            }
+        }
+    }
+}
+
+fn adjust_physics_tuning(
+    input: Res<Input<KeyCode>>,
+    mut focus: ResMut<TuningFocus>,
+    mut tuning: ResMut<PhysicsTuning>,
+    mut player: Query<&mut KinematicCharacterController, With<Player>>,
+) {
+    let count = TuningParameter::ALL.len() + ControllerParameter::ALL.len();
+    if input.just_pressed(KeyCode::BracketLeft) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+    if input.just_pressed(KeyCode::BracketRight) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+    let steps =
+        input.just_pressed(KeyCode::Equals) as i32 - input.just_pressed(KeyCode::Minus) as i32;
+    if steps == 0 {
+        return;
+    }
+    match TuningLine::all().nth(focus.0) {
+        Some(TuningLine::Physics(parameter)) => parameter.adjust(&mut tuning, steps),
+        Some(TuningLine::Controller(parameter)) => {
+            if let Ok(mut controller) = player.get_single_mut() {
+                parameter.adjust(&mut controller, steps);
+            }
+        }
+        None => {}
+    }
+}
+
+fn update_tuning_text(
+    tuning: Res<PhysicsTuning>,
+    focus: Res<TuningFocus>,
+    player: Query<&KinematicCharacterController, With<Player>>,
+    mut texts: Query<&mut Text, With<TuningText>>,
+) {
+    // The player's controller changes every frame, so there's no point checking for changes.
+    let controller = player.get_single().ok();
+    let lines: Vec<String> = TuningLine::all()
+        .enumerate()
+        .map(|(index, line)| {
+            let marker = if index == focus.0 { ">" } else { " " };
+            let (label, value) = match line {
+                TuningLine::Physics(parameter) => (parameter.label(), parameter.value(&tuning)),
+                TuningLine::Controller(parameter) => (
+                    parameter.label(),
+                    controller.map_or("-".to_string(), |controller| parameter.value(controller)),
+                ),
+            };
+            format!("{marker} {label}: {value}")
+        })
+        .collect();
+    for mut text in &mut texts {
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,206 @@
+//! Marks left on the level: scuffs where projectiles first hit it and scorches under explosions.
+//!
+//! A [`PlaceDecal`] lays a soft round quad flat on the surface at the point, turned to the normal
+//! of the ray that found it and lifted just off it. Decals stay for their kind's [`Lifetime`] and
+//! [`FadeOut`] at its end, and only the newest [`MAX_DECALS`] are kept, so a long fight doesn't
+//! pile them up.
+
+use std::collections::VecDeque;
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_rapier3d::prelude::*;
+
+use crate::lifetime::{FadeOut, Lifetime};
+use crate::particles::{BurstKind, ParticleBurst};
+
+/// Decals kept at most; placing another removes the oldest.
+pub const MAX_DECALS: usize = 64;
+/// How far above the surface decals sit, in meters, so they don't flicker into it.
+const SURFACE_OFFSET: f32 = 0.01;
+/// How far below an explosion a surface gets scorched, in meters.
+const SCORCH_REACH: f32 = 3.0;
+/// Width and height of the decal texture, in pixels.
+const TEXTURE_SIZE: u32 = 64;
+
+pub struct DecalsPlugin;
+
+impl Plugin for DecalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaceDecal>()
+            .init_resource::<DecalAssets>()
+            .init_resource::<PlacedDecals>()
+            .add_systems(Update, (scorch_under_explosions, place_decals).chain());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecalKind {
+    /// Where a projectile hit.
+    Mark,
+    /// Under an explosion.
+    Scorch,
+}
+
+impl DecalKind {
+    /// Width of a decal of strength 1, in meters.
+    fn size(self) -> f32 {
+        match self {
+            DecalKind::Mark => 0.35,
+            DecalKind::Scorch => 2.0,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            DecalKind::Mark => Color::rgba(0.15, 0.12, 0.1, 0.7),
+            DecalKind::Scorch => Color::rgba(0.05, 0.04, 0.03, 0.9),
+        }
+    }
+
+    /// Seconds a decal stays at full strength, and then takes to fade out.
+    fn lifetime(self) -> f32 {
+        match self {
+            DecalKind::Mark => 20.0,
+            DecalKind::Scorch => 40.0,
+        }
+    }
+
+    fn fade_time(self) -> f32 {
+        match self {
+            DecalKind::Mark => 3.0,
+            DecalKind::Scorch => 8.0,
+        }
+    }
+}
+
+/// Sent to leave a mark on the surface at `point`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaceDecal {
+    pub kind: DecalKind,
+    pub point: Vec3,
+    /// The surface's normal at `point`.
+    pub normal: Vec3,
+    /// Scales the decal's size; 1 for a typical hit.
+    pub strength: f32,
+}
+
+/// A decal on a surface.
+#[derive(Component)]
+pub struct Decal;
+
+#[derive(Resource)]
+struct DecalAssets {
+    /// A square facing up, one meter wide.
+    mesh: Handle<Mesh>,
+    /// A round mark, solid in the middle and fading to nothing at the edge.
+    texture: Handle<Image>,
+}
+
+impl FromWorld for DecalAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(shape::Plane::from_size(1.0).into());
+        let half = TEXTURE_SIZE as f32 / 2.0;
+        let data = (0..TEXTURE_SIZE * TEXTURE_SIZE)
+            .flat_map(|index| {
+                let x = (index % TEXTURE_SIZE) as f32 + 0.5 - half;
+                let y = (index / TEXTURE_SIZE) as f32 + 0.5 - half;
+                let distance = Vec2::new(x, y).length() / half;
+                let alpha = (1.0 - distance * distance).clamp(0.0, 1.0);
+                [255, 255, 255, (alpha * 255.0) as u8]
+            })
+            .collect();
+        let texture = world.resource_mut::<Assets<Image>>().add(Image::new(
+            Extent3d {
+                width: TEXTURE_SIZE,
+                height: TEXTURE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        ));
+        Self { mesh, texture }
+    }
+}
+
+/// The decals placed, oldest first.
+#[derive(Resource, Default)]
+struct PlacedDecals(VecDeque<Entity>);
+
+/// Scorches the ground under explosions.
+fn scorch_under_explosions(
+    rapier_context: Res<RapierContext>,
+    mut bursts: EventReader<ParticleBurst>,
+    mut decals: EventWriter<PlaceDecal>,
+) {
+    for burst in bursts.read() {
+        if burst.kind != BurstKind::Explosion {
+            continue;
+        }
+        let hit = rapier_context.cast_ray_and_get_normal(
+            burst.position,
+            Vec3::NEG_Y,
+            SCORCH_REACH,
+            true,
+            QueryFilter::only_fixed().exclude_sensors(),
+        );
+        if let Some((_, intersection)) = hit {
+            decals.send(PlaceDecal {
+                kind: DecalKind::Scorch,
+                point: intersection.point,
+                normal: intersection.normal,
+                strength: burst.strength,
+            });
+        }
+    }
+}
+
+fn place_decals(
+    mut commands: Commands,
+    assets: Res<DecalAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut placed: ResMut<PlacedDecals>,
+    mut events: EventReader<PlaceDecal>,
+) {
+    for event in events.read() {
+        let normal = event.normal.try_normalize().unwrap_or(Vec3::Y);
+        // Each decal has its own material, to fade on its own.
+        let material = materials.add(StandardMaterial {
+            base_color: event.kind.color(),
+            base_color_texture: Some(assets.texture.clone()),
+            alpha_mode: AlphaMode::Blend,
+            perceptual_roughness: 1.0,
+            depth_bias: 1.0,
+            ..default()
+        });
+        let decal = commands
+            .spawn((
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material,
+                    transform: Transform::from_translation(event.point + normal * SURFACE_OFFSET)
+                        .with_rotation(Quat::from_rotation_arc(Vec3::Y, normal))
+                        .with_scale(Vec3::splat(event.kind.size() * event.strength)),
+                    ..default()
+                },
+                NotShadowCaster,
+                Lifetime::from_seconds(event.kind.lifetime() + event.kind.fade_time()),
+                FadeOut::new(event.kind.fade_time()),
+                Decal,
+                Name::new("Decal"),
+            ))
+            .id();
+        placed.0.push_back(decal);
+        while placed.0.len() > MAX_DECALS {
+            if let Some(oldest) = placed.0.pop_front() {
+                if let Some(entity) = commands.get_entity(oldest) {
+                    entity.despawn_recursive();
+                }
+            }
+        }
+    }
+}
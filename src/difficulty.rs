@@ -0,0 +1,118 @@
+//! How hard the game is: the [`Difficulty`] picked on the settings screen, and saved with the
+//! [`Settings`], scales how fast enemies walk, how much hazards and falls hurt, how high a fall
+//! has to be to hurt at all, and how often spawners drop their props.
+//!
+//! `difficulty [easy|normal|hard]` in the console shows or changes it.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::console::ConsoleAppExt;
+use crate::settings::Settings;
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_console_command(
+                "difficulty",
+                "difficulty [easy|normal|hard] - show or change how hard the game is",
+                difficulty_command,
+            )
+            .add_systems(
+                PreUpdate,
+                apply_difficulty.run_if(resource_changed::<Settings>()),
+            );
+    }
+}
+
+/// The difficulty in play, following [`Settings::difficulty`].
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// Localization key of the difficulty's name.
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+
+    /// The difficulty `offset` places along [`Difficulty::ALL`], wrapping around.
+    pub fn cycle(self, offset: isize) -> Self {
+        let count = Self::ALL.len() as isize;
+        Self::ALL[(self as isize + offset).rem_euclid(count) as usize]
+    }
+
+    /// Scales enemies' walking speed.
+    pub fn enemy_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    /// Scales the damage characters take from the level's hazards and from falls.
+    pub fn damage(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Scales how often spawners spawn.
+    pub fn spawn_rate(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Scales the height a fall has to be to hurt.
+    pub fn fall_height(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.75,
+        }
+    }
+}
+
+fn apply_difficulty(settings: Res<Settings>, mut difficulty: ResMut<Difficulty>) {
+    if *difficulty != settings.difficulty {
+        *difficulty = settings.difficulty;
+    }
+}
+
+fn difficulty_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let Some(name) = args.first() else {
+        return Ok(world.resource::<Difficulty>().name().to_string());
+    };
+    let difficulty = Difficulty::ALL
+        .into_iter()
+        .find(|difficulty| difficulty.name() == *name)
+        .ok_or_else(|| format!("unknown difficulty {name}"))?;
+    world.resource_mut::<Settings>().difficulty = difficulty;
+    Ok(format!("difficulty set to {name}"))
+}
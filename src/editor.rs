@@ -0,0 +1,1252 @@
+//! The level editor, [`MyStates::Editor`], entered and left with F2 from the sandbox.
+//!
+//! Physics is paused while editing. The camera flies freely: WASD moves it, Q and E lower and
+//! raise it, and it looks around while the right mouse button is held. Tab picks the prefab from
+//! the palette of loaded [`Prefabs`], and a left click places it where the cursor points, or
+//! selects the placed entity clicked.
+//!
+//! The selection moves one grid step with the arrow keys and Page Up and Page Down, turns by the
+//! snap angle with R (Shift+R the other way), is duplicated with Ctrl+D and deleted with Delete.
+//! G and H cycle through the [`GRID_STEPS`] and [`ANGLE_STEPS`] everything snaps to.
+//!
+//! The selection also shows a [`GizmoMode`] handle for each axis, picked with 1, 2 and 3: dragging
+//! a handle with the left mouse button moves the selection along the axis, turns it around the
+//! axis or scales it along it. Rapier rescales the collider with the transform.
+//!
+//! The property panel in the bottom right corner shows what the selection is made of: [ and ]
+//! pick a property, and - and = change its value, the material, surface, body type, density or
+//! collision group used instead of the prefab's. The properties are saved with the level.
+//!
+//! Placing, deleting, moving, turning, scaling and property changes are recorded in the
+//! [`EditorHistory`]: Ctrl+Z undoes the last of them and Ctrl+Y (or Ctrl+Shift+Z) redoes it, up to
+//! [`MAX_HISTORY`] operations back.
+//!
+//! Ctrl+S writes everything placed in the editor to [`EDITOR_LEVEL_PATH`], which is spawned again
+//! on the next start.
+
+use std::collections::VecDeque;
+use std::fs;
+
+use crate::assets::{asset_root, Models};
+use crate::camera::{overview_transform, CameraMode, MainCamera};
+use crate::conditions::text_input_closed;
+use crate::level_data::{BodyKind, EntityData, LevelData, LevelSpawner, Prefabs};
+use crate::materials::MaterialPreset;
+use crate::settings::Settings;
+use crate::surface::Surface;
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+use bevy::asset::LoadState;
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_rapier3d::prelude::*;
+
+/// Where the editor saves the level it authored, relative to the [`asset_root`].
+pub const EDITOR_LEVEL_PATH: &str = "levels/editor.level.ron";
+/// Speed of the free camera, in meters per second.
+const CAMERA_SPEED: f32 = 8.0;
+/// Farthest the cursor can place or remove things, in meters.
+const PICK_RANGE: f32 = 200.0;
+/// Placed entities are lifted this far off the surface clicked, along its normal.
+const PLACE_OFFSET: f32 = 0.5;
+/// Grid sizes positions snap to, in meters; 0 turns snapping off.
+pub const GRID_STEPS: [f32; 5] = [0.0, 0.25, 0.5, 1.0, 2.0];
+/// Angles rotations snap to, in degrees; 0 turns snapping off.
+pub const ANGLE_STEPS: [f32; 4] = [0.0, 15.0, 45.0, 90.0];
+/// How far the selection moves and turns when snapping is off.
+const FREE_STEP: f32 = 0.1;
+const FREE_ANGLE_STEP: f32 = 5.0;
+/// Length of the gizmo's axis handles, in meters.
+const HANDLE_LENGTH: f32 = 1.5;
+/// How close the cursor has to be to a handle to grab it, in pixels.
+const HANDLE_PICK_DISTANCE: f32 = 10.0;
+/// Radians turned per pixel dragged along a rotation handle.
+const ROTATE_SPEED: f32 = 0.01;
+/// Smallest scale the gizmo shrinks an axis to.
+const MIN_SCALE: f32 = 0.05;
+/// Densities the property panel offers, in kilograms per cubic meter.
+const DENSITIES: [f32; 6] = [0.1, 0.5, 1.0, 2.0, 5.0, 10.0];
+/// Collision groups the property panel offers; each is a member of one group and collides with
+/// every group.
+const COLLISION_GROUPS: [(u32, u32); 4] = [
+    (1 << 0, u32::MAX),
+    (1 << 1, u32::MAX),
+    (1 << 2, u32::MAX),
+    (1 << 3, u32::MAX),
+];
+/// Most operations the [`EditorHistory`] keeps; older ones can no longer be undone.
+pub const MAX_HISTORY: usize = 100;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorPalette>()
+            .init_resource::<EditorSnap>()
+            .init_resource::<EditorSelection>()
+            .init_resource::<EditorHistory>()
+            .init_resource::<GizmoMode>()
+            .init_resource::<GizmoDrag>()
+            .init_resource::<PropertyFocus>()
+            .add_systems(OnEnter(MyStates::Next), load_saved_level.run_if(run_once()))
+            .add_systems(OnEnter(MyStates::Editor), enter_editor)
+            .add_systems(OnExit(MyStates::Editor), exit_editor)
+            .add_systems(
+                Update,
+                (
+                    spawn_saved_level.run_if(resource_exists::<PendingEditorLevel>()),
+                    toggle_editor.run_if(
+                        in_state(MyStates::Next)
+                            .or_else(in_state(MyStates::Editor))
+                            .and_then(text_input_closed()),
+                    ),
+                    (
+                        fly_camera,
+                        select_prefab,
+                        change_snapping,
+                        select_gizmo_mode,
+                        drag_gizmo,
+                        click,
+                        edit_selection,
+                        undo_redo,
+                        edit_properties,
+                        save_level,
+                        update_palette_text,
+                        update_property_text,
+                        draw_selection,
+                        draw_gizmo,
+                    )
+                        .chain()
+                        .run_if(in_state(MyStates::Editor).and_then(text_input_closed())),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// An entity placed with the editor, saved with the level.
+#[derive(Component, Clone, Debug)]
+pub struct EditorPlaced {
+    pub prefab: String,
+    /// Stays the same when the entity is deleted and spawned again by undo and redo.
+    id: u32,
+    pub properties: PlacedProperties,
+}
+
+impl EditorPlaced {
+    /// The level entity this is saved as, at `transform`.
+    fn data(&self, transform: Transform) -> EntityData {
+        EntityData {
+            name: self.prefab.clone(),
+            translation: transform.translation,
+            rotation: transform.rotation,
+            scale: transform.scale,
+            prefab: Some(self.prefab.clone()),
+            material: self.properties.material.clone(),
+            body: self.properties.body,
+            surface: self.properties.surface,
+            density: self.properties.density,
+            collision_groups: self.properties.collision_groups,
+            ..default()
+        }
+    }
+}
+
+/// What the property panel sets instead of the prefab's; `None` keeps the prefab's.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct PlacedProperties {
+    /// A [`crate::materials::MaterialLibrary::by_name`] name.
+    pub material: Option<String>,
+    pub surface: Option<Surface>,
+    pub body: Option<BodyKind>,
+    pub density: Option<f32>,
+    /// Membership and filter bits.
+    pub collision_groups: Option<(u32, u32)>,
+}
+
+/// A row of the property panel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PropertyRow {
+    Material,
+    Surface,
+    Body,
+    Density,
+    CollisionGroup,
+}
+
+impl PropertyRow {
+    const ALL: [PropertyRow; 5] = [
+        PropertyRow::Material,
+        PropertyRow::Surface,
+        PropertyRow::Body,
+        PropertyRow::Density,
+        PropertyRow::CollisionGroup,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PropertyRow::Material => "material",
+            PropertyRow::Surface => "surface",
+            PropertyRow::Body => "body",
+            PropertyRow::Density => "density",
+            PropertyRow::CollisionGroup => "group",
+        }
+    }
+
+    fn value(self, properties: &PlacedProperties) -> String {
+        let value = match self {
+            PropertyRow::Material => properties.material.clone(),
+            PropertyRow::Surface => properties.surface.map(|surface| format!("{surface:?}")),
+            PropertyRow::Body => properties.body.map(|body| format!("{body:?}")),
+            PropertyRow::Density => properties.density.map(|density| format!("{density} kg/m³")),
+            PropertyRow::CollisionGroup => properties
+                .collision_groups
+                .map(|(memberships, _)| format!("{}", memberships.trailing_zeros() + 1)),
+        };
+        value.unwrap_or_else(|| "prefab's".to_string())
+    }
+
+    /// Moves the property `steps` values on through the values offered.
+    fn adjust(self, properties: &mut PlacedProperties, steps: i32) {
+        match self {
+            PropertyRow::Material => {
+                let names: Vec<String> = ["prototype", "floor", "crate"]
+                    .into_iter()
+                    .chain(MaterialPreset::ALL.map(MaterialPreset::name))
+                    .map(String::from)
+                    .collect();
+                properties.material = cycle(&properties.material, &names, steps);
+            }
+            PropertyRow::Surface => {
+                properties.surface = cycle(&properties.surface, &Surface::ALL, steps);
+            }
+            PropertyRow::Body => {
+                let bodies = [BodyKind::Fixed, BodyKind::Dynamic, BodyKind::Kinematic];
+                properties.body = cycle(&properties.body, &bodies, steps);
+            }
+            PropertyRow::Density => {
+                properties.density = cycle(&properties.density, &DENSITIES, steps);
+            }
+            PropertyRow::CollisionGroup => {
+                properties.collision_groups =
+                    cycle(&properties.collision_groups, &COLLISION_GROUPS, steps);
+            }
+        }
+    }
+}
+
+/// The value `steps` after `current` in `None` followed by `options`, wrapping around.
+fn cycle<T: Clone + PartialEq>(current: &Option<T>, options: &[T], steps: i32) -> Option<T> {
+    let index = current
+        .as_ref()
+        .and_then(|current| options.iter().position(|option| option == current))
+        .map_or(0, |index| index + 1);
+    let next = (index as i32 + steps).rem_euclid(options.len() as i32 + 1) as usize;
+    next.checked_sub(1).map(|next| options[next].clone())
+}
+
+/// The focused row of the property panel, as an index into [`PropertyRow::ALL`].
+#[derive(Resource, Default)]
+struct PropertyFocus(usize);
+
+#[derive(Component)]
+struct PropertyPanel;
+
+/// The prefab placed by a click, as an index into the sorted prefab names.
+#[derive(Resource, Default)]
+struct EditorPalette {
+    selected: usize,
+}
+
+/// Indices into [`GRID_STEPS`] and [`ANGLE_STEPS`] of the current snapping.
+#[derive(Resource)]
+pub struct EditorSnap {
+    grid: usize,
+    angle: usize,
+}
+
+impl Default for EditorSnap {
+    fn default() -> Self {
+        Self { grid: 3, angle: 1 }
+    }
+}
+
+impl EditorSnap {
+    /// Grid size in meters, 0 if off.
+    pub fn grid(&self) -> f32 {
+        GRID_STEPS[self.grid]
+    }
+
+    /// Snap angle in degrees, 0 if off.
+    pub fn angle(&self) -> f32 {
+        ANGLE_STEPS[self.angle]
+    }
+
+    fn step(&self) -> f32 {
+        if self.grid() > 0.0 {
+            self.grid()
+        } else {
+            FREE_STEP
+        }
+    }
+
+    fn angle_step(&self) -> f32 {
+        if self.angle() > 0.0 {
+            self.angle().to_radians()
+        } else {
+            FREE_ANGLE_STEP.to_radians()
+        }
+    }
+
+    /// `position` with x and z on the grid.
+    fn position(&self, position: Vec3) -> Vec3 {
+        let grid = self.grid();
+        if grid == 0.0 {
+            return position;
+        }
+        let snap = |value: f32| (value / grid).round() * grid;
+        Vec3::new(snap(position.x), position.y, snap(position.z))
+    }
+
+    /// `distance` rounded to the grid.
+    fn distance(&self, distance: f32) -> f32 {
+        let grid = self.grid();
+        if grid == 0.0 {
+            return distance;
+        }
+        (distance / grid).round() * grid
+    }
+
+    /// `angle` rounded to the snap angle, in radians.
+    fn round_angle(&self, angle: f32) -> f32 {
+        let step = self.angle().to_radians();
+        if step == 0.0 {
+            return angle;
+        }
+        (angle / step).round() * step
+    }
+}
+
+/// An edit that can be undone, naming placed entities by [`EditorPlaced`] id.
+#[derive(Clone, Debug)]
+enum EditorOp {
+    Spawn {
+        placed: EditorPlaced,
+        transform: Transform,
+    },
+    Delete {
+        placed: EditorPlaced,
+        transform: Transform,
+    },
+    Move {
+        id: u32,
+        before: Transform,
+        after: Transform,
+    },
+    Properties {
+        id: u32,
+        before: PlacedProperties,
+        after: PlacedProperties,
+    },
+}
+
+impl EditorOp {
+    /// The operation undoing this one.
+    fn inverse(self) -> Self {
+        match self {
+            EditorOp::Spawn { placed, transform } => EditorOp::Delete { placed, transform },
+            EditorOp::Delete { placed, transform } => EditorOp::Spawn { placed, transform },
+            EditorOp::Move { id, before, after } => EditorOp::Move {
+                id,
+                before: after,
+                after: before,
+            },
+            EditorOp::Properties { id, before, after } => EditorOp::Properties {
+                id,
+                before: after,
+                after: before,
+            },
+        }
+    }
+}
+
+/// Operations to undo, most recent last, and the ones undone that can be redone.
+#[derive(Resource, Default)]
+pub struct EditorHistory {
+    undo: VecDeque<EditorOp>,
+    redo: Vec<EditorOp>,
+    next_id: u32,
+}
+
+impl EditorHistory {
+    /// An id for a newly placed entity.
+    fn new_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Adds an operation just done, forgetting what was undone before it.
+    fn record(&mut self, op: EditorOp) {
+        self.redo.clear();
+        self.undo.push_back(op);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.pop_front();
+        }
+    }
+}
+
+/// What dragging the selection's handles does.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// The handle being dragged, and the selection's transform and the cursor when it was grabbed.
+#[derive(Resource, Default)]
+struct GizmoDrag(Option<Drag>);
+
+struct Drag {
+    axis: usize,
+    before: Transform,
+    start: Vec2,
+    /// The handle on screen, from the selection to its tip, in pixels.
+    handle: Vec2,
+}
+
+/// The axis handles of the gizmo on `transform`: local axes when scaling, world axes otherwise.
+fn gizmo_axes(mode: GizmoMode, transform: &Transform) -> [Vec3; 3] {
+    match mode {
+        GizmoMode::Scale => [transform.right(), transform.up(), transform.back()],
+        GizmoMode::Translate | GizmoMode::Rotate => [Vec3::X, Vec3::Y, Vec3::Z],
+    }
+}
+
+const AXIS_COLORS: [Color; 3] = [Color::RED, Color::GREEN, Color::BLUE];
+
+/// The placed entity edited by the keyboard.
+#[derive(Resource, Default)]
+struct EditorSelection(Option<Entity>);
+
+#[derive(Component)]
+struct EditorPanel;
+
+/// The level the editor saved last time, while it loads.
+#[derive(Resource)]
+struct PendingEditorLevel(Handle<LevelData>);
+
+fn prefab_names(models: &Models, prefabs: &Assets<Prefabs>) -> Vec<String> {
+    prefabs
+        .get(&models.prefabs)
+        .map(|prefabs| prefabs.0.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Loads what the editor saved last time, if anything.
+fn load_saved_level(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if asset_root().join(EDITOR_LEVEL_PATH).exists() {
+        commands.insert_resource(PendingEditorLevel(asset_server.load(EDITOR_LEVEL_PATH)));
+    }
+}
+
+/// Spawns the saved level once it's loaded.
+fn spawn_saved_level(
+    mut commands: Commands,
+    mut spawner: LevelSpawner,
+    mut history: ResMut<EditorHistory>,
+    pending: Res<PendingEditorLevel>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<LevelData>>,
+) {
+    let Some(level) = levels.get(&pending.0) else {
+        if asset_server.get_load_state(&pending.0) == Some(LoadState::Failed) {
+            warn!("ignoring invalid {EDITOR_LEVEL_PATH}");
+            commands.remove_resource::<PendingEditorLevel>();
+        }
+        return;
+    };
+    commands.remove_resource::<PendingEditorLevel>();
+    for data in &level.entities {
+        let entity = spawner.spawn(data);
+        if let Some(prefab) = &data.prefab {
+            commands.entity(entity).insert(EditorPlaced {
+                prefab: prefab.clone(),
+                id: history.new_id(),
+                properties: PlacedProperties {
+                    material: data.material.clone(),
+                    surface: data.surface,
+                    body: data.body,
+                    density: data.density,
+                    collision_groups: data.collision_groups,
+                },
+            });
+        }
+    }
+    info!(
+        "spawned {} entities from {EDITOR_LEVEL_PATH}",
+        level.entities.len()
+    );
+}
+
+fn toggle_editor(
+    input: Res<Input<KeyCode>>,
+    state: Res<State<MyStates>>,
+    mut next_state: ResMut<NextState<MyStates>>,
+) {
+    if !input.just_pressed(KeyCode::F2) {
+        return;
+    }
+    next_state.set(if *state.get() == MyStates::Editor {
+        MyStates::Next
+    } else {
+        MyStates::Editor
+    });
+}
+
+fn enter_editor(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    mut rapier: ResMut<RapierConfiguration>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    rapier.physics_pipeline_active = false;
+    *camera_mode = CameraMode::Overview;
+    for mut window in &mut windows {
+        window.cursor.visible = true;
+        window.cursor.grab_mode = CursorGrabMode::None;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(5.0),
+                    right: Val::Px(5.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            EditorPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new("EDITOR\n", theme.heading()),
+                TextSection::new(
+                    "WASD/QE: move, right mouse: look\nTab: next prefab, click: place or select\narrows/PgUp/PgDn: move, R: turn\nCtrl+D: duplicate, Del: delete\nG/H: grid/angle snap, Ctrl+Z/Y: undo/redo\n1/2/3: move/turn/scale handles\nCtrl+S: save, F2: play\n",
+                    theme.muted(),
+                ),
+                TextSection::new("", theme.small()),
+            ]));
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(5.0),
+                    right: Val::Px(5.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            PropertyPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([
+                TextSection::new("PROPERTIES\n", theme.heading()),
+                TextSection::new("[/]: pick, -/=: change\n", theme.muted()),
+                TextSection::new("", theme.small()),
+            ]));
+        });
+}
+
+fn exit_editor(
+    mut commands: Commands,
+    mut rapier: ResMut<RapierConfiguration>,
+    mut selection: ResMut<EditorSelection>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+    panels: Query<Entity, Or<(With<EditorPanel>, With<PropertyPanel>)>>,
+) {
+    rapier.physics_pipeline_active = true;
+    selection.0 = None;
+    for mut transform in &mut cameras {
+        *transform = overview_transform();
+    }
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn fly_camera(
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Ok(mut transform) = cameras.get_single_mut() else {
+        return;
+    };
+    if mouse.pressed(MouseButton::Right) {
+        let sensitivity = settings.input.mouse_sensitivity;
+        let (mut yaw, mut pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+        for event in motion.read() {
+            yaw -= event.delta.x * sensitivity;
+            pitch = (pitch - event.delta.y * sensitivity).clamp(-1.5, 1.5);
+        }
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+    } else {
+        motion.clear();
+    }
+
+    // Ctrl is for shortcuts, like Ctrl+S.
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let axis = |positive, negative| {
+        keys.pressed(positive) as i32 as f32 - keys.pressed(negative) as i32 as f32
+    };
+    let forward = transform.forward();
+    let right = transform.right();
+    let direction = forward * axis(KeyCode::W, KeyCode::S)
+        + right * axis(KeyCode::D, KeyCode::A)
+        + Vec3::Y * axis(KeyCode::E, KeyCode::Q);
+    transform.translation += direction.normalize_or_zero() * CAMERA_SPEED * time.delta_seconds();
+}
+
+fn select_prefab(
+    keys: Res<Input<KeyCode>>,
+    models: Res<Models>,
+    prefabs: Res<Assets<Prefabs>>,
+    mut palette: ResMut<EditorPalette>,
+) {
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+    let count = prefab_names(&models, &prefabs).len().max(1);
+    palette.selected = (palette.selected + 1) % count;
+}
+
+/// The ray from the main camera through the cursor.
+fn cursor_ray(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) -> Option<Ray> {
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    let (camera, transform) = cameras.get_single().ok()?;
+    camera.viewport_to_world(transform, cursor)
+}
+
+/// Selects the placed entity clicked, or places the palette's prefab where the cursor points and
+/// selects it.
+fn click(
+    mut commands: Commands,
+    mut spawner: LevelSpawner,
+    mouse: Res<Input<MouseButton>>,
+    models: Res<Models>,
+    prefabs: Res<Assets<Prefabs>>,
+    palette: Res<EditorPalette>,
+    snap: Res<EditorSnap>,
+    mut selection: ResMut<EditorSelection>,
+    mut history: ResMut<EditorHistory>,
+    drag: Res<GizmoDrag>,
+    rapier_context: Res<RapierContext>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    placed: Query<(), With<EditorPlaced>>,
+) {
+    // The press grabbed a gizmo handle instead.
+    if !mouse.just_pressed(MouseButton::Left) || drag.0.is_some() {
+        return;
+    }
+    let Some(ray) = cursor_ray(&windows, &cameras) else {
+        return;
+    };
+    let Some((entity, hit)) = rapier_context.cast_ray_and_get_normal(
+        ray.origin,
+        ray.direction,
+        PICK_RANGE,
+        true,
+        QueryFilter::default(),
+    ) else {
+        selection.0 = None;
+        return;
+    };
+    if placed.contains(entity) {
+        selection.0 = Some(entity);
+        return;
+    }
+
+    let Some(prefab) = prefab_names(&models, &prefabs)
+        .into_iter()
+        .nth(palette.selected)
+    else {
+        return;
+    };
+    let position = snap.position(hit.point + hit.normal * PLACE_OFFSET);
+    match spawner.spawn_prefab(&prefab, Transform::from_translation(position)) {
+        Ok(entity) => {
+            let placed = EditorPlaced {
+                prefab,
+                id: history.new_id(),
+                properties: default(),
+            };
+            history.record(EditorOp::Spawn {
+                placed: placed.clone(),
+                transform: Transform::from_translation(position),
+            });
+            commands.entity(entity).insert(placed);
+            selection.0 = Some(entity);
+        }
+        Err(error) => warn!("could not place `{prefab}`: {error}"),
+    }
+}
+
+fn change_snapping(keys: Res<Input<KeyCode>>, mut snap: ResMut<EditorSnap>) {
+    if keys.just_pressed(KeyCode::G) {
+        snap.grid = (snap.grid + 1) % GRID_STEPS.len();
+    }
+    if keys.just_pressed(KeyCode::H) {
+        snap.angle = (snap.angle + 1) % ANGLE_STEPS.len();
+    }
+}
+
+/// Moves, turns, duplicates and deletes the selected entity.
+fn edit_selection(
+    mut commands: Commands,
+    mut spawner: LevelSpawner,
+    keys: Res<Input<KeyCode>>,
+    snap: Res<EditorSnap>,
+    mut selection: ResMut<EditorSelection>,
+    mut history: ResMut<EditorHistory>,
+    mut placed: Query<(&EditorPlaced, &mut Transform, Option<&mut Velocity>)>,
+) {
+    let Some(entity) = selection.0 else {
+        return;
+    };
+    let Ok((placed, mut transform, velocity)) = placed.get_mut(entity) else {
+        selection.0 = None;
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Delete) {
+        history.record(EditorOp::Delete {
+            placed: placed.clone(),
+            transform: *transform,
+        });
+        commands.entity(entity).despawn_recursive();
+        selection.0 = None;
+        return;
+    }
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if ctrl && keys.just_pressed(KeyCode::D) {
+        let copy = transform.with_translation(transform.translation + Vec3::X * snap.step());
+        let placed = EditorPlaced {
+            id: history.new_id(),
+            ..placed.clone()
+        };
+        let duplicate = spawner.spawn(&placed.data(copy));
+        history.record(EditorOp::Spawn {
+            placed: placed.clone(),
+            transform: copy,
+        });
+        commands.entity(duplicate).insert(placed);
+        selection.0 = Some(duplicate);
+        return;
+    }
+
+    let axis = |positive, negative| {
+        keys.just_pressed(positive) as i32 as f32 - keys.just_pressed(negative) as i32 as f32
+    };
+    let nudge = Vec3::new(
+        axis(KeyCode::Right, KeyCode::Left),
+        axis(KeyCode::PageUp, KeyCode::PageDown),
+        axis(KeyCode::Down, KeyCode::Up),
+    );
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let turn = if keys.just_pressed(KeyCode::R) {
+        if shift {
+            -1.0
+        } else {
+            1.0
+        }
+    } else {
+        0.0
+    };
+    if nudge == Vec3::ZERO && turn == 0.0 {
+        return;
+    }
+
+    // Rapier picks up the new transform, and the body doesn't keep drifting once physics resumes.
+    let before = *transform;
+    transform.translation = snap.position(transform.translation + nudge * snap.step());
+    if turn != 0.0 {
+        // Turn about Y to the snapped yaw, keeping the pitch and roll.
+        let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+        let yaw = snap.round_angle(yaw + turn * snap.angle_step());
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+    }
+    if let Some(mut velocity) = velocity {
+        *velocity = Velocity::zero();
+    }
+    history.record(EditorOp::Move {
+        id: placed.id,
+        before,
+        after: *transform,
+    });
+}
+
+/// Ctrl+Z undoes the last operation, Ctrl+Y or Ctrl+Shift+Z redoes the last one undone.
+fn undo_redo(
+    mut commands: Commands,
+    mut spawner: LevelSpawner,
+    keys: Res<Input<KeyCode>>,
+    mut selection: ResMut<EditorSelection>,
+    mut history: ResMut<EditorHistory>,
+    mut placed: Query<(Entity, &EditorPlaced, &mut Transform, Option<&mut Velocity>)>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !ctrl {
+        return;
+    }
+    let redo = keys.just_pressed(KeyCode::Y) || (shift && keys.just_pressed(KeyCode::Z));
+    let undo = !shift && keys.just_pressed(KeyCode::Z);
+
+    let op = if undo {
+        let Some(op) = history.undo.pop_back() else {
+            return;
+        };
+        history.redo.push(op.clone());
+        op.inverse()
+    } else if redo {
+        let Some(op) = history.redo.pop() else {
+            return;
+        };
+        history.undo.push_back(op.clone());
+        op
+    } else {
+        return;
+    };
+
+    let find = |id: u32| {
+        placed
+            .iter()
+            .find_map(|(entity, placed, ..)| (placed.id == id).then_some(entity))
+    };
+    match op {
+        EditorOp::Spawn { placed, transform } => {
+            let entity = spawner.spawn(&placed.data(transform));
+            commands.entity(entity).insert(placed);
+            selection.0 = Some(entity);
+        }
+        EditorOp::Delete { placed, .. } => {
+            if let Some(entity) = find(placed.id) {
+                commands.entity(entity).despawn_recursive();
+                if selection.0 == Some(entity) {
+                    selection.0 = None;
+                }
+            }
+        }
+        EditorOp::Move { id, after, .. } => {
+            let Some(entity) = find(id) else {
+                return;
+            };
+            if let Ok((_, _, mut transform, velocity)) = placed.get_mut(entity) {
+                *transform = after;
+                if let Some(mut velocity) = velocity {
+                    *velocity = Velocity::zero();
+                }
+                selection.0 = Some(entity);
+            }
+        }
+        EditorOp::Properties { id, after, .. } => {
+            let Some(entity) = find(id) else {
+                return;
+            };
+            if let Ok((_, placed, transform, _)) = placed.get(entity) {
+                let placed = EditorPlaced {
+                    properties: after,
+                    ..placed.clone()
+                };
+                selection.0 = Some(respawn_placed(
+                    &mut commands,
+                    &mut spawner,
+                    entity,
+                    placed,
+                    *transform,
+                ));
+            }
+        }
+    }
+}
+
+fn select_gizmo_mode(keys: Res<Input<KeyCode>>, mut mode: ResMut<GizmoMode>) {
+    for (key, new_mode) in [
+        (KeyCode::Key1, GizmoMode::Translate),
+        (KeyCode::Key2, GizmoMode::Rotate),
+        (KeyCode::Key3, GizmoMode::Scale),
+    ] {
+        if keys.just_pressed(key) {
+            *mode = new_mode;
+        }
+    }
+}
+
+/// The handle of the selection's gizmo under the cursor, as an index into its axes, and the handle
+/// on screen.
+fn hovered_handle(
+    mode: GizmoMode,
+    transform: &Transform,
+    cursor: Vec2,
+    camera: (&Camera, &GlobalTransform),
+) -> Option<(usize, Vec2)> {
+    let (camera, camera_transform) = camera;
+    let origin = camera.world_to_viewport(camera_transform, transform.translation)?;
+    gizmo_axes(mode, transform)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(axis, direction)| {
+            let tip = camera.world_to_viewport(
+                camera_transform,
+                transform.translation + direction * HANDLE_LENGTH,
+            )?;
+            let handle = tip - origin;
+            let along = ((cursor - origin).dot(handle) / handle.length_squared()).clamp(0.0, 1.0);
+            let distance = cursor.distance(origin + handle * along);
+            (distance < HANDLE_PICK_DISTANCE).then_some((axis, handle, distance))
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(axis, handle, _)| (axis, handle))
+}
+
+/// Grabs a handle of the selection's gizmo, applies the drag to the selection and records it once
+/// released.
+fn drag_gizmo(
+    mouse: Res<Input<MouseButton>>,
+    mode: Res<GizmoMode>,
+    snap: Res<EditorSnap>,
+    selection: Res<EditorSelection>,
+    mut drag: ResMut<GizmoDrag>,
+    mut history: ResMut<EditorHistory>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut placed: Query<(&EditorPlaced, &mut Transform, Option<&mut Velocity>)>,
+) {
+    let Some((placed, mut transform, velocity)) =
+        selection.0.and_then(|entity| placed.get_mut(entity).ok())
+    else {
+        drag.0 = None;
+        return;
+    };
+    let Some(cursor) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        let Ok(camera) = cameras.get_single() else {
+            return;
+        };
+        drag.0 = hovered_handle(*mode, &transform, cursor, camera).map(|(axis, handle)| Drag {
+            axis,
+            before: *transform,
+            start: cursor,
+            handle,
+        });
+        return;
+    }
+    let Some(current) = &drag.0 else {
+        return;
+    };
+    if mouse.just_released(MouseButton::Left) {
+        if *transform != current.before {
+            history.record(EditorOp::Move {
+                id: placed.id,
+                before: current.before,
+                after: *transform,
+            });
+        }
+        drag.0 = None;
+        return;
+    }
+
+    // How far the cursor moved along the handle, in pixels and in handle lengths.
+    let pixels = (cursor - current.start).dot(current.handle.normalize_or_zero());
+    let lengths = pixels / current.handle.length().max(1.0);
+    let before = current.before;
+    let axis = gizmo_axes(*mode, &before)[current.axis];
+    match *mode {
+        GizmoMode::Translate => {
+            transform.translation =
+                before.translation + axis * snap.distance(lengths * HANDLE_LENGTH);
+        }
+        GizmoMode::Rotate => {
+            let angle = snap.round_angle(pixels * ROTATE_SPEED);
+            transform.rotation = Quat::from_axis_angle(axis, angle) * before.rotation;
+        }
+        GizmoMode::Scale => {
+            let mut scale = before.scale;
+            scale[current.axis] = (scale[current.axis] * (1.0 + lengths)).max(MIN_SCALE);
+            transform.scale = scale;
+        }
+    }
+    if let Some(mut velocity) = velocity {
+        *velocity = Velocity::zero();
+    }
+}
+
+/// The handles of the selection's gizmo, the one dragged or under the cursor highlighted.
+fn draw_gizmo(
+    mut gizmos: Gizmos,
+    mode: Res<GizmoMode>,
+    selection: Res<EditorSelection>,
+    drag: Res<GizmoDrag>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    transforms: Query<&Transform, With<EditorPlaced>>,
+) {
+    let Some(transform) = selection.0.and_then(|entity| transforms.get(entity).ok()) else {
+        return;
+    };
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let highlighted = match (&drag.0, cursor, cameras.get_single()) {
+        (Some(drag), ..) => Some(drag.axis),
+        (None, Some(cursor), Ok(camera)) => {
+            hovered_handle(*mode, transform, cursor, camera).map(|(axis, _)| axis)
+        }
+        _ => None,
+    };
+
+    let origin = transform.translation;
+    for (axis, direction) in gizmo_axes(*mode, transform).into_iter().enumerate() {
+        let color = if highlighted == Some(axis) {
+            Color::YELLOW
+        } else {
+            AXIS_COLORS[axis]
+        };
+        let tip = origin + direction * HANDLE_LENGTH;
+        gizmos.line(origin, tip, color);
+        match *mode {
+            GizmoMode::Translate => {
+                gizmos.sphere(tip, Quat::IDENTITY, 0.08, color);
+            }
+            GizmoMode::Rotate => {
+                gizmos.circle(origin, direction, HANDLE_LENGTH * 0.8, color);
+            }
+            GizmoMode::Scale => {
+                gizmos.cuboid(
+                    Transform::from_translation(tip)
+                        .with_rotation(transform.rotation)
+                        .with_scale(Vec3::splat(0.15)),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Changes the focused property of the selection with - and =, spawning it again from its prefab
+/// with the new properties.
+fn edit_properties(
+    mut commands: Commands,
+    mut spawner: LevelSpawner,
+    keys: Res<Input<KeyCode>>,
+    mut focus: ResMut<PropertyFocus>,
+    mut selection: ResMut<EditorSelection>,
+    mut history: ResMut<EditorHistory>,
+    placed: Query<(&EditorPlaced, &Transform)>,
+) {
+    let count = PropertyRow::ALL.len();
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+    let steps =
+        keys.just_pressed(KeyCode::Equals) as i32 - keys.just_pressed(KeyCode::Minus) as i32;
+    if steps == 0 {
+        return;
+    }
+    let Some((entity, (placed, transform))) = selection
+        .0
+        .and_then(|entity| Some((entity, placed.get(entity).ok()?)))
+    else {
+        return;
+    };
+
+    let before = placed.properties.clone();
+    let mut placed = placed.clone();
+    PropertyRow::ALL[focus.0].adjust(&mut placed.properties, steps);
+    if placed.properties == before {
+        return;
+    }
+    history.record(EditorOp::Properties {
+        id: placed.id,
+        before,
+        after: placed.properties.clone(),
+    });
+    selection.0 = Some(respawn_placed(
+        &mut commands,
+        &mut spawner,
+        entity,
+        placed,
+        *transform,
+    ));
+}
+
+/// Replaces `entity` with `placed` spawned again at `transform`, returning the new entity.
+/// Spawning it again puts back the prefab's own values when a property goes back to `None`.
+fn respawn_placed(
+    commands: &mut Commands,
+    spawner: &mut LevelSpawner,
+    entity: Entity,
+    placed: EditorPlaced,
+    transform: Transform,
+) -> Entity {
+    commands.entity(entity).despawn_recursive();
+    let respawned = spawner.spawn(&placed.data(transform));
+    commands.entity(respawned).insert(placed);
+    respawned
+}
+
+fn update_property_text(
+    focus: Res<PropertyFocus>,
+    selection: Res<EditorSelection>,
+    placed: Query<&EditorPlaced>,
+    panels: Query<&Children, With<PropertyPanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let text = match selection.0.and_then(|entity| placed.get(entity).ok()) {
+        Some(placed) => {
+            let mut lines = vec![placed.prefab.clone()];
+            lines.extend(PropertyRow::ALL.iter().enumerate().map(|(index, row)| {
+                let marker = if index == focus.0 { ">" } else { " " };
+                format!(
+                    "{marker} {}: {}",
+                    row.label(),
+                    row.value(&placed.properties)
+                )
+            }));
+            lines.join("\n")
+        }
+        None => "nothing selected".to_string(),
+    };
+    for children in &panels {
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut section) = iter.fetch_next() {
+            section.sections[2].value = text.clone();
+        }
+    }
+}
+
+fn draw_selection(
+    mut gizmos: Gizmos,
+    selection: Res<EditorSelection>,
+    transforms: Query<&GlobalTransform>,
+) {
+    if let Some(transform) = selection.0.and_then(|entity| transforms.get(entity).ok()) {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        gizmos.sphere(translation, rotation, 0.75, Color::YELLOW);
+    }
+}
+
+fn save_level(keys: Res<Input<KeyCode>>, placed: Query<(&EditorPlaced, &Name, &Transform)>) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !ctrl || !keys.just_pressed(KeyCode::S) {
+        return;
+    }
+    let level = LevelData {
+        entities: placed
+            .iter()
+            .map(|(placed, name, transform)| EntityData {
+                name: name.to_string(),
+                ..placed.data(*transform)
+            })
+            .collect(),
+    };
+
+    let path = asset_root().join(EDITOR_LEVEL_PATH);
+    let result = ron::ser::to_string_pretty(&level, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+            }
+            fs::write(&path, contents).map_err(|error| error.to_string())
+        });
+    match result {
+        Ok(()) => info!(
+            "saved {} entities to {}",
+            level.entities.len(),
+            path.display()
+        ),
+        Err(error) => error!("could not save {}: {error}", path.display()),
+    }
+}
+
+fn update_palette_text(
+    models: Res<Models>,
+    prefabs: Res<Assets<Prefabs>>,
+    palette: Res<EditorPalette>,
+    snap: Res<EditorSnap>,
+    history: Res<EditorHistory>,
+    mode: Res<GizmoMode>,
+    panels: Query<&Children, With<EditorPanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let snapping = |value: f32, unit| {
+        if value > 0.0 {
+            format!("{value}{unit}")
+        } else {
+            "off".to_string()
+        }
+    };
+    let mut lines = vec![
+        format!(
+            "grid: {}, angle: {}",
+            snapping(snap.grid(), " m"),
+            snapping(snap.angle(), "°")
+        ),
+        format!(
+            "handles: {:?}, undo: {}, redo: {}",
+            *mode,
+            history.undo.len(),
+            history.redo.len()
+        ),
+    ];
+    lines.extend(
+        prefab_names(&models, &prefabs)
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let marker = if index == palette.selected { ">" } else { " " };
+                format!("{marker} {name}")
+            }),
+    );
+    for children in &panels {
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[2].value = lines.join("\n");
+        }
+    }
+}
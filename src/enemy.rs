@@ -0,0 +1,175 @@
+//! Enemies: capsules that chase the player while keeping apart from each other. Their steering runs
+//! in [`AiTick`] and moves them through their [`MoveTarget`]; `enemies <count>` in the console
+//! spawns some around the middle of the level, and [`make_enemy`] turns any entity into one, as
+//! the `enemy` prefab does. Each enemy walks a little faster or slower, and is a little tougher or
+//! weaker, than the next, as the [`WorldRng`] decides, and the [`Difficulty`] speeds them all up
+//! or slows them down.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::ai::{AiClock, AiTick, MoveTarget};
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::difficulty::Difficulty;
+use crate::health::Health;
+use crate::lod::MeshLod;
+use crate::minimap::MinimapIcon;
+use crate::world_rng::WorldRng;
+use crate::Player;
+
+/// Typical walking speed in meters per second, and health.
+const ENEMY_SPEED: f32 = 2.5;
+const ENEMY_HEALTH: f32 = 50.0;
+/// How far an enemy's speed and health are from the typical ones at most, as a share of them.
+const VARIATION: f32 = 0.2;
+/// Enemies stop this far from the player.
+const STOP_DISTANCE: f32 = 1.5;
+/// Enemies closer than this push each other apart.
+const SEPARATION_DISTANCE: f32 = 1.5;
+const ENEMY_RADIUS: f32 = 0.4;
+const ENEMY_HALF_HEIGHT: f32 = 0.4;
+/// Most enemies spawned by one command.
+const MAX_SPAWN: usize = 500;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemyAssets>()
+            .add_console_command(
+                "enemies",
+                "enemies <count> - spawn enemies chasing the player",
+                spawn_enemies,
+            )
+            .add_systems(AiTick, steer_enemies);
+    }
+}
+
+#[derive(Component)]
+pub struct Enemy {
+    /// Walking speed in meters per second.
+    pub speed: f32,
+}
+
+#[derive(Resource)]
+struct EnemyAssets {
+    /// Detail levels for the [`MeshLod`].
+    meshes: Vec<Handle<Mesh>>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for EnemyAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut mesh_assets = world.resource_mut::<Assets<Mesh>>();
+        let meshes = [(32, 16), (16, 8), (8, 4)]
+            .into_iter()
+            .map(|(longitudes, latitudes)| {
+                mesh_assets.add(Mesh::from(shape::Capsule {
+                    radius: ENEMY_RADIUS,
+                    depth: ENEMY_HALF_HEIGHT * 2.0,
+                    longitudes,
+                    latitudes,
+                    ..default()
+                }))
+            })
+            .collect();
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::rgb(0.8, 0.15, 0.1).into());
+        Self { meshes, material }
+    }
+}
+
+fn spawn_enemies(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let count: usize = parse_arg(args, 0, "count")?;
+    if count > MAX_SPAWN {
+        return Err(format!("at most {MAX_SPAWN} enemies at a time"));
+    }
+    for index in 0..count {
+        // Spread out on a sunflower spiral so none of them overlap.
+        let angle = index as f32 * 2.4;
+        let radius = 6.0 + (index as f32).sqrt();
+        let position = Vec3::new(radius * angle.cos(), 2.0, radius * angle.sin());
+        let entity = world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_translation(position)),
+                Name::new("Enemy"),
+            ))
+            .id();
+        make_enemy(world, entity);
+    }
+    Ok(format!("spawned {count} enemies"))
+}
+
+/// Turns `entity` into an enemy where it stands, adding its mesh, collider, controller and health.
+pub fn make_enemy(world: &mut World, entity: Entity) {
+    let mut vary = |value: f32| {
+        world
+            .get_resource_mut::<WorldRng>()
+            .map_or(value, |mut rng| {
+                value * rng.range(1.0 - VARIATION, 1.0 + VARIATION)
+            })
+    };
+    let (speed, health) = (vary(ENEMY_SPEED), vary(ENEMY_HEALTH));
+    let assets = world.resource::<EnemyAssets>();
+    let (lod, material) = (MeshLod::new(assets.meshes.clone()), assets.material.clone());
+    let Some(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    let position = entity
+        .get::<Transform>()
+        .map_or(Vec3::ZERO, |transform| transform.translation);
+    entity.insert((
+        lod.full(),
+        material,
+        VisibilityBundle::default(),
+        Collider::capsule_y(ENEMY_HALF_HEIGHT, ENEMY_RADIUS),
+        KinematicCharacterController {
+            filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            ..default()
+        },
+        lod,
+        MoveTarget::new(position),
+        Health::new(health),
+        MinimapIcon::Enemy,
+        Enemy { speed },
+    ));
+}
+
+/// Seeks the player and steers away from nearby enemies.
+fn steer_enemies(
+    clock: Res<AiClock>,
+    difficulty: Res<Difficulty>,
+    player: Query<&Transform, With<Player>>,
+    mut enemies: Query<(Entity, &Enemy, &Transform, &mut MoveTarget)>,
+) {
+    let goal = player
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation);
+    let positions: Vec<(Entity, Vec3)> = enemies
+        .iter()
+        .map(|(entity, _, transform, _)| (entity, transform.translation))
+        .collect();
+
+    for (entity, enemy, transform, mut target) in &mut enemies {
+        let position = transform.translation;
+        let seek = goal
+            .map(|goal| (goal - position).xz())
+            .filter(|offset| offset.length() > STOP_DISTANCE)
+            .map_or(Vec2::ZERO, |offset| offset.normalize());
+        let separation: Vec2 = positions
+            .iter()
+            .filter(|(other, _)| *other != entity)
+            .map(|(_, other)| (position - *other).xz())
+            .filter(|offset| offset.length() < SEPARATION_DISTANCE)
+            .map(|offset| {
+                offset.normalize_or_zero() * (1.0 - offset.length() / SEPARATION_DISTANCE)
+            })
+            .sum();
+
+        let direction = (seek + separation).clamp_length_max(1.0);
+        let step = direction * enemy.speed * difficulty.enemy_speed() * clock.step();
+        target.set(position + Vec3::new(step.x, 0.0, step.y));
+    }
+}
@@ -0,0 +1,89 @@
+//! Fall damage: characters landing from higher than [`SAFE_FALL_HEIGHT`] get hurt, more for every
+//! meter above it.
+//!
+//! The height is from the highest point a character reached since its controller last reported it
+//! grounded, so a jump counts from the top of its arc. The [`Difficulty`] scales both the safe
+//! height and the damage. Every landing sends a [`Landed`] with the height, hurt or not.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::difficulty::Difficulty;
+use crate::health::DamageEvent;
+
+/// Falls up to this high, in meters, don't hurt.
+pub const SAFE_FALL_HEIGHT: f32 = 4.0;
+/// Damage for each meter fallen above the safe height.
+const DAMAGE_PER_METER: f32 = 2.0;
+
+pub struct FallDamagePlugin;
+
+impl Plugin for FallDamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Landed>()
+            .add_systems(Update, (add_fall_tracking, hurt_on_landing).chain());
+    }
+}
+
+/// Sent when a character lands.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Landed {
+    pub character: Entity,
+    /// Meters fallen from the highest point since the character was last grounded.
+    pub height: f32,
+}
+
+/// The highest a character has been since it was last grounded.
+#[derive(Component, Default)]
+pub struct FallTracking {
+    highest: Option<f32>,
+}
+
+fn add_fall_tracking(
+    mut commands: Commands,
+    characters: Query<Entity, Added<KinematicCharacterController>>,
+) {
+    for character in &characters {
+        commands.entity(character).insert(FallTracking::default());
+    }
+}
+
+fn hurt_on_landing(
+    difficulty: Res<Difficulty>,
+    mut damage: EventWriter<DamageEvent>,
+    mut landed: EventWriter<Landed>,
+    mut characters: Query<(
+        Entity,
+        &mut FallTracking,
+        &GlobalTransform,
+        &KinematicCharacterControllerOutput,
+    )>,
+) {
+    let safe_height = SAFE_FALL_HEIGHT * difficulty.fall_height();
+    for (entity, mut tracking, transform, output) in &mut characters {
+        let height = transform.translation().y;
+        if !output.grounded {
+            tracking.highest = Some(
+                tracking
+                    .highest
+                    .map_or(height, |highest| highest.max(height)),
+            );
+            continue;
+        }
+        let Some(highest) = tracking.highest.take() else {
+            continue;
+        };
+        let fallen = highest - height;
+        landed.send(Landed {
+            character: entity,
+            height: fallen,
+        });
+        if fallen > safe_height {
+            damage.send(DamageEvent {
+                target: entity,
+                amount: (fallen - safe_height) * DAMAGE_PER_METER * difficulty.damage(),
+                point: transform.translation(),
+            });
+        }
+    }
+}
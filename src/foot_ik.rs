@@ -0,0 +1,230 @@
+//! Foot placement for [`CharacterModel`]s: the animated pose is planted on the ground under each
+//! foot, so characters stand on slopes and stairs instead of with a foot in the air or in a step.
+//!
+//! After the clip is applied, a ray is cast down past each foot onto the level's fixed colliders.
+//! The hips drop by the most any foot has to reach down, then each leg is bent with two-bone IK so
+//! its sole sits on its own ground, keeping the lift the clip gives it. Only characters on the
+//! ground are placed, and the adjustments ease in and out so stepping up a stair doesn't pop.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_rapier3d::prelude::*;
+
+use crate::character_animation::{keep_root_bones_in_place, CharacterModel};
+
+/// Names of the thigh and shin bones of each leg.
+const LEGS: [[&str; 2]; 2] = [["Leg.L", "Shin.L"], ["Leg.R", "Shin.R"]];
+/// Name of the bone the legs hang from.
+const HIPS: &str = "Hips";
+/// The sole of the foot, in the shin bone's space.
+const SOLE: Vec3 = Vec3::new(0.0, -0.48, 0.0);
+/// How far above a foot its ray starts, in meters: higher than any step the character climbs.
+const RAY_START: f32 = 0.6;
+/// How far the hips drop at most to reach ground below the character, in meters.
+const MAX_HIP_DROP: f32 = 0.4;
+/// How quickly the adjustments follow the ground, as a share of the gap per second.
+const BLEND_RATE: f32 = 15.0;
+
+pub struct FootIkPlugin;
+
+impl Plugin for FootIkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (add_foot_placement, place_feet)
+                .chain()
+                .after(PhysicsSet::Writeback)
+                .after(keep_root_bones_in_place)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// The bones [`place_feet`] moves, once the model's scene is spawned, and how far it moves them.
+#[derive(Component, Default)]
+pub struct FootPlacement {
+    hips: Option<Entity>,
+    /// The thigh and shin bones of each leg.
+    legs: [Option<[Entity; 2]>; 2],
+    /// How far the hips and each sole are moved up, in meters of the model.
+    hip_offset: f32,
+    foot_offsets: [f32; 2],
+}
+
+fn add_foot_placement(mut commands: Commands, models: Query<Entity, Added<CharacterModel>>) {
+    for model in &models {
+        commands.entity(model).insert(FootPlacement::default());
+    }
+}
+
+/// Rotates `thigh` and `shin`, hanging from `parent`, so the sole at the end of the shin reaches
+/// `target`, with the knee bending towards `forward`. Everything is in the same space as `parent`.
+fn two_bone_ik(
+    parent: Transform,
+    thigh: &mut Transform,
+    shin: &mut Transform,
+    target: Vec3,
+    forward: Vec3,
+) {
+    let thigh_global = parent.mul_transform(*thigh);
+    let shin_global = thigh_global.mul_transform(*shin);
+    let hip = thigh_global.translation;
+    let knee = shin_global.translation;
+    let sole = shin_global.transform_point(SOLE);
+
+    let upper = knee.distance(hip);
+    let lower = sole.distance(knee);
+    let reach = target.distance(hip).clamp(0.01, (upper + lower) * 0.999);
+    let angle = |a: Vec3, b: Vec3| a.normalize().dot(b.normalize()).clamp(-1.0, 1.0).acos();
+    // The angle between the sides `a` and `b` of a triangle.
+    let law_of_cosines = |a: f32, b: f32, opposite: f32| {
+        ((a * a + b * b - opposite * opposite) / (2.0 * a * b))
+            .clamp(-1.0, 1.0)
+            .acos()
+    };
+
+    // Bend the knee and the hip until the sole is as far from the hip as the target, without
+    // moving it off the line from the hip, then swing the leg onto the target.
+    let bend_axis = (sole - hip).cross(forward).normalize_or_zero();
+    let hip_bend = Quat::from_axis_angle(
+        bend_axis,
+        law_of_cosines(upper, reach, lower) - angle(sole - hip, knee - hip),
+    );
+    let knee_bend = Quat::from_axis_angle(
+        bend_axis,
+        law_of_cosines(upper, lower, reach) - angle(hip - knee, sole - knee),
+    );
+    let swing = match (sole - hip).cross(target - hip).try_normalize() {
+        Some(axis) => Quat::from_axis_angle(axis, angle(sole - hip, target - hip)),
+        None => Quat::IDENTITY,
+    };
+
+    // The rotations are about axes in the parent's space; turn them into the bones' own.
+    thigh.rotation = parent.rotation.inverse() * swing * hip_bend * thigh_global.rotation;
+    shin.rotation = thigh_global.rotation.inverse() * knee_bend * shin_global.rotation;
+}
+
+/// Finds the bones of `model` by name.
+fn find_bones(
+    model: Entity,
+    placement: &mut FootPlacement,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+) {
+    let find = |name: &str| {
+        children.iter_descendants(model).find(|&bone| {
+            names
+                .get(bone)
+                .is_ok_and(|bone_name| bone_name.as_str() == name)
+        })
+    };
+    placement.hips = find(HIPS);
+    placement.legs = LEGS.map(|[thigh, shin]| Some([find(thigh)?, find(shin)?]));
+}
+
+fn place_feet(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    characters: Query<
+        (&Transform, Option<&KinematicCharacterControllerOutput>),
+        With<KinematicCharacterController>,
+    >,
+    mut models: Query<(Entity, &mut FootPlacement, &Parent)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    parents: Query<&Parent>,
+    mut transforms: Query<&mut Transform, Without<KinematicCharacterController>>,
+) {
+    let blend = (BLEND_RATE * time.delta_seconds()).min(1.0);
+    for (model, mut placement, parent) in &mut models {
+        if placement.hips.is_none() {
+            find_bones(model, &mut placement, &children, &names);
+        }
+        let (Some(hips), [Some(left), Some(right)]) = (placement.hips, placement.legs) else {
+            continue;
+        };
+        let (Ok((character, output)), Ok(model_transform)) =
+            (characters.get(parent.get()), transforms.get(model))
+        else {
+            continue;
+        };
+        // The IK works in the model's space, where the clips put the ground at zero.
+        let model_global = character.mul_transform(*model_transform);
+        let to_model = model_global.compute_matrix().inverse();
+        let mut above_hips = Transform::IDENTITY;
+        for ancestor in parents
+            .iter_ancestors(hips)
+            .take_while(|&ancestor| ancestor != model)
+        {
+            if let Ok(transform) = transforms.get(ancestor) {
+                above_hips = transform.mul_transform(above_hips);
+            }
+        }
+        let sole = |hips: Transform, [thigh, shin]: [Entity; 2]| {
+            let (Ok(thigh), Ok(shin)) = (transforms.get(thigh), transforms.get(shin)) else {
+                return None;
+            };
+            Some(
+                above_hips
+                    .mul_transform(hips)
+                    .mul_transform(*thigh)
+                    .mul_transform(*shin)
+                    .transform_point(SOLE),
+            )
+        };
+        let Ok(&hips_transform) = transforms.get(hips) else {
+            continue;
+        };
+
+        // How far the ground under each sole is above the model's, while on the ground.
+        let mut ground = [0.0; 2];
+        if output.is_some_and(|output| output.grounded) {
+            for (index, leg) in [left, right].into_iter().enumerate() {
+                let Some(sole) = sole(hips_transform, leg) else {
+                    continue;
+                };
+                let start = model_global.transform_point(sole) + Vec3::Y * RAY_START;
+                let hit = rapier_context.cast_ray(
+                    start,
+                    Vec3::NEG_Y,
+                    RAY_START + MAX_HIP_DROP,
+                    true,
+                    QueryFilter::only_fixed()
+                        .exclude_sensors()
+                        .exclude_collider(parent.get()),
+                );
+                if let Some((_, distance)) = hit {
+                    ground[index] = to_model.transform_point3(start - Vec3::Y * distance).y;
+                }
+            }
+        }
+
+        let hip_target = ground
+            .into_iter()
+            .fold(0.0_f32, f32::min)
+            .max(-MAX_HIP_DROP);
+        placement.hip_offset += (hip_target - placement.hip_offset) * blend;
+        for (offset, target) in placement.foot_offsets.iter_mut().zip(ground) {
+            *offset += (target - *offset) * blend;
+        }
+        let hip_offset = placement.hip_offset;
+        let foot_offsets = placement.foot_offsets;
+
+        let hips_transform =
+            hips_transform.with_translation(hips_transform.translation + Vec3::Y * hip_offset);
+        let targets = [left, right].map(|leg| sole(hips_transform, leg));
+        if let Ok(mut hips) = transforms.get_mut(hips) {
+            *hips = hips_transform;
+        }
+        let hips_global = above_hips.mul_transform(hips_transform);
+        for ((leg, target), offset) in [left, right].into_iter().zip(targets).zip(foot_offsets) {
+            let (Some(target), Ok([mut thigh, mut shin])) = (target, transforms.get_many_mut(leg))
+            else {
+                continue;
+            };
+            // The sole keeps the lift the clip gives it, above its own ground.
+            let target = target + Vec3::Y * (offset - hip_offset);
+            two_bone_ik(hips_global, &mut thigh, &mut shin, target, Vec3::Z);
+        }
+    }
+}
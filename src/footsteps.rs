@@ -0,0 +1,97 @@
+//! Footstep sounds for the player character.
+//!
+//! A step plays every [`STRIDE_LENGTH`] walked while grounded, so the cadence follows the speed.
+//! The sound comes from the [`Surface`] under the character and plays at its feet.
+
+use bevy::audio::PlaybackMode;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::AudioAssets;
+use crate::audio_mixer::{AudioChannel, AudioMixer};
+use crate::spatial_audio::play_at;
+use crate::surface::Surface;
+use crate::Player;
+
+/// Meters walked per footstep.
+const STRIDE_LENGTH: f32 = 1.2;
+/// How far below the character's center the ground is looked for.
+const GROUND_PROBE: f32 = 1.5;
+
+pub struct FootstepsPlugin;
+
+impl Plugin for FootstepsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stride>().add_systems(
+            Update,
+            play_footsteps.run_if(resource_exists::<AudioAssets>()),
+        );
+    }
+}
+
+/// Distance walked since the last step, and how many steps have played.
+#[derive(Resource, Default)]
+struct Stride {
+    distance: f32,
+    steps: usize,
+}
+
+fn play_footsteps(
+    mut commands: Commands,
+    audio: Res<AudioAssets>,
+    mixer: Res<AudioMixer>,
+    rapier_context: Res<RapierContext>,
+    mut stride: ResMut<Stride>,
+    player: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &KinematicCharacterControllerOutput,
+        ),
+        With<Player>,
+    >,
+    surfaces: Query<&Surface>,
+) {
+    let Ok((entity, transform, output)) = player.get_single() else {
+        return;
+    };
+    if !output.grounded {
+        stride.distance = 0.0;
+        return;
+    }
+    stride.distance += output.effective_translation.xz().length();
+    if stride.distance < STRIDE_LENGTH {
+        return;
+    }
+    stride.distance -= STRIDE_LENGTH;
+
+    let origin = transform.translation();
+    let ground = rapier_context.cast_ray(
+        origin,
+        Vec3::NEG_Y,
+        GROUND_PROBE,
+        true,
+        QueryFilter::default().exclude_collider(entity),
+    );
+    let surface = ground
+        .and_then(|(ground, _)| surfaces.get(ground).ok().copied())
+        .unwrap_or_default();
+    let feet = origin + Vec3::NEG_Y * ground.map_or(GROUND_PROBE, |(_, distance)| distance);
+    let sounds = surface.footstep_sounds(&audio);
+    if sounds.is_empty() {
+        return;
+    }
+
+    play_at(
+        &mut commands,
+        feet,
+        sounds[stride.steps % sounds.len()].clone(),
+        PlaybackSettings {
+            mode: PlaybackMode::Despawn,
+            volume: mixer.playback_volume(AudioChannel::Sfx, 0.6),
+            speed: surface.pitch(),
+            ..default()
+        },
+    );
+    stride.steps += 1;
+}
@@ -0,0 +1,90 @@
+//! A bar graph of recent frame times, drawn above the bottom-right diagnostic text so spikes
+//! (collider generation, large spawns) stand out at a glance.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+/// Number of frames kept in the graph.
+const HISTORY_LEN: usize = 90;
+/// Frame time mapped to the full height of the graph, in milliseconds.
+const GRAPH_MAX_MS: f32 = 50.0;
+const GRAPH_HEIGHT: f32 = 40.0;
+const BAR_WIDTH: f32 = 2.0;
+
+pub struct FpsGraphPlugin;
+
+impl Plugin for FpsGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameTimeHistory>()
+            .add_systems(Startup, spawn_fps_graph)
+            .add_systems(Update, (record_frame_time, update_fps_graph).chain());
+    }
+}
+
+/// Frame times of the last [`HISTORY_LEN`] frames in milliseconds, oldest first.
+#[derive(Resource)]
+struct FrameTimeHistory(VecDeque<f32>);
+
+impl Default for FrameTimeHistory {
+    fn default() -> Self {
+        Self(VecDeque::from(vec![0.0; HISTORY_LEN]))
+    }
+}
+
+/// One bar of the graph; the index counts from the oldest frame.
+#[derive(Component)]
+struct FpsGraphBar(usize);
+
+fn spawn_fps_graph(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(80.0),
+                right: Val::Px(15.0),
+                height: Val::Px(GRAPH_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for index in 0..HISTORY_LEN {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(BAR_WIDTH),
+                            height: Val::Px(0.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    FpsGraphBar(index),
+                ));
+            }
+        });
+}
+
+fn record_frame_time(time: Res<Time<Real>>, mut history: ResMut<FrameTimeHistory>) {
+    history.0.pop_front();
+    history.0.push_back(time.delta_seconds() * 1000.0);
+}
+
+fn update_fps_graph(
+    history: Res<FrameTimeHistory>,
+    mut bars: Query<(&FpsGraphBar, &mut Style, &mut BackgroundColor)>,
+) {
+    for (bar, mut style, mut color) in &mut bars {
+        let frame_time = history.0[bar.0];
+        style.height = Val::Px((frame_time / GRAPH_MAX_MS).min(1.0) * GRAPH_HEIGHT);
+        color.0 = if frame_time > 33.4 {
+            Color::RED
+        } else if frame_time > 16.7 {
+            Color::YELLOW
+        } else {
+            Color::GREEN
+        };
+    }
+}
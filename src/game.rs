@@ -0,0 +1,380 @@
+//! [`GameBuilder`], which adds the game to an [`App`]: either a new one, as the binary does, or
+//! one from another Bevy project embedding this crate.
+//!
+//! Every subsystem is added by default. The debug renderer, the diagnostics, the player character
+//! and the level can be left out; the rest of the game copes with their absence. The debug
+//! renderer and the diagnostics are also left out when the crate is built without their features.
+
+#[cfg(feature = "diagnostics")]
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::window::ExitCondition;
+
+use crate::achievements::AchievementsPlugin;
+use crate::activation::PhysicsActivationPlugin;
+use crate::ai::AiPlugin;
+use crate::audio_mixer::AudioMixerPlugin;
+use crate::bench::BenchPlugin;
+use crate::buoyancy::BuoyancyPlugin;
+use crate::camera::CameraPlugin;
+use crate::capture::CapturePlugin;
+use crate::character_animation::CharacterAnimationPlugin;
+use crate::character_hits::CharacterHitsPlugin;
+use crate::chat::ChatPlugin;
+use crate::cloth::ClothPlugin;
+use crate::console::ConsolePlugin;
+use crate::crash::CrashPlugin;
+use crate::death::DeathPlugin;
+#[cfg(feature = "debug_render")]
+use crate::debug_gizmos::DebugGizmosPlugin;
+#[cfg(feature = "diagnostics")]
+use crate::debug_ui::DebugUiPlugin;
+use crate::decals::DecalsPlugin;
+use crate::difficulty::DifficultyPlugin;
+use crate::editor::EditorPlugin;
+use crate::enemy::EnemyPlugin;
+use crate::fall_damage::FallDamagePlugin;
+use crate::foot_ik::FootIkPlugin;
+use crate::footsteps::FootstepsPlugin;
+#[cfg(feature = "diagnostics")]
+use crate::fps_graph::FpsGraphPlugin;
+use crate::game_set::GameSetPlugin;
+use crate::gltf_export::GltfExportPlugin;
+use crate::health::HealthPlugin;
+use crate::hitstop::HitstopPlugin;
+use crate::hot_reload::HotReloadPlugin;
+use crate::hud::HudPlugin;
+use crate::impact_sounds::ImpactSoundsPlugin;
+use crate::interaction::InteractionPlugin;
+use crate::joints::JointsPlugin;
+use crate::launch_options::LaunchOptions;
+use crate::level::LevelPlugin;
+use crate::level_data::LevelDataPlugin;
+use crate::lifetime::LifetimePlugin;
+use crate::load_failure::LoadFailurePlugin;
+use crate::loading_screen::LoadingScreenPlugin;
+use crate::lobby::LobbyPlugin;
+use crate::localization::LocalizationPlugin;
+use crate::lod::LodPlugin;
+use crate::logging::LoggingPlugin;
+use crate::materials::MaterialsPlugin;
+use crate::melee::MeleePlugin;
+use crate::mesh_import::MeshImportPlugin;
+use crate::movement_dust::MovementDustPlugin;
+use crate::music::MusicPlugin;
+use crate::network::NetworkPlugin;
+use crate::node_templates::NodeTemplatesPlugin;
+use crate::particles::ParticlesPlugin;
+use crate::pause_menu::PauseMenuPlugin;
+#[cfg(feature = "diagnostics")]
+use crate::physics_setup::PhysicsDiagnosticsPlugin;
+use crate::physics_setup::PhysicsSetupPlugin;
+use crate::player::PlayerPlugin;
+use crate::prediction::PredictionPlugin;
+use crate::projectile::ProjectilePlugin;
+use crate::quit_confirm::QuitConfirmPlugin;
+use crate::replay::ReplayPlugin;
+use crate::save::SavePlugin;
+use crate::scene_export::SceneExportPlugin;
+use crate::screenshot::ScreenshotPlugin;
+use crate::settings::SettingsPlugin;
+use crate::settings_menu::SettingsMenuPlugin;
+use crate::soft_body::SoftBodyPlugin;
+use crate::spatial_audio::SpatialAudioPlugin;
+use crate::spawner::SpawnerPlugin;
+use crate::state::StatePlugin;
+use crate::stats::StatsPlugin;
+use crate::streaming::LevelStreamingPlugin;
+use crate::terrain::TerrainPlugin;
+use crate::text_theme::TextThemePlugin;
+use crate::toast::ToastPlugin;
+use crate::trails::TrailsPlugin;
+use crate::wind::WindPlugin;
+use crate::world_rng::WorldRngPlugin;
+
+/// Chooses the subsystems of the game and adds them to an [`App`].
+///
+/// ```no_run
+/// use asset_test::GameBuilder;
+///
+/// GameBuilder::new().debug_render(false).build().run();
+/// ```
+pub struct GameBuilder {
+    options: LaunchOptions,
+    debug_render: bool,
+    diagnostics: bool,
+    character: bool,
+    level: bool,
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self {
+            options: LaunchOptions::default(),
+            debug_render: true,
+            diagnostics: true,
+            character: true,
+            level: true,
+        }
+    }
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The options the game runs with, as given on the command line to the binary.
+    pub fn options(mut self, options: LaunchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Rapier's collider wireframes and the debug gizmos, toggled with F4. Needs the
+    /// `debug_render` feature.
+    pub fn debug_render(mut self, enabled: bool) -> Self {
+        self.debug_render = enabled;
+        self
+    }
+
+    /// Frame time and physics diagnostics, logged and shown in the debug panel and the FPS graph.
+    /// Needs the `diagnostics` feature.
+    pub fn diagnostics(mut self, enabled: bool) -> Self {
+        self.diagnostics = enabled;
+        self
+    }
+
+    /// The player character, its animated model, its movement and its attack.
+    pub fn character(mut self, enabled: bool) -> Self {
+        self.character = enabled;
+        self
+    }
+
+    /// The level scene and the props dropped into it.
+    pub fn level(mut self, enabled: bool) -> Self {
+        self.level = enabled;
+        self
+    }
+
+    /// A new app with Bevy's default plugins, logging to [`crate::logging::LOG_PATH`] instead of
+    /// Bevy's `LogPlugin`, and the game.
+    pub fn build(self) -> App {
+        let window_plugin = if self.options.headless {
+            WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            }
+        } else {
+            // Closing the window saves before quitting; see `quit_confirm`.
+            WindowPlugin {
+                close_when_requested: false,
+                ..default()
+            }
+        };
+
+        let mut app = App::new();
+        app.add_plugins((
+            LoggingPlugin {
+                level: self.options.log_level(),
+            },
+            DefaultPlugins.set(window_plugin).disable::<LogPlugin>(),
+        ));
+        self.add_to(&mut app);
+        app
+    }
+
+    /// Adds the game to `app`, which must already have Bevy's default plugins.
+    pub fn add_to(self, app: &mut App) {
+        let options = self.options;
+        app.insert_resource(options.clone())
+            .add_plugins((
+                StatePlugin,
+                LevelDataPlugin,
+                GameSetPlugin,
+                PhysicsSetupPlugin {
+                    debug_render: self.debug_render,
+                },
+                HotReloadPlugin,
+                MaterialsPlugin,
+                AiPlugin,
+                EnemyPlugin,
+                ProjectilePlugin,
+                LodPlugin,
+                PhysicsActivationPlugin,
+                CharacterHitsPlugin,
+                HitstopPlugin,
+                WorldRngPlugin,
+                SpawnerPlugin,
+            ))
+            .add_plugins((
+                AudioMixerPlugin,
+                MusicPlugin,
+                SpatialAudioPlugin,
+                ImpactSoundsPlugin,
+                FootstepsPlugin,
+                LifetimePlugin,
+                ParticlesPlugin,
+                MovementDustPlugin,
+                DecalsPlugin,
+                TrailsPlugin,
+            ))
+            .add_plugins((ConsolePlugin, CrashPlugin))
+            .add_plugins((
+                CameraPlugin,
+                HudPlugin,
+                HealthPlugin,
+                DeathPlugin,
+                FallDamagePlugin,
+                AchievementsPlugin,
+                InteractionPlugin,
+                PauseMenuPlugin,
+                SettingsPlugin,
+                DifficultyPlugin,
+                SettingsMenuPlugin,
+                LocalizationPlugin,
+                TextThemePlugin,
+                LoadingScreenPlugin,
+                LoadFailurePlugin,
+            ))
+            .add_plugins((
+                ToastPlugin,
+                SavePlugin,
+                QuitConfirmPlugin,
+                StatsPlugin,
+                SceneExportPlugin,
+                GltfExportPlugin,
+                MeshImportPlugin,
+                TerrainPlugin,
+                JointsPlugin,
+                WindPlugin,
+                ClothPlugin,
+                SoftBodyPlugin,
+                BuoyancyPlugin,
+                ReplayPlugin,
+                ScreenshotPlugin,
+            ))
+            .add_plugins((
+                NetworkPlugin {
+                    role: options.network,
+                },
+                PredictionPlugin,
+                LobbyPlugin,
+                ChatPlugin,
+            ));
+
+        #[cfg(feature = "debug_render")]
+        if self.debug_render {
+            app.add_plugins(DebugGizmosPlugin);
+        }
+        #[cfg(feature = "diagnostics")]
+        if self.diagnostics {
+            app.add_plugins((
+                DebugUiPlugin,
+                FrameTimeDiagnosticsPlugin,
+                PhysicsDiagnosticsPlugin,
+                LogDiagnosticsPlugin::filtered(vec![
+                    FrameTimeDiagnosticsPlugin::FPS,
+                    PhysicsDiagnosticsPlugin::RIGID_BODY_COUNT,
+                    PhysicsDiagnosticsPlugin::ACTIVE_BODY_COUNT,
+                    PhysicsDiagnosticsPlugin::CONTACT_PAIR_COUNT,
+                    PhysicsDiagnosticsPlugin::STEP_TIME,
+                ]),
+                FpsGraphPlugin,
+            ));
+        }
+        if self.character {
+            app.add_plugins((
+                PlayerPlugin,
+                CharacterAnimationPlugin,
+                FootIkPlugin,
+                MeleePlugin,
+            ));
+        }
+        if self.level {
+            app.add_plugins((
+                LevelPlugin,
+                LevelStreamingPlugin,
+                NodeTemplatesPlugin,
+                EditorPlugin,
+            ));
+        }
+
+        #[cfg(feature = "inspector")]
+        app.add_plugins(crate::inspector::InspectorPlugin);
+
+        if options.capture {
+            app.add_plugins(CapturePlugin {
+                frame_limit: options.capture_frames,
+            });
+        }
+        if let Some(frames) = options.bench {
+            app.add_plugins(BenchPlugin { frames });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::settings::{RenderCreation, WgpuSettings};
+    use bevy::render::RenderPlugin;
+    use std::time::Duration;
+
+    use bevy::winit::WinitPlugin;
+
+    use super::*;
+    use crate::MyStates;
+
+    /// Bevy's default plugins without a window or a GPU.
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }),
+                })
+                .disable::<WinitPlugin>()
+                .disable::<LogPlugin>(),
+        );
+        app
+    }
+
+    #[test]
+    fn runs_without_character_and_level() {
+        let mut app = headless_app();
+        GameBuilder::new()
+            .options(LaunchOptions {
+                headless: true,
+                ..default()
+            })
+            .character(false)
+            .level(false)
+            .add_to(&mut app);
+        // Some asset loaders are only registered when the app is finished.
+        app.finish();
+        app.cleanup();
+
+        let state = |app: &App| app.world.resource::<State<MyStates>>().get().clone();
+        for _ in 0..1000 {
+            if state(&app) != MyStates::AssetLoading {
+                break;
+            }
+            app.update();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        for _ in 0..10 {
+            app.update();
+        }
+        // Panics are caught by the crash screen rather than failing the test.
+        assert_eq!(state(&app), MyStates::Next);
+    }
+}
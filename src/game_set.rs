@@ -0,0 +1,67 @@
+//! [`GameSet`], which orders the game's per-frame work around Rapier's [`PhysicsSet`]s.
+//!
+//! Input and movement run in `Update`, before Rapier moves the character in `PostUpdate`. Each
+//! character controller's translation is built up in order: the base movement sets it, root motion
+//! adds the animation's travel, modifiers such as knockback add theirs, and swimming scales the
+//! result. The
+//! camera then follows the position Rapier wrote back in the same frame, so it doesn't lag a frame
+//! behind the character. UI placed from world positions runs after the camera and before the UI
+//! layout, which Bevy runs before transforms are propagated, so it reads the global transforms of
+//! the frame before.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy::ui::UiSystem;
+use bevy_rapier3d::prelude::*;
+
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameSet {
+    /// Reads the keyboard and mouse, in `Update`.
+    Input,
+    /// Sets the character controllers' translations, in `Update` after [`GameSet::Input`].
+    Movement,
+    /// Adds the animations' root motion to the translations, after [`GameSet::Movement`].
+    RootMotion,
+    /// Adds external movement such as knockback or riding platforms, after
+    /// [`GameSet::RootMotion`].
+    MovementModifiers,
+    /// Scales the whole translation, such as when swimming, after
+    /// [`GameSet::MovementModifiers`].
+    MovementScaling,
+    /// Reads the results of the simulation step, in `PostUpdate` after [`PhysicsSet::Writeback`].
+    PhysicsWriteback,
+    /// Places the camera, in `PostUpdate` after [`GameSet::PhysicsWriteback`] and before
+    /// transforms are propagated.
+    Camera,
+    /// Places UI over world positions, in `PostUpdate` after [`GameSet::Camera`] and before the UI
+    /// layout.
+    Ui,
+}
+
+pub struct GameSetPlugin;
+
+impl Plugin for GameSetPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (
+                GameSet::Input,
+                GameSet::Movement,
+                GameSet::RootMotion,
+                GameSet::MovementModifiers,
+                GameSet::MovementScaling,
+            )
+                .chain(),
+        )
+        .configure_sets(
+            PostUpdate,
+            (
+                (GameSet::PhysicsWriteback, GameSet::Camera)
+                    .chain()
+                    .after(PhysicsSet::Writeback)
+                    .before(TransformSystem::TransformPropagate),
+                GameSet::Ui.after(GameSet::Camera).before(UiSystem::Layout),
+            ),
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Export of the world's meshes to glTF, so levels built in the editor can be opened in Blender
+//! and other tools.
+//!
+//! `export_gltf <name>` writes every visible triangle mesh with its world transform and material
+//! colors to `assets/exports/<name>.gltf`, with the vertex data in `<name>.bin` next to it.
+//! Textures aren't exported. The physics of each node is kept in its `extras`, which Blender shows
+//! as custom properties: the collider as a [`ColliderShape`], the body type, the density, the
+//! [`Surface`] and the editor prefab, when there are any.
+
+use std::collections::HashMap;
+use std::fs;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy_rapier3d::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::console::ConsoleAppExt;
+use crate::editor::EditorPlaced;
+use crate::scene_export::ColliderShape;
+use crate::surface::Surface;
+
+/// Directory of exported glTF files, under `assets/`.
+const EXPORTS_DIR: &str = "exports";
+
+/// glTF accessor component types and buffer view targets.
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const TRIANGLES: u32 = 4;
+
+pub struct GltfExportPlugin;
+
+impl Plugin for GltfExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command(
+            "export_gltf",
+            "export_gltf <name> - save the world's meshes to assets/exports/<name>.gltf",
+            export_gltf,
+        );
+    }
+}
+
+/// The glTF document being written: its JSON arrays and the binary buffer they point into.
+#[derive(Default)]
+struct GltfWriter {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    materials: Vec<Value>,
+    nodes: Vec<Value>,
+    /// glTF mesh of each mesh and material pair already written.
+    mesh_indices: HashMap<(AssetId<Mesh>, Option<AssetId<StandardMaterial>>), usize>,
+    material_indices: HashMap<AssetId<StandardMaterial>, usize>,
+}
+
+impl GltfWriter {
+    /// Appends `bytes` to the buffer as a new buffer view, returning its index.
+    fn push_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        // Accessors of 4-byte components have to start on a multiple of 4.
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+        self.buffer_views.len() - 1
+    }
+
+    /// Writes float vectors as a new accessor of `kind`, like `VEC3`, returning its index.
+    fn push_floats<const N: usize>(&mut self, values: &[[f32; N]], kind: &str) -> usize {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let view = self.push_view(&bytes, ARRAY_BUFFER);
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": kind,
+        });
+        // Positions need their bounds; other attributes may have them.
+        let bounds = |pick: fn(f32, f32) -> f32, start: f32| {
+            (0..N)
+                .map(|component| {
+                    values
+                        .iter()
+                        .map(|value| value[component])
+                        .fold(start, pick)
+                })
+                .collect::<Vec<f32>>()
+        };
+        accessor["min"] = json!(bounds(f32::min, f32::INFINITY));
+        accessor["max"] = json!(bounds(f32::max, f32::NEG_INFINITY));
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let bytes: Vec<u8> = indices
+            .iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect();
+        let view = self.push_view(&bytes, ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+
+    /// The glTF material of `handle`, written the first time it's used.
+    fn material(
+        &mut self,
+        handle: &Handle<StandardMaterial>,
+        materials: &Assets<StandardMaterial>,
+    ) -> Option<usize> {
+        if let Some(index) = self.material_indices.get(&handle.id()) {
+            return Some(*index);
+        }
+        let material = materials.get(handle)?;
+        let [r, g, b, _] = material.emissive.as_linear_rgba_f32();
+        let mut gltf_material = json!({
+            "pbrMetallicRoughness": {
+                "baseColorFactor": material.base_color.as_linear_rgba_f32(),
+                "metallicFactor": material.metallic,
+                "roughnessFactor": material.perceptual_roughness,
+            },
+            // Plain glTF caps emission at 1.
+            "emissiveFactor": [r.min(1.0), g.min(1.0), b.min(1.0)],
+            "doubleSided": material.double_sided,
+        });
+        if !matches!(material.alpha_mode, AlphaMode::Opaque) {
+            gltf_material["alphaMode"] = json!("BLEND");
+        }
+        self.materials.push(gltf_material);
+        let index = self.materials.len() - 1;
+        self.material_indices.insert(handle.id(), index);
+        Some(index)
+    }
+
+    /// The glTF mesh of `mesh` with `material`, written the first time the pair is used, or
+    /// `None` if it isn't a triangle mesh with positions.
+    fn mesh(
+        &mut self,
+        handle: &Handle<Mesh>,
+        material: Option<&Handle<StandardMaterial>>,
+        meshes: &Assets<Mesh>,
+        materials: &Assets<StandardMaterial>,
+    ) -> Option<usize> {
+        let key = (handle.id(), material.map(Handle::id));
+        if let Some(index) = self.mesh_indices.get(&key) {
+            return Some(*index);
+        }
+        let mesh = meshes.get(handle)?;
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+        if positions.is_empty() {
+            return None;
+        }
+
+        let mut attributes = Map::new();
+        attributes.insert(
+            "POSITION".into(),
+            json!(self.push_floats(positions, "VEC3")),
+        );
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            attributes.insert("NORMAL".into(), json!(self.push_floats(normals, "VEC3")));
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            attributes.insert("TEXCOORD_0".into(), json!(self.push_floats(uvs, "VEC2")));
+        }
+        let mut primitive = json!({ "attributes": attributes, "mode": TRIANGLES });
+        if let Some(indices) = mesh.indices() {
+            let indices: Vec<u32> = match indices {
+                Indices::U16(indices) => indices.iter().map(|&index| index as u32).collect(),
+                Indices::U32(indices) => indices.clone(),
+            };
+            primitive["indices"] = json!(self.push_indices(&indices));
+        }
+        if let Some(material) = material.and_then(|material| self.material(material, materials)) {
+            primitive["material"] = json!(material);
+        }
+
+        self.meshes.push(json!({ "primitives": [primitive] }));
+        let index = self.meshes.len() - 1;
+        self.mesh_indices.insert(key, index);
+        Some(index)
+    }
+
+    /// The glTF JSON, with the binary buffer at `buffer_uri`.
+    fn document(self, buffer_uri: &str) -> Value {
+        json!({
+            "asset": { "version": "2.0", "generator": env!("CARGO_PKG_NAME") },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..self.nodes.len()).collect::<Vec<_>>() }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": self.materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "uri": buffer_uri, "byteLength": self.buffer.len() }],
+        })
+    }
+}
+
+/// The physics of a node, for its `extras`.
+fn physics_extras(
+    collider: Option<&Collider>,
+    body: Option<&RigidBody>,
+    mass: Option<&ColliderMassProperties>,
+    surface: Option<&Surface>,
+    placed: Option<&EditorPlaced>,
+) -> Map<String, Value> {
+    let mut extras = Map::new();
+    if let Some(shape) = collider.and_then(ColliderShape::from_collider) {
+        extras.insert("collider".into(), json!(shape));
+    }
+    if let Some(body) = body {
+        extras.insert("body".into(), json!(format!("{body:?}")));
+    }
+    if let Some(ColliderMassProperties::Density(density)) = mass {
+        extras.insert("density".into(), json!(density));
+    }
+    if let Some(surface) = surface {
+        extras.insert("surface".into(), json!(surface));
+    }
+    if let Some(placed) = placed {
+        extras.insert("prefab".into(), json!(placed.prefab));
+    }
+    extras
+}
+
+fn export_gltf(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = *args.first().ok_or("missing argument <name>")?;
+
+    let mut query = world.query::<(
+        Option<&Name>,
+        &Handle<Mesh>,
+        Option<&Handle<StandardMaterial>>,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&Collider>,
+        Option<&RigidBody>,
+        Option<&ColliderMassProperties>,
+        Option<&Surface>,
+        Option<&EditorPlaced>,
+    )>();
+    let meshes = world.resource::<Assets<Mesh>>();
+    let materials = world.resource::<Assets<StandardMaterial>>();
+    let mut writer = GltfWriter::default();
+    let mut skipped = 0;
+    for (node_name, mesh, material, transform, visibility, collider, body, mass, surface, placed) in
+        query.iter(world)
+    {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(mesh) = writer.mesh(mesh, material, meshes, materials) else {
+            skipped += 1;
+            continue;
+        };
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let mut node = json!({
+            "mesh": mesh,
+            "translation": translation.to_array(),
+            "rotation": rotation.to_array(),
+            "scale": scale.to_array(),
+        });
+        if let Some(node_name) = node_name {
+            node["name"] = json!(node_name.as_str());
+        }
+        let extras = physics_extras(collider, body, mass, surface, placed);
+        if !extras.is_empty() {
+            node["extras"] = Value::Object(extras);
+        }
+        writer.nodes.push(node);
+    }
+
+    let node_count = writer.nodes.len();
+    let dir = format!("assets/{EXPORTS_DIR}");
+    let buffer_path = format!("{dir}/{name}.bin");
+    let path = format!("{dir}/{name}.gltf");
+    fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+    fs::write(&buffer_path, &writer.buffer)
+        .map_err(|error| format!("could not write {buffer_path}: {error}"))?;
+    let contents = serde_json::to_string_pretty(&writer.document(&format!("{name}.bin")))
+        .map_err(|error| error.to_string())?;
+    fs::write(&path, contents).map_err(|error| format!("could not write {path}: {error}"))?;
+    Ok(format!(
+        "exported {node_count} meshes to {path} ({skipped} that aren't triangle meshes skipped)"
+    ))
+}
@@ -0,0 +1,33 @@
+//! Named access to the contents of loaded glTF files, so spawning code can refer to "Crate" or
+//! "Ball" instead of primitive indices like `#Mesh0/Primitive0`.
+
+use bevy::ecs::system::SystemParam;
+use bevy::gltf::{Gltf, GltfMesh, GltfNode};
+use bevy::prelude::*;
+
+#[derive(SystemParam)]
+pub struct GltfAssets<'w> {
+    gltfs: Res<'w, Assets<Gltf>>,
+    nodes: Res<'w, Assets<GltfNode>>,
+    meshes: Res<'w, Assets<GltfMesh>>,
+}
+
+impl<'w> GltfAssets<'w> {
+    /// The mesh of the first primitive of the node, or else the mesh, called `name`.
+    pub fn mesh(&self, gltf: &Handle<Gltf>, name: &str) -> Option<Handle<Mesh>> {
+        let gltf = self.gltfs.get(gltf)?;
+        let gltf_mesh = gltf
+            .named_nodes
+            .get(name)
+            .and_then(|node| self.nodes.get(node))
+            .and_then(|node| node.mesh.as_ref())
+            .or_else(|| gltf.named_meshes.get(name))?;
+        let primitive = self.meshes.get(gltf_mesh)?.primitives.first()?;
+        Some(primitive.mesh.clone())
+    }
+
+    /// The material called `name`.
+    pub fn material(&self, gltf: &Handle<Gltf>, name: &str) -> Option<Handle<StandardMaterial>> {
+        self.gltfs.get(gltf)?.named_materials.get(name).cloned()
+    }
+}
@@ -0,0 +1,97 @@
+//! Health and damage.
+//!
+//! Anything that hurts an entity sends a [`DamageEvent`]; [`apply_damage`] is the only system
+//! that writes to [`Health`], and sends [`PlayerDied`] when the player's health runs out.
+
+use bevy::prelude::*;
+
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::Player;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<PlayerDied>()
+            .add_console_command(
+                "damage",
+                "damage <amount> - hurt the player (negative heals)",
+                damage_player,
+            )
+            .add_systems(Update, apply_damage);
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Remaining health between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            (self.current / self.max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Request to remove `amount` health from `target`. Negative amounts heal.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    /// World-space position the damage was dealt at.
+    pub point: Vec3,
+}
+
+/// Sent once when the player's health drops to zero; see [`crate::death`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerDied {
+    pub entity: Entity,
+    /// Where the killing blow landed.
+    pub point: Vec3,
+}
+
+pub fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut died: EventWriter<PlayerDied>,
+    mut healths: Query<(&mut Health, Has<Player>)>,
+) {
+    for event in events.read() {
+        let Ok((mut health, is_player)) = healths.get_mut(event.target) else {
+            continue;
+        };
+        let was_alive = health.current > 0.0;
+        health.current = (health.current - event.amount).clamp(0.0, health.max);
+        if is_player && was_alive && health.current <= 0.0 {
+            died.send(PlayerDied {
+                entity: event.target,
+                point: event.point,
+            });
+        }
+    }
+}
+
+fn damage_player(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let amount: f32 = parse_arg(args, 0, "amount")?;
+    let mut player = world.query_filtered::<(Entity, &Transform), With<Player>>();
+    let (target, transform) = player
+        .get_single(world)
+        .map_err(|_| "no player to damage")?;
+    let point = transform.translation;
+    world.send_event(DamageEvent {
+        target,
+        amount,
+        point,
+    });
+    Ok(format!("dealt {amount} damage"))
+}
@@ -0,0 +1,131 @@
+//! HUD health bar for the player's [`Health`], with a trailing drain bar and a red vignette when
+//! health is low.
+
+use bevy::prelude::*;
+
+use crate::health::Health;
+use crate::Player;
+
+const BAR_WIDTH: f32 = 240.0;
+const BAR_HEIGHT: f32 = 14.0;
+/// Fraction of the missing drain bar caught up per second.
+const DRAIN_SPEED: f32 = 3.0;
+/// Health fraction below which the vignette fades in.
+const LOW_HEALTH: f32 = 0.3;
+const VIGNETTE_WIDTH: f32 = 60.0;
+
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_health_bar)
+            .add_systems(Update, (update_health_bar, update_low_health_vignette));
+    }
+}
+
+/// Fill showing the current health.
+#[derive(Component)]
+struct HealthFill;
+
+/// Fill trailing behind [`HealthFill`] after damage, draining smoothly.
+#[derive(Component)]
+struct HealthDrain {
+    fraction: f32,
+}
+
+#[derive(Component)]
+struct LowHealthVignette;
+
+fn spawn_health_bar(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                border: UiRect::all(Val::Px(VIGNETTE_WIDTH)),
+                ..default()
+            },
+            border_color: Color::NONE.into(),
+            ..default()
+        },
+        LowHealthVignette,
+    ));
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                left: Val::Px(15.0),
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(BAR_HEIGHT),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            border_color: Color::rgba(1.0, 1.0, 1.0, 0.6).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.9, 0.8, 0.2).into(),
+                    ..default()
+                },
+                HealthDrain { fraction: 1.0 },
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.8, 0.1, 0.1).into(),
+                    ..default()
+                },
+                HealthFill,
+            ));
+        });
+}
+
+fn update_health_bar(
+    time: Res<Time>,
+    player: Query<&Health, With<Player>>,
+    mut fills: Query<&mut Style, (With<HealthFill>, Without<HealthDrain>)>,
+    mut drains: Query<(&mut Style, &mut HealthDrain), Without<HealthFill>>,
+) {
+    let fraction = player.get_single().map_or(1.0, Health::fraction);
+
+    for mut style in &mut fills {
+        style.width = Val::Percent(fraction * 100.0);
+    }
+    for (mut style, mut drain) in &mut drains {
+        let step = (drain.fraction - fraction).max(0.05) * DRAIN_SPEED * time.delta_seconds();
+        drain.fraction = (drain.fraction - step).max(fraction);
+        style.width = Val::Percent(drain.fraction * 100.0);
+    }
+}
+
+fn update_low_health_vignette(
+    time: Res<Time>,
+    player: Query<&Health, With<Player>>,
+    mut vignettes: Query<&mut BorderColor, With<LowHealthVignette>>,
+) {
+    let fraction = player.get_single().map_or(1.0, Health::fraction);
+    let intensity = ((LOW_HEALTH - fraction) / LOW_HEALTH).clamp(0.0, 1.0);
+    // Pulse faster the closer the player is to dying.
+    let pulse = 0.75 + 0.25 * (time.elapsed_seconds() * (2.0 + 4.0 * intensity)).sin();
+
+    for mut border in &mut vignettes {
+        border.0 = Color::rgba(0.6, 0.0, 0.0, 0.5 * intensity * pulse);
+    }
+}
@@ -0,0 +1,116 @@
+//! Hitstop: on a significant hit, such as a melee swing landing, the animations freeze for a few
+//! frames and the game slows down, then eases back to full speed.
+//!
+//! Gameplay sends a [`Hitstop`] for each such hit; its strength scales the durations set in the
+//! [`HitstopSettings`]. The slow motion scales [`Time<Virtual>`], so the simulation, the AI and the
+//! animations all slow down together, while its own timing follows real time. `hitstop [strength]`
+//! in the console plays one.
+
+use bevy::prelude::*;
+
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::settings::{HitstopSettings, Settings};
+
+pub struct HitstopPlugin;
+
+impl Plugin for HitstopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Hitstop>()
+            .init_resource::<HitstopState>()
+            .add_console_command(
+                "hitstop",
+                "hitstop [strength] - freeze and slow the game as for a hit",
+                hitstop_command,
+            )
+            .add_systems(PreUpdate, (start_hitstop, update_hitstop).chain());
+    }
+}
+
+/// Sent for a hit that should be felt.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Hitstop {
+    /// How much of the [`HitstopSettings`]' freeze and slow motion it plays: 1 for a melee hit.
+    pub strength: f32,
+}
+
+/// The hitstop being played.
+#[derive(Resource, Default)]
+struct HitstopState {
+    /// Frames the animations stay frozen for.
+    freeze_frames: u32,
+    /// The animation players frozen, to resume afterwards.
+    frozen: Vec<Entity>,
+    /// Real seconds of slow motion left, out of `slow_duration`.
+    slow_remaining: f32,
+    slow_duration: f32,
+    /// Whether the game's speed was changed, and is to be put back.
+    slowed: bool,
+}
+
+fn start_hitstop(
+    settings: Res<Settings>,
+    mut hitstops: EventReader<Hitstop>,
+    mut state: ResMut<HitstopState>,
+) {
+    let HitstopSettings {
+        freeze_frames,
+        slow_motion_duration,
+        ..
+    } = settings.hitstop;
+    for hitstop in hitstops.read() {
+        let frames = (freeze_frames as f32 * hitstop.strength).round() as u32;
+        state.freeze_frames = state.freeze_frames.max(frames);
+        let duration = slow_motion_duration * hitstop.strength;
+        if duration > state.slow_remaining {
+            state.slow_remaining = duration;
+            state.slow_duration = duration;
+        }
+    }
+}
+
+fn update_hitstop(
+    real_time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    mut state: ResMut<HitstopState>,
+    mut time: ResMut<Time<Virtual>>,
+    mut animation_players: Query<(Entity, &mut AnimationPlayer)>,
+) {
+    let state = &mut *state;
+    if state.freeze_frames > 0 {
+        state.freeze_frames -= 1;
+        for (entity, mut player) in &mut animation_players {
+            if !player.is_paused() {
+                player.pause();
+                state.frozen.push(entity);
+            }
+        }
+    } else {
+        for entity in state.frozen.drain(..) {
+            if let Ok((_, mut player)) = animation_players.get_mut(entity) {
+                player.resume();
+            }
+        }
+    }
+
+    if state.slow_remaining > 0.0 {
+        // From the slowest speed back up to full speed, over the slow motion.
+        let left = state.slow_remaining / state.slow_duration;
+        let speed = settings.hitstop.slow_motion_speed.max(0.0);
+        time.set_relative_speed(1.0 + (speed - 1.0) * left);
+        state.slow_remaining -= real_time.delta_seconds();
+        state.slowed = true;
+    } else if state.slowed {
+        time.set_relative_speed(1.0);
+        state.slowed = false;
+    }
+}
+
+fn hitstop_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let strength = if args.is_empty() {
+        1.0
+    } else {
+        parse_arg::<f32>(args, 0, "strength")?
+    };
+    world.send_event(Hitstop { strength });
+    Ok(format!("hitstop of strength {strength}"))
+}
@@ -0,0 +1,44 @@
+//! Keeps mesh-derived colliders in sync with their meshes when assets are hot-reloaded.
+//!
+//! Build with `--features hot_reload` to watch the `assets/` directory; saving
+//! `models/floor/floor.gltf` then rebuilds the level's trimesh colliders without a restart.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, rebuild_mesh_colliders);
+    }
+}
+
+/// Marks an entity whose [`Collider`] is a trimesh built from its `Handle<Mesh>`.
+#[derive(Component)]
+pub struct MeshCollider;
+
+fn rebuild_mesh_colliders(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Mesh>>,
+    meshes: Res<Assets<Mesh>>,
+    bodies: Query<(Entity, &Handle<Mesh>), With<MeshCollider>>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(mesh) = meshes.get(*id) else {
+            continue;
+        };
+        for (entity, _) in bodies.iter().filter(|(_, handle)| handle.id() == *id) {
+            match Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh) {
+                Some(collider) => {
+                    info!("rebuilt the collider of {entity:?} from its reloaded mesh");
+                    commands.entity(entity).insert(collider);
+                }
+                None => warn!("could not build a collider for {entity:?} from its reloaded mesh"),
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! The heads-up display: the crosshair, health bar, damage numbers and minimap, and the info text
+//! in the bottom corners, with frame and physics statistics when built with the `diagnostics`
+//! feature.
+
+#[cfg(feature = "diagnostics")]
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+#[cfg(feature = "diagnostics")]
+use bevy::ecs::{archetype::Archetypes, entity::Entities};
+use bevy::prelude::*;
+#[cfg(feature = "diagnostics")]
+use bevy_rapier3d::prelude::*;
+
+use crate::crosshair::CrosshairPlugin;
+use crate::damage_numbers::DamageNumbersPlugin;
+use crate::health_bar::HealthBarPlugin;
+#[cfg(feature = "diagnostics")]
+use crate::localization::Localization;
+use crate::localization::Localized;
+use crate::minimap::MinimapPlugin;
+#[cfg(feature = "diagnostics")]
+use crate::physics_setup::PhysicsDiagnosticsPlugin;
+use crate::text_theme::TextTheme;
+#[cfg(feature = "diagnostics")]
+use crate::MyStates;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            CrosshairPlugin,
+            HealthBarPlugin,
+            DamageNumbersPlugin,
+            MinimapPlugin,
+        ))
+        .add_systems(Startup, infotext_system);
+
+        #[cfg(feature = "diagnostics")]
+        app.init_resource::<TextRefreshTimer>()
+            .add_systems(Startup, spawn_stats_text)
+            .add_systems(Update, change_text_system.run_if(in_state(MyStates::Next)));
+    }
+}
+
+/// The diagnostic text in the bottom-right corner.
+#[cfg(feature = "diagnostics")]
+#[derive(Component)]
+pub struct TextChanges;
+
+/// Throttles [`change_text_system`] so the diagnostic string isn't rebuilt every frame.
+#[cfg(feature = "diagnostics")]
+#[derive(Resource)]
+struct TextRefreshTimer(Timer);
+
+#[cfg(feature = "diagnostics")]
+impl Default for TextRefreshTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.25, TimerMode::Repeating))
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+fn change_text_system(
+    time: Res<Time>,
+    mut refresh: ResMut<TextRefreshTimer>,
+    diagnostics: Res<DiagnosticsStore>,
+    localization: Res<Localization>,
+    entities: &Entities,
+    archetypes: &Archetypes,
+    rigid_bodies: Query<&RigidBody>,
+    colliders: Query<(), With<Collider>>,
+    mut query: Query<&mut Text, With<TextChanges>>,
+) {
+    if !refresh.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let entity_count = entities.len();
+    let archetype_count = archetypes.len();
+    let dynamic_bodies = rigid_bodies
+        .iter()
+        .filter(|body| **body == RigidBody::Dynamic)
+        .count();
+    let collider_count = colliders.iter().count();
+
+    for mut text in &mut query {
+        let mut fps = 0.0;
+        if let Some(fps_diagnostic) = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS) {
+            if let Some(fps_smoothed) = fps_diagnostic.smoothed() {
+                fps = fps_smoothed;
+            }
+        }
+
+        let mut frame_time = time.delta_seconds_f64();
+        if let Some(frame_time_diagnostic) = diagnostics.get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        {
+            if let Some(frame_time_smoothed) = frame_time_diagnostic.smoothed() {
+                frame_time = frame_time_smoothed;
+            }
+        }
+
+        let physics = |id| {
+            diagnostics
+                .get(id)
+                .and_then(|diagnostic| diagnostic.smoothed())
+                .unwrap_or(0.0)
+        };
+        let bodies = physics(PhysicsDiagnosticsPlugin::RIGID_BODY_COUNT);
+        let active_bodies = physics(PhysicsDiagnosticsPlugin::ACTIVE_BODY_COUNT);
+        let contact_pairs = physics(PhysicsDiagnosticsPlugin::CONTACT_PAIR_COUNT);
+        let step_time = physics(PhysicsDiagnosticsPlugin::STEP_TIME);
+
+        text.sections[0].value = localization.format(
+            "info.stats",
+            &[
+                ("fps", &format!("{fps:.1}")),
+                ("frame_time", &format!("{frame_time:.3}")),
+                ("bodies", &format!("{bodies:.0}")),
+                ("active_bodies", &format!("{active_bodies:.0}")),
+                ("contact_pairs", &format!("{contact_pairs:.0}")),
+                ("step_time", &format!("{step_time:.3}")),
+                ("entities", &entity_count),
+                ("archetypes", &archetype_count),
+                ("dynamic_bodies", &dynamic_bodies),
+                ("colliders", &collider_count),
+            ],
+        );
+
+        //text.sections[2].value = format!("{fps:.1}");
+        //text.sections[4].value = format!("{frame_time:.3}");
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+fn spawn_stats_text(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<TextTheme>,
+) {
+    commands.spawn((
+        TextBundle::from_sections([TextSection::new(
+            localization.get("info.changing"),
+            theme.small(),
+        )])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.0),
+            right: Val::Px(15.0),
+            ..default()
+        }),
+        TextChanges,
+    ));
+}
+
+fn infotext_system(mut commands: Commands, theme: Res<TextTheme>) {
+    commands.spawn((
+        TextBundle::from_section("", theme.small()).with_style(Style {
+            align_self: AlignSelf::FlexEnd,
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.0),
+            left: Val::Px(15.0),
+            width: Val::Px(200.0),
+            ..default()
+        }),
+        Localized("info.line_breaks"),
+    ));
+}
@@ -0,0 +1,171 @@
+//! Impact sounds played from Rapier's contact force events, and from characters bumping into
+//! things.
+//!
+//! Volume and pitch follow the impulse of the contact and the [`Surface`]s involved. Each body
+//! pair has a cooldown and only a few impacts play per frame, so resting stacks stay quiet.
+//! Sounds play between the two bodies, so they pan with the camera.
+//!
+//! Characters are kinematic, so their [`CharacterHit`]s stand in for contact forces: a bump plays
+//! when a character walks into something it wasn't already touching, louder the faster it went.
+
+use bevy::audio::PlaybackMode;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::AudioAssets;
+use crate::audio_mixer::{AudioChannel, AudioMixer};
+use crate::character_hits::CharacterHit;
+use crate::spatial_audio::play_at;
+use crate::surface::Surface;
+
+/// Contact forces below this don't generate events at all.
+const FORCE_THRESHOLD: f32 = 400.0;
+/// Impulses (N·s) mapped to silent and full volume.
+const QUIET_IMPULSE: f32 = 5.0;
+const LOUD_IMPULSE: f32 = 60.0;
+/// Seconds before the same pair of colliders can sound again.
+const PAIR_COOLDOWN: f32 = 0.15;
+const MAX_IMPACTS_PER_FRAME: usize = 4;
+/// Speeds into a surface, in meters per second, mapped to silent and full volume for bumps.
+const QUIET_BUMP: f32 = 1.0;
+const LOUD_BUMP: f32 = 6.0;
+/// Hits with a normal pointing up more than this are the ground under the character.
+const GROUND_NORMAL_Y: f32 = 0.7;
+
+pub struct ImpactSoundsPlugin;
+
+impl Plugin for ImpactSoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImpactCooldowns>()
+            .init_resource::<CharacterContacts>()
+            .add_systems(
+                Update,
+                (
+                    enable_contact_force_events,
+                    play_impact_sounds.run_if(resource_exists::<AudioAssets>()),
+                    play_bump_sounds.run_if(resource_exists::<AudioAssets>()),
+                ),
+            );
+    }
+}
+
+/// When each collider pair last made a sound, in elapsed seconds.
+#[derive(Resource, Default)]
+struct ImpactCooldowns(HashMap<(Entity, Entity), f32>);
+
+/// Characters and the colliders other than the ground they hit last frame.
+#[derive(Resource, Default)]
+struct CharacterContacts(HashSet<(Entity, Entity)>);
+
+fn enable_contact_force_events(
+    mut commands: Commands,
+    bodies: Query<(Entity, &RigidBody, Option<&ActiveEvents>), Added<Collider>>,
+) {
+    for (entity, body, events) in &bodies {
+        if *body != RigidBody::Dynamic {
+            continue;
+        }
+        let events = events.copied().unwrap_or_default() | ActiveEvents::CONTACT_FORCE_EVENTS;
+        commands
+            .entity(entity)
+            .insert((events, ContactForceEventThreshold(FORCE_THRESHOLD)));
+    }
+}
+
+fn play_impact_sounds(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    audio: Res<AudioAssets>,
+    mixer: Res<AudioMixer>,
+    mut events: EventReader<ContactForceEvent>,
+    mut cooldowns: ResMut<ImpactCooldowns>,
+    surfaces: Query<&Surface>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let now = time.elapsed_seconds();
+    cooldowns.0.retain(|_, last| now - *last < PAIR_COOLDOWN);
+
+    let mut played = 0;
+    for event in events.read() {
+        if played == MAX_IMPACTS_PER_FRAME {
+            break;
+        }
+        let pair = if event.collider1 < event.collider2 {
+            (event.collider1, event.collider2)
+        } else {
+            (event.collider2, event.collider1)
+        };
+        if cooldowns.0.contains_key(&pair) {
+            continue;
+        }
+
+        // Contact forces are reported per physics step, which can differ from the frame time.
+        let impulse = event.total_force_magnitude * rapier_context.integration_parameters.dt;
+        let loudness = ((impulse - QUIET_IMPULSE) / (LOUD_IMPULSE - QUIET_IMPULSE)).clamp(0.0, 1.0);
+        if loudness == 0.0 {
+            continue;
+        }
+
+        let surface = |entity| surfaces.get(entity).copied().unwrap_or_default();
+        let (a, b) = (surface(event.collider1), surface(event.collider2));
+        let harder = if a.hardness() >= b.hardness() { a } else { b };
+        // Harder hits ring slightly higher.
+        let pitch = (a.pitch() + b.pitch()) / 2.0 * (0.9 + 0.2 * loudness);
+
+        let position = |entity| {
+            transforms
+                .get(entity)
+                .map_or(Vec3::ZERO, GlobalTransform::translation)
+        };
+        play_at(
+            &mut commands,
+            position(event.collider1).lerp(position(event.collider2), 0.5),
+            harder.impact_sound(&audio),
+            PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: mixer.playback_volume(AudioChannel::Sfx, loudness),
+                speed: pitch,
+                ..default()
+            },
+        );
+        cooldowns.0.insert(pair, now);
+        played += 1;
+    }
+}
+
+fn play_bump_sounds(
+    mut commands: Commands,
+    audio: Res<AudioAssets>,
+    mixer: Res<AudioMixer>,
+    mut hits: EventReader<CharacterHit>,
+    mut contacts: ResMut<CharacterContacts>,
+    surfaces: Query<&Surface>,
+) {
+    let previous = std::mem::take(&mut contacts.0);
+    for hit in hits.read() {
+        if hit.normal.y > GROUND_NORMAL_Y || !contacts.0.insert((hit.character, hit.entity)) {
+            continue;
+        }
+        if previous.contains(&(hit.character, hit.entity)) {
+            continue;
+        }
+        let loudness = ((hit.speed_into() - QUIET_BUMP) / (LOUD_BUMP - QUIET_BUMP)).clamp(0.0, 1.0);
+        if loudness == 0.0 {
+            continue;
+        }
+        let surface = surfaces.get(hit.entity).copied().unwrap_or_default();
+        play_at(
+            &mut commands,
+            hit.point,
+            surface.impact_sound(&audio),
+            PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: mixer.playback_volume(AudioChannel::Sfx, loudness),
+                speed: surface.pitch(),
+                ..default()
+            },
+        );
+    }
+}
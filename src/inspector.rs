@@ -0,0 +1,91 @@
+//! egui world inspector, only compiled with the `inspector` feature.
+//!
+//! F1 toggles the inspector windows. Left-clicking a collider selects its entity, whose
+//! components (including Rapier ones such as [`GravityScale`] and [`Velocity`]) can then be
+//! edited in the "Selected" window.
+
+use bevy::input::common_conditions::input_toggle_active;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_inspector_egui::bevy_egui::{EguiContext, EguiContexts};
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_inspector_egui::{bevy_inspector, egui};
+use bevy_rapier3d::prelude::*;
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(
+            WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::F1)),
+        )
+        .init_resource::<InspectorSelection>()
+        .add_systems(
+            Update,
+            (select_on_click, selected_entity_ui)
+                .chain()
+                .run_if(input_toggle_active(false, KeyCode::F1)),
+        );
+    }
+}
+
+/// Entity shown in the "Selected" inspector window.
+#[derive(Resource, Default)]
+pub struct InspectorSelection(pub Option<Entity>);
+
+fn select_on_click(
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    rapier_context: Res<RapierContext>,
+    mut contexts: EguiContexts,
+    mut selection: ResMut<InspectorSelection>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) || contexts.ctx_mut().wants_pointer_input() {
+        return;
+    }
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    selection.0 = rapier_context
+        .cast_ray(
+            ray.origin,
+            ray.direction,
+            1000.0,
+            true,
+            QueryFilter::default(),
+        )
+        .map(|(entity, _)| entity);
+}
+
+fn selected_entity_ui(world: &mut World) {
+    let Some(entity) = world.resource::<InspectorSelection>().0 else {
+        return;
+    };
+    if world.get_entity(entity).is_none() {
+        world.resource_mut::<InspectorSelection>().0 = None;
+        return;
+    }
+    let Ok(egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    let mut egui_context = egui_context.clone();
+
+    egui::Window::new("Selected")
+        .default_pos((10.0, 300.0))
+        .show(egui_context.get_mut(), |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                bevy_inspector::ui_for_entity(world, entity, ui);
+            });
+        });
+}
@@ -0,0 +1,186 @@
+//! Contextual interaction prompts.
+//!
+//! A ray cast through the center of the [`MainCamera`] picks the [`Interactable`] being looked
+//! at, whose prompt is shown as "Press E to …" (the `interaction.prompt` string). Pressing E
+//! sends an [`InteractEvent`].
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::conditions::text_input_closed;
+use crate::crosshair::ReticleHit;
+use crate::localization::Localization;
+use crate::text_theme::TextTheme;
+use crate::Player;
+
+/// Maximum distance from the camera at which interactables can be used.
+const INTERACT_RANGE: f32 = 15.0;
+/// Impulse applied to dynamic bodies when they are interacted with.
+const PUSH_IMPULSE: f32 = 8.0;
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InteractEvent>()
+            .init_resource::<InteractionTarget>()
+            .add_systems(Startup, spawn_interaction_prompt)
+            .add_systems(
+                Update,
+                (
+                    find_interaction_target,
+                    update_interaction_prompt,
+                    interact.run_if(text_input_closed()),
+                    push_interacted_bodies,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Something the player can interact with; `prompt` completes "Press E to …".
+#[derive(Component, Clone, Debug)]
+pub struct Interactable {
+    pub prompt: String,
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+        }
+    }
+}
+
+/// Sent when the player presses E while looking at an [`Interactable`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct InteractEvent {
+    pub entity: Entity,
+    /// Where the interaction ray hit the entity.
+    pub point: Vec3,
+    /// Direction of the interaction ray.
+    pub direction: Vec3,
+}
+
+/// The interactable currently looked at, if any.
+#[derive(Resource, Default)]
+struct InteractionTarget(Option<InteractEvent>);
+
+#[derive(Component)]
+struct InteractionPrompt;
+
+fn spawn_interaction_prompt(mut commands: Commands, theme: Res<TextTheme>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Percent(58.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section("", theme.body()),
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                InteractionPrompt,
+            ));
+        });
+}
+
+fn find_interaction_target(
+    rapier_context: Res<RapierContext>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    player: Query<Entity, With<Player>>,
+    interactables: Query<(), With<Interactable>>,
+    mut target: ResMut<InteractionTarget>,
+) {
+    target.0 = None;
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let mut filter = QueryFilter::default();
+    if let Ok(player) = player.get_single() {
+        filter = filter.exclude_collider(player);
+    }
+
+    let origin = camera.translation();
+    let direction = camera.forward();
+    if let Some((entity, toi)) =
+        rapier_context.cast_ray(origin, direction, INTERACT_RANGE, true, filter)
+    {
+        if interactables.contains(entity) {
+            target.0 = Some(InteractEvent {
+                entity,
+                point: origin + direction * toi,
+                direction,
+            });
+        }
+    }
+}
+
+fn update_interaction_prompt(
+    target: Res<InteractionTarget>,
+    localization: Res<Localization>,
+    interactables: Query<&Interactable>,
+    mut prompts: Query<(&mut Text, &mut Visibility), With<InteractionPrompt>>,
+) {
+    let prompt = target
+        .0
+        .and_then(|target| interactables.get(target.entity).ok());
+
+    for (mut text, mut visibility) in &mut prompts {
+        match prompt {
+            Some(interactable) => {
+                let prompt =
+                    localization.format("interaction.prompt", &[("action", &interactable.prompt)]);
+                text.sections[0].value = format!(" {prompt} ");
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn interact(
+    input: Res<Input<KeyCode>>,
+    target: Res<InteractionTarget>,
+    mut interactions: EventWriter<InteractEvent>,
+    mut hits: EventWriter<ReticleHit>,
+) {
+    if !input.just_pressed(KeyCode::E) {
+        return;
+    }
+    if let Some(event) = target.0 {
+        interactions.send(event);
+        hits.send(ReticleHit {
+            entity: event.entity,
+        });
+    }
+}
+
+/// Default interaction for dynamic bodies: push them away from the camera, at the point looked at.
+fn push_interacted_bodies(
+    mut commands: Commands,
+    mut interactions: EventReader<InteractEvent>,
+    bodies: Query<(&RigidBody, &GlobalTransform)>,
+) {
+    for event in interactions.read() {
+        if let Ok((RigidBody::Dynamic, transform)) = bodies.get(event.entity) {
+            commands
+                .entity(event.entity)
+                .insert(ExternalImpulse::at_point(
+                    event.direction * PUSH_IMPULSE,
+                    event.point,
+                    transform.translation(),
+                ));
+        }
+    }
+}
@@ -0,0 +1,654 @@
+//! Joint demos, one for each kind of Rapier joint: a door on a revolute hinge, a block on a
+//! prismatic rail, a chain of balls on spherical joints and an L of boxes welded with a fixed
+//! joint, plus a bridge of hinged planks and a wrecking ball swinging into a wall of crates.
+//! `joints <door|slider|chain|weld|bridge|wrecking_ball|all> [x y z]` in the console spawns them,
+//! in front of the camera by default, and the `spawn_*` functions spawn them from code.
+//!
+//! Each demo is a fixed anchor and dynamic bodies carrying an [`ImpulseJoint`] to the body before
+//! them. A [`BreakableJoint`] comes apart when the force holding it together gets too strong, like
+//! the hinges of the bridge under a heavy load, and sends a [`JointBroken`].
+//!
+//! Spinners are hazards on motorized revolute joints: a platform turning under whatever stands
+//! on it, or a blade sweeping at knee height. `spinner <platform|blade> [speed] [torque]` spawns
+//! one in front of the camera. The motor drives the body towards its target speed with at most
+//! the given torque, so heavy props can slow or stall it. Characters have no body for the spinner
+//! to push, so [`ride_spinners`] moves them along with the surface they touch, and blades hurt
+//! them, as much as the [`Difficulty`] says.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::difficulty::Difficulty;
+use crate::game_set::GameSet;
+use crate::health::DamageEvent;
+use crate::level_data::spawn_prefab;
+use crate::materials::MaterialLibrary;
+use crate::surface::Surface;
+
+/// How far in front of the camera the demos appear, in meters.
+const SPAWN_DISTANCE: f32 = 6.0;
+/// Links of the chain.
+const CHAIN_LINKS: usize = 8;
+const CHAIN_LINK_RADIUS: f32 = 0.15;
+/// How far the slider's block travels either way along its rail, in meters.
+const SLIDER_TRAVEL: f32 = 2.0;
+/// Planks of the bridge, and how far apart their joints are, in meters.
+const BRIDGE_PLANKS: usize = 10;
+const BRIDGE_PLANK_LENGTH: f32 = 0.7;
+/// How steeply the bridge slopes down from its posts, in radians.
+const BRIDGE_SAG: f32 = 0.25;
+/// Planks are as dense as wood, in kilograms per cubic meter.
+const BRIDGE_PLANK_DENSITY: f32 = 600.0;
+/// Force that snaps a joint of the bridge, in newtons.
+const BRIDGE_BREAK_FORCE: f32 = 30_000.0;
+/// Height of the wrecking ball's anchor, in meters; the `pendulum` prefab hangs 5 m of rope.
+const PENDULUM_HEIGHT: f32 = 6.0;
+/// Crates across and up the wall the wrecking ball swings into.
+const WALL_SIZE: (usize, usize) = (3, 3);
+/// How strongly spinner motors correct towards their target speed.
+const MOTOR_FACTOR: f32 = 1.0;
+/// How far below a character spinners are still touching it, so standing on a platform counts.
+const CONTACT_MARGIN: f32 = 0.05;
+/// Shortest time between two hits of the same blade, in seconds.
+const BLADE_HIT_INTERVAL: f32 = 0.5;
+
+pub struct JointsPlugin;
+
+impl Plugin for JointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command(
+            "joints",
+            "joints <door|slider|chain|weld|bridge|wrecking_ball|all> [x y z] - spawn joint demos, in front of the camera by default",
+            joints_command,
+        )
+        .add_console_command(
+            "spinner",
+            "spinner <platform|blade> [speed] [torque] - spawn a motorized spinner in front of the camera",
+            spinner_command,
+        )
+        .add_event::<JointBroken>()
+        .add_systems(Update, ride_spinners.in_set(GameSet::MovementModifiers))
+        .add_systems(
+            PostUpdate,
+            break_overloaded_joints.in_set(GameSet::PhysicsWriteback),
+        );
+    }
+}
+
+/// Removes the [`ImpulseJoint`] of its entity once the joint has to hold it with more than
+/// `force_threshold` newtons.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BreakableJoint {
+    pub force_threshold: f32,
+}
+
+/// Sent when the [`BreakableJoint`] of `entity` breaks, with the force that broke it.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct JointBroken {
+    pub entity: Entity,
+    pub force: f32,
+}
+
+/// The kinds of motorized spinner.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpinnerKind {
+    /// A disc to ride on, turning slowly.
+    Platform,
+    /// A bar sweeping at knee height, hurting what it hits.
+    Blade,
+}
+
+impl SpinnerKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "platform" => Some(SpinnerKind::Platform),
+            "blade" => Some(SpinnerKind::Blade),
+            _ => None,
+        }
+    }
+
+    /// Target speed when none is given, in radians per second.
+    pub fn default_speed(self) -> f32 {
+        match self {
+            SpinnerKind::Platform => 0.6,
+            SpinnerKind::Blade => 3.0,
+        }
+    }
+
+    /// Strongest torque of the motor when none is given, in newton meters.
+    pub fn default_torque(self) -> f32 {
+        match self {
+            SpinnerKind::Platform => 5000.0,
+            SpinnerKind::Blade => 2000.0,
+        }
+    }
+}
+
+/// The moving part of a spinner, carrying characters that touch it.
+#[derive(Component, Debug)]
+pub struct Spinner {
+    /// Health taken from a character each time the spinner hits it on normal difficulty; zero for
+    /// platforms.
+    pub damage: f32,
+    /// Seconds until the spinner can hit again.
+    cooldown: f32,
+}
+
+impl Spinner {
+    pub fn new(damage: f32) -> Self {
+        Self {
+            damage,
+            cooldown: 0.0,
+        }
+    }
+}
+
+/// Spawns a body with a mesh, a collider and the prototype material.
+fn spawn_body(
+    world: &mut World,
+    name: &str,
+    body: RigidBody,
+    mesh: Mesh,
+    collider: Collider,
+    transform: Transform,
+) -> Entity {
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(mesh);
+    let material = match world.get_resource::<MaterialLibrary>() {
+        Some(library) => library.prototype.clone(),
+        None => world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::SILVER.into()),
+    };
+    world
+        .spawn((
+            PbrBundle {
+                mesh,
+                material,
+                transform,
+                ..default()
+            },
+            body,
+            collider,
+            Name::new(name.to_string()),
+        ))
+        .id()
+}
+
+/// A door standing on the ground at `position`, swinging 110° either way around a post on its
+/// left edge.
+pub fn spawn_hinged_door(world: &mut World, position: Vec3) -> Entity {
+    let door_size = Vec3::new(1.0, 2.0, 0.1);
+    let post = spawn_body(
+        world,
+        "Door post",
+        RigidBody::Fixed,
+        shape::Box::new(0.1, door_size.y, 0.1).into(),
+        Collider::cuboid(0.05, door_size.y / 2.0, 0.05),
+        Transform::from_translation(position + Vec3::Y * door_size.y / 2.0),
+    );
+    let hinge = RevoluteJointBuilder::new(Vec3::Y)
+        .local_anchor1(Vec3::ZERO)
+        .local_anchor2(Vec3::new(-door_size.x / 2.0 - 0.1, 0.0, 0.0))
+        .limits([-110f32.to_radians(), 110f32.to_radians()]);
+    let door = spawn_body(
+        world,
+        "Door",
+        RigidBody::Dynamic,
+        shape::Box::new(door_size.x, door_size.y, door_size.z).into(),
+        Collider::cuboid(door_size.x / 2.0, door_size.y / 2.0, door_size.z / 2.0),
+        Transform::from_translation(
+            position + Vec3::new(door_size.x / 2.0 + 0.1, door_size.y / 2.0, 0.0),
+        ),
+    );
+    world.entity_mut(door).insert((
+        ImpulseJoint::new(post, hinge),
+        Damping {
+            linear_damping: 0.0,
+            angular_damping: 1.0,
+        },
+        Surface::Wood,
+    ));
+    door
+}
+
+/// A block sliding along a rail on the ground at `position`, stopping [`SLIDER_TRAVEL`] either
+/// way.
+pub fn spawn_slider(world: &mut World, position: Vec3) -> Entity {
+    let rail_length = SLIDER_TRAVEL * 2.0 + 1.0;
+    let rail = spawn_body(
+        world,
+        "Slider rail",
+        RigidBody::Fixed,
+        shape::Box::new(rail_length, 0.1, 0.2).into(),
+        Collider::cuboid(rail_length / 2.0, 0.05, 0.1),
+        Transform::from_translation(position + Vec3::Y * 0.05),
+    );
+    let slide = PrismaticJointBuilder::new(Vec3::X)
+        .local_anchor1(Vec3::Y * 0.5)
+        .local_anchor2(Vec3::ZERO)
+        .limits([-SLIDER_TRAVEL, SLIDER_TRAVEL]);
+    let block = spawn_body(
+        world,
+        "Slider block",
+        RigidBody::Dynamic,
+        shape::Box::new(0.8, 0.8, 0.8).into(),
+        Collider::cuboid(0.4, 0.4, 0.4),
+        Transform::from_translation(position + Vec3::Y * 0.55),
+    );
+    world
+        .entity_mut(block)
+        .insert(ImpulseJoint::new(rail, slide));
+    block
+}
+
+/// A chain of [`CHAIN_LINKS`] balls hanging from an anchor `position`, each free to swing around
+/// the one above. Returns the anchor.
+pub fn spawn_chain(world: &mut World, position: Vec3) -> Entity {
+    let spacing = CHAIN_LINK_RADIUS * 2.5;
+    let anchor = spawn_body(
+        world,
+        "Chain anchor",
+        RigidBody::Fixed,
+        shape::Cube::new(0.3).into(),
+        Collider::cuboid(0.15, 0.15, 0.15),
+        Transform::from_translation(position),
+    );
+    let mut previous = anchor;
+    for link in 1..=CHAIN_LINKS {
+        // The first link hangs to the side, so the chain swings when it spawns.
+        let offset = if link == 1 {
+            Vec3::X * spacing
+        } else {
+            Vec3::NEG_Y * spacing
+        };
+        let socket = SphericalJointBuilder::new()
+            .local_anchor1(offset)
+            .local_anchor2(Vec3::ZERO);
+        let entity = spawn_body(
+            world,
+            "Chain link",
+            RigidBody::Dynamic,
+            shape::UVSphere {
+                radius: CHAIN_LINK_RADIUS,
+                ..default()
+            }
+            .into(),
+            Collider::ball(CHAIN_LINK_RADIUS),
+            Transform::from_translation(
+                position + Vec3::X * spacing - Vec3::Y * spacing * (link - 1) as f32,
+            ),
+        );
+        world
+            .entity_mut(entity)
+            .insert(ImpulseJoint::new(previous, socket));
+        previous = entity;
+    }
+    anchor
+}
+
+/// Two boxes welded into an L at `position`, which fall and tumble as one body would.
+pub fn spawn_welded(world: &mut World, position: Vec3) -> Entity {
+    let size = Vec3::new(1.5, 0.4, 0.4);
+    let mesh = || shape::Box::new(size.x, size.y, size.z).into();
+    let collider = || Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0);
+    let base = spawn_body(
+        world,
+        "Welded base",
+        RigidBody::Dynamic,
+        mesh(),
+        collider(),
+        Transform::from_translation(position + Vec3::Y * 2.0),
+    );
+    // The upright's own x axis points up, so its end meets the end of the base.
+    let corner = Vec3::new(size.x / 2.0 - size.y / 2.0, size.y / 2.0, 0.0);
+    let weld = FixedJointBuilder::new()
+        .local_anchor1(corner)
+        .local_anchor2(Vec3::new(-size.x / 2.0, 0.0, 0.0))
+        .local_basis2(Quat::from_rotation_z(-FRAC_PI_2));
+    let upright = spawn_body(
+        world,
+        "Welded upright",
+        RigidBody::Dynamic,
+        mesh(),
+        collider(),
+        Transform::from_translation(position + Vec3::Y * 2.0 + corner + Vec3::Y * size.x / 2.0)
+            .with_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+    );
+    world
+        .entity_mut(upright)
+        .insert(ImpulseJoint::new(base, weld));
+    base
+}
+
+/// A bridge of [`BRIDGE_PLANKS`] hinged planks hanging between two posts, centered on `position`
+/// and spanning along x. Each hinge snaps under [`BRIDGE_BREAK_FORCE`]. Returns the first post.
+pub fn spawn_bridge(world: &mut World, position: Vec3) -> Entity {
+    let plank_size = Vec3::new(BRIDGE_PLANK_LENGTH - 0.05, 0.1, 1.5);
+    // The planks start out sagging the way a hanging chain settles, steepest at the posts.
+    let slope = |plank: usize| {
+        let along = (plank as f32 + 0.5) / BRIDGE_PLANKS as f32 * 2.0 - 1.0;
+        (along * BRIDGE_SAG.tan()).atan()
+    };
+    let span: f32 = (0..BRIDGE_PLANKS)
+        .map(|plank| slope(plank).cos() * BRIDGE_PLANK_LENGTH)
+        .sum();
+    let deck_height = 2.0;
+    let post = |world: &mut World, x: f32| {
+        spawn_body(
+            world,
+            "Bridge post",
+            RigidBody::Fixed,
+            shape::Box::new(0.3, deck_height, plank_size.z).into(),
+            Collider::cuboid(0.15, deck_height / 2.0, plank_size.z / 2.0),
+            Transform::from_translation(position + Vec3::new(x, deck_height / 2.0, 0.0)),
+        )
+    };
+    let start = post(world, -span / 2.0 - 0.15);
+    let end = post(world, span / 2.0 + 0.15);
+    let post_top = Vec3::new(0.15, deck_height / 2.0, 0.0);
+
+    let mut previous = (start, post_top);
+    let mut hinge_position = position + Vec3::new(-span / 2.0, deck_height, 0.0);
+    for plank in 0..BRIDGE_PLANKS {
+        let rotation = Quat::from_rotation_z(slope(plank));
+        let along = rotation * Vec3::X * BRIDGE_PLANK_LENGTH;
+        let entity = spawn_body(
+            world,
+            "Bridge plank",
+            RigidBody::Dynamic,
+            shape::Box::new(plank_size.x, plank_size.y, plank_size.z).into(),
+            Collider::cuboid(plank_size.x / 2.0, plank_size.y / 2.0, plank_size.z / 2.0),
+            Transform::from_translation(hinge_position + along / 2.0).with_rotation(rotation),
+        );
+        hinge_position += along;
+        // Joins the end of the previous plank, or the top of the first post, to this one's start.
+        let hinge = RevoluteJointBuilder::new(Vec3::Z)
+            .local_anchor1(previous.1)
+            .local_anchor2(Vec3::X * -BRIDGE_PLANK_LENGTH / 2.0);
+        world.entity_mut(entity).insert((
+            ImpulseJoint::new(previous.0, hinge),
+            BreakableJoint {
+                force_threshold: BRIDGE_BREAK_FORCE,
+            },
+            ColliderMassProperties::Density(BRIDGE_PLANK_DENSITY),
+            Surface::Wood,
+        ));
+        previous = (entity, Vec3::X * BRIDGE_PLANK_LENGTH / 2.0);
+    }
+    // The last plank already has a joint, so the second post carries the one between them.
+    let closing = RevoluteJointBuilder::new(Vec3::Z)
+        .local_anchor1(previous.1)
+        .local_anchor2(post_top * Vec3::new(-1.0, 1.0, 1.0));
+    world.entity_mut(end).insert((
+        ImpulseJoint::new(previous.0, closing),
+        BreakableJoint {
+            force_threshold: BRIDGE_BREAK_FORCE,
+        },
+    ));
+    start
+}
+
+/// The `pendulum` prefab hanging over `position`, its ball let go to one side, and a wall of
+/// crates past the bottom of its swing. The ball hangs 5 m from the anchor on a ball joint, which
+/// swings like a rope that stays taut. Returns the pendulum's anchor.
+pub fn spawn_wrecking_ball(world: &mut World, position: Vec3) -> Result<Entity, String> {
+    let anchor = spawn_prefab(
+        world,
+        "pendulum",
+        Transform::from_translation(position + Vec3::Y * PENDULUM_HEIGHT),
+    )?;
+    let (across, up) = WALL_SIZE;
+    for column in 0..across {
+        for layer in 0..up {
+            let offset = Vec3::new(
+                -1.2,
+                layer as f32 * 1.01 + 0.5,
+                (column as f32 - (across - 1) as f32 / 2.0) * 1.01,
+            );
+            spawn_prefab(
+                world,
+                "crate",
+                Transform::from_translation(position + offset),
+            )?;
+        }
+    }
+    Ok(anchor)
+}
+
+/// A spinner standing on the ground at `position`, driven around the vertical axis at `speed`
+/// radians per second by a motor of at most `max_torque`. Returns the moving part.
+pub fn spawn_spinner(
+    world: &mut World,
+    position: Vec3,
+    kind: SpinnerKind,
+    speed: f32,
+    max_torque: f32,
+) -> Entity {
+    // The hub is the post the moving part turns on, and the moving part rests just above it.
+    let (hub_height, mesh, collider, height, damage): (f32, Mesh, _, f32, _) = match kind {
+        SpinnerKind::Platform => (
+            0.2,
+            shape::Cylinder {
+                radius: 2.5,
+                height: 0.3,
+                ..default()
+            }
+            .into(),
+            Collider::cylinder(0.15, 2.5),
+            0.3,
+            0.0,
+        ),
+        SpinnerKind::Blade => (
+            0.7,
+            shape::Box::new(4.0, 0.2, 0.3).into(),
+            Collider::cuboid(2.0, 0.1, 0.15),
+            0.2,
+            20.0,
+        ),
+    };
+    let hub = spawn_body(
+        world,
+        "Spinner hub",
+        RigidBody::Fixed,
+        shape::Cylinder {
+            radius: 0.2,
+            height: hub_height,
+            ..default()
+        }
+        .into(),
+        Collider::cylinder(hub_height / 2.0, 0.2),
+        Transform::from_translation(position + Vec3::Y * hub_height / 2.0),
+    );
+    let mut axle = RevoluteJointBuilder::new(Vec3::Y)
+        .local_anchor1(Vec3::Y * (hub_height + height) / 2.0)
+        .local_anchor2(Vec3::ZERO)
+        .motor_velocity(speed, MOTOR_FACTOR)
+        .motor_max_force(max_torque)
+        .build();
+    axle.set_contacts_enabled(false);
+    let name = match kind {
+        SpinnerKind::Platform => "Spinning platform",
+        SpinnerKind::Blade => "Spinning blade",
+    };
+    let spinner = spawn_body(
+        world,
+        name,
+        RigidBody::Dynamic,
+        mesh,
+        collider,
+        Transform::from_translation(position + Vec3::Y * (hub_height + height / 2.0)),
+    );
+    world.entity_mut(spinner).insert((
+        ImpulseJoint::new(hub, axle),
+        Velocity::default(),
+        Spinner::new(damage),
+    ));
+    spinner
+}
+
+/// Moves characters touching a spinner along with its surface, and lets blades hit them.
+fn ride_spinners(
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    rapier_context: Res<RapierContext>,
+    mut spinners: Query<(&GlobalTransform, &Velocity, &mut Spinner)>,
+    mut characters: Query<(
+        Entity,
+        &GlobalTransform,
+        &Collider,
+        &mut KinematicCharacterController,
+    )>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let dt = time.delta_seconds();
+    for (_, _, mut spinner) in &mut spinners {
+        spinner.cooldown = (spinner.cooldown - dt).max(0.0);
+    }
+
+    for (entity, transform, collider, mut controller) in &mut characters {
+        let (_, rotation, position) = transform.to_scale_rotation_translation();
+        let mut touching = Vec::new();
+        rapier_context.intersections_with_shape(
+            position + Vec3::NEG_Y * CONTACT_MARGIN,
+            rotation,
+            collider,
+            QueryFilter::default().exclude_collider(entity),
+            |hit| {
+                if spinners.contains(hit) {
+                    touching.push(hit);
+                }
+                true
+            },
+        );
+
+        for hit in touching {
+            let Ok((spinner_transform, velocity, mut spinner)) = spinners.get_mut(hit) else {
+                continue;
+            };
+            let offset = position - spinner_transform.translation();
+            let surface_velocity = velocity.linvel + velocity.angvel.cross(offset);
+            let carried = Vec3::new(surface_velocity.x, 0.0, surface_velocity.z) * dt;
+            controller.translation = Some(controller.translation.unwrap_or_default() + carried);
+
+            if spinner.damage > 0.0 && spinner.cooldown <= 0.0 {
+                spinner.cooldown = BLADE_HIT_INTERVAL;
+                damage.send(DamageEvent {
+                    target: entity,
+                    amount: spinner.damage * difficulty.damage(),
+                    point: position,
+                });
+            }
+        }
+    }
+}
+
+fn break_overloaded_joints(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    joints: Query<(Entity, &BreakableJoint, &RapierImpulseJointHandle)>,
+    mut broken: EventWriter<JointBroken>,
+) {
+    let dt = rapier_context.integration_parameters.dt;
+    if dt <= 0.0 {
+        return;
+    }
+    for (entity, breakable, handle) in &joints {
+        let Some(joint) = rapier_context.impulse_joints.get(handle.0) else {
+            continue;
+        };
+        // The linear part of the impulse the solver applied over the last step.
+        let force = joint.impulses.xyz().norm() / dt;
+        if force > breakable.force_threshold {
+            commands
+                .entity(entity)
+                .remove::<(ImpulseJoint, BreakableJoint)>();
+            broken.send(JointBroken { entity, force });
+        }
+    }
+}
+
+fn spinner_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = *args.first().ok_or("missing argument <kind>")?;
+    let kind = SpinnerKind::from_name(name)
+        .ok_or_else(|| format!("unknown spinner `{name}`, expected platform or blade"))?;
+    let speed = if args.len() > 1 {
+        parse_arg(args, 1, "speed")?
+    } else {
+        kind.default_speed()
+    };
+    let torque: f32 = if args.len() > 2 {
+        parse_arg(args, 2, "torque")?
+    } else {
+        kind.default_torque()
+    };
+    if torque < 0.0 {
+        return Err("the torque can't be negative".to_string());
+    }
+    let position = world
+        .query_filtered::<&GlobalTransform, With<MainCamera>>()
+        .get_single(world)
+        .map_or(Vec3::ZERO, |camera| {
+            let ahead = camera.translation() + camera.forward() * SPAWN_DISTANCE;
+            Vec3::new(ahead.x, 0.0, ahead.z)
+        });
+    spawn_spinner(world, position, kind, speed, torque);
+    Ok(format!("spawned a {name} spinning at {speed} rad/s"))
+}
+
+fn joints_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let demo = *args.first().ok_or("missing argument <demo>")?;
+    let position = if args.len() > 1 {
+        Vec3::new(
+            parse_arg(args, 1, "x")?,
+            parse_arg(args, 2, "y")?,
+            parse_arg(args, 3, "z")?,
+        )
+    } else {
+        world
+            .query_filtered::<&GlobalTransform, With<MainCamera>>()
+            .get_single(world)
+            .map_or(Vec3::ZERO, |camera| {
+                let ahead = camera.translation() + camera.forward() * SPAWN_DISTANCE;
+                Vec3::new(ahead.x, 0.0, ahead.z)
+            })
+    };
+
+    match demo {
+        "door" => {
+            spawn_hinged_door(world, position);
+        }
+        "slider" => {
+            spawn_slider(world, position);
+        }
+        "chain" => {
+            spawn_chain(world, position + Vec3::Y * 4.0);
+        }
+        "weld" => {
+            spawn_welded(world, position);
+        }
+        "bridge" => {
+            spawn_bridge(world, position);
+        }
+        "wrecking_ball" => {
+            spawn_wrecking_ball(world, position)?;
+        }
+        "all" => {
+            spawn_hinged_door(world, position + Vec3::X * -4.0);
+            spawn_slider(world, position + Vec3::X * -1.0);
+            spawn_chain(world, position + Vec3::new(2.0, 4.0, 0.0));
+            spawn_welded(world, position + Vec3::X * 5.0);
+            spawn_bridge(world, position + Vec3::Z * -4.0);
+            spawn_wrecking_ball(world, position + Vec3::Z * 4.0)?;
+        }
+        _ => {
+            return Err(format!(
+                "unknown demo `{demo}`, expected door, slider, chain, weld, bridge, wrecking_ball or all"
+            ))
+        }
+    }
+    Ok(format!("spawned the {demo} joint demo"))
+}
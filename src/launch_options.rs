@@ -0,0 +1,258 @@
+//! Command-line options, parsed in `main` before the app is built and kept as the
+//! [`LaunchOptions`] resource.
+//!
+//! Run with `--help` for the list. Invalid arguments print the error and the usage and exit, since
+//! logging isn't set up yet while the options are parsed.
+
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::log::Level;
+use bevy::prelude::*;
+
+use crate::network::{NetworkRole, DEFAULT_PORT};
+
+/// Frames run by `--bench` when no count is given.
+const DEFAULT_BENCH_FRAMES: u32 = 1000;
+
+const USAGE: &str = "\
+usage: asset_test [options]
+
+  --level <path>          spawn the glTF scene at <path> (relative to assets/) instead of the playground,
+                          the block map at <path> if it ends with .blocks.txt, or stream the chunks
+                          listed in <path> if it ends with .chunks.ron
+  --headless              run without a window
+  --bench [frames]        run [frames] frames after loading, print frame time statistics and quit
+  --exec <command>        run the console <command> once loading finishes (repeatable), for example
+                          --exec \"joints wrecking_ball\" to benchmark a scenario with --bench
+  --debug [flags]         comma-separated debug views to start with: render, panel (default: all)
+  --seed <n>              seed for anything random, to reproduce a run
+  -v, --verbose           log debug messages; twice (-vv) to also log trace messages
+  -q, --quiet             only log warnings and errors
+  --host [port]           host a networked game on [port]
+  --connect <address>     join the networked game at <address>
+  --capture               step at a fixed rate and save every frame to captures/
+  --capture-frames <n>    quit after capturing <n> frames
+  --help                  print this message";
+
+/// Debug views turned on with `--debug`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DebugFlags {
+    /// Collider wireframes and gizmos, as toggled with F4.
+    pub render: bool,
+    /// The debug panel, as toggled with F3.
+    pub panel: bool,
+}
+
+/// The options the game was started with.
+#[derive(Resource, Clone, Debug)]
+pub struct LaunchOptions {
+    /// Asset path of the level scene or chunk manifest, if not the playground.
+    pub level: Option<String>,
+    pub headless: bool,
+    /// Frames to benchmark before quitting.
+    pub bench: Option<u32>,
+    /// Console commands to run once loading finishes, in order.
+    pub exec: Vec<String>,
+    pub debug: DebugFlags,
+    /// Given with `--seed`, or taken from the clock.
+    pub seed: u64,
+    /// Raised by `-v`, lowered by `-q`; 0 logs info messages and above.
+    pub verbosity: i8,
+    pub network: Option<NetworkRole>,
+    pub capture: bool,
+    pub capture_frames: Option<u32>,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            level: None,
+            headless: false,
+            bench: None,
+            exec: Vec::new(),
+            debug: DebugFlags::default(),
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+            verbosity: 0,
+            network: None,
+            capture: false,
+            capture_frames: None,
+        }
+    }
+}
+
+impl LaunchOptions {
+    /// Parses the process arguments, exiting with the usage on `--help` or an invalid argument.
+    pub fn from_args() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Some(options)) => options,
+            Ok(None) => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Err(error) => {
+                eprintln!("{error}\n\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    /// Parses `args`, without the program name. Returns `None` if `--help` was passed.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            // The value of options whose value can be left out.
+            let mut optional_value = || args.next_if(|next| !next.starts_with('-'));
+            match arg.as_str() {
+                "--level" => options.level = Some(required(&arg, optional_value())?),
+                "--headless" => options.headless = true,
+                "--bench" => {
+                    options.bench = Some(match optional_value() {
+                        Some(frames) => parse_value(&arg, &frames)?,
+                        None => DEFAULT_BENCH_FRAMES,
+                    });
+                }
+                "--exec" => options.exec.push(required(&arg, optional_value())?),
+                "--debug" => {
+                    options.debug = match optional_value() {
+                        Some(flags) => parse_debug_flags(&flags)?,
+                        None => DebugFlags {
+                            render: true,
+                            panel: true,
+                        },
+                    };
+                }
+                "--seed" => options.seed = parse_value(&arg, &required(&arg, optional_value())?)?,
+                "--host" => {
+                    options.network = Some(NetworkRole::Server(match optional_value() {
+                        Some(port) => parse_value(&arg, &port)?,
+                        None => DEFAULT_PORT,
+                    }));
+                }
+                "--connect" => {
+                    let address: SocketAddr =
+                        parse_value(&arg, &required(&arg, optional_value())?)?;
+                    options.network = Some(NetworkRole::Client(address));
+                }
+                "-v" | "--verbose" => options.verbosity += 1,
+                "-vv" => options.verbosity += 2,
+                "-q" | "--quiet" => options.verbosity -= 1,
+                "--capture" => options.capture = true,
+                "--capture-frames" => {
+                    options.capture_frames =
+                        Some(parse_value(&arg, &required(&arg, optional_value())?)?);
+                }
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        Ok(Some(options))
+    }
+
+    /// The most verbose level that is logged.
+    pub fn log_level(&self) -> Level {
+        match self.verbosity {
+            ..=-1 => Level::WARN,
+            0 => Level::INFO,
+            1 => Level::DEBUG,
+            _ => Level::TRACE,
+        }
+    }
+}
+
+fn required(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{flag} needs a value"))
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for {flag}"))
+}
+
+fn parse_debug_flags(flags: &str) -> Result<DebugFlags, String> {
+    let mut debug = DebugFlags::default();
+    for flag in flags.split(',') {
+        match flag.trim() {
+            "render" => debug.render = true,
+            "panel" => debug.panel = true,
+            other => return Err(format!("unknown debug flag `{other}`")),
+        }
+    }
+    Ok(debug)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<LaunchOptions>, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn options(args: &[&str]) -> LaunchOptions {
+        parse(args).unwrap().unwrap()
+    }
+
+    #[test]
+    fn bench_frames_are_optional() {
+        assert_eq!(options(&["--bench"]).bench, Some(DEFAULT_BENCH_FRAMES));
+        assert_eq!(options(&["--bench", "250"]).bench, Some(250));
+        let options = options(&["--bench", "--headless"]);
+        assert_eq!(options.bench, Some(DEFAULT_BENCH_FRAMES));
+        assert!(options.headless);
+    }
+
+    #[test]
+    fn verbosity_adds_up() {
+        assert_eq!(options(&[]).log_level(), Level::INFO);
+        assert_eq!(options(&["-v"]).log_level(), Level::DEBUG);
+        assert_eq!(options(&["-vv"]).log_level(), Level::TRACE);
+        assert_eq!(options(&["-q"]).log_level(), Level::WARN);
+        assert_eq!(options(&["-vv", "-q"]).verbosity, 1);
+    }
+
+    #[test]
+    fn debug_flags() {
+        let all = options(&["--debug"]).debug;
+        assert!(all.render && all.panel);
+        let listed = options(&["--debug", "render,panel"]).debug;
+        assert!(listed.render && listed.panel);
+        let render = options(&["--debug", "render"]).debug;
+        assert!(render.render && !render.panel);
+        assert!(parse(&["--debug", "render,sound"]).is_err());
+    }
+
+    #[test]
+    fn values() {
+        let options = options(&[
+            "--level",
+            "a.blocks.txt",
+            "--seed",
+            "7",
+            "--exec",
+            "spawn ball",
+        ]);
+        assert_eq!(options.level.as_deref(), Some("a.blocks.txt"));
+        assert_eq!(options.seed, 7);
+        assert_eq!(options.exec, ["spawn ball"]);
+    }
+
+    #[test]
+    fn help_returns_none() {
+        assert!(parse(&["--help"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn unknown_and_missing_values_are_errors() {
+        assert!(parse(&["--fast"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "--headless"]).is_err());
+        assert!(parse(&["--seed", "many"]).is_err());
+        assert!(parse(&["--bench", "many"]).is_err());
+        assert!(parse(&["--connect", "nowhere"]).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! The playable level: a glTF scene spawned when loading finishes (the playground, or the scene
+//! passed with `--level`), with a fixed trimesh collider generated for each of its meshes and the
+//! floor material from the [`MaterialLibrary`], and the props listed in its
+//! [`LevelData`](crate::level_data::LevelData). Levels can instead be drawn as a
+//! [`crate::block_map`], or streamed in chunks; see [`crate::streaming`].
+//!
+//! Gameplay entities can be placed in the scene by node name; see [`crate::node_templates`].
+
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice};
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::Models;
+use crate::block_map::is_block_map;
+use crate::hot_reload::MeshCollider;
+use crate::launch_options::LaunchOptions;
+use crate::level_data::{LevelData, LevelSpawner};
+use crate::materials::MaterialLibrary;
+use crate::settings::Settings;
+use crate::streaming::is_streamed;
+use crate::MyStates;
+
+/// Level meshes turned into colliders per task.
+const COLLIDER_CHUNK_SIZE: usize = 8;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        // Only the first time, so the level isn't spawned again when coming back from the lobby.
+        app.add_systems(
+            OnEnter(MyStates::Next),
+            (
+                spawn_level.run_if(run_once()),
+                spawn_props.run_if(run_once()),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                generate_level_colliders,
+                apply_level_material.run_if(resource_exists::<MaterialLibrary>()),
+                spawn_block_map.run_if(resource_exists::<PendingBlockMap>()),
+            ),
+        );
+    }
+}
+
+/// Root of the spawned level scene.
+#[derive(Component)]
+pub struct Level;
+
+/// A [`crate::block_map`] level being loaded, spawned by [`spawn_block_map`] once it is ready.
+#[derive(Resource)]
+struct PendingBlockMap(Handle<LevelData>);
+
+fn spawn_level(
+    mut commands: Commands,
+    models: Res<Models>,
+    options: Res<LaunchOptions>,
+    asset_server: Res<AssetServer>,
+) {
+    let scene = match &options.level {
+        Some(path) if is_streamed(path) => return,
+        Some(path) if is_block_map(path) => {
+            commands.insert_resource(PendingBlockMap(asset_server.load(path)));
+            return;
+        }
+        Some(path) => asset_server.load(path),
+        None => models.level.clone(),
+    };
+    commands.spawn((
+        SceneBundle { scene, ..default() },
+        RigidBody::Fixed,
+        Level,
+        Name::new("Level"),
+    ));
+}
+
+/// Lights the level and spawns the entities listed in its [`LevelData`].
+fn spawn_props(
+    mut spawner: LevelSpawner,
+    models: Res<Models>,
+    settings: Res<Settings>,
+    level_data: Res<Assets<LevelData>>,
+) {
+    spawner.commands().insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: settings.graphics.ambient_brightness,
+    });
+
+    let Some(level_data) = level_data.get(&models.level_entities) else {
+        error!("the level entities aren't loaded");
+        return;
+    };
+    for entity in &level_data.entities {
+        spawner.spawn(entity);
+    }
+}
+
+fn spawn_block_map(
+    mut spawner: LevelSpawner,
+    pending: Res<PendingBlockMap>,
+    asset_server: Res<AssetServer>,
+    level_data: Res<Assets<LevelData>>,
+) {
+    let Some(block_map) = level_data.get(&pending.0) else {
+        if asset_server.get_load_state(&pending.0) == Some(LoadState::Failed) {
+            error!("could not load the block map");
+            spawner.commands().remove_resource::<PendingBlockMap>();
+        }
+        return;
+    };
+    spawner.commands().remove_resource::<PendingBlockMap>();
+
+    // Not a `Level`: the blocks bring their own box colliders.
+    let blocks: Vec<Entity> = block_map
+        .entities
+        .iter()
+        .map(|entity| spawner.spawn(entity))
+        .collect();
+    info!("spawned a block map of {} boxes", blocks.len());
+    spawner
+        .commands()
+        .spawn((SpatialBundle::default(), Name::new("Block map")))
+        .push_children(&blocks);
+}
+
+/// Gives every mesh of the level a trimesh collider once the scene has been instantiated.
+///
+/// Building a trimesh is the slow part of loading a large level, so the colliders are built in
+/// parallel before they are inserted.
+fn generate_level_colliders(
+    mut commands: Commands,
+    meshes: Res<Assets<Mesh>>,
+    candidates: Query<(Entity, &Handle<Mesh>), Without<MeshCollider>>,
+    parents: Query<&Parent>,
+    levels: Query<(), With<Level>>,
+) {
+    let level_meshes: Vec<(Entity, &Mesh)> = candidates
+        .iter()
+        .filter(|(entity, _)| {
+            parents
+                .iter_ancestors(*entity)
+                .any(|ancestor| levels.contains(ancestor))
+        })
+        .filter_map(|(entity, handle)| Some((entity, meshes.get(handle)?)))
+        .collect();
+    if level_meshes.is_empty() {
+        return;
+    }
+
+    let colliders =
+        level_meshes.par_chunk_map(ComputeTaskPool::get(), COLLIDER_CHUNK_SIZE, |chunk| {
+            chunk
+                .iter()
+                .map(|(entity, mesh)| {
+                    (
+                        *entity,
+                        Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+    for (entity, collider) in colliders.into_iter().flatten() {
+        match collider {
+            Some(collider) => {
+                commands.entity(entity).insert((collider, MeshCollider));
+            }
+            None => {
+                warn!("could not build a collider for level mesh {entity:?}");
+                commands.entity(entity).insert(MeshCollider);
+            }
+        }
+    }
+}
+
+/// Replaces the materials of the level's meshes with the library's floor.
+fn apply_level_material(
+    mut commands: Commands,
+    library: Res<MaterialLibrary>,
+    added: Query<Entity, Added<Handle<StandardMaterial>>>,
+    parents: Query<&Parent>,
+    levels: Query<(), With<Level>>,
+) {
+    for entity in &added {
+        if parents
+            .iter_ancestors(entity)
+            .any(|ancestor| levels.contains(ancestor))
+        {
+            commands.entity(entity).insert(library.floor.clone());
+        }
+    }
+}
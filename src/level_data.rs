@@ -0,0 +1,583 @@
+//! The data-driven level format: a [`LevelData`] asset, loaded from `.level.ron` files or
+//! [`crate::block_map`]s, lists the entities placed in a level with their transform, mesh, body,
+//! collider and gameplay components.
+//!
+//! The playground's props come from the file under the `levels.playground_entities` key of the
+//! asset manifest; see [`crate::level`]. Reusable entities are [`Prefabs`], listed under the
+//! `prefabs` key.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::ecs::system::{SystemParam, SystemState};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::{AudioAssets, Models};
+use crate::block_map::BlockMapLoader;
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::enemy::make_enemy;
+use crate::gltf_lookup::GltfAssets;
+use crate::interaction::Interactable;
+use crate::materials::MaterialLibrary;
+use crate::settings::Settings;
+use crate::shapes::Shape;
+use crate::soft_body::SoftBodyData;
+use crate::spatial_audio::MotionSound;
+use crate::spawner::Spawner;
+use crate::surface::Surface;
+
+pub struct LevelDataPlugin;
+
+impl Plugin for LevelDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelData>()
+            .register_asset_loader(LevelDataLoader)
+            .register_asset_loader(BlockMapLoader)
+            .init_asset::<Prefabs>()
+            .register_asset_loader(PrefabsLoader)
+            .add_console_command(
+                "prefab",
+                "prefab <name> [x y z] - spawn a prefab, above the origin by default",
+                prefab_command,
+            );
+    }
+}
+
+/// The entities of a level.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug)]
+pub struct LevelData {
+    pub entities: Vec<EntityData>,
+}
+
+/// One entity of a [`LevelData`]. Everything but the name is optional.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EntityData {
+    pub name: String,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// Scales the mesh and the collider alike.
+    pub scale: Vec3,
+    pub mesh: Option<MeshRef>,
+    /// Spawns the [`Prefabs`] entry of this name at this entity's transform instead. Of the other
+    /// fields, only `material`, `body`, `surface`, `density` and `collision_groups` apply, in
+    /// place of the prefab's.
+    pub prefab: Option<String>,
+    /// Name of a [`MaterialLibrary`] material; meshes without one use the prop's own material, or
+    /// the prototype material.
+    pub material: Option<String>,
+    pub body: Option<BodyKind>,
+    pub collider: Option<ColliderShape>,
+    /// A generated shape, giving the entity its mesh and collider in place of `mesh` and
+    /// `collider`.
+    pub shape: Option<Shape>,
+    /// Makes the collider a sensor that reports collision events instead of blocking.
+    pub trigger: bool,
+    pub surface: Option<Surface>,
+    /// Density of the collider, in kilograms per cubic meter, instead of rapier's default of 1.
+    pub density: Option<f32>,
+    /// Membership and filter bits of the collider's [`CollisionGroups`].
+    pub collision_groups: Option<(u32, u32)>,
+    /// Completes "Press E to …" for an [`Interactable`].
+    pub interaction: Option<String>,
+    /// Uses the `ball_gravity_scale` setting as the body's gravity scale.
+    pub ball_gravity: bool,
+    /// Plays the rolling loop while the body moves; see [`MotionSound`].
+    pub rolling_sound: bool,
+    pub force: Option<ForceData>,
+    pub impulse: Option<ForceData>,
+    /// Makes the entity an [`crate::enemy::Enemy`], with its mesh, controller and health.
+    pub enemy: bool,
+    /// Slows the body's spinning, like [`Damping::angular_damping`].
+    pub angular_damping: Option<f32>,
+    /// Entities spawned as children of this one, their transforms relative to it. A dynamic child
+    /// moves on its own unless a `joint` holds it to this one.
+    pub children: Vec<EntityData>,
+    /// Joins a child's body to its parent's.
+    pub joint: Option<JointData>,
+    /// Makes the entity a soft body, with a skinned mesh in place of `mesh` and `shape`. Its
+    /// balls get the `surface`.
+    pub soft_body: Option<SoftBodyData>,
+    /// Makes the entity a [`Spawner`], dropping its prefab around it.
+    pub spawner: Option<Spawner>,
+}
+
+impl Default for EntityData {
+    fn default() -> Self {
+        Self {
+            name: "Entity".to_string(),
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            mesh: None,
+            prefab: None,
+            material: None,
+            body: None,
+            collider: None,
+            shape: None,
+            trigger: false,
+            surface: None,
+            density: None,
+            collision_groups: None,
+            interaction: None,
+            ball_gravity: false,
+            rolling_sound: false,
+            force: None,
+            impulse: None,
+            enemy: false,
+            angular_damping: None,
+            children: Vec::new(),
+            joint: None,
+            soft_body: None,
+            spawner: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum MeshRef {
+    /// A mesh of `props.gltf`, by name.
+    Prop(String),
+    /// A cube with the given side.
+    Cube(f32),
+    /// A sphere with the given radius.
+    Sphere(f32),
+    /// A box with the given size.
+    Box(Vec3),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BodyKind {
+    Dynamic,
+    Fixed,
+    Kinematic,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ColliderShape {
+    Ball(f32),
+    /// Half extents.
+    Cuboid(Vec3),
+    /// Half height of the cylinder part and radius.
+    Capsule(f32, f32),
+}
+
+/// A joint between a child entity and its parent. Points and axes are in the parent's space.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum JointData {
+    /// Turns around `axis` through `anchor`, between `limits` degrees if given. The axis is the
+    /// same in the child's own axes, so the child shouldn't be rotated around another one.
+    Hinge {
+        anchor: Vec3,
+        axis: Vec3,
+        #[serde(default)]
+        limits: Option<(f32, f32)>,
+    },
+    /// Tilts any way around `anchor`.
+    Ball { anchor: Vec3 },
+}
+
+impl JointData {
+    /// The joint for a child at `child` relative to its parent.
+    fn joint(self, child: &Transform) -> GenericJoint {
+        let local = |anchor: Vec3| child.rotation.inverse() * (anchor - child.translation);
+        match self {
+            JointData::Hinge {
+                anchor,
+                axis,
+                limits,
+            } => {
+                let mut hinge = RevoluteJointBuilder::new(axis.normalize())
+                    .local_anchor1(anchor)
+                    .local_anchor2(local(anchor));
+                if let Some((min, max)) = limits {
+                    hinge = hinge.limits([min.to_radians(), max.to_radians()]);
+                }
+                hinge.into()
+            }
+            JointData::Ball { anchor } => SphericalJointBuilder::new()
+                .local_anchor1(anchor)
+                .local_anchor2(local(anchor))
+                .into(),
+        }
+    }
+}
+
+/// A force and a torque, applied continuously or once depending on the field.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ForceData {
+    pub force: Vec3,
+    #[serde(default)]
+    pub torque: Vec3,
+}
+
+#[derive(Debug)]
+pub enum LevelDataError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    /// An invalid [`crate::block_map`].
+    BlockMap(String),
+}
+
+impl fmt::Display for LevelDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelDataError::Io(error) => write!(f, "could not read the level: {error}"),
+            LevelDataError::Ron(error) => write!(f, "invalid level: {error}"),
+            LevelDataError::BlockMap(error) => write!(f, "invalid block map: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelDataError {}
+
+/// Named entity templates, loaded from a `.prefabs.ron` file mapping names to [`EntityData`].
+/// They are spawned with [`LevelSpawner::spawn_prefab`], [`spawn_prefab`], the `prefab` console
+/// command, or a level entity's `prefab` field.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct Prefabs(pub BTreeMap<String, EntityData>);
+
+#[derive(Default)]
+struct PrefabsLoader;
+
+impl AssetLoader for PrefabsLoader {
+    type Asset = Prefabs;
+    type Settings = ();
+    type Error = LevelDataError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Prefabs, LevelDataError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(LevelDataError::Io)?;
+            ron::de::from_bytes(&bytes).map_err(LevelDataError::Ron)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["prefabs.ron"]
+    }
+}
+
+/// Spawns the prefab called `name` at `transform`.
+pub fn spawn_prefab(world: &mut World, name: &str, transform: Transform) -> Result<Entity, String> {
+    if !world.contains_resource::<MaterialLibrary>() {
+        return Err("assets are still loading".to_string());
+    }
+    let mut state = SystemState::<LevelSpawner>::new(world);
+    let entity = state.get_mut(world).spawn_prefab(name, transform);
+    state.apply(world);
+    entity
+}
+
+pub trait PrefabCommandsExt {
+    /// Spawns the prefab called `name` at `transform` when the commands are applied, logging a
+    /// warning if there is no such prefab.
+    fn spawn_prefab(&mut self, name: impl Into<String>, transform: Transform);
+}
+
+impl PrefabCommandsExt for Commands<'_, '_> {
+    fn spawn_prefab(&mut self, name: impl Into<String>, transform: Transform) {
+        let name = name.into();
+        self.add(move |world: &mut World| {
+            if let Err(error) = spawn_prefab(world, &name, transform) {
+                warn!("could not spawn prefab `{name}`: {error}");
+            }
+        });
+    }
+}
+
+fn prefab_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = *args.first().ok_or("missing argument <name>")?;
+    let translation = if args.len() > 1 {
+        Vec3::new(
+            parse_arg(args, 1, "x")?,
+            parse_arg(args, 2, "y")?,
+            parse_arg(args, 3, "z")?,
+        )
+    } else {
+        Vec3::new(0.0, 5.0, 0.0)
+    };
+    spawn_prefab(world, name, Transform::from_translation(translation))?;
+    Ok(format!("spawned {name}"))
+}
+
+#[derive(Default)]
+struct LevelDataLoader;
+
+impl AssetLoader for LevelDataLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelDataError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<LevelData, LevelDataError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(LevelDataError::Io)?;
+            ron::de::from_bytes(&bytes).map_err(LevelDataError::Ron)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Spawns [`EntityData`], resolving prefab names against the loaded [`Prefabs`].
+#[derive(SystemParam)]
+pub struct LevelSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    models: Res<'w, Models>,
+    audio: Res<'w, AudioAssets>,
+    gltf_assets: GltfAssets<'w>,
+    library: Res<'w, MaterialLibrary>,
+    settings: Res<'w, Settings>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    prefabs: Res<'w, Assets<Prefabs>>,
+}
+
+impl<'w, 's> LevelSpawner<'w, 's> {
+    /// The commands the entities are spawned with.
+    pub fn commands(&mut self) -> &mut Commands<'w, 's> {
+        &mut self.commands
+    }
+
+    /// Spawns `data`, or the prefab it names at its transform.
+    pub fn spawn(&mut self, data: &EntityData) -> Entity {
+        let transform = Transform::from_translation(data.translation)
+            .with_rotation(data.rotation)
+            .with_scale(data.scale);
+        let Some(name) = &data.prefab else {
+            return self.spawn_at(data, transform, false);
+        };
+        match self.prefab(name) {
+            Ok(prefab) => {
+                let prefab = EntityData {
+                    material: data.material.clone().or(prefab.material),
+                    body: data.body.or(prefab.body),
+                    surface: data.surface.or(prefab.surface),
+                    density: data.density.or(prefab.density),
+                    collision_groups: data.collision_groups.or(prefab.collision_groups),
+                    ..prefab
+                };
+                self.spawn_at(&prefab, transform, true)
+            }
+            Err(error) => {
+                warn!("level entity `{}`: {error}", data.name);
+                self.commands.spawn_empty().id()
+            }
+        }
+    }
+
+    /// Spawns the prefab called `name` at `transform`.
+    pub fn spawn_prefab(&mut self, name: &str, transform: Transform) -> Result<Entity, String> {
+        let prefab = self.prefab(name)?;
+        Ok(self.spawn_at(&prefab, transform, true))
+    }
+
+    fn prefab(&self, name: &str) -> Result<EntityData, String> {
+        // The prefab's own `prefab` field isn't followed, nor are its children's, so prefabs can't
+        // nest.
+        self.prefabs
+            .get(&self.models.prefabs)
+            .ok_or("the prefabs aren't loaded")?
+            .0
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("no prefab `{name}`"))
+    }
+
+    /// Spawns `data` at `transform`. Children of a prefab are spawned as they are, so a prefab
+    /// naming itself can't recurse.
+    fn spawn_at(&mut self, data: &EntityData, transform: Transform, in_prefab: bool) -> Entity {
+        let mesh = match data.shape {
+            Some(shape) => Some((self.meshes.add(shape.mesh()), self.material(data, None))),
+            None => data.mesh.as_ref().and_then(|mesh| self.mesh(data, mesh)),
+        };
+        let mut entity = self.commands.spawn((
+            TransformBundle::from_transform(transform),
+            Name::new(data.name.clone()),
+        ));
+
+        if let Some((mesh, material)) = mesh {
+            entity.insert((mesh, material, VisibilityBundle::default()));
+        }
+        if let Some(body) = data.body {
+            entity.insert(match body {
+                BodyKind::Dynamic => RigidBody::Dynamic,
+                BodyKind::Fixed => RigidBody::Fixed,
+                BodyKind::Kinematic => RigidBody::KinematicPositionBased,
+            });
+            if matches!(body, BodyKind::Dynamic) {
+                entity.insert(Velocity::default());
+            }
+        }
+        if let Some(shape) = data.shape {
+            entity.insert(shape.collider());
+        } else if let Some(shape) = data.collider {
+            entity.insert(match shape {
+                ColliderShape::Ball(radius) => Collider::ball(radius),
+                ColliderShape::Cuboid(half_extents) => {
+                    Collider::cuboid(half_extents.x, half_extents.y, half_extents.z)
+                }
+                ColliderShape::Capsule(half_height, radius) => {
+                    Collider::capsule_y(half_height, radius)
+                }
+            });
+        }
+        if data.trigger {
+            entity.insert((Sensor, ActiveEvents::COLLISION_EVENTS));
+        }
+        if let Some(surface) = data.surface {
+            entity.insert(surface);
+        }
+        if let Some(density) = data.density {
+            entity.insert(ColliderMassProperties::Density(density));
+        }
+        if let Some((memberships, filters)) = data.collision_groups {
+            entity.insert(CollisionGroups::new(
+                Group::from_bits_truncate(memberships),
+                Group::from_bits_truncate(filters),
+            ));
+        }
+        if let Some(prompt) = &data.interaction {
+            entity.insert(Interactable::new(prompt.clone()));
+        }
+        if data.ball_gravity {
+            entity.insert(GravityScale(self.settings.physics.ball_gravity_scale));
+        }
+        if data.rolling_sound {
+            entity.insert(MotionSound(self.audio.rolling_loop.clone()));
+        }
+        if let Some(force) = data.force {
+            entity.insert(ExternalForce {
+                force: force.force,
+                torque: force.torque,
+            });
+        }
+        if let Some(impulse) = data.impulse {
+            entity.insert(ExternalImpulse {
+                impulse: impulse.force,
+                torque_impulse: impulse.torque,
+            });
+        }
+
+        if let Some(damping) = data.angular_damping {
+            entity.insert(Damping {
+                linear_damping: 0.0,
+                angular_damping: damping,
+            });
+        }
+        if let Some(spawner) = &data.spawner {
+            entity.insert(spawner.clone());
+        }
+
+        let id = entity.id();
+        for child in &data.children {
+            let child_transform = Transform::from_translation(child.translation)
+                .with_rotation(child.rotation)
+                .with_scale(child.scale);
+            let child_id = if !in_prefab {
+                self.spawn(child)
+            } else {
+                if let Some(name) = &child.prefab {
+                    warn!(
+                        "prefab child `{}`: ignoring its prefab `{name}`, prefabs can't nest",
+                        child.name
+                    );
+                }
+                self.spawn_at(child, child_transform, true)
+            };
+            if let Some(joint) = child.joint {
+                self.commands
+                    .entity(child_id)
+                    .insert(ImpulseJoint::new(id, joint.joint(&child_transform)));
+            }
+            self.commands.entity(id).add_child(child_id);
+        }
+        if let Some(soft_body) = data.soft_body {
+            let material = self.material(data, None);
+            soft_body.spawn(
+                &mut self.commands,
+                &mut self.meshes,
+                id,
+                material,
+                data.surface,
+            );
+        }
+        if data.enemy {
+            self.commands
+                .add(move |world: &mut World| make_enemy(world, id));
+        }
+        id
+    }
+
+    /// The mesh and material of `data`, or `None` if its prop mesh doesn't exist.
+    fn mesh(
+        &mut self,
+        data: &EntityData,
+        mesh: &MeshRef,
+    ) -> Option<(Handle<Mesh>, Handle<StandardMaterial>)> {
+        let (mesh, prop_material) = match mesh {
+            MeshRef::Prop(name) => {
+                let Some(mesh) = self.gltf_assets.mesh(&self.models.props, name) else {
+                    warn!("level entity `{}`: no prop mesh `{name}`", data.name);
+                    return None;
+                };
+                (mesh, self.gltf_assets.material(&self.models.props, name))
+            }
+            MeshRef::Cube(size) => (self.meshes.add(shape::Cube::new(*size).into()), None),
+            MeshRef::Box(size) => (
+                self.meshes
+                    .add(shape::Box::new(size.x, size.y, size.z).into()),
+                None,
+            ),
+            MeshRef::Sphere(radius) => (
+                self.meshes.add(
+                    shape::UVSphere {
+                        radius: *radius,
+                        ..default()
+                    }
+                    .into(),
+                ),
+                None,
+            ),
+        };
+        Some((mesh, self.material(data, prop_material)))
+    }
+
+    /// The material named by `data`, or else the prop's own material or the prototype material.
+    fn material(
+        &self,
+        data: &EntityData,
+        prop_material: Option<Handle<StandardMaterial>>,
+    ) -> Handle<StandardMaterial> {
+        match data.material.as_deref() {
+            Some(name) => self.library.by_name(name).unwrap_or_else(|| {
+                warn!("level entity `{}`: unknown material `{name}`", data.name);
+                self.library.prototype.clone()
+            }),
+            None => prop_material.unwrap_or_else(|| self.library.prototype.clone()),
+        }
+    }
+}
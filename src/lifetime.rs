@@ -0,0 +1,102 @@
+//! [`Lifetime`]: entities that go away on their own after a while, like projectiles, particles
+//! and decals.
+//!
+//! Lifetimes follow game time, so they stop while the game is paused. Expired entities are
+//! despawned at the end of the frame, unless they come from a [`Pool`](crate::pool::Pool): pools
+//! release the expired entities of their kind instead.
+//!
+//! A [`FadeOut`] fades an entity over the end of its lifetime. Its alpha is there for whatever
+//! draws the entity to follow, and the entity's own [`StandardMaterial`], if it has one, fades
+//! with it.
+
+use bevy::prelude::*;
+
+use crate::pool::Pooled;
+
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (tick_lifetimes, fade_out).chain())
+            .add_systems(Last, despawn_expired);
+    }
+}
+
+/// Time left before the entity is despawned.
+#[derive(Component, Clone, Debug)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+
+    /// Share of the lifetime left, from 1 down to 0.
+    pub fn left(&self) -> f32 {
+        self.0.percent_left()
+    }
+
+    pub fn expired(&self) -> bool {
+        self.0.finished()
+    }
+}
+
+/// Fades the entity out over the last `duration` seconds of its [`Lifetime`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FadeOut {
+    pub duration: f32,
+    /// How opaque the entity is, from 1 down to 0 as it expires.
+    pub alpha: f32,
+    /// The alpha of the entity's material before it started fading.
+    material_alpha: Option<f32>,
+}
+
+impl FadeOut {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            alpha: 1.0,
+            material_alpha: None,
+        }
+    }
+}
+
+fn tick_lifetimes(time: Res<Time>, mut lifetimes: Query<&mut Lifetime, Without<Pooled>>) {
+    for mut lifetime in &mut lifetimes {
+        lifetime.0.tick(time.delta());
+    }
+}
+
+/// Updates the alpha of fading entities and of their material, which should be their own.
+fn fade_out(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut fading: Query<
+        (&Lifetime, &mut FadeOut, Option<&Handle<StandardMaterial>>),
+        Without<Pooled>,
+    >,
+) {
+    for (lifetime, mut fade, material) in &mut fading {
+        let alpha = if fade.duration > 0.0 {
+            (lifetime.0.remaining_secs() / fade.duration).min(1.0)
+        } else {
+            1.0
+        };
+        if alpha == fade.alpha {
+            continue;
+        }
+        fade.alpha = alpha;
+        let Some(material) = material.and_then(|material| materials.get_mut(material)) else {
+            continue;
+        };
+        let opaque = *fade.material_alpha.get_or_insert(material.base_color.a());
+        material.base_color.set_a(opaque * alpha);
+    }
+}
+
+fn despawn_expired(mut commands: Commands, lifetimes: Query<(Entity, &Lifetime), Without<Pooled>>) {
+    for (entity, lifetime) in &lifetimes {
+        if lifetime.expired() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
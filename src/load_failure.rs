@@ -0,0 +1,189 @@
+//! The screen shown in [`MyStates::FailedLoading`] when the asset manifest or an asset collection
+//! fails to load.
+//!
+//! It lists the asset paths that failed, going by the paths in the manifest, and offers to retry
+//! loading or quit. The loading state waits on the manifest forever if it is missing or invalid, so
+//! that failure is watched for here.
+
+use bevy::app::AppExit;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+
+use crate::assets::MANIFEST_PATH;
+use crate::menu::{self, MenuInput, BUTTON_COLOR, FOCUSED_BUTTON_COLOR};
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+pub struct LoadFailurePlugin;
+
+impl Plugin for LoadFailurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FailedAssets>()
+            .init_resource::<FailureFocus>()
+            .add_systems(
+                OnEnter(MyStates::FailedLoading),
+                (find_failed_assets, spawn_failure_screen).chain(),
+            )
+            .add_systems(OnExit(MyStates::FailedLoading), despawn_failure_screen)
+            .add_systems(
+                Update,
+                (
+                    watch_manifest.run_if(in_state(MyStates::AssetLoading)),
+                    (navigate_failure_screen, highlight_failure_focus)
+                        .chain()
+                        .run_if(in_state(MyStates::FailedLoading)),
+                ),
+            );
+    }
+}
+
+/// Paths of the assets that failed to load.
+#[derive(Resource, Default)]
+struct FailedAssets(Vec<String>);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum FailureButton {
+    Retry,
+    Quit,
+}
+
+impl FailureButton {
+    const ALL: [FailureButton; 2] = [FailureButton::Retry, FailureButton::Quit];
+
+    /// Localization key of the button's label.
+    fn label(self) -> &'static str {
+        match self {
+            FailureButton::Retry => "failed.retry",
+            FailureButton::Quit => "failed.quit",
+        }
+    }
+}
+
+/// Index into [`FailureButton::ALL`] of the focused button.
+#[derive(Resource, Default)]
+struct FailureFocus(usize);
+
+#[derive(Component)]
+struct FailureScreen;
+
+/// Whether the asset server has given up on `path`. Labeled paths go by their file.
+fn has_failed(asset_server: &AssetServer, path: &str) -> bool {
+    let file = path.split('#').next().unwrap_or(path);
+    asset_server
+        .get_handle_untyped(file.to_string())
+        .is_some_and(|handle| asset_server.get_load_state(handle.id()) == Some(LoadState::Failed))
+}
+
+/// Moves on to the failure screen when the manifest can't be read or parsed.
+fn watch_manifest(asset_server: Res<AssetServer>, mut next_state: ResMut<NextState<MyStates>>) {
+    if has_failed(&asset_server, MANIFEST_PATH) {
+        next_state.set(MyStates::FailedLoading);
+    }
+}
+
+/// Collects the failed paths among the manifest and the files it lists.
+fn find_failed_assets(
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<StandardDynamicAssetCollection>>,
+    mut failed: ResMut<FailedAssets>,
+) {
+    let mut paths = vec![MANIFEST_PATH.to_string()];
+    let manifest = asset_server
+        .get_handle::<StandardDynamicAssetCollection>(MANIFEST_PATH)
+        .and_then(|handle| manifests.get(handle));
+    for asset in manifest.iter().flat_map(|manifest| manifest.0.values()) {
+        match asset {
+            StandardDynamicAsset::File { path } | StandardDynamicAsset::Folder { path } => {
+                paths.push(path.clone());
+            }
+            StandardDynamicAsset::Files { paths: files } => paths.extend(files.iter().cloned()),
+        }
+    }
+    paths.retain(|path| has_failed(&asset_server, path));
+    paths.sort();
+    paths.dedup();
+    failed.0 = paths;
+}
+
+fn spawn_failure_screen(
+    mut commands: Commands,
+    failed: Res<FailedAssets>,
+    theme: Res<TextTheme>,
+    mut focus: ResMut<FailureFocus>,
+) {
+    focus.0 = 0;
+    commands
+        .spawn((menu::overlay(), FailureScreen))
+        .with_children(|parent| {
+            parent.spawn(menu::localized_label("failed.title", theme.title()));
+            parent.spawn(menu::localized_label("failed.hint", theme.body()));
+            for path in &failed.0 {
+                parent.spawn(menu::label(path.clone(), theme.muted()));
+            }
+            for button in FailureButton::ALL {
+                parent
+                    .spawn((menu::button(), button))
+                    .with_children(|parent| {
+                        parent.spawn(menu::localized_label(button.label(), theme.heading()));
+                    });
+            }
+        });
+}
+
+fn despawn_failure_screen(mut commands: Commands, screens: Query<Entity, With<FailureScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_failure_screen(
+    menu_input: MenuInput,
+    mut focus: ResMut<FailureFocus>,
+    buttons: Query<(&Interaction, &FailureButton), Changed<Interaction>>,
+    failed: Res<FailedAssets>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<MyStates>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    menu_input.navigate(&mut focus.0, FailureButton::ALL.len());
+    let mut activated = menu_input.activate().then_some(FailureButton::ALL[focus.0]);
+
+    for (interaction, button) in &buttons {
+        match interaction {
+            Interaction::Pressed => activated = Some(*button),
+            Interaction::Hovered => {
+                focus.0 = FailureButton::ALL
+                    .iter()
+                    .position(|candidate| candidate == button)
+                    .unwrap_or(focus.0);
+            }
+            Interaction::None => {}
+        }
+    }
+
+    match activated {
+        Some(FailureButton::Retry) => {
+            // Failed paths are only loaded again when forced to.
+            for path in &failed.0 {
+                asset_server.reload(path.split('#').next().unwrap_or(path).to_string());
+            }
+            next_state.set(MyStates::AssetLoading);
+        }
+        Some(FailureButton::Quit) => exit.send(AppExit),
+        None => {}
+    }
+}
+
+fn highlight_failure_focus(
+    focus: Res<FailureFocus>,
+    mut buttons: Query<(&FailureButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in &mut buttons {
+        color.0 = if *button == FailureButton::ALL[focus.0] {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
@@ -0,0 +1,128 @@
+//! The loading screen shown while [`MyStates::AssetLoading`] is active: a spinning, pulsing logo
+//! and a rotating gameplay tip.
+
+use bevy::prelude::*;
+
+use crate::localization::{Localization, Localized};
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+/// Localization keys of the tips shown while loading.
+const TIPS: [&str; 5] = [
+    "loading.tip.pause",
+    "loading.tip.camera",
+    "loading.tip.interact",
+    "loading.tip.console",
+    "loading.tip.debug",
+];
+/// Seconds each tip stays on screen.
+const TIP_DURATION: f32 = 3.0;
+/// Logo rotation, in radians per second.
+const SPIN_SPEED: f32 = 1.5;
+
+pub struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MyStates::AssetLoading), spawn_loading_screen)
+            .add_systems(OnExit(MyStates::AssetLoading), despawn_loading_screen)
+            .add_systems(
+                Update,
+                (animate_loading_logo, rotate_tips).run_if(in_state(MyStates::AssetLoading)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingLogo;
+
+/// The tip text, cycling through [`TIPS`].
+#[derive(Component)]
+struct LoadingTip {
+    index: usize,
+    timer: Timer,
+}
+
+fn spawn_loading_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    theme: Res<TextTheme>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(24.0),
+                    ..default()
+                },
+                background_color: Color::rgb(0.05, 0.05, 0.08).into(),
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(128.0),
+                        height: Val::Px(128.0),
+                        ..default()
+                    },
+                    image: asset_server.load("branding/icon.png").into(),
+                    ..default()
+                },
+                LoadingLogo,
+            ));
+            parent.spawn((
+                TextBundle::from_section("", theme.heading()),
+                Localized("loading.title"),
+            ));
+            parent.spawn((
+                TextBundle::from_section(localization.get(TIPS[0]), theme.muted()),
+                LoadingTip {
+                    index: 0,
+                    timer: Timer::from_seconds(TIP_DURATION, TimerMode::Repeating),
+                },
+            ));
+        });
+}
+
+fn despawn_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn animate_loading_logo(time: Res<Time>, mut logos: Query<&mut Transform, With<LoadingLogo>>) {
+    let elapsed = time.elapsed_seconds();
+    for mut transform in &mut logos {
+        transform.rotation = Quat::from_rotation_z(-elapsed * SPIN_SPEED);
+        transform.scale = Vec3::splat(1.0 + 0.08 * (elapsed * 4.0).sin());
+    }
+}
+
+fn rotate_tips(
+    time: Res<Time>,
+    localization: Res<Localization>,
+    mut tips: Query<(&mut LoadingTip, &mut Text)>,
+) {
+    for (mut tip, mut text) in &mut tips {
+        if tip.timer.tick(time.delta()).just_finished() {
+            tip.index = (tip.index + 1) % TIPS.len();
+        }
+        if tip.timer.just_finished() || localization.is_changed() {
+            text.sections[0].value = localization.get(TIPS[tip.index]).to_string();
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! The multiplayer screens: the lobby to host or join a game, the connecting screen, and the
+//! error screen shown when a connection fails.
+//!
+//! The lobby is opened from the pause menu. The address is typed as an IPv4 address and port;
+//! Backspace deletes and Escape goes back. Hosting goes straight to [`MyStates::InGame`], joining
+//! waits in [`MyStates::Connecting`] until the server answers.
+
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+use crate::localization::Localization;
+use crate::menu::{self, MenuInput, BUTTON_COLOR, FOCUSED_BUTTON_COLOR};
+use crate::network::{self, Network, NetworkRole, DEFAULT_PORT};
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+pub struct LobbyPlugin;
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LobbyAddress>()
+            .init_resource::<ConnectionError>()
+            .init_resource::<LobbyFocus>()
+            .add_systems(OnEnter(MyStates::Lobby), (network::disconnect, spawn_lobby))
+            .add_systems(OnEnter(MyStates::Connecting), spawn_connecting_screen)
+            .add_systems(OnEnter(MyStates::ConnectionFailed), spawn_failure_screen)
+            .add_systems(OnExit(MyStates::Lobby), despawn_lobby_screens)
+            .add_systems(OnExit(MyStates::Connecting), despawn_lobby_screens)
+            .add_systems(OnExit(MyStates::ConnectionFailed), despawn_lobby_screens)
+            .add_systems(
+                Update,
+                (
+                    edit_address.run_if(in_state(MyStates::Lobby)),
+                    wait_for_server.run_if(in_state(MyStates::Connecting)),
+                    watch_connection.run_if(in_state(MyStates::InGame)),
+                    (navigate_lobby, update_lobby_screens).chain().run_if(
+                        in_state(MyStates::Lobby)
+                            .or_else(in_state(MyStates::Connecting))
+                            .or_else(in_state(MyStates::ConnectionFailed)),
+                    ),
+                ),
+            );
+    }
+}
+
+/// The address typed into the lobby.
+#[derive(Resource)]
+struct LobbyAddress(String);
+
+impl Default for LobbyAddress {
+    fn default() -> Self {
+        Self(format!("127.0.0.1:{DEFAULT_PORT}"))
+    }
+}
+
+/// Why the last connection failed, shown in [`MyStates::ConnectionFailed`].
+#[derive(Resource, Default)]
+struct ConnectionError(String);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum LobbyButton {
+    Host,
+    Join,
+    Back,
+}
+
+impl LobbyButton {
+    /// The buttons of the screen shown in `state`.
+    fn for_state(state: &MyStates) -> &'static [LobbyButton] {
+        match state {
+            MyStates::Lobby => &[LobbyButton::Host, LobbyButton::Join, LobbyButton::Back],
+            _ => &[LobbyButton::Back],
+        }
+    }
+
+    /// Localization key of the button's label.
+    fn label(self) -> &'static str {
+        match self {
+            LobbyButton::Host => "lobby.host",
+            LobbyButton::Join => "lobby.join",
+            LobbyButton::Back => "menu.back",
+        }
+    }
+}
+
+/// Index into [`LobbyButton::for_state`] of the focused button.
+#[derive(Resource, Default)]
+struct LobbyFocus(usize);
+
+/// Root of the lobby, connecting and failure screens.
+#[derive(Component)]
+struct LobbyScreen;
+
+/// Text showing the typed address.
+#[derive(Component)]
+struct AddressText;
+
+fn spawn_screen(
+    commands: &mut Commands,
+    theme: &TextTheme,
+    title: &'static str,
+    text: impl Into<String>,
+    buttons: &[LobbyButton],
+) {
+    commands
+        .spawn((menu::overlay(), LobbyScreen))
+        .with_children(|parent| {
+            parent.spawn(menu::localized_label(title, theme.title()));
+            parent.spawn((menu::label(text, theme.body()), AddressText));
+            for button in buttons {
+                parent
+                    .spawn((menu::button(), *button))
+                    .with_children(|parent| {
+                        parent.spawn(menu::localized_label(button.label(), theme.heading()));
+                    });
+            }
+        });
+}
+
+fn spawn_lobby(mut commands: Commands, theme: Res<TextTheme>, mut focus: ResMut<LobbyFocus>) {
+    focus.0 = 0;
+    spawn_screen(
+        &mut commands,
+        &theme,
+        "lobby.title",
+        "",
+        LobbyButton::for_state(&MyStates::Lobby),
+    );
+}
+
+fn spawn_connecting_screen(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    localization: Res<Localization>,
+    address: Res<LobbyAddress>,
+    mut focus: ResMut<LobbyFocus>,
+) {
+    focus.0 = 0;
+    spawn_screen(
+        &mut commands,
+        &theme,
+        "lobby.title",
+        localization.format("lobby.connecting", &[("address", &address.0)]),
+        LobbyButton::for_state(&MyStates::Connecting),
+    );
+}
+
+fn spawn_failure_screen(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    error: Res<ConnectionError>,
+    mut focus: ResMut<LobbyFocus>,
+) {
+    focus.0 = 0;
+    spawn_screen(
+        &mut commands,
+        &theme,
+        "connection.failed",
+        error.0.clone(),
+        LobbyButton::for_state(&MyStates::ConnectionFailed),
+    );
+}
+
+fn despawn_lobby_screens(mut commands: Commands, screens: Query<Entity, With<LobbyScreen>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn edit_address(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut address: ResMut<LobbyAddress>,
+) {
+    for event in characters.read() {
+        if event.char.is_ascii_digit() || event.char == '.' || event.char == ':' {
+            address.0.push(event.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        address.0.pop();
+    }
+}
+
+fn fail(commands: &mut Commands, next_state: &mut NextState<MyStates>, message: String) {
+    warn!("{message}");
+    commands.insert_resource(ConnectionError(message));
+    next_state.set(MyStates::ConnectionFailed);
+}
+
+fn host(
+    commands: &mut Commands,
+    next_state: &mut NextState<MyStates>,
+    localization: &Localization,
+    address: &str,
+) {
+    let port = address
+        .rsplit_once(':')
+        .and_then(|(_, port)| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    match Network::open(NetworkRole::Server(port)) {
+        Ok(network) => {
+            commands.insert_resource(network);
+            next_state.set(MyStates::InGame);
+        }
+        Err(error) => fail(
+            commands,
+            next_state,
+            localization.format("connection.socket", &[("error", &error)]),
+        ),
+    }
+}
+
+fn join(
+    commands: &mut Commands,
+    next_state: &mut NextState<MyStates>,
+    localization: &Localization,
+    address: &str,
+) {
+    let Ok(server) = address.parse() else {
+        fail(
+            commands,
+            next_state,
+            localization.format("connection.invalid_address", &[("address", &address)]),
+        );
+        return;
+    };
+    match Network::open(NetworkRole::Client(server)) {
+        Ok(network) => {
+            commands.insert_resource(network);
+            next_state.set(MyStates::Connecting);
+        }
+        Err(error) => fail(
+            commands,
+            next_state,
+            localization.format("connection.socket", &[("error", &error)]),
+        ),
+    }
+}
+
+fn navigate_lobby(
+    mut commands: Commands,
+    menu_input: MenuInput,
+    state: Res<State<MyStates>>,
+    mut next_state: ResMut<NextState<MyStates>>,
+    mut focus: ResMut<LobbyFocus>,
+    localization: Res<Localization>,
+    address: Res<LobbyAddress>,
+    buttons: Query<(&Interaction, &LobbyButton), Changed<Interaction>>,
+) {
+    let available = LobbyButton::for_state(state.get());
+    menu_input.navigate(&mut focus.0, available.len());
+    focus.0 = focus.0.min(available.len() - 1);
+    let mut activated = menu_input.activate().then_some(available[focus.0]);
+
+    for (interaction, button) in &buttons {
+        match interaction {
+            Interaction::Pressed => activated = Some(*button),
+            Interaction::Hovered => {
+                focus.0 = available
+                    .iter()
+                    .position(|candidate| candidate == button)
+                    .unwrap_or(focus.0);
+            }
+            Interaction::None => {}
+        }
+    }
+    // Backspace edits the address, so only Escape leaves the lobby.
+    if menu_input.pause() {
+        activated = Some(LobbyButton::Back);
+    }
+
+    match activated {
+        Some(LobbyButton::Host) => host(&mut commands, &mut next_state, &localization, &address.0),
+        Some(LobbyButton::Join) => join(&mut commands, &mut next_state, &localization, &address.0),
+        Some(LobbyButton::Back) => next_state.set(match state.get() {
+            MyStates::Lobby => MyStates::Next,
+            _ => MyStates::Lobby,
+        }),
+        None => {}
+    }
+}
+
+fn update_lobby_screens(
+    state: Res<State<MyStates>>,
+    focus: Res<LobbyFocus>,
+    localization: Res<Localization>,
+    address: Res<LobbyAddress>,
+    mut buttons: Query<(&LobbyButton, &mut BackgroundColor)>,
+    mut texts: Query<&mut Text, With<AddressText>>,
+) {
+    let available = LobbyButton::for_state(state.get());
+    for (button, mut color) in &mut buttons {
+        color.0 = if available.get(focus.0) == Some(button) {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+    if *state.get() == MyStates::Lobby {
+        for mut text in &mut texts {
+            text.sections[0].value =
+                localization.format("lobby.address", &[("address", &address.0)]);
+        }
+    }
+}
+
+fn wait_for_server(
+    mut commands: Commands,
+    network: Option<Res<Network>>,
+    localization: Res<Localization>,
+    address: Res<LobbyAddress>,
+    mut next_state: ResMut<NextState<MyStates>>,
+) {
+    let Some(network) = network else {
+        return;
+    };
+    if network.connected() {
+        next_state.set(MyStates::InGame);
+    } else if network.timed_out() {
+        fail(
+            &mut commands,
+            &mut next_state,
+            localization.format("connection.no_answer", &[("address", &address.0)]),
+        );
+    }
+}
+
+fn watch_connection(
+    mut commands: Commands,
+    network: Option<Res<Network>>,
+    localization: Res<Localization>,
+    mut next_state: ResMut<NextState<MyStates>>,
+) {
+    if network.is_some_and(|network| network.timed_out()) {
+        fail(
+            &mut commands,
+            &mut next_state,
+            localization.get("connection.lost").to_string(),
+        );
+    }
+}
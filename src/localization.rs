@@ -0,0 +1,189 @@
+//! Translated UI strings.
+//!
+//! Language files are `lang/<code>.lang.ron` assets mapping string keys to text. The active
+//! language comes from [`Settings::language`] and can be switched at runtime from the settings
+//! screen or with the `language` console command; [`Localized`] texts update as soon as the
+//! language's file is loaded.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+
+use crate::console::ConsoleAppExt;
+use crate::level_data::LevelDataError;
+use crate::settings::Settings;
+
+/// Language codes with a file in `assets/lang`, in the order the settings screen cycles them.
+pub const LANGUAGES: [&str; 2] = ["en", "de"];
+/// Language used for keys missing from the active language.
+const FALLBACK_LANGUAGE: &str = "en";
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LanguageFile>()
+            .register_asset_loader(LanguageFileLoader)
+            .init_resource::<Localization>()
+            .add_console_command(
+                "language",
+                "language <code> - switch the UI language (en, de)",
+                set_language,
+            )
+            .add_systems(
+                Update,
+                (
+                    switch_language.run_if(resource_changed::<Settings>()),
+                    update_strings,
+                    localize_texts,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The strings of a language file.
+#[derive(Asset, TypePath, Debug)]
+pub struct LanguageFile(HashMap<String, String>);
+
+#[derive(Default)]
+struct LanguageFileLoader;
+
+impl AssetLoader for LanguageFileLoader {
+    type Asset = LanguageFile;
+    type Settings = ();
+    type Error = LevelDataError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<LanguageFile, LevelDataError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(LevelDataError::Io)?;
+            ron::de::from_bytes(&bytes)
+                .map(LanguageFile)
+                .map_err(LevelDataError::Ron)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang.ron"]
+    }
+}
+
+/// Asset path of the file of `language`.
+fn language_path(language: &str) -> String {
+    format!("lang/{language}.lang.ron")
+}
+
+/// The strings of the active language, empty until its file is loaded.
+#[derive(Resource)]
+pub struct Localization {
+    language: String,
+    file: Handle<LanguageFile>,
+    fallback_file: Handle<LanguageFile>,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Localization {
+    pub fn load(language: &str, asset_server: &AssetServer) -> Self {
+        Self {
+            language: language.to_string(),
+            file: asset_server.load(language_path(language)),
+            fallback_file: asset_server.load(language_path(FALLBACK_LANGUAGE)),
+            strings: HashMap::new(),
+            fallback: HashMap::new(),
+        }
+    }
+
+    /// The text for `key`, or the key itself if no language defines it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// The text for `key` with each `{name}` placeholder replaced by its argument.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+}
+
+impl FromWorld for Localization {
+    fn from_world(world: &mut World) -> Self {
+        let language = world
+            .get_resource::<Settings>()
+            .map_or(FALLBACK_LANGUAGE, |settings| settings.language.as_str())
+            .to_string();
+        Self::load(&language, world.resource::<AssetServer>())
+    }
+}
+
+/// Keeps the first section of a [`Text`] set to the translation of a key.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Localized(pub &'static str);
+
+fn switch_language(
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut localization: ResMut<Localization>,
+) {
+    if localization.language != settings.language {
+        *localization = Localization::load(&settings.language, &asset_server);
+    }
+}
+
+/// Copies the strings of the language files in once they are loaded or changed, and when the
+/// language is switched to one loaded before.
+fn update_strings(
+    mut events: EventReader<AssetEvent<LanguageFile>>,
+    files: Res<Assets<LanguageFile>>,
+    mut localization: ResMut<Localization>,
+) {
+    if events.read().count() == 0 && !localization.is_changed() {
+        return;
+    }
+    let localization = localization.as_mut();
+    if let Some(file) = files.get(&localization.file) {
+        localization.strings = file.0.clone();
+    }
+    if let Some(file) = files.get(&localization.fallback_file) {
+        localization.fallback = file.0.clone();
+    }
+}
+
+fn localize_texts(localization: Res<Localization>, mut texts: Query<(Ref<Localized>, &mut Text)>) {
+    for (localized, mut text) in &mut texts {
+        if localization.is_changed() || localized.is_changed() {
+            text.sections[0].value = localization.get(localized.0).to_string();
+        }
+    }
+}
+
+fn set_language(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let code = *args.first().ok_or("missing argument <code>")?;
+    if !LANGUAGES.contains(&code) {
+        return Err(format!(
+            "unknown language `{code}`, expected one of {}",
+            LANGUAGES.join(", ")
+        ));
+    }
+    world.resource_mut::<Settings>().language = code.to_string();
+    Ok(format!("language set to {code}"))
+}
@@ -0,0 +1,166 @@
+//! Distance-based level of detail for props.
+//!
+//! Entities with a [`MeshLod`] swap to coarser meshes as they get farther from the
+//! [`MainCamera`], by [`LOD_DISTANCES`]. Entities that also have a [`ColliderLod`] switch to a
+//! simpler collider past [`COLLIDER_LOD_DISTANCE`], where nothing needs precise contacts with them.
+//! [`LodMeshes`] holds the detail levels of the shapes the console spawns.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::MainCamera;
+
+/// Camera distances, in meters, past which each coarser mesh is used.
+pub const LOD_DISTANCES: [f32; 2] = [15.0, 40.0];
+/// Camera distance past which [`ColliderLod::simple`] is used.
+pub const COLLIDER_LOD_DISTANCE: f32 = 30.0;
+
+pub struct LodPlugin;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LodMeshes>()
+            .add_systems(Update, (update_mesh_lods, update_collider_lods));
+    }
+}
+
+/// A prop's meshes from the most to the least detailed, one per [`LOD_DISTANCES`] band.
+#[derive(Component, Clone, Debug)]
+pub struct MeshLod {
+    levels: Vec<Handle<Mesh>>,
+    current: usize,
+}
+
+impl MeshLod {
+    /// `levels` must not be empty; the entity should start out with the first one.
+    pub fn new(levels: Vec<Handle<Mesh>>) -> Self {
+        assert!(!levels.is_empty(), "a MeshLod needs at least one mesh");
+        Self { levels, current: 0 }
+    }
+
+    /// The most detailed mesh.
+    pub fn full(&self) -> Handle<Mesh> {
+        self.levels[0].clone()
+    }
+}
+
+/// A detailed collider and a cheaper stand-in used far from the camera.
+#[derive(Component, Clone)]
+pub struct ColliderLod {
+    pub detailed: Collider,
+    pub simple: Collider,
+    simplified: bool,
+}
+
+impl ColliderLod {
+    pub fn new(detailed: Collider, simple: Collider) -> Self {
+        Self {
+            detailed,
+            simple,
+            simplified: false,
+        }
+    }
+}
+
+/// Detail levels of the meshes spawned by the `spawn` console command.
+#[derive(Resource)]
+pub struct LodMeshes {
+    pub ball: Vec<Handle<Mesh>>,
+    pub torus: Vec<Handle<Mesh>>,
+    /// Convex pieces of the torus for close up, and its hull for far away.
+    pub torus_collider: ColliderLod,
+}
+
+impl FromWorld for LodMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let ball_levels = [(36, 18), (16, 8), (8, 4)];
+        let torus_levels = [(32, 16), (16, 8), (8, 4)];
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+
+        let ball = ball_levels
+            .into_iter()
+            .map(|(sectors, stacks)| {
+                meshes.add(Mesh::from(shape::UVSphere {
+                    radius: 0.5,
+                    sectors,
+                    stacks,
+                }))
+            })
+            .collect();
+        let torus_mesh = |segments, sides| {
+            Mesh::from(shape::Torus {
+                radius: 0.5,
+                ring_radius: 0.2,
+                subdivisions_segments: segments,
+                subdivisions_sides: sides,
+            })
+        };
+        let torus = torus_levels
+            .into_iter()
+            .map(|(segments, sides)| meshes.add(torus_mesh(segments, sides)))
+            .collect();
+
+        // Built from the coarsest mesh, which is plenty for collisions and quick to decompose.
+        let collision_mesh = torus_mesh(8, 4);
+        let collider = |shape| {
+            Collider::from_bevy_mesh(&collision_mesh, shape).unwrap_or_else(|| Collider::ball(0.7))
+        };
+        let torus_collider = ColliderLod::new(
+            collider(&ComputedColliderShape::ConvexDecomposition(
+                VHACDParameters::default(),
+            )),
+            collider(&ComputedColliderShape::ConvexHull),
+        );
+
+        Self {
+            ball,
+            torus,
+            torus_collider,
+        }
+    }
+}
+
+fn update_mesh_lods(
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    mut props: Query<(&GlobalTransform, &mut MeshLod, &mut Handle<Mesh>)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let eye = camera.translation();
+    for (transform, mut lod, mut mesh) in &mut props {
+        let distance_squared = transform.translation().distance_squared(eye);
+        let level = LOD_DISTANCES
+            .iter()
+            .take_while(|distance| distance_squared > *distance * *distance)
+            .count()
+            .min(lod.levels.len() - 1);
+        // Only touched on a switch, so the mesh isn't re-extracted every frame.
+        if level != lod.current {
+            lod.current = level;
+            *mesh = lod.levels[level].clone();
+        }
+    }
+}
+
+fn update_collider_lods(
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    mut props: Query<(&GlobalTransform, &mut ColliderLod, &mut Collider)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let eye = camera.translation();
+    for (transform, mut lod, mut collider) in &mut props {
+        let far = transform.translation().distance_squared(eye)
+            > COLLIDER_LOD_DISTANCE * COLLIDER_LOD_DISTANCE;
+        if far != lod.simplified {
+            lod.simplified = far;
+            *collider = if far {
+                lod.simple.clone()
+            } else {
+                lod.detailed.clone()
+            };
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Logging to stderr and to [`LOG_PATH`], replacing Bevy's `LogPlugin`.
+//!
+//! The log file is rotated when the game starts and whenever it grows past [`MAX_LOG_BYTES`],
+//! keeping [`KEPT_LOGS`] older files next to it. The level comes from `-v`/`-q` on the command
+//! line and can be overridden per module with `RUST_LOG`. Besides what the game and its
+//! dependencies log (Rapier's warnings included), state transitions, loaded assets and spawned
+//! body counts are logged here.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bevy::asset::{Asset, AssetEvent};
+use bevy::ecs::schedule::States;
+use bevy::gltf::Gltf;
+use bevy::log::Level;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use tracing_log::LogTracer;
+use tracing_subscriber::{prelude::*, EnvFilter, Registry};
+
+use crate::pause_menu::PauseState;
+use crate::replay::ReplayState;
+use crate::MyStates;
+
+pub const LOG_PATH: &str = "logs/game.log";
+/// Size at which the log file is rotated.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated log files kept.
+const KEPT_LOGS: u32 = 4;
+/// Default filters for noisy dependencies, as in Bevy's `LogPlugin`.
+const DEFAULT_FILTER: &str = "wgpu=error,naga=warn";
+
+pub struct LoggingPlugin {
+    pub level: Level,
+}
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, app: &mut App) {
+        install_subscriber(self.level);
+        app.add_systems(
+            Update,
+            (
+                log_state_transitions::<MyStates>,
+                log_state_transitions::<PauseState>,
+                log_state_transitions::<ReplayState>,
+                log_asset_loads::<Scene>,
+                log_asset_loads::<Gltf>,
+                log_asset_loads::<AudioSource>,
+                log_asset_loads::<Image>,
+                log_spawned_bodies,
+            ),
+        );
+    }
+}
+
+fn install_subscriber(level: Level) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(format!("{level},{DEFAULT_FILTER}")))
+        .expect("the default log filter is valid");
+    let stderr_layer = tracing_subscriber::fmt::layer().with_writer(io::stderr);
+    let file_layer = match RotatingFile::open(LOG_PATH) {
+        Ok(file) => Some(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Mutex::new(file)),
+        ),
+        Err(error) => {
+            // The subscriber isn't installed yet, so this can't be logged.
+            eprintln!("could not open {LOG_PATH}: {error}");
+            None
+        }
+    };
+    let subscriber = Registry::default()
+        .with(filter)
+        .with(stderr_layer)
+        .with(file_layer);
+
+    if LogTracer::init().is_err()
+        || bevy::utils::tracing::subscriber::set_global_default(subscriber).is_err()
+    {
+        eprintln!("a logger was already installed; {LOG_PATH} is not written");
+    }
+}
+
+/// A log file that is moved aside to `<name>.1.log` (and so on) when it gets too large.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Rotates the previous run's log and starts a new one.
+    fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        rotate(&path)?;
+        Ok(Self {
+            file: File::create(&path)?,
+            path,
+            written: 0,
+        })
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() as u64 > MAX_LOG_BYTES {
+            self.file.flush()?;
+            rotate(&self.path)?;
+            self.file = File::create(&self.path)?;
+            self.written = 0;
+        }
+        let len = self.file.write(buf)?;
+        self.written += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// The path of the `index`th rotated log, `logs/game.<index>.log`.
+fn rotated_path(path: &Path, index: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("log");
+    path.with_file_name(format!("{stem}.{index}.log"))
+}
+
+/// Shifts the rotated logs up by one, dropping the oldest, and moves `path` to the first slot.
+fn rotate(path: &Path) -> io::Result<()> {
+    for index in (1..KEPT_LOGS).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            fs::rename(from, rotated_path(path, index + 1))?;
+        }
+    }
+    if path.exists() {
+        fs::rename(path, rotated_path(path, 1))?;
+    }
+    Ok(())
+}
+
+fn log_state_transitions<S: States>(state: Res<State<S>>, mut previous: Local<Option<S>>) {
+    if !state.is_changed() {
+        return;
+    }
+    let current = state.get().clone();
+    match previous.replace(current.clone()) {
+        Some(previous) => info!(from = ?previous, to = ?current, "state transition"),
+        None => info!(state = ?current, "initial state"),
+    }
+}
+
+fn log_asset_loads<A: Asset>(
+    asset_server: Res<AssetServer>,
+    mut events: EventReader<AssetEvent<A>>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } = event {
+            // Assets created at runtime have no path and aren't worth logging.
+            if let Some(path) = asset_server.get_path(*id) {
+                info!(asset = std::any::type_name::<A>(), %path, "loaded");
+            }
+        }
+    }
+}
+
+fn log_spawned_bodies(bodies: Query<&RigidBody, Added<RigidBody>>) {
+    let (mut dynamic, mut other) = (0, 0);
+    for body in &bodies {
+        if *body == RigidBody::Dynamic {
+            dynamic += 1;
+        } else {
+            other += 1;
+        }
+    }
+    if dynamic + other > 0 {
+        info!(dynamic, other, "spawned rigid bodies");
+    }
+}
@@ -0,0 +1,264 @@
+//! The material library: textured PBR materials built once the [`TextureAssets`] are loaded.
+//!
+//! [`MaterialPreset`]s give sensors and collectibles looks that stand apart from solid geometry;
+//! sensor colliders pick up the hologram preset automatically.
+//!
+//! Meshes given a normal-mapped material get tangents generated for them, and meshes without UVs
+//! get a top-down projection so tiling textures still work on them.
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::TextureAssets;
+
+/// World meters covered by one repetition of a projected texture.
+const METERS_PER_TILE: f32 = 4.0;
+
+pub struct MaterialsPlugin;
+
+impl Plugin for MaterialsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            tint_sensors.run_if(resource_exists::<MaterialLibrary>()),
+        )
+        .add_systems(PostUpdate, prepare_normal_mapped_meshes);
+    }
+}
+
+/// Materials shared by everything spawned in the playground.
+#[derive(Resource)]
+pub struct MaterialLibrary {
+    /// Untextured-looking checker for placeholder geometry.
+    pub prototype: Handle<StandardMaterial>,
+    pub floor: Handle<StandardMaterial>,
+    pub wood_crate: Handle<StandardMaterial>,
+    presets: HashMap<MaterialPreset, Handle<StandardMaterial>>,
+}
+
+impl MaterialLibrary {
+    pub fn preset(&self, preset: MaterialPreset) -> Handle<StandardMaterial> {
+        self.presets[&preset].clone()
+    }
+
+    /// The material called `name` in data files: `prototype`, `floor`, `crate`, or a
+    /// [`MaterialPreset`].
+    pub fn by_name(&self, name: &str) -> Option<Handle<StandardMaterial>> {
+        match name {
+            "prototype" => Some(self.prototype.clone()),
+            "floor" => Some(self.floor.clone()),
+            "crate" => Some(self.wood_crate.clone()),
+            _ => MaterialPreset::from_name(name).map(|preset| self.preset(preset)),
+        }
+    }
+}
+
+/// Looks for things that aren't solid geometry.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MaterialPreset {
+    Glass,
+    /// Emissive gold, for collectibles.
+    GlowingPickup,
+    /// Translucent, unlit cyan, for trigger zones.
+    Hologram,
+    /// Translucent, glossy green, for soft bodies.
+    Jelly,
+}
+
+impl MaterialPreset {
+    pub const ALL: [MaterialPreset; 4] = [
+        MaterialPreset::Glass,
+        MaterialPreset::GlowingPickup,
+        MaterialPreset::Hologram,
+        MaterialPreset::Jelly,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MaterialPreset::Glass => "glass",
+            MaterialPreset::GlowingPickup => "pickup",
+            MaterialPreset::Hologram => "hologram",
+            MaterialPreset::Jelly => "jelly",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    fn material(self) -> StandardMaterial {
+        match self {
+            MaterialPreset::Glass => StandardMaterial {
+                base_color: Color::rgba(0.8, 0.9, 1.0, 0.25),
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 0.05,
+                reflectance: 0.8,
+                ..default()
+            },
+            MaterialPreset::GlowingPickup => StandardMaterial {
+                base_color: Color::GOLD,
+                emissive: Color::rgb_linear(6.0, 4.0, 0.6),
+                perceptual_roughness: 0.3,
+                ..default()
+            },
+            MaterialPreset::Hologram => StandardMaterial {
+                base_color: Color::rgba(0.2, 0.8, 1.0, 0.2),
+                emissive: Color::rgb_linear(0.2, 1.2, 1.6),
+                alpha_mode: AlphaMode::Add,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            },
+            MaterialPreset::Jelly => StandardMaterial {
+                base_color: Color::rgba(0.3, 0.9, 0.2, 0.6),
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 0.1,
+                reflectance: 0.6,
+                ..default()
+            },
+        }
+    }
+}
+
+/// Base color, normal and metallic/roughness maps of one material.
+struct PbrTextures<'a> {
+    base_color: &'a Handle<Image>,
+    normal: &'a Handle<Image>,
+    metallic_roughness: &'a Handle<Image>,
+}
+
+impl PbrTextures<'_> {
+    fn material(&self, images: &mut Assets<Image>) -> StandardMaterial {
+        for (handle, is_color) in [
+            (self.base_color, true),
+            (self.normal, false),
+            (self.metallic_roughness, false),
+        ] {
+            if let Some(image) = images.get_mut(handle) {
+                prepare_tiling_image(image, is_color);
+            }
+        }
+        StandardMaterial {
+            base_color_texture: Some(self.base_color.clone()),
+            normal_map_texture: Some(self.normal.clone()),
+            metallic_roughness_texture: Some(self.metallic_roughness.clone()),
+            // Scaled by the texture's channels.
+            metallic: 1.0,
+            perceptual_roughness: 1.0,
+            ..default()
+        }
+    }
+}
+
+impl FromWorld for MaterialLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let world = world.cell();
+        let textures = world.resource::<TextureAssets>();
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+
+        let floor = PbrTextures {
+            base_color: &textures.floor_base_color,
+            normal: &textures.floor_normal,
+            metallic_roughness: &textures.floor_metallic_roughness,
+        }
+        .material(&mut images);
+        let wood_crate = PbrTextures {
+            base_color: &textures.crate_base_color,
+            normal: &textures.crate_normal,
+            metallic_roughness: &textures.crate_metallic_roughness,
+        }
+        .material(&mut images);
+        if let Some(checker) = images.get_mut(&textures.checker) {
+            prepare_tiling_image(checker, true);
+        }
+
+        Self {
+            prototype: materials.add(StandardMaterial {
+                base_color: Color::SILVER,
+                base_color_texture: Some(textures.checker.clone()),
+                ..default()
+            }),
+            floor: materials.add(floor),
+            wood_crate: materials.add(wood_crate),
+            presets: MaterialPreset::ALL
+                .into_iter()
+                .map(|preset| (preset, materials.add(preset.material())))
+                .collect(),
+        }
+    }
+}
+
+/// Makes `image` repeat, and stores non-color data (normals, roughness) as linear values.
+fn prepare_tiling_image(image: &mut Image, is_color: bool) {
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    if !is_color && image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb {
+        image.texture_descriptor.format = TextureFormat::Rgba8Unorm;
+    }
+}
+
+/// Gives newly spawned sensor colliders the hologram look.
+fn tint_sensors(
+    mut commands: Commands,
+    library: Res<MaterialLibrary>,
+    sensors: Query<Entity, (Added<Sensor>, With<Handle<StandardMaterial>>)>,
+) {
+    for entity in &sensors {
+        commands
+            .entity(entity)
+            .insert(library.preset(MaterialPreset::Hologram));
+    }
+}
+
+fn prepare_normal_mapped_meshes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    entities: Query<
+        (&Handle<Mesh>, &Handle<StandardMaterial>),
+        Or<(Changed<Handle<Mesh>>, Changed<Handle<StandardMaterial>>)>,
+    >,
+) {
+    for (mesh, material) in &entities {
+        let normal_mapped = materials
+            .get(material)
+            .is_some_and(|material| material.normal_map_texture.is_some());
+        if !normal_mapped {
+            continue;
+        }
+        let Some(mesh) = meshes.get_mut(mesh) else {
+            continue;
+        };
+        if mesh.attribute(Mesh::ATTRIBUTE_TANGENT).is_some() {
+            continue;
+        }
+        if mesh.attribute(Mesh::ATTRIBUTE_UV_0).is_none() {
+            add_planar_uvs(mesh);
+        }
+        if let Err(error) = mesh.generate_tangents() {
+            warn!("could not generate tangents for a normal-mapped mesh: {error}");
+        }
+    }
+}
+
+/// Projects the mesh's positions onto the XZ plane as UVs.
+fn add_planar_uvs(mesh: &mut Mesh) {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return;
+    };
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|[x, _, z]| [x / METERS_PER_TILE, z / METERS_PER_TILE])
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+}
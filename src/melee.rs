@@ -0,0 +1,251 @@
+//! Melee attacks: F swings the player's fist, playing the character's attack clip.
+//!
+//! The hitbox is a sensor ahead of the model, which only gets its ball collider while the clip is
+//! in its strike, so a swing lands when the fist does and not while winding up. Every [`Health`] it
+//! touches loses [`DAMAGE`], dynamic bodies are knocked away with an impulse and characters are
+//! shoved back. A swing hits each collider once, and the first hit of a swing plays a [`Hitstop`].
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::ai::MoveTarget;
+use crate::character_animation::{AnimationState, CharacterModel};
+use crate::conditions::{in_gameplay, player_alive, text_input_closed};
+use crate::game_set::GameSet;
+use crate::health::{DamageEvent, Health};
+use crate::hitstop::Hitstop;
+use crate::particles::{BurstKind, ParticleBurst};
+use crate::Player;
+
+/// Seconds into the attack clip the hitbox has its collider between.
+const STRIKE: (f32, f32) = (0.2, 0.35);
+const HITBOX_RADIUS: f32 = 0.45;
+/// Where the hitbox sits, from the middle of the character facing the way the model does.
+const HITBOX_OFFSET: Vec3 = Vec3::new(0.0, 0.3, 0.7);
+const DAMAGE: f32 = 20.0;
+/// Impulse applied to dynamic bodies hit, in newton-seconds.
+const KNOCKBACK_IMPULSE: f32 = 6.0;
+/// Speed characters hit are shoved back at, in meters per second, and how quickly it wears off
+/// as a share per second.
+const KNOCKBACK_SPEED: f32 = 6.0;
+const KNOCKBACK_DRAG: f32 = 8.0;
+/// Knockback slower than this, in meters per second, stops.
+const KNOCKBACK_STOP: f32 = 0.1;
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                start_attack.run_if(
+                    in_gameplay()
+                        .and_then(player_alive)
+                        .and_then(text_input_closed()),
+                ),
+                update_attacks,
+                apply_knockback.in_set(GameSet::MovementModifiers),
+            )
+                .chain()
+                .after(GameSet::Movement),
+        );
+    }
+}
+
+/// A swing in progress, on the attacking character.
+#[derive(Component)]
+pub struct MeleeAttack {
+    model: Entity,
+    hitbox: Entity,
+    /// Whether the model has started the clip yet.
+    started: bool,
+    /// Colliders this swing already hit.
+    hit: Vec<Entity>,
+}
+
+/// The sensor of a [`MeleeAttack`].
+#[derive(Component)]
+struct Hitbox;
+
+/// A character controller being shoved back, at `velocity` in meters per second.
+#[derive(Component)]
+pub struct Knockback {
+    velocity: Vec3,
+}
+
+fn start_attack(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    player: Query<(Entity, &Children), (With<Player>, Without<MeleeAttack>)>,
+    mut models: Query<&mut CharacterModel>,
+) {
+    if !input.just_pressed(KeyCode::F) {
+        return;
+    }
+    let Ok((character, children)) = player.get_single() else {
+        return;
+    };
+    let Some(model) = children
+        .iter()
+        .copied()
+        .find(|&child| models.contains(child))
+    else {
+        return;
+    };
+    let Ok(mut character_model) = models.get_mut(model) else {
+        return;
+    };
+    if character_model.in_action() {
+        return;
+    }
+    character_model.play_action(AnimationState::Attack);
+
+    let hitbox = commands
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(HITBOX_OFFSET)),
+            Sensor,
+            // The hitbox has no body, and nor do characters: let it meet them.
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_STATIC
+                | ActiveCollisionTypes::STATIC_STATIC,
+            Hitbox,
+            Name::new("Hitbox"),
+        ))
+        .id();
+    commands
+        .entity(character)
+        .insert(MeleeAttack {
+            model,
+            hitbox,
+            started: false,
+            hit: Vec::new(),
+        })
+        .add_child(hitbox);
+}
+
+/// Moves the hitboxes with the models, gives them their collider during the strike and deals the
+/// hits. The collider is added and removed rather than disabled: a re-enabled collider that
+/// doesn't move doesn't find what it overlaps.
+fn update_attacks(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut damage: EventWriter<DamageEvent>,
+    mut hitstops: EventWriter<Hitstop>,
+    mut bursts: EventWriter<ParticleBurst>,
+    mut attackers: Query<(Entity, &mut MeleeAttack, &GlobalTransform)>,
+    models: Query<(&CharacterModel, &Transform)>,
+    mut hitboxes: Query<
+        (&mut Transform, &GlobalTransform, Has<Collider>),
+        (With<Hitbox>, Without<CharacterModel>),
+    >,
+    targets: Query<(&GlobalTransform, Option<&RigidBody>, Has<Health>)>,
+    controllers: Query<(), With<KinematicCharacterController>>,
+) {
+    for (character, mut attack, character_transform) in &mut attackers {
+        let Ok((model, model_transform)) = models.get(attack.model) else {
+            continue;
+        };
+        let Some(time) = model.action_time() else {
+            // The clip is over, or the model is gone.
+            if attack.started || !model.in_action() {
+                commands.entity(attack.hitbox).despawn_recursive();
+                commands.entity(character).remove::<MeleeAttack>();
+            }
+            continue;
+        };
+        attack.started = true;
+        let Ok((mut hitbox_transform, hitbox_global, has_collider)) =
+            hitboxes.get_mut(attack.hitbox)
+        else {
+            continue;
+        };
+        hitbox_transform.translation = model_transform.rotation * HITBOX_OFFSET;
+
+        let striking = (STRIKE.0..=STRIKE.1).contains(&time);
+        match (striking, has_collider) {
+            (true, false) => {
+                commands
+                    .entity(attack.hitbox)
+                    .insert(Collider::ball(HITBOX_RADIUS));
+            }
+            (false, true) => {
+                commands.entity(attack.hitbox).remove::<Collider>();
+            }
+            _ => {}
+        }
+        if !striking {
+            continue;
+        }
+
+        let point = hitbox_global.translation();
+        for (a, b, intersecting) in rapier_context.intersections_with(attack.hitbox) {
+            let target = if a == attack.hitbox { b } else { a };
+            if !intersecting || target == character || attack.hit.contains(&target) {
+                continue;
+            }
+            let Ok((target_transform, body, has_health)) = targets.get(target) else {
+                continue;
+            };
+            if attack.hit.is_empty() {
+                hitstops.send(Hitstop { strength: 1.0 });
+            }
+            attack.hit.push(target);
+            bursts.send(ParticleBurst {
+                kind: BurstKind::Dust,
+                position: point,
+                direction: Vec3::ZERO,
+                strength: 1.0,
+            });
+            if has_health {
+                damage.send(DamageEvent {
+                    target,
+                    amount: DAMAGE,
+                    point,
+                });
+            }
+            // Away from the attacker, and a little up.
+            let away = ((target_transform.translation() - character_transform.translation())
+                * Vec3::new(1.0, 0.0, 1.0))
+            .normalize_or_zero()
+                + Vec3::Y * 0.3;
+            if body == Some(&RigidBody::Dynamic) {
+                commands.entity(target).insert(ExternalImpulse::at_point(
+                    away * KNOCKBACK_IMPULSE,
+                    point,
+                    target_transform.translation(),
+                ));
+            } else if controllers.contains(target) {
+                commands.entity(target).insert(Knockback {
+                    velocity: away * Vec3::new(1.0, 0.0, 1.0) * KNOCKBACK_SPEED,
+                });
+            }
+        }
+    }
+}
+
+/// Adds the knockback of characters to their movement. AI targets move along, so the AI doesn't
+/// walk them straight back.
+fn apply_knockback(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut characters: Query<(
+        Entity,
+        &mut Knockback,
+        &mut KinematicCharacterController,
+        Option<&mut MoveTarget>,
+    )>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut knockback, mut controller, move_target) in &mut characters {
+        let step = knockback.velocity * dt;
+        controller.translation = Some(controller.translation.unwrap_or_default() + step);
+        if let Some(mut move_target) = move_target {
+            move_target.displace(step);
+        }
+        knockback.velocity *= (-KNOCKBACK_DRAG * dt).exp();
+        if knockback.velocity.length() < KNOCKBACK_STOP {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Shared pieces of the menu screens: keyboard/gamepad navigation input and button styling.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::localization::Localized;
+
+pub const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.18);
+pub const FOCUSED_BUTTON_COLOR: Color = Color::rgb(0.3, 0.3, 0.45);
+pub const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+
+/// Menu navigation from the keyboard and any connected gamepad.
+#[derive(SystemParam)]
+pub struct MenuInput<'w> {
+    keys: Res<'w, Input<KeyCode>>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+}
+
+impl<'w> MenuInput<'w> {
+    fn gamepad_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.buttons
+                .just_pressed(GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    pub fn up(&self) -> bool {
+        self.keys.any_just_pressed([KeyCode::Up, KeyCode::W])
+            || self.gamepad_pressed(GamepadButtonType::DPadUp)
+    }
+
+    pub fn down(&self) -> bool {
+        self.keys.any_just_pressed([KeyCode::Down, KeyCode::S])
+            || self.gamepad_pressed(GamepadButtonType::DPadDown)
+    }
+
+    pub fn left(&self) -> bool {
+        self.keys.any_just_pressed([KeyCode::Left, KeyCode::A])
+            || self.gamepad_pressed(GamepadButtonType::DPadLeft)
+    }
+
+    pub fn right(&self) -> bool {
+        self.keys.any_just_pressed([KeyCode::Right, KeyCode::D])
+            || self.gamepad_pressed(GamepadButtonType::DPadRight)
+    }
+
+    pub fn activate(&self) -> bool {
+        self.keys
+            .any_just_pressed([KeyCode::Return, KeyCode::Space])
+            || self.gamepad_pressed(GamepadButtonType::South)
+    }
+
+    /// Backspace or the gamepad's east button.
+    pub fn back(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Back) || self.gamepad_pressed(GamepadButtonType::East)
+    }
+
+    /// Escape or the gamepad's start button.
+    pub fn pause(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Escape) || self.gamepad_pressed(GamepadButtonType::Start)
+    }
+
+    /// Moves `focus` up or down through `count` entries, wrapping around.
+    pub fn navigate(&self, focus: &mut usize, count: usize) {
+        if count == 0 {
+            return;
+        }
+        if self.up() {
+            *focus = (*focus + count - 1) % count;
+        }
+        if self.down() {
+            *focus = (*focus + 1) % count;
+        }
+    }
+}
+
+/// A full-screen node centering a column of menu entries on top of the game.
+pub fn overlay() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        background_color: PANEL_COLOR.into(),
+        z_index: ZIndex::Global(5),
+        ..default()
+    }
+}
+
+pub fn button() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            width: Val::Px(260.0),
+            height: Val::Px(48.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    }
+}
+
+pub fn label(text: impl Into<String>, style: TextStyle) -> TextBundle {
+    TextBundle::from_section(text, style)
+}
+
+/// A [`label`] showing the translation of `key`.
+pub fn localized_label(key: &'static str, style: TextStyle) -> (TextBundle, Localized) {
+    (label("", style), Localized(key))
+}
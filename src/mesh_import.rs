@@ -0,0 +1,430 @@
+//! Runtime import of OBJ and STL meshes, to try out your own geometry against the character
+//! controller without going through the asset pipeline.
+//!
+//! `import_mesh <path> [trimesh|convex]` in the console, or dropping a `.obj` or `.stl` file on the
+//! window, spawns the mesh in front of the camera. A trimesh collider keeps the exact shape and
+//! makes a fixed body; a convex hull makes a dynamic body that can be pushed around.
+//!
+//! OBJ faces with more than three corners are split into fans, and everything but vertex
+//! positions and faces is ignored; normals are recomputed flat. Both ASCII and binary STL work.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::window::FileDragAndDrop;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::console::ConsoleAppExt;
+use crate::materials::MaterialLibrary;
+
+/// How far in front of the camera imported meshes appear, in meters.
+const SPAWN_DISTANCE: f32 = 5.0;
+/// Size of a binary STL header, before the triangle count.
+const STL_HEADER_LEN: usize = 80;
+/// Size of a triangle in a binary STL: normal, three corners and an attribute byte count.
+const STL_TRIANGLE_LEN: usize = 50;
+
+pub struct MeshImportPlugin;
+
+impl Plugin for MeshImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command(
+            "import_mesh",
+            "import_mesh <path> [trimesh|convex] - spawn an OBJ or STL mesh in front of the camera",
+            import_mesh_command,
+        )
+        .add_systems(Update, import_dropped_files);
+    }
+}
+
+/// The collider given to an imported mesh.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ImportCollider {
+    /// The exact triangles, on a fixed body.
+    #[default]
+    Trimesh,
+    /// The convex hull of the vertices, on a dynamic body.
+    Convex,
+}
+
+impl ImportCollider {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "trimesh" => Some(ImportCollider::Trimesh),
+            "convex" => Some(ImportCollider::Convex),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MeshImportError {
+    Io(std::io::Error),
+    /// Not a `.obj` or `.stl` file.
+    UnknownFormat,
+    Obj {
+        line: usize,
+        message: String,
+    },
+    Stl(String),
+    /// The file has no triangles.
+    Empty,
+    /// Rapier couldn't build a collider, for example from flat geometry.
+    Collider,
+}
+
+impl fmt::Display for MeshImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshImportError::Io(error) => write!(f, "could not read the file: {error}"),
+            MeshImportError::UnknownFormat => write!(f, "only .obj and .stl files can be imported"),
+            MeshImportError::Obj { line, message } => {
+                write!(f, "invalid OBJ, line {line}: {message}")
+            }
+            MeshImportError::Stl(message) => write!(f, "invalid STL: {message}"),
+            MeshImportError::Empty => write!(f, "the file has no triangles"),
+            MeshImportError::Collider => write!(f, "could not build a collider for the mesh"),
+        }
+    }
+}
+
+impl std::error::Error for MeshImportError {}
+
+/// Vertex positions and the triangles between them.
+#[derive(Default, Debug)]
+pub struct TriangleMesh {
+    pub vertices: Vec<Vec3>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl TriangleMesh {
+    /// Reads the OBJ or STL file at `path`, going by its extension.
+    pub fn load(path: &Path) -> Result<Self, MeshImportError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let bytes = fs::read(path).map_err(MeshImportError::Io)?;
+        let mesh = match extension.as_deref() {
+            Some("obj") => Self::from_obj(&String::from_utf8_lossy(&bytes))?,
+            Some("stl") => Self::from_stl(&bytes)?,
+            _ => return Err(MeshImportError::UnknownFormat),
+        };
+        if mesh.triangles.is_empty() {
+            return Err(MeshImportError::Empty);
+        }
+        Ok(mesh)
+    }
+
+    pub fn from_obj(source: &str) -> Result<Self, MeshImportError> {
+        let mut mesh = TriangleMesh::default();
+        for (index, line) in source.lines().enumerate() {
+            let error = |message: String| MeshImportError::Obj {
+                line: index + 1,
+                message,
+            };
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("v") => {
+                    let coordinates: Vec<f32> = words
+                        .take(3)
+                        .map(|word| {
+                            word.parse()
+                                .map_err(|_| error(format!("bad number `{word}`")))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    let [x, y, z] = coordinates[..] else {
+                        return Err(error("a vertex needs three coordinates".to_string()));
+                    };
+                    mesh.vertices.push(Vec3::new(x, y, z));
+                }
+                Some("f") => {
+                    // Corners look like `v`, `v/vt`, `v//vn` or `v/vt/vn`; negative indices count
+                    // back from the last vertex.
+                    let corners: Vec<u32> = words
+                        .map(|word| {
+                            let position = word.split('/').next().unwrap_or(word);
+                            let index: i64 = position
+                                .parse()
+                                .map_err(|_| error(format!("bad face corner `{word}`")))?;
+                            let count = mesh.vertices.len() as i64;
+                            let index = if index < 0 { count + index } else { index - 1 };
+                            if (0..count).contains(&index) {
+                                Ok(index as u32)
+                            } else {
+                                Err(error(format!("no vertex {position}")))
+                            }
+                        })
+                        .collect::<Result<_, _>>()?;
+                    if corners.len() < 3 {
+                        return Err(error("a face needs three corners".to_string()));
+                    }
+                    for pair in corners[1..].windows(2) {
+                        mesh.triangles.push([corners[0], pair[0], pair[1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(mesh)
+    }
+
+    pub fn from_stl(bytes: &[u8]) -> Result<Self, MeshImportError> {
+        let binary_len = |count: usize| STL_HEADER_LEN + 4 + count * STL_TRIANGLE_LEN;
+        let count = bytes
+            .get(STL_HEADER_LEN..STL_HEADER_LEN + 4)
+            .map(|count| u32::from_le_bytes(count.try_into().unwrap()) as usize);
+        // ASCII files start with `solid`, but so do some binary ones, so the size decides.
+        match count {
+            Some(count) if bytes.len() == binary_len(count) => Ok(Self::from_binary_stl(bytes)),
+            _ if bytes.starts_with(b"solid") => {
+                Self::from_ascii_stl(&String::from_utf8_lossy(bytes))
+            }
+            _ => Err(MeshImportError::Stl(
+                "neither ASCII nor a binary file of the right size".to_string(),
+            )),
+        }
+    }
+
+    fn from_binary_stl(bytes: &[u8]) -> Self {
+        let read_vec3 = |bytes: &[u8]| {
+            let float =
+                |offset: usize| f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            Vec3::new(float(0), float(4), float(8))
+        };
+        let mut mesh = TriangleMesh::default();
+        for triangle in bytes[STL_HEADER_LEN + 4..].chunks_exact(STL_TRIANGLE_LEN) {
+            // The stored normal is skipped; it's often zero, and is recomputed anyway.
+            mesh.push_triangle([12, 24, 36].map(|offset| read_vec3(&triangle[offset..])));
+        }
+        mesh
+    }
+
+    fn from_ascii_stl(source: &str) -> Result<Self, MeshImportError> {
+        let mut mesh = TriangleMesh::default();
+        let mut corners = Vec::with_capacity(3);
+        for line in source.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("vertex") => {
+                    let coordinates: Vec<f32> = words
+                        .map(|word| {
+                            word.parse()
+                                .map_err(|_| MeshImportError::Stl(format!("bad number `{word}`")))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    let [x, y, z] = coordinates[..] else {
+                        return Err(MeshImportError::Stl(
+                            "a vertex needs three coordinates".to_string(),
+                        ));
+                    };
+                    corners.push(Vec3::new(x, y, z));
+                }
+                Some("endloop") => {
+                    let Ok(triangle) = <[Vec3; 3]>::try_from(corners.as_slice()) else {
+                        return Err(MeshImportError::Stl(
+                            "a facet needs three vertices".to_string(),
+                        ));
+                    };
+                    mesh.push_triangle(triangle);
+                    corners.clear();
+                }
+                _ => {}
+            }
+        }
+        Ok(mesh)
+    }
+
+    fn push_triangle(&mut self, corners: [Vec3; 3]) {
+        let start = self.vertices.len() as u32;
+        self.vertices.extend(corners);
+        self.triangles.push([start, start + 1, start + 2]);
+    }
+
+    /// The mesh to render, with flat normals.
+    pub fn mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.vertices.clone());
+        mesh.set_indices(Some(Indices::U32(self.triangles.concat())));
+        mesh.duplicate_vertices();
+        mesh.compute_flat_normals();
+        mesh
+    }
+
+    pub fn collider(&self, kind: ImportCollider) -> Option<Collider> {
+        match kind {
+            ImportCollider::Trimesh => Some(Collider::trimesh(
+                self.vertices.clone(),
+                self.triangles.clone(),
+            )),
+            ImportCollider::Convex => Collider::convex_hull(&self.vertices),
+        }
+    }
+}
+
+/// Spawns the mesh at `path` in front of the camera.
+pub fn import_mesh(
+    world: &mut World,
+    path: &Path,
+    kind: ImportCollider,
+) -> Result<Entity, MeshImportError> {
+    let imported = TriangleMesh::load(path)?;
+    let collider = imported.collider(kind).ok_or(MeshImportError::Collider)?;
+    let position = world
+        .query_filtered::<&GlobalTransform, With<MainCamera>>()
+        .get_single(world)
+        .map_or(Vec3::Y * 2.0, |camera| {
+            camera.translation() + camera.forward() * SPAWN_DISTANCE
+        });
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(imported.mesh());
+    let material = match world.get_resource::<MaterialLibrary>() {
+        Some(library) => library.prototype.clone(),
+        None => world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::SILVER.into()),
+    };
+    let name = path
+        .file_stem()
+        .map_or("mesh".into(), |stem| stem.to_string_lossy());
+
+    let mut entity = world.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        collider,
+        Name::new(format!("Imported {name}")),
+    ));
+    match kind {
+        ImportCollider::Trimesh => entity.insert(RigidBody::Fixed),
+        ImportCollider::Convex => entity.insert((RigidBody::Dynamic, Velocity::default())),
+    };
+    Ok(entity.id())
+}
+
+fn import_mesh_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let path = *args.first().ok_or("missing argument <path>")?;
+    let kind = match args.get(1) {
+        Some(name) => ImportCollider::from_name(name)
+            .ok_or_else(|| format!("unknown collider `{name}`, expected trimesh or convex"))?,
+        None => ImportCollider::default(),
+    };
+    import_mesh(world, Path::new(path), kind).map_err(|error| error.to_string())?;
+    Ok(format!("imported {path}"))
+}
+
+fn import_dropped_files(mut commands: Commands, mut drops: EventReader<FileDragAndDrop>) {
+    for drop in drops.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = drop else {
+            continue;
+        };
+        let path = path_buf.clone();
+        commands.add(move |world: &mut World| {
+            match import_mesh(world, &path, ImportCollider::default()) {
+                Ok(_) => info!("imported {}", path.display()),
+                Err(error) => warn!("could not import {}: {error}", path.display()),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: [Vec3; 3] = [Vec3::ZERO, Vec3::X, Vec3::Y];
+
+    fn binary_stl(header: &[u8], triangles: &[[Vec3; 3]]) -> Vec<u8> {
+        let mut bytes = header.to_vec();
+        bytes.resize(STL_HEADER_LEN, 0);
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            bytes.extend_from_slice(&[0; 12]);
+            for corner in triangle {
+                for coordinate in corner.to_array() {
+                    bytes.extend_from_slice(&coordinate.to_le_bytes());
+                }
+            }
+            bytes.extend_from_slice(&[0; 2]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn obj_faces_split_into_fans() {
+        let mesh = TriangleMesh::from_obj(
+            "# a quad and a pentagon\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0.5 2 0\n\
+             f 1 2 3 4\n\
+             f 1/1 2/2/2 3//3 5 4\n",
+        )
+        .unwrap();
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(
+            mesh.triangles,
+            [[0, 1, 2], [0, 2, 3], [0, 1, 2], [0, 2, 4], [0, 4, 3]]
+        );
+    }
+
+    #[test]
+    fn obj_negative_indices_count_back() {
+        let mesh = TriangleMesh::from_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n").unwrap();
+        assert_eq!(mesh.triangles, [[0, 1, 2]]);
+    }
+
+    #[test]
+    fn obj_errors_name_their_line() {
+        for (source, line) in [
+            ("v 0 0 0\nv 1 0\n", 2),
+            ("v 0 0 0\nv 1 0 0\nf 1 2\n", 3),
+            ("v 0 0 0\nf 1 2 3\n", 2),
+            ("f 0 1 2\n", 1),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -4 1 2\n", 4),
+        ] {
+            match TriangleMesh::from_obj(source) {
+                Err(MeshImportError::Obj { line: found, .. }) => assert_eq!(found, line),
+                other => panic!("{source:?}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_stl() {
+        let mesh = TriangleMesh::from_stl(
+            b"solid test\n\
+              facet normal 0 0 1\n outer loop\n\
+              vertex 0 0 0\n vertex 1 0 0\n vertex 0 1 0\n\
+              endloop\n endfacet\n\
+              endsolid test\n",
+        )
+        .unwrap();
+        assert_eq!(mesh.vertices, TRIANGLE);
+        assert_eq!(mesh.triangles, [[0, 1, 2]]);
+    }
+
+    #[test]
+    fn binary_stl_even_when_it_starts_with_solid() {
+        for header in [&b"binary"[..], b"solid but binary"] {
+            let mesh = TriangleMesh::from_stl(&binary_stl(header, &[TRIANGLE, TRIANGLE])).unwrap();
+            assert_eq!(mesh.vertices.len(), 6);
+            assert_eq!(&mesh.vertices[3..], TRIANGLE);
+            assert_eq!(mesh.triangles, [[0, 1, 2], [3, 4, 5]]);
+        }
+    }
+
+    #[test]
+    fn truncated_binary_stl_is_an_error() {
+        let mut bytes = binary_stl(b"binary", &[TRIANGLE]);
+        bytes.pop();
+        assert!(matches!(
+            TriangleMesh::from_stl(&bytes),
+            Err(MeshImportError::Stl(_))
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+//! A top-down minimap in the top-right corner, drawn procedurally from the positions of entities
+//! carrying a [`MinimapIcon`]. The map is centered on the player with north (-Z) up.
+//!
+//! Bodies with the pickup material are shown as pickups. Other dynamic props get a dot of their own
+//! only up to `MAX_PROP_DOTS`, so a scene full of crates doesn't fill the UI with nodes; when
+//! icons go away, props without one take their place.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::UiAssets;
+use crate::game_set::GameSet;
+use crate::materials::{MaterialLibrary, MaterialPreset};
+use crate::Player;
+
+const MAP_SIZE: f32 = 160.0;
+/// Minimap pixels per world meter.
+const PIXELS_PER_METER: f32 = 4.0;
+/// Most props shown at once; props past it have no dot until others go away.
+const MAX_PROP_DOTS: usize = 200;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap).add_systems(
+            PostUpdate,
+            (tag_minimap_icons, sync_minimap_dots, place_minimap_dots)
+                .chain()
+                .in_set(GameSet::Ui),
+        );
+    }
+}
+
+/// Shows the entity on the minimap.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MinimapIcon {
+    Player,
+    Enemy,
+    Pickup,
+    Prop,
+}
+
+impl MinimapIcon {
+    fn color(self) -> Color {
+        match self {
+            MinimapIcon::Player => Color::WHITE,
+            MinimapIcon::Enemy => Color::RED,
+            MinimapIcon::Pickup => Color::GOLD,
+            MinimapIcon::Prop => Color::GRAY,
+        }
+    }
+
+    fn size(self) -> f32 {
+        match self {
+            MinimapIcon::Player => 8.0,
+            MinimapIcon::Enemy | MinimapIcon::Pickup => 6.0,
+            MinimapIcon::Prop => 4.0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct MinimapPanel;
+
+/// Minimap dot of each icon entity.
+#[derive(Component, Default)]
+struct MinimapDots(HashMap<Entity, Entity>);
+
+fn spawn_minimap(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(15.0),
+                right: Val::Px(15.0),
+                width: Val::Px(MAP_SIZE),
+                height: Val::Px(MAP_SIZE),
+                border: UiRect::all(Val::Px(2.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: Color::rgba(0.1, 0.15, 0.1, 0.7).into(),
+            border_color: Color::rgba(1.0, 1.0, 1.0, 0.5).into(),
+            ..default()
+        },
+        MinimapPanel,
+        MinimapDots::default(),
+    ));
+}
+
+type UntaggedBody = (
+    Entity,
+    &'static RigidBody,
+    Option<&'static Handle<StandardMaterial>>,
+);
+
+/// Gives the player, pickups and dynamic props a default icon, props only while fewer than
+/// [`MAX_PROP_DOTS`] have one. Once icons are removed, untagged props are looked at again.
+fn tag_minimap_icons(
+    mut commands: Commands,
+    library: Option<Res<MaterialLibrary>>,
+    players: Query<Entity, (Added<Player>, Without<MinimapIcon>)>,
+    bodies: Query<UntaggedBody, (Added<RigidBody>, Without<MinimapIcon>, Without<Player>)>,
+    untagged: Query<UntaggedBody, (Without<MinimapIcon>, Without<Player>)>,
+    icons: Query<&MinimapIcon>,
+    mut removed: RemovedComponents<MinimapIcon>,
+) {
+    for entity in &players {
+        commands.entity(entity).insert(MinimapIcon::Player);
+    }
+    let freed = removed.read().count() > 0;
+    if bodies.is_empty() && !freed {
+        return;
+    }
+    let pickup = library.map(|library| library.preset(MaterialPreset::GlowingPickup));
+    let mut props = icons
+        .iter()
+        .filter(|icon| **icon == MinimapIcon::Prop)
+        .count();
+    let candidates = if freed {
+        untagged.iter().collect::<Vec<_>>()
+    } else {
+        bodies.iter().collect()
+    };
+    for (entity, body, material) in candidates {
+        if pickup.is_some() && material == pickup.as_ref() {
+            commands.entity(entity).insert(MinimapIcon::Pickup);
+        } else if *body == RigidBody::Dynamic && props < MAX_PROP_DOTS {
+            commands.entity(entity).insert(MinimapIcon::Prop);
+            props += 1;
+        }
+    }
+}
+
+/// Spawns a dot for each new icon and despawns the dots of removed ones.
+fn sync_minimap_dots(
+    mut commands: Commands,
+    ui_assets: Option<Res<UiAssets>>,
+    icons: Query<(Entity, &MinimapIcon), Added<MinimapIcon>>,
+    mut removed: RemovedComponents<MinimapIcon>,
+    mut panel: Query<(Entity, &mut MinimapDots), With<MinimapPanel>>,
+) {
+    let Ok((panel, mut dots)) = panel.get_single_mut() else {
+        return;
+    };
+
+    for entity in removed.read() {
+        if let Some(dot) = dots.0.remove(&entity) {
+            commands.entity(dot).despawn_recursive();
+        }
+    }
+
+    for (entity, icon) in &icons {
+        let size = icon.size();
+        let dot = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(size),
+                    height: Val::Px(size),
+                    ..default()
+                },
+                background_color: icon.color().into(),
+                // Keep the player above everything else.
+                z_index: ZIndex::Local(i32::from(*icon == MinimapIcon::Player)),
+                ..default()
+            })
+            .id();
+        if let (MinimapIcon::Player, Some(ui_assets)) = (icon, &ui_assets) {
+            commands
+                .entity(dot)
+                .insert(UiImage::new(ui_assets.player_marker.clone()));
+        }
+        commands.entity(panel).add_child(dot);
+        if let Some(old) = dots.0.insert(entity, dot) {
+            commands.entity(old).despawn_recursive();
+        }
+    }
+}
+
+fn place_minimap_dots(
+    player: Query<&GlobalTransform, With<Player>>,
+    icons: Query<(&GlobalTransform, &MinimapIcon)>,
+    panel: Query<&MinimapDots, With<MinimapPanel>>,
+    mut styles: Query<(&mut Style, &mut Visibility)>,
+) {
+    let center = player
+        .get_single()
+        .map_or(Vec3::ZERO, GlobalTransform::translation);
+    let Ok(dots) = panel.get_single() else {
+        return;
+    };
+
+    for (entity, dot) in &dots.0 {
+        let (Ok((transform, icon)), Ok((mut style, mut visibility))) =
+            (icons.get(*entity), styles.get_mut(*dot))
+        else {
+            continue;
+        };
+        let offset = (transform.translation() - center).xz() * PIXELS_PER_METER;
+        let half = MAP_SIZE / 2.0;
+        let inside = offset.x.abs() < half && offset.y.abs() < half;
+
+        *visibility = if inside {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        style.left = Val::Px(half + offset.x - icon.size() / 2.0);
+        style.top = Val::Px(half + offset.y - icon.size() / 2.0);
+    }
+}
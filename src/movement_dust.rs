@@ -0,0 +1,116 @@
+//! Dust kicked up by characters on dusty [`Surface`]s: a puff when they land from a fall, and
+//! small ones at their feet every few steps while they run.
+//!
+//! Landings are the frames a character's controller reports it grounded again, as hard as it was
+//! falling the frame before. Running is moving at least the [`PlayerSettings`]' run speed on the
+//! ground. The surface comes from a ray down from the character, as for footsteps.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::particles::{BurstKind, ParticleBurst};
+use crate::settings::{PlayerSettings, Settings};
+use crate::surface::Surface;
+
+/// Characters landing slower than this, in meters per second, raise no dust; landing at this speed
+/// raises a puff of strength 1.
+const LANDING_SPEED: f32 = 4.0;
+/// Meters run between puffs, and their strength.
+const PUFF_SPACING: f32 = 0.9;
+const PUFF_STRENGTH: f32 = 0.4;
+/// How far below the character's center the ground is looked for.
+const GROUND_PROBE: f32 = 1.5;
+
+pub struct MovementDustPlugin;
+
+impl Plugin for MovementDustPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (add_movement_dust, kick_up_dust).chain());
+    }
+}
+
+/// How a character moved last frame, to tell when it lands or has run far enough for a puff.
+#[derive(Component, Default)]
+pub struct MovementDust {
+    grounded: bool,
+    /// Speed downwards, in meters per second.
+    fall_speed: f32,
+    /// Meters run since the last puff.
+    distance: f32,
+}
+
+fn add_movement_dust(
+    mut commands: Commands,
+    characters: Query<Entity, Added<KinematicCharacterController>>,
+) {
+    for character in &characters {
+        commands.entity(character).insert(MovementDust::default());
+    }
+}
+
+fn kick_up_dust(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    rapier_context: Res<RapierContext>,
+    mut bursts: EventWriter<ParticleBurst>,
+    mut characters: Query<(
+        Entity,
+        &mut MovementDust,
+        &GlobalTransform,
+        &KinematicCharacterControllerOutput,
+    )>,
+    surfaces: Query<&Surface>,
+) {
+    let dt = time.delta_seconds();
+    if dt <= 0.0 {
+        return;
+    }
+    let PlayerSettings { run_speed, .. } = settings.player;
+    for (entity, mut dust, transform, output) in &mut characters {
+        let velocity = output.effective_translation / dt;
+        let landed = output.grounded && !dust.grounded;
+        let landing_speed = dust.fall_speed;
+        dust.grounded = output.grounded;
+        dust.fall_speed = (-velocity.y).max(0.0);
+
+        let mut strength = 0.0;
+        if landed && landing_speed >= LANDING_SPEED {
+            strength = landing_speed / LANDING_SPEED;
+            dust.distance = 0.0;
+        } else if output.grounded && velocity.xz().length() >= run_speed {
+            dust.distance += velocity.xz().length() * dt;
+            if dust.distance >= PUFF_SPACING {
+                dust.distance -= PUFF_SPACING;
+                strength = PUFF_STRENGTH;
+            }
+        } else {
+            dust.distance = 0.0;
+        }
+        if strength == 0.0 {
+            continue;
+        }
+
+        let origin = transform.translation();
+        let ground = rapier_context.cast_ray(
+            origin,
+            Vec3::NEG_Y,
+            GROUND_PROBE,
+            true,
+            QueryFilter::default()
+                .exclude_sensors()
+                .exclude_collider(entity),
+        );
+        let Some((ground, distance)) = ground else {
+            continue;
+        };
+        if !surfaces.get(ground).copied().unwrap_or_default().dusty() {
+            continue;
+        }
+        bursts.send(ParticleBurst {
+            kind: BurstKind::Dust,
+            position: origin + Vec3::NEG_Y * distance,
+            direction: Vec3::Y,
+            strength,
+        });
+    }
+}
@@ -0,0 +1,125 @@
+//! Background music picked from the game state, crossfading whenever the track changes.
+
+use bevy::audio::{PlaybackMode, Volume, VolumeLevel};
+use bevy::prelude::*;
+
+use crate::assets::AudioAssets;
+use crate::audio_mixer::{AudioChannel, AudioMixer};
+use crate::pause_menu::PauseState;
+use crate::MyStates;
+
+/// Seconds a crossfade between two tracks takes.
+const FADE_SECONDS: f32 = 1.5;
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentMusic>().add_systems(
+            Update,
+            (switch_music, fade_music)
+                .chain()
+                .run_if(resource_exists::<AudioAssets>()),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MusicTrack {
+    Menu,
+    Gameplay,
+    GameOver,
+}
+
+impl MusicTrack {
+    fn for_state(state: &MyStates, pause: &PauseState) -> Option<Self> {
+        match (state, pause) {
+            (MyStates::AssetLoading | MyStates::Crashed | MyStates::Editor, _) => None,
+            (
+                MyStates::FailedLoading
+                | MyStates::Lobby
+                | MyStates::Connecting
+                | MyStates::ConnectionFailed,
+                _,
+            )
+            | (_, PauseState::Paused | PauseState::Settings) => Some(MusicTrack::Menu),
+            (MyStates::Next | MyStates::InGame, PauseState::Running) => Some(MusicTrack::Gameplay),
+            (MyStates::GameOver, PauseState::Running) => Some(MusicTrack::GameOver),
+        }
+    }
+
+    fn source(self, audio: &AudioAssets) -> Handle<AudioSource> {
+        match self {
+            MusicTrack::Menu => audio.menu_music.clone(),
+            MusicTrack::Gameplay => audio.gameplay_music.clone(),
+            MusicTrack::GameOver => audio.game_over_music.clone(),
+        }
+    }
+}
+
+/// The track that is playing or fading in.
+#[derive(Resource, Default)]
+struct CurrentMusic(Option<MusicTrack>);
+
+/// A playing music track, fading towards `target` volume.
+#[derive(Component)]
+struct Music {
+    volume: f32,
+    target: f32,
+}
+
+fn switch_music(
+    mut commands: Commands,
+    audio: Res<AudioAssets>,
+    state: Res<State<MyStates>>,
+    pause: Res<State<PauseState>>,
+    mut current: ResMut<CurrentMusic>,
+    mut playing: Query<&mut Music>,
+) {
+    let wanted = MusicTrack::for_state(state.get(), pause.get());
+    if wanted == current.0 {
+        return;
+    }
+    current.0 = wanted;
+
+    for mut music in &mut playing {
+        music.target = 0.0;
+    }
+    if let Some(track) = wanted {
+        commands.spawn((
+            AudioBundle {
+                source: track.source(&audio),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    volume: Volume::Absolute(VolumeLevel::new(0.0)),
+                    ..default()
+                },
+            },
+            Music {
+                volume: 0.0,
+                target: 1.0,
+            },
+        ));
+    }
+}
+
+/// Moves every track's volume towards its target, despawning tracks that have faded out.
+fn fade_music(
+    mut commands: Commands,
+    // Real time, so music keeps fading while the game is paused.
+    time: Res<Time<Real>>,
+    mixer: Res<AudioMixer>,
+    mut tracks: Query<(Entity, &mut Music, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / FADE_SECONDS;
+    for (entity, mut music, sink) in &mut tracks {
+        music.volume += (music.target - music.volume).clamp(-step, step);
+        if music.target == 0.0 && music.volume <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(mixer.volume(AudioChannel::Music, music.volume));
+        }
+    }
+}
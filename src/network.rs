@@ -0,0 +1,497 @@
+//! Optional client/server mode over UDP, so several instances can watch the same sandbox.
+//!
+//! Hosting and joining is done from the lobby (see [`crate::lobby`]), or from the command line by
+//! starting one instance with `--host [port]` and the others with `--connect <address>`. The server
+//! runs the simulation and streams the pose of every body with a [`SaveId`] to its clients
+//! [`SNAPSHOT_RATE`] times a second. Clients turn their bodies kinematic and interpolate between
+//! the last two snapshots, spawning bodies the server has and they don't.
+//!
+//! Messages are [`NetMessage`]s encoded as RON, one per datagram. Snapshots are split into parts of
+//! at most [`BODIES_PER_PART`] bodies so each fits in a datagram, and clients apply a snapshot once
+//! all of its parts have arrived.
+
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::character_input::CharacterInput;
+use crate::game_set::GameSet;
+use crate::materials::MaterialLibrary;
+use crate::save::SaveId;
+use crate::scene_export::ColliderShape;
+
+pub const DEFAULT_PORT: u16 = 7777;
+/// Snapshots sent per second.
+const SNAPSHOT_RATE: f32 = 20.0;
+/// Seconds between a client's keep-alive messages.
+const HELLO_INTERVAL: f32 = 1.0;
+/// Seconds without a message after which a client is dropped, or a server is given up on.
+pub const TIMEOUT: f32 = 5.0;
+/// Largest datagram that is read.
+const MAX_DATAGRAM: usize = 65_507;
+/// Bodies in one part of a snapshot. A body takes at most a few hundred bytes of RON, so a part
+/// stays well under [`MAX_DATAGRAM`].
+const BODIES_PER_PART: usize = 150;
+
+/// Which side of the connection this instance is.
+#[derive(Clone, Copy, Debug)]
+pub enum NetworkRole {
+    /// Listens on the port.
+    Server(u16),
+    /// Connects to the server at the address.
+    Client(SocketAddr),
+}
+
+/// Sets up networking, connecting right away if a role is given (see
+/// [`crate::launch_options`]).
+pub struct NetworkPlugin {
+    pub role: Option<NetworkRole>,
+}
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(role) = self.role {
+            match Network::open(role) {
+                Ok(network) => {
+                    app.insert_resource(network);
+                }
+                Err(error) => error!("could not open the network socket: {error}"),
+            }
+        }
+        app.add_event::<NetReceived>()
+            .init_resource::<SnapshotTimer>()
+            .add_systems(
+                PreUpdate,
+                receive_messages.run_if(resource_exists::<Network>()),
+            )
+            .add_systems(Update, track_clients.run_if(is_server))
+            .add_systems(
+                PostUpdate,
+                send_snapshots
+                    .in_set(GameSet::PhysicsWriteback)
+                    .run_if(is_server),
+            )
+            .add_systems(
+                Update,
+                (
+                    send_hello,
+                    watch_server,
+                    make_bodies_kinematic,
+                    apply_snapshots,
+                    interpolate_bodies,
+                )
+                    .chain()
+                    .run_if(is_client),
+            );
+    }
+}
+
+/// Everything sent between server and clients.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum NetMessage {
+    /// Sent by clients to join, then periodically to stay connected.
+    Hello,
+    /// The server's answer to a client's first hello.
+    Welcome,
+    /// Part `part` of `parts` of the snapshot `tick`.
+    Snapshot {
+        tick: u32,
+        part: u16,
+        parts: u16,
+        bodies: Vec<BodyState>,
+    },
+    /// A client's movement inputs the server hasn't acknowledged yet, oldest first.
+    Inputs(Vec<CharacterInput>),
+    /// Where the server put a client's character after applying its inputs up to `sequence`.
+    CharacterState { sequence: u32, translation: Vec3 },
+    /// A chat line. Servers replace the sender of lines from clients with the client's address.
+    Chat { sender: String, text: String },
+}
+
+/// A body's pose in a snapshot. The shape lets clients spawn bodies they don't have.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BodyState {
+    pub id: u32,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub shape: Option<ColliderShape>,
+}
+
+/// A message that arrived this frame.
+#[derive(Event, Clone, Debug)]
+pub struct NetReceived {
+    pub from: SocketAddr,
+    pub message: NetMessage,
+}
+
+/// The open socket and the state of the connection.
+#[derive(Resource)]
+pub struct Network {
+    socket: UdpSocket,
+    pub role: NetworkRole,
+    /// Clients of a server, with the elapsed seconds they were last heard from.
+    clients: HashMap<SocketAddr, f32>,
+    /// Seconds since a client last said hello.
+    since_hello: f32,
+    /// Seconds since a client last heard from the server.
+    since_server: f32,
+    /// Whether a client's server has answered.
+    connected: bool,
+    /// Tick of the newest snapshot sent or applied.
+    tick: u32,
+    /// A client's parts of the newest snapshot it has started receiving, and that snapshot's tick.
+    snapshot_parts: Vec<Option<Vec<BodyState>>>,
+    snapshot_tick: u32,
+}
+
+impl Network {
+    pub fn open(role: NetworkRole) -> std::io::Result<Self> {
+        let socket = match role {
+            NetworkRole::Server(port) => UdpSocket::bind(("0.0.0.0", port))?,
+            NetworkRole::Client(_) => UdpSocket::bind(("0.0.0.0", 0))?,
+        };
+        socket.set_nonblocking(true)?;
+        info!("network {role:?} on {}", socket.local_addr()?);
+        Ok(Self {
+            socket,
+            role,
+            clients: HashMap::new(),
+            since_hello: HELLO_INTERVAL,
+            since_server: 0.0,
+            connected: false,
+            tick: 0,
+            snapshot_parts: Vec::new(),
+            snapshot_tick: 0,
+        })
+    }
+
+    pub fn send(&self, to: SocketAddr, message: &NetMessage) {
+        let result = ron::to_string(message)
+            .map_err(|error| error.to_string())
+            .and_then(|text| {
+                self.socket
+                    .send_to(text.as_bytes(), to)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("could not send to {to}: {error}");
+        }
+    }
+
+    /// Whether the server has answered a client; always true for a server.
+    pub fn connected(&self) -> bool {
+        matches!(self.role, NetworkRole::Server(_)) || self.connected
+    }
+
+    /// Whether a client has gone [`TIMEOUT`] seconds without hearing from its server.
+    pub fn timed_out(&self) -> bool {
+        matches!(self.role, NetworkRole::Client(_)) && self.since_server >= TIMEOUT
+    }
+
+    pub fn has_client(&self, client: SocketAddr) -> bool {
+        self.clients.contains_key(&client)
+    }
+
+    /// The addresses of a server's clients.
+    pub fn clients(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.clients.keys().copied()
+    }
+
+    /// Sends `message` to the server, or from the server to every client.
+    pub fn broadcast(&self, message: &NetMessage) {
+        match self.role {
+            NetworkRole::Server(_) => {
+                for client in self.clients() {
+                    self.send(client, message);
+                }
+            }
+            NetworkRole::Client(server) => self.send(server, message),
+        }
+    }
+}
+
+pub fn is_server(network: Option<Res<Network>>) -> bool {
+    network.is_some_and(|network| matches!(network.role, NetworkRole::Server(_)))
+}
+
+pub fn is_client(network: Option<Res<Network>>) -> bool {
+    network.is_some_and(|network| matches!(network.role, NetworkRole::Client(_)))
+}
+
+fn receive_messages(network: Res<Network>, mut received: EventWriter<NetReceived>) {
+    let mut buffer = vec![0; MAX_DATAGRAM];
+    loop {
+        match network.socket.recv_from(&mut buffer) {
+            Ok((len, from)) => {
+                let message = std::str::from_utf8(&buffer[..len])
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| ron::from_str(text).map_err(|error| error.to_string()));
+                match message {
+                    Ok(message) => received.send(NetReceived { from, message }),
+                    Err(error) => warn!("ignoring invalid message from {from}: {error}"),
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            // Windows reports unreachable peers as errors on the next receive.
+            Err(error) if error.kind() == ErrorKind::ConnectionReset => continue,
+            Err(error) => {
+                warn!("network receive failed: {error}");
+                break;
+            }
+        }
+    }
+}
+
+fn track_clients(
+    time: Res<Time<Real>>,
+    mut network: ResMut<Network>,
+    mut received: EventReader<NetReceived>,
+) {
+    let now = time.elapsed_seconds();
+    for event in received.read() {
+        if let NetMessage::Hello = event.message {
+            if network.clients.insert(event.from, now).is_none() {
+                info!("client {} connected", event.from);
+                network.send(event.from, &NetMessage::Welcome);
+            }
+        } else if let Some(last_seen) = network.clients.get_mut(&event.from) {
+            *last_seen = now;
+        }
+    }
+    network.clients.retain(|client, last_seen| {
+        let alive = now - *last_seen < TIMEOUT;
+        if !alive {
+            info!("client {client} timed out");
+        }
+        alive
+    });
+}
+
+#[derive(Resource, Default)]
+struct SnapshotTimer(f32);
+
+fn send_snapshots(
+    time: Res<Time<Real>>,
+    mut timer: ResMut<SnapshotTimer>,
+    mut network: ResMut<Network>,
+    bodies: Query<(&SaveId, &Transform, Option<&Collider>)>,
+) {
+    timer.0 += time.delta_seconds();
+    if timer.0 < 1.0 / SNAPSHOT_RATE {
+        return;
+    }
+    timer.0 = 0.0;
+    if network.clients.is_empty() {
+        return;
+    }
+    network.tick += 1;
+    let states: Vec<BodyState> = bodies
+        .iter()
+        .map(|(id, transform, collider)| BodyState {
+            id: id.0,
+            translation: transform.translation,
+            rotation: transform.rotation,
+            shape: collider.and_then(ColliderShape::from_collider),
+        })
+        .collect();
+    // At least one part, so clients learn that every body is gone.
+    let parts = states.len().div_ceil(BODIES_PER_PART).max(1);
+    let Ok(part_count) = u16::try_from(parts) else {
+        warn!("too many bodies for a snapshot: {}", states.len());
+        return;
+    };
+    for part in 0..parts {
+        let bodies = states
+            .iter()
+            .skip(part * BODIES_PER_PART)
+            .take(BODIES_PER_PART)
+            .cloned()
+            .collect();
+        network.broadcast(&NetMessage::Snapshot {
+            tick: network.tick,
+            part: part as u16,
+            parts: part_count,
+            bodies,
+        });
+    }
+}
+
+fn send_hello(time: Res<Time<Real>>, mut network: ResMut<Network>) {
+    network.since_hello += time.delta_seconds();
+    if network.since_hello >= HELLO_INTERVAL {
+        network.since_hello = 0.0;
+        network.broadcast(&NetMessage::Hello);
+    }
+}
+
+/// Tracks whether the server is answering.
+fn watch_server(
+    time: Res<Time<Real>>,
+    mut network: ResMut<Network>,
+    mut received: EventReader<NetReceived>,
+) {
+    if received.read().count() > 0 {
+        if !network.connected {
+            info!("connected to the server");
+        }
+        network.connected = true;
+        network.since_server = 0.0;
+    } else {
+        network.since_server += time.delta_seconds();
+    }
+}
+
+/// Pose a client's body moves between, from the previous snapshot to the latest one.
+#[derive(Component)]
+pub struct Interpolated {
+    from: Transform,
+    to: Transform,
+    elapsed: f32,
+}
+
+/// Bodies on a client are moved by snapshots instead of the solver.
+fn make_bodies_kinematic(mut bodies: Query<&mut RigidBody, With<SaveId>>) {
+    for mut body in &mut bodies {
+        if *body != RigidBody::KinematicPositionBased {
+            *body = RigidBody::KinematicPositionBased;
+        }
+    }
+}
+
+/// Closes the connection, handing a client's bodies back to the solver.
+pub fn disconnect(
+    mut commands: Commands,
+    network: Option<Res<Network>>,
+    mut bodies: Query<(Entity, &mut RigidBody), With<Interpolated>>,
+) {
+    let Some(network) = network else {
+        return;
+    };
+    info!("closing the {:?} connection", network.role);
+    commands.remove_resource::<Network>();
+    for (entity, mut body) in &mut bodies {
+        *body = RigidBody::Dynamic;
+        commands.entity(entity).remove::<Interpolated>();
+    }
+}
+
+fn apply_snapshots(
+    mut commands: Commands,
+    mut network: ResMut<Network>,
+    mut received: EventReader<NetReceived>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    library: Option<Res<MaterialLibrary>>,
+    mut bodies: Query<(Entity, &SaveId, &Transform, Option<&mut Interpolated>)>,
+) {
+    // Parts are gathered for the newest snapshot only; datagrams can arrive out of order, and a
+    // snapshot missing a part is dropped for the next one.
+    for event in received.read() {
+        let NetMessage::Snapshot {
+            tick,
+            part,
+            parts,
+            bodies,
+        } = &event.message
+        else {
+            continue;
+        };
+        if *tick <= network.tick || *tick < network.snapshot_tick {
+            continue;
+        }
+        if *tick > network.snapshot_tick {
+            network.snapshot_tick = *tick;
+            network.snapshot_parts = vec![None; *parts as usize];
+        }
+        if let Some(slot) = network.snapshot_parts.get_mut(*part as usize) {
+            *slot = Some(bodies.clone());
+        }
+    }
+    if network.snapshot_parts.is_empty() || network.snapshot_parts.iter().any(Option::is_none) {
+        return;
+    }
+    network.tick = network.snapshot_tick;
+    let parts = std::mem::take(&mut network.snapshot_parts);
+
+    let mut states: HashMap<u32, &BodyState> = parts
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|state| (state.id, state))
+        .collect();
+    for (entity, id, transform, interpolated) in &mut bodies {
+        let Some(state) = states.remove(&id.0) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        let target = Transform {
+            translation: state.translation,
+            rotation: state.rotation,
+            ..*transform
+        };
+        match interpolated {
+            Some(mut interpolated) => {
+                interpolated.from = *transform;
+                interpolated.to = target;
+                interpolated.elapsed = 0.0;
+            }
+            None => {
+                commands.entity(entity).insert(Interpolated {
+                    from: target,
+                    to: target,
+                    elapsed: 0.0,
+                });
+            }
+        }
+    }
+
+    for state in states.into_values() {
+        let Some(shape) = state.shape else {
+            continue;
+        };
+        let transform =
+            Transform::from_translation(state.translation).with_rotation(state.rotation);
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(shape.mesh()),
+                material: match &library {
+                    Some(library) => library.prototype.clone(),
+                    None => materials.add(Color::SILVER.into()),
+                },
+                transform,
+                ..default()
+            },
+            RigidBody::KinematicPositionBased,
+            shape.collider(),
+            SaveId(state.id),
+            Interpolated {
+                from: transform,
+                to: transform,
+                elapsed: 0.0,
+            },
+        ));
+    }
+}
+
+fn interpolate_bodies(
+    time: Res<Time<Real>>,
+    mut bodies: Query<(&mut Transform, &mut Interpolated)>,
+) {
+    for (mut transform, mut interpolated) in &mut bodies {
+        interpolated.elapsed += time.delta_seconds();
+        let t = (interpolated.elapsed * SNAPSHOT_RATE).min(1.0);
+        transform.translation = interpolated
+            .from
+            .translation
+            .lerp(interpolated.to.translation, t);
+        transform.rotation = interpolated
+            .from
+            .rotation
+            .slerp(interpolated.to.rotation, t);
+    }
+}
@@ -0,0 +1,133 @@
+//! Gameplay entities placed in the level's glTF scene by node name, so they can be laid out in
+//! Blender along with the geometry.
+//!
+//! - `SPAWN_<prefab>` nodes are replaced by the [`Prefabs`](crate::level_data::Prefabs) entry of
+//!   that name, at the node's transform.
+//! - `TRIGGER_<name>` nodes become invisible sensors. Their meshes still get colliders from
+//!   [`crate::level`], and a [`TriggerEntered`] is sent whenever something enters one.
+//!
+//! Suffixes Blender adds to duplicate names, like `.001`, are ignored.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::level::Level;
+use crate::level_data::LevelSpawner;
+use crate::materials::MaterialLibrary;
+
+pub const SPAWN_PREFIX: &str = "SPAWN_";
+pub const TRIGGER_PREFIX: &str = "TRIGGER_";
+
+pub struct NodeTemplatesPlugin;
+
+impl Plugin for NodeTemplatesPlugin {
+    fn build(&self, app: &mut App) {
+        // In `PreUpdate`, so replaced nodes are gone before the level colliders are generated.
+        app.add_event::<TriggerEntered>()
+            .add_systems(
+                PreUpdate,
+                instantiate_node_templates.run_if(resource_exists::<MaterialLibrary>()),
+            )
+            .add_systems(Update, (make_trigger_sensors, detect_triggers).chain());
+    }
+}
+
+/// A trigger volume, named after its node without the prefix.
+#[derive(Component, Clone, Debug)]
+pub struct Trigger(pub String);
+
+/// Sent when `entity` starts touching the trigger called `trigger`.
+#[derive(Event, Clone, Debug)]
+pub struct TriggerEntered {
+    pub trigger: String,
+    pub entity: Entity,
+}
+
+/// `name` without `prefix` and any `.001` style suffix, if it starts with `prefix`.
+fn template_name<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
+    let name = name.strip_prefix(prefix)?;
+    Some(name.split_once('.').map_or(name, |(name, _)| name))
+}
+
+fn instantiate_node_templates(
+    mut commands: Commands,
+    mut spawner: LevelSpawner,
+    nodes: Query<(Entity, &Name), Added<Name>>,
+    transforms: Query<&Transform>,
+    parents: Query<&Parent>,
+    levels: Query<(), With<Level>>,
+) {
+    for (entity, name) in &nodes {
+        let in_level = parents
+            .iter_ancestors(entity)
+            .any(|ancestor| levels.contains(ancestor));
+        if !in_level {
+            continue;
+        }
+
+        if let Some(prefab) = template_name(name.as_str(), SPAWN_PREFIX) {
+            // The node's transform relative to the world, since the scene hasn't been propagated.
+            let transform = std::iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .filter_map(|entity| transforms.get(entity).ok())
+                .fold(Transform::IDENTITY, |child, parent| {
+                    parent.mul_transform(child)
+                });
+            match spawner.spawn_prefab(prefab, transform) {
+                Ok(_) => debug!("spawned prefab `{prefab}` for node `{name}`"),
+                Err(error) => warn!("node `{name}`: {error}"),
+            }
+            commands.entity(entity).despawn_recursive();
+        } else if let Some(trigger) = template_name(name.as_str(), TRIGGER_PREFIX) {
+            commands
+                .entity(entity)
+                .insert((Trigger(trigger.to_string()), Visibility::Hidden));
+        }
+    }
+}
+
+/// Turns the colliders generated for trigger meshes into sensors.
+fn make_trigger_sensors(
+    mut commands: Commands,
+    colliders: Query<Entity, (Added<Collider>, Without<Sensor>)>,
+    parents: Query<&Parent>,
+    triggers: Query<(), With<Trigger>>,
+) {
+    for entity in &colliders {
+        if std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .any(|entity| triggers.contains(entity))
+        {
+            commands
+                .entity(entity)
+                .insert((Sensor, ActiveEvents::COLLISION_EVENTS));
+        }
+    }
+}
+
+fn detect_triggers(
+    mut collisions: EventReader<CollisionEvent>,
+    mut entered: EventWriter<TriggerEntered>,
+    parents: Query<&Parent>,
+    triggers: Query<&Trigger>,
+) {
+    let trigger_of = |entity: Entity| {
+        std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .find_map(|entity| triggers.get(entity).ok())
+    };
+    for collision in collisions.read() {
+        let CollisionEvent::Started(a, b, _) = *collision else {
+            continue;
+        };
+        for (sensor, other) in [(a, b), (b, a)] {
+            if let Some(trigger) = trigger_of(sensor) {
+                debug!("{other:?} entered trigger `{}`", trigger.0);
+                entered.send(TriggerEntered {
+                    trigger: trigger.0.clone(),
+                    entity: other,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Particle bursts: sparks and dust where bodies hit hard, dust kicked up by characters (see
+//! [`crate::movement_dust`]), and the fireball of explosions.
+//!
+//! A [`ParticleBurst`] throws out a handful of small unlit cubes that fly, fall or drift, and
+//! shrink away over their [`Lifetime`]. They don't collide with anything, and they come from a
+//! [`Pool`], so even a busy scene adds no colliders and spawns few entities. Hard surfaces spark
+//! where dynamic bodies hit, from the same contact force events as [`crate::impact_sounds`]; soft
+//! ones raise dust.
+//!
+//! `burst <sparks|dust|explosion> [strength]` in the console sets one off at the player.
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::launch_options::LaunchOptions;
+use crate::lifetime::Lifetime;
+use crate::pool::{Pool, PoolAppExt, Pooled};
+use crate::surface::Surface;
+use crate::Player;
+
+/// Released particles kept for reuse.
+const POOL_CAPACITY: usize = 256;
+/// Bursts set off in one frame at most; the rest are dropped.
+const MAX_BURSTS_PER_FRAME: usize = 6;
+/// Contact impulses, in newton-seconds, for a burst of strength 1, and below which there is none.
+const FULL_IMPULSE: f32 = 40.0;
+const MIN_IMPULSE: f32 = 10.0;
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ParticleBurst>()
+            .add_pool::<Particle>(POOL_CAPACITY)
+            .init_resource::<ParticleAssets>()
+            .init_resource::<ParticleRng>()
+            .add_console_command(
+                "burst",
+                "burst <sparks|dust|explosion> [strength] - set off particles at the player",
+                burst_command,
+            )
+            .add_systems(
+                Update,
+                (burst_from_impacts, spawn_bursts, update_particles).chain(),
+            );
+    }
+}
+
+/// What a [`ParticleBurst`] throws out.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BurstKind {
+    Sparks,
+    Dust,
+    Explosion,
+}
+
+impl BurstKind {
+    pub const ALL: [BurstKind; 3] = [BurstKind::Sparks, BurstKind::Dust, BurstKind::Explosion];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BurstKind::Sparks => "sparks",
+            BurstKind::Dust => "dust",
+            BurstKind::Explosion => "explosion",
+        }
+    }
+
+    /// Particles in a burst of strength 1.
+    fn count(self) -> f32 {
+        match self {
+            BurstKind::Sparks => 12.0,
+            BurstKind::Dust => 8.0,
+            BurstKind::Explosion => 40.0,
+        }
+    }
+
+    /// Launch speed, in meters per second.
+    fn speed(self) -> f32 {
+        match self {
+            BurstKind::Sparks => 6.0,
+            BurstKind::Dust => 1.5,
+            BurstKind::Explosion => 8.0,
+        }
+    }
+
+    /// Seconds a particle lasts.
+    fn lifetime(self) -> f32 {
+        match self {
+            BurstKind::Sparks => 0.35,
+            BurstKind::Dust => 0.8,
+            BurstKind::Explosion => 0.9,
+        }
+    }
+
+    /// Width of a particle when it's launched, in meters.
+    fn size(self) -> f32 {
+        match self {
+            BurstKind::Sparks => 0.03,
+            BurstKind::Dust => 0.12,
+            BurstKind::Explosion => 0.18,
+        }
+    }
+
+    /// Pull downwards, in meters per second squared; dust rises slowly instead.
+    fn gravity(self) -> f32 {
+        match self {
+            BurstKind::Sparks => 9.81,
+            BurstKind::Dust => -0.3,
+            BurstKind::Explosion => 1.0,
+        }
+    }
+
+    /// How quickly particles slow down, as a share of their velocity per second.
+    fn drag(self) -> f32 {
+        match self {
+            BurstKind::Sparks => 1.0,
+            BurstKind::Dust => 3.0,
+            BurstKind::Explosion => 3.5,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            BurstKind::Sparks => Color::rgb(1.0, 0.8, 0.3),
+            BurstKind::Dust => Color::rgb(0.6, 0.55, 0.5),
+            BurstKind::Explosion => Color::rgb(1.0, 0.45, 0.1),
+        }
+    }
+}
+
+/// Sent to throw out particles at `position`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ParticleBurst {
+    pub kind: BurstKind,
+    pub position: Vec3,
+    /// The particles fly out around this direction, or every way when it's zero.
+    pub direction: Vec3,
+    /// Scales the number of particles and how fast they fly; 1 for a typical hit.
+    pub strength: f32,
+}
+
+/// A particle in flight.
+#[derive(Component, Default)]
+pub struct Particle {
+    velocity: Vec3,
+    size: f32,
+    gravity: f32,
+    drag: f32,
+}
+
+#[derive(Resource)]
+struct ParticleAssets {
+    mesh: Handle<Mesh>,
+    /// One per [`BurstKind`], in the order of [`BurstKind::ALL`].
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::Cube { size: 1.0 }));
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials = BurstKind::ALL
+            .map(|kind| {
+                materials.add(StandardMaterial {
+                    base_color: kind.color(),
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .to_vec();
+        Self { mesh, materials }
+    }
+}
+
+/// Random numbers for the particles, seeded from the [`LaunchOptions`].
+#[derive(Resource)]
+struct ParticleRng(u64);
+
+impl FromWorld for ParticleRng {
+    fn from_world(world: &mut World) -> Self {
+        let seed = world
+            .get_resource::<LaunchOptions>()
+            .map_or(0, |options| options.seed);
+        // Xorshift never leaves zero.
+        Self(seed | 1)
+    }
+}
+
+impl ParticleRng {
+    /// Between 0 and 1.
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A direction within the half of the sphere around `around`, or anywhere when it's zero.
+    fn direction(&mut self, around: Vec3) -> Vec3 {
+        let z = self.next() * 2.0 - 1.0;
+        let angle = self.next() * std::f32::consts::TAU;
+        let ring = (1.0 - z * z).sqrt();
+        let direction = Vec3::new(ring * angle.cos(), z, ring * angle.sin());
+        if direction.dot(around) < 0.0 {
+            -direction
+        } else {
+            direction
+        }
+    }
+}
+
+/// Sparks or dust where dynamic bodies hit hard, depending on the harder of their surfaces.
+fn burst_from_impacts(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut events: EventReader<ContactForceEvent>,
+    mut bursts: EventWriter<ParticleBurst>,
+    surfaces: Query<&Surface>,
+) {
+    for event in events.read() {
+        // Contact forces are reported per physics step.
+        let impulse = event.total_force_magnitude * time.delta_seconds();
+        if impulse < MIN_IMPULSE {
+            continue;
+        }
+        let Some(point) = rapier_context
+            .contact_pair(event.collider1, event.collider2)
+            .and_then(|pair| {
+                pair.manifolds().find_map(|manifold| {
+                    manifold
+                        .solver_contacts()
+                        .next()
+                        .map(|contact| contact.point())
+                })
+            })
+        else {
+            continue;
+        };
+        let surface = |entity| surfaces.get(entity).copied().unwrap_or_default();
+        let (a, b) = (surface(event.collider1), surface(event.collider2));
+        let kind = if a.hardness().max(b.hardness()) >= Surface::Concrete.hardness() {
+            BurstKind::Sparks
+        } else {
+            BurstKind::Dust
+        };
+        bursts.send(ParticleBurst {
+            kind,
+            position: point,
+            direction: Vec3::ZERO,
+            strength: (impulse / FULL_IMPULSE).min(2.0),
+        });
+    }
+}
+
+fn spawn_bursts(
+    mut commands: Commands,
+    assets: Res<ParticleAssets>,
+    mut rng: ResMut<ParticleRng>,
+    mut pool: ResMut<Pool<Particle>>,
+    mut bursts: EventReader<ParticleBurst>,
+) {
+    for burst in bursts.read().take(MAX_BURSTS_PER_FRAME) {
+        let kind = burst.kind;
+        let material = &assets.materials[kind as usize];
+        let count = (kind.count() * burst.strength).round().max(1.0) as usize;
+        for _ in 0..count {
+            let speed = kind.speed() * burst.strength.sqrt() * (0.5 + rng.next());
+            let velocity = rng.direction(burst.direction) * speed;
+            let lifetime = kind.lifetime() * (0.7 + 0.6 * rng.next());
+            pool.acquire(
+                &mut commands,
+                (
+                    PbrBundle {
+                        mesh: assets.mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(burst.position)
+                            .with_scale(Vec3::splat(kind.size())),
+                        ..default()
+                    },
+                    NotShadowCaster,
+                    Lifetime::from_seconds(lifetime),
+                    Particle {
+                        velocity,
+                        size: kind.size(),
+                        gravity: kind.gravity(),
+                        drag: kind.drag(),
+                    },
+                    Name::new("Particle"),
+                ),
+            );
+        }
+    }
+    // Dropped, so a pile-up doesn't carry over.
+    bursts.clear();
+}
+
+fn update_particles(
+    time: Res<Time>,
+    mut particles: Query<(&mut Particle, &Lifetime, &mut Transform), Without<Pooled>>,
+) {
+    let dt = time.delta_seconds();
+    for (mut particle, lifetime, mut transform) in &mut particles {
+        let drag = (-particle.drag * dt).exp();
+        particle.velocity = particle.velocity * drag - Vec3::Y * particle.gravity * dt;
+        transform.translation += particle.velocity * dt;
+        transform.scale = Vec3::splat(particle.size * lifetime.left());
+    }
+}
+
+fn burst_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = args
+        .first()
+        .ok_or("usage: burst <sparks|dust|explosion> [strength]")?;
+    let kind = BurstKind::ALL
+        .into_iter()
+        .find(|kind| kind.name() == *name)
+        .ok_or_else(|| format!("unknown burst {name}"))?;
+    let strength = if args.len() > 1 {
+        parse_arg::<f32>(args, 1, "strength")?
+    } else {
+        1.0
+    };
+    let mut player = world.query_filtered::<&Transform, With<Player>>();
+    let position = player
+        .get_single(world)
+        .map_err(|_| "no player to set it off at")?
+        .translation;
+    world.send_event(ParticleBurst {
+        kind,
+        position,
+        direction: Vec3::ZERO,
+        strength,
+    });
+    Ok(format!("{name} of strength {strength}"))
+}
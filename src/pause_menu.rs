@@ -0,0 +1,206 @@
+//! Pausing the game and the pause menu overlay.
+//!
+//! Escape (or a gamepad's start button) toggles [`PauseState`] while playing. The simulation is
+//! frozen while not running, and a Resume / Settings / Multiplayer / Quit menu is shown that can be
+//! navigated with the keyboard, a gamepad or the mouse.
+
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::CameraMode;
+use crate::conditions::{in_play_state, text_input_closed};
+use crate::menu::{self, MenuInput, BUTTON_COLOR, FOCUSED_BUTTON_COLOR};
+use crate::quit_confirm::{quit_dialog_closed, RequestQuit};
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .add_event::<OpenSettings>()
+            .init_resource::<PauseFocus>()
+            .add_systems(OnEnter(PauseState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(PauseState::Paused), despawn_pause_menu)
+            .add_systems(OnExit(PauseState::Running), pause_simulation)
+            .add_systems(OnEnter(PauseState::Running), resume_simulation)
+            .add_systems(
+                Update,
+                (
+                    toggle_pause
+                        .run_if(in_play_state())
+                        .run_if(text_input_closed()),
+                    (navigate_pause_menu, highlight_pause_focus)
+                        .chain()
+                        .run_if(in_state(PauseState::Paused)),
+                )
+                    .chain()
+                    .run_if(quit_dialog_closed),
+            );
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    /// The settings screen, opened from the pause menu.
+    Settings,
+}
+
+/// Sent by the pause menu's Settings button.
+#[derive(Event)]
+pub struct OpenSettings;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum PauseButton {
+    Resume,
+    Settings,
+    Multiplayer,
+    Quit,
+}
+
+impl PauseButton {
+    const ALL: [PauseButton; 4] = [
+        PauseButton::Resume,
+        PauseButton::Settings,
+        PauseButton::Multiplayer,
+        PauseButton::Quit,
+    ];
+
+    /// Localization key of the button's label.
+    fn label(self) -> &'static str {
+        match self {
+            PauseButton::Resume => "pause.resume",
+            PauseButton::Settings => "pause.settings",
+            PauseButton::Multiplayer => "pause.multiplayer",
+            PauseButton::Quit => "pause.quit",
+        }
+    }
+}
+
+/// Index into [`PauseButton::ALL`] of the focused button.
+#[derive(Resource, Default)]
+struct PauseFocus(usize);
+
+#[derive(Component)]
+struct PauseMenu;
+
+fn toggle_pause(
+    menu_input: MenuInput,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if menu_input.pause() {
+        next_state.set(match state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+            PauseState::Settings => PauseState::Paused,
+        });
+    }
+}
+
+fn pause_simulation(
+    mut time: ResMut<Time<Virtual>>,
+    mut rapier: ResMut<RapierConfiguration>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    time.pause();
+    rapier.physics_pipeline_active = false;
+    // Free the cursor captured by the first-person camera so the menu can be clicked.
+    for mut window in &mut windows {
+        window.cursor.visible = true;
+        window.cursor.grab_mode = CursorGrabMode::None;
+    }
+}
+
+fn resume_simulation(
+    mut time: ResMut<Time<Virtual>>,
+    mut rapier: ResMut<RapierConfiguration>,
+    camera_mode: Res<CameraMode>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    time.unpause();
+    rapier.physics_pipeline_active = true;
+    if *camera_mode == CameraMode::FirstPerson {
+        for mut window in &mut windows {
+            window.cursor.visible = false;
+            window.cursor.grab_mode = CursorGrabMode::Locked;
+        }
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands, theme: Res<TextTheme>, mut focus: ResMut<PauseFocus>) {
+    focus.0 = 0;
+    commands
+        .spawn((menu::overlay(), PauseMenu))
+        .with_children(|parent| {
+            parent.spawn(menu::localized_label("pause.title", theme.title()));
+            for button in PauseButton::ALL {
+                parent
+                    .spawn((menu::button(), button))
+                    .with_children(|parent| {
+                        parent.spawn(menu::localized_label(button.label(), theme.heading()));
+                    });
+            }
+        });
+}
+
+fn despawn_pause_menu(mut commands: Commands, menus: Query<Entity, With<PauseMenu>>) {
+    for entity in &menus {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_pause_menu(
+    menu_input: MenuInput,
+    mut focus: ResMut<PauseFocus>,
+    buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut next_game_state: ResMut<NextState<MyStates>>,
+    mut settings: EventWriter<OpenSettings>,
+    mut quit: EventWriter<RequestQuit>,
+) {
+    menu_input.navigate(&mut focus.0, PauseButton::ALL.len());
+    let mut activated = menu_input.activate().then_some(PauseButton::ALL[focus.0]);
+
+    for (interaction, button) in &buttons {
+        match interaction {
+            Interaction::Pressed => activated = Some(*button),
+            Interaction::Hovered => {
+                focus.0 = PauseButton::ALL
+                    .iter()
+                    .position(|candidate| candidate == button)
+                    .unwrap_or(focus.0);
+            }
+            Interaction::None => {}
+        }
+    }
+
+    match activated {
+        Some(PauseButton::Resume) => next_state.set(PauseState::Running),
+        Some(PauseButton::Settings) => settings.send(OpenSettings),
+        Some(PauseButton::Multiplayer) => {
+            next_state.set(PauseState::Running);
+            next_game_state.set(MyStates::Lobby);
+        }
+        Some(PauseButton::Quit) => quit.send(RequestQuit),
+        None => {}
+    }
+}
+
+fn highlight_pause_focus(
+    focus: Res<PauseFocus>,
+    mut buttons: Query<(&PauseButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in &mut buttons {
+        color.0 = if *button == PauseButton::ALL[focus.0] {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
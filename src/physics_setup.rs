@@ -0,0 +1,454 @@
+//! Rapier setup: the physics and debug render plugins, the [`PhysicsTuning`] of the solver and
+//! new bodies, the [`ControllerParameter`]s of character controllers, the F4 toggle of the
+//! collider wireframes, and, with the `diagnostics` feature, [`PhysicsDiagnosticsPlugin`].
+
+use std::f32::consts::FRAC_PI_2;
+
+#[cfg(feature = "diagnostics")]
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+#[cfg(feature = "diagnostics")]
+use bevy::utils::Instant;
+use bevy_rapier3d::prelude::*;
+
+#[cfg(feature = "debug_render")]
+use crate::settings::Settings;
+#[cfg(feature = "debug_render")]
+use crate::MyStates;
+
+/// Adds Rapier and, unless `debug_render` is off or the crate is built without the `debug_render`
+/// feature, its debug renderer, whose visibility follows [`DebugRenderSetting`].
+pub struct PhysicsSetupPlugin {
+    pub debug_render: bool,
+}
+
+impl Default for PhysicsSetupPlugin {
+    fn default() -> Self {
+        Self { debug_render: true }
+    }
+}
+
+impl Plugin for PhysicsSetupPlugin {
+    fn build(&self, app: &mut App) {
+        // The setting is kept without the renderer, since the settings write it.
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+            .init_resource::<DebugRenderSetting>()
+            .init_resource::<PhysicsTuning>()
+            .add_systems(
+                Update,
+                (
+                    apply_physics_tuning.run_if(resource_changed::<PhysicsTuning>()),
+                    tune_new_bodies,
+                ),
+            );
+        #[cfg(feature = "debug_render")]
+        if self.debug_render {
+            add_debug_render(app);
+        }
+    }
+}
+
+#[cfg(feature = "debug_render")]
+fn add_debug_render(app: &mut App) {
+    app.add_plugins(RapierDebugRenderPlugin::default())
+        .add_systems(OnEnter(MyStates::Next), apply_debug_render)
+        .add_systems(
+            Update,
+            (
+                toggle_debug_render,
+                apply_debug_render.run_if(resource_changed::<DebugRenderSetting>()),
+            )
+                .chain(),
+        );
+}
+
+/// Whether the Rapier debug renderer draws collider wireframes, mirrored from [`Settings`] and
+/// toggled with F4.
+///
+/// Kept apart from [`DebugRenderContext`] so the choice is re-applied on state transitions.
+#[derive(Resource)]
+pub struct DebugRenderSetting {
+    pub enabled: bool,
+}
+
+impl Default for DebugRenderSetting {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Solver settings and the damping and sleep thresholds given to dynamic bodies that don't set
+/// their own. Adjusted from the debug panel; see [`TuningParameter`].
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct PhysicsTuning {
+    /// Linear velocity below which a body may fall asleep, in meters per second.
+    pub linear_sleep_threshold: f32,
+    /// Angular velocity below which a body may fall asleep, in radians per second.
+    pub angular_sleep_threshold: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// Velocity solver iterations per step.
+    pub solver_iterations: usize,
+    /// Substeps per physics step.
+    pub substeps: usize,
+}
+
+impl Default for PhysicsTuning {
+    fn default() -> Self {
+        Self {
+            linear_sleep_threshold: 0.4,
+            angular_sleep_threshold: 0.5,
+            linear_damping: 0.0,
+            angular_damping: 0.05,
+            solver_iterations: 4,
+            substeps: 1,
+        }
+    }
+}
+
+/// A field of [`PhysicsTuning`], for editing it one value at a time.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TuningParameter {
+    LinearSleepThreshold,
+    AngularSleepThreshold,
+    LinearDamping,
+    AngularDamping,
+    SolverIterations,
+    Substeps,
+}
+
+impl TuningParameter {
+    pub const ALL: [TuningParameter; 6] = [
+        TuningParameter::LinearSleepThreshold,
+        TuningParameter::AngularSleepThreshold,
+        TuningParameter::LinearDamping,
+        TuningParameter::AngularDamping,
+        TuningParameter::SolverIterations,
+        TuningParameter::Substeps,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TuningParameter::LinearSleepThreshold => "linear sleep threshold",
+            TuningParameter::AngularSleepThreshold => "angular sleep threshold",
+            TuningParameter::LinearDamping => "linear damping",
+            TuningParameter::AngularDamping => "angular damping",
+            TuningParameter::SolverIterations => "solver iterations",
+            TuningParameter::Substeps => "substeps",
+        }
+    }
+
+    pub fn value(self, tuning: &PhysicsTuning) -> String {
+        match self {
+            TuningParameter::LinearSleepThreshold => {
+                format!("{:.2}", tuning.linear_sleep_threshold)
+            }
+            TuningParameter::AngularSleepThreshold => {
+                format!("{:.2}", tuning.angular_sleep_threshold)
+            }
+            TuningParameter::LinearDamping => format!("{:.2}", tuning.linear_damping),
+            TuningParameter::AngularDamping => format!("{:.2}", tuning.angular_damping),
+            TuningParameter::SolverIterations => tuning.solver_iterations.to_string(),
+            TuningParameter::Substeps => tuning.substeps.to_string(),
+        }
+    }
+
+    /// Moves the value `steps` increments up or down, keeping it in a sensible range.
+    pub fn adjust(self, tuning: &mut PhysicsTuning, steps: i32) {
+        let step = |value: &mut f32, increment: f32, max: f32| {
+            *value = (*value + increment * steps as f32).clamp(0.0, max);
+        };
+        let count = |value: &mut usize, max: i32| {
+            *value = (*value as i32 + steps).clamp(1, max) as usize;
+        };
+        match self {
+            TuningParameter::LinearSleepThreshold => {
+                step(&mut tuning.linear_sleep_threshold, 0.05, 5.0)
+            }
+            TuningParameter::AngularSleepThreshold => {
+                step(&mut tuning.angular_sleep_threshold, 0.05, 5.0)
+            }
+            TuningParameter::LinearDamping => step(&mut tuning.linear_damping, 0.05, 10.0),
+            TuningParameter::AngularDamping => step(&mut tuning.angular_damping, 0.05, 10.0),
+            TuningParameter::SolverIterations => count(&mut tuning.solver_iterations, 32),
+            TuningParameter::Substeps => count(&mut tuning.substeps, 16),
+        }
+    }
+}
+
+/// A setting of a [`KinematicCharacterController`], for editing a live character one value at a
+/// time. Lengths keep whether they are absolute or relative to the character's size.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControllerParameter {
+    Offset,
+    /// Off at zero.
+    SnapToGround,
+    /// Off at zero.
+    AutostepHeight,
+    AutostepWidth,
+    Slide,
+    MaxSlopeClimbAngle,
+    MinSlopeSlideAngle,
+}
+
+impl ControllerParameter {
+    pub const ALL: [ControllerParameter; 7] = [
+        ControllerParameter::Offset,
+        ControllerParameter::SnapToGround,
+        ControllerParameter::AutostepHeight,
+        ControllerParameter::AutostepWidth,
+        ControllerParameter::Slide,
+        ControllerParameter::MaxSlopeClimbAngle,
+        ControllerParameter::MinSlopeSlideAngle,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ControllerParameter::Offset => "controller offset",
+            ControllerParameter::SnapToGround => "snap to ground",
+            ControllerParameter::AutostepHeight => "autostep height",
+            ControllerParameter::AutostepWidth => "autostep width",
+            ControllerParameter::Slide => "slide",
+            ControllerParameter::MaxSlopeClimbAngle => "max slope climb angle",
+            ControllerParameter::MinSlopeSlideAngle => "min slope slide angle",
+        }
+    }
+
+    pub fn value(self, controller: &KinematicCharacterController) -> String {
+        let length = |length: Option<CharacterLength>| match length {
+            Some(CharacterLength::Absolute(value)) => format!("{value:.2} m"),
+            Some(CharacterLength::Relative(value)) => format!("{value:.2} x size"),
+            None => "off".to_string(),
+        };
+        let autostep = controller.autostep;
+        match self {
+            ControllerParameter::Offset => length(Some(controller.offset)),
+            ControllerParameter::SnapToGround => length(controller.snap_to_ground),
+            ControllerParameter::AutostepHeight => length(autostep.map(|step| step.max_height)),
+            ControllerParameter::AutostepWidth => length(autostep.map(|step| step.min_width)),
+            ControllerParameter::Slide => controller.slide.to_string(),
+            ControllerParameter::MaxSlopeClimbAngle => {
+                format!("{:.0} deg", controller.max_slope_climb_angle.to_degrees())
+            }
+            ControllerParameter::MinSlopeSlideAngle => {
+                format!("{:.0} deg", controller.min_slope_slide_angle.to_degrees())
+            }
+        }
+    }
+
+    /// Moves the value `steps` increments up or down, keeping it in a sensible range. Toggles
+    /// `slide` on any step.
+    pub fn adjust(self, controller: &mut KinematicCharacterController, steps: i32) {
+        let step = |length: CharacterLength, increment: f32, max: f32| {
+            let value = |value: f32| (value + increment * steps as f32).clamp(0.0, max);
+            match length {
+                CharacterLength::Absolute(length) => CharacterLength::Absolute(value(length)),
+                CharacterLength::Relative(length) => CharacterLength::Relative(value(length)),
+            }
+        };
+        // Optional lengths start from zero when off, and turn off again at zero.
+        let optional = |length: Option<CharacterLength>, increment: f32, max: f32| {
+            let length = step(
+                length.unwrap_or(CharacterLength::Absolute(0.0)),
+                increment,
+                max,
+            );
+            match length {
+                CharacterLength::Absolute(value) | CharacterLength::Relative(value)
+                    if value <= 0.0 =>
+                {
+                    None
+                }
+                length => Some(length),
+            }
+        };
+        let angle = |angle: &mut f32| {
+            *angle = (*angle + (5.0 * steps as f32).to_radians()).clamp(0.0, FRAC_PI_2);
+        };
+        match self {
+            ControllerParameter::Offset => {
+                controller.offset = step(controller.offset, 0.01, 0.5);
+            }
+            ControllerParameter::SnapToGround => {
+                controller.snap_to_ground = optional(controller.snap_to_ground, 0.05, 1.0);
+            }
+            ControllerParameter::AutostepHeight => {
+                let autostep = controller.autostep.unwrap_or_default();
+                controller.autostep = optional(
+                    controller.autostep.map(|autostep| autostep.max_height),
+                    0.05,
+                    1.0,
+                )
+                .map(|max_height| CharacterAutostep {
+                    max_height,
+                    ..autostep
+                });
+            }
+            ControllerParameter::AutostepWidth => {
+                if let Some(autostep) = &mut controller.autostep {
+                    autostep.min_width = step(autostep.min_width, 0.05, 1.0);
+                }
+            }
+            ControllerParameter::Slide => controller.slide = !controller.slide,
+            ControllerParameter::MaxSlopeClimbAngle => {
+                angle(&mut controller.max_slope_climb_angle);
+            }
+            ControllerParameter::MinSlopeSlideAngle => {
+                angle(&mut controller.min_slope_slide_angle);
+            }
+        }
+    }
+}
+
+/// Marks a body whose damping and sleep thresholds come from [`PhysicsTuning`], so they follow
+/// its changes.
+#[derive(Component)]
+struct Tuned;
+
+impl PhysicsTuning {
+    fn damping(&self) -> Damping {
+        Damping {
+            linear_damping: self.linear_damping,
+            angular_damping: self.angular_damping,
+        }
+    }
+
+    fn sleeping(&self) -> Sleeping {
+        Sleeping {
+            linear_threshold: self.linear_sleep_threshold,
+            angular_threshold: self.angular_sleep_threshold,
+            sleeping: false,
+        }
+    }
+}
+
+fn apply_physics_tuning(
+    tuning: Res<PhysicsTuning>,
+    mut rapier: ResMut<RapierConfiguration>,
+    mut rapier_context: ResMut<RapierContext>,
+    mut bodies: Query<(&mut Damping, &mut Sleeping), With<Tuned>>,
+) {
+    rapier_context
+        .integration_parameters
+        .max_velocity_iterations = tuning.solver_iterations;
+    match &mut rapier.timestep_mode {
+        TimestepMode::Fixed { substeps, .. }
+        | TimestepMode::Variable { substeps, .. }
+        | TimestepMode::Interpolated { substeps, .. } => *substeps = tuning.substeps,
+    }
+    for (mut damping, mut sleeping) in &mut bodies {
+        *damping = tuning.damping();
+        sleeping.linear_threshold = tuning.linear_sleep_threshold;
+        sleeping.angular_threshold = tuning.angular_sleep_threshold;
+    }
+}
+
+/// Gives new dynamic bodies the tuned damping and sleep thresholds, unless they set either.
+fn tune_new_bodies(
+    mut commands: Commands,
+    tuning: Res<PhysicsTuning>,
+    bodies: Query<(Entity, &RigidBody, Has<Damping>, Has<Sleeping>), Added<RigidBody>>,
+) {
+    for (entity, body, has_damping, has_sleeping) in &bodies {
+        if *body == RigidBody::Dynamic && !has_damping && !has_sleeping {
+            commands
+                .entity(entity)
+                .insert((tuning.damping(), tuning.sleeping(), Tuned));
+        }
+    }
+}
+
+#[cfg(feature = "debug_render")]
+fn toggle_debug_render(input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::F4) {
+        settings.graphics.debug_render = !settings.graphics.debug_render;
+    }
+}
+
+#[cfg(feature = "debug_render")]
+fn apply_debug_render(
+    setting: Res<DebugRenderSetting>,
+    mut debug_render: ResMut<DebugRenderContext>,
+) {
+    debug_render.enabled = setting.enabled;
+}
+
+/// Adds Rapier diagnostics (body counts, contact pairs and step duration) to the
+/// [`DiagnosticsStore`], next to the ones from [`FrameTimeDiagnosticsPlugin`].
+#[cfg(feature = "diagnostics")]
+pub struct PhysicsDiagnosticsPlugin;
+
+#[cfg(feature = "diagnostics")]
+impl Plugin for PhysicsDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::RIGID_BODY_COUNT, "rigid_bodies", 20))
+            .register_diagnostic(Diagnostic::new(
+                Self::ACTIVE_BODY_COUNT,
+                "active_bodies",
+                20,
+            ))
+            .register_diagnostic(Diagnostic::new(
+                Self::CONTACT_PAIR_COUNT,
+                "contact_pairs",
+                20,
+            ))
+            .register_diagnostic(
+                Diagnostic::new(Self::STEP_TIME, "physics_step_time", 20).with_suffix("ms"),
+            )
+            .init_resource::<PhysicsStepStart>()
+            .add_systems(
+                PostUpdate,
+                (
+                    Self::start_step_timer
+                        .after(PhysicsSet::SyncBackend)
+                        .before(PhysicsSet::StepSimulation),
+                    Self::diagnostic_system.after(PhysicsSet::StepSimulation),
+                ),
+            );
+    }
+}
+
+/// When the current Rapier step started, used to measure [`PhysicsDiagnosticsPlugin::STEP_TIME`].
+#[cfg(feature = "diagnostics")]
+#[derive(Resource, Default)]
+struct PhysicsStepStart(Option<Instant>);
+
+#[cfg(feature = "diagnostics")]
+impl PhysicsDiagnosticsPlugin {
+    pub const RIGID_BODY_COUNT: DiagnosticId =
+        DiagnosticId::from_u128(141318208237401625316377407627938640712);
+    pub const ACTIVE_BODY_COUNT: DiagnosticId =
+        DiagnosticId::from_u128(208932165497412081716522960834156236290);
+    pub const CONTACT_PAIR_COUNT: DiagnosticId =
+        DiagnosticId::from_u128(35210917862373014496850293765105187214);
+    pub const STEP_TIME: DiagnosticId =
+        DiagnosticId::from_u128(297713467590121946337036482919474308531);
+
+    fn start_step_timer(mut step_start: ResMut<PhysicsStepStart>) {
+        step_start.0 = Some(Instant::now());
+    }
+
+    fn diagnostic_system(
+        mut diagnostics: Diagnostics,
+        mut step_start: ResMut<PhysicsStepStart>,
+        rapier_context: Res<RapierContext>,
+    ) {
+        if let Some(start) = step_start.0.take() {
+            diagnostics.add_measurement(Self::STEP_TIME, || start.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        diagnostics.add_measurement(Self::RIGID_BODY_COUNT, || {
+            rapier_context.bodies.len() as f64
+        });
+        diagnostics.add_measurement(Self::ACTIVE_BODY_COUNT, || {
+            (rapier_context.islands.active_dynamic_bodies().len()
+                + rapier_context.islands.active_kinematic_bodies().len()) as f64
+        });
+        diagnostics.add_measurement(Self::CONTACT_PAIR_COUNT, || {
+            rapier_context
+                .contact_pairs()
+                .filter(|pair| pair.has_any_active_contacts())
+                .count() as f64
+        });
+    }
+}
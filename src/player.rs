@@ -0,0 +1,226 @@
+//! The player character: spawned with the sandbox and moved by the [`CharacterInput`] read each
+//! frame while playing.
+//!
+//! Characters are capsules sized by their [`CharacterDimensions`], and the player is drawn with the
+//! animated [`CharacterModel`]. Changing the dimensions resizes the collider, and the mesh of
+//! characters drawn as capsules, with the feet kept in place, and moves the first-person camera to
+//! the new eye height. Holding C crouches to the crouching height, and the character stands back up
+//! once there is room above it.
+
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::Models;
+use crate::character_animation::{character_scene, CharacterModel};
+use crate::character_input::CharacterInput;
+use crate::conditions::{in_gameplay, player_alive, text_input_closed};
+use crate::game_set::GameSet;
+use crate::health::Health;
+use crate::settings::Settings;
+use crate::surface::Surface;
+use crate::MyStates;
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CharacterInput>()
+            // The player is kept when coming back from the lobby.
+            .add_systems(
+                OnEnter(MyStates::Next),
+                // Again after a game over removed the dead player.
+                spawn_player.run_if(not(any_with_component::<Player>())),
+            )
+            .add_systems(
+                Update,
+                (
+                    read_character_input.in_set(GameSet::Input),
+                    movement.in_set(GameSet::Movement),
+                )
+                    .run_if(in_gameplay())
+                    .run_if(text_input_closed())
+                    .run_if(player_alive),
+            )
+            .add_systems(Update, resize_characters.after(GameSet::Movement));
+    }
+}
+
+/// Distance from the top of a character to its eyes, in meters.
+const EYE_BELOW_TOP: f32 = 0.2;
+/// Share of a character's radius checked for room when it stands up, so touching a wall doesn't
+/// keep it crouched.
+const HEADROOM_RADIUS: f32 = 0.9;
+
+/// Marks the character driven by [`movement`].
+#[derive(Component)]
+pub struct Player;
+
+/// The size of a character's capsule, standing and crouching, in meters.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct CharacterDimensions {
+    pub radius: f32,
+    /// From the bottom of the capsule to its top.
+    pub height: f32,
+    pub crouch_height: f32,
+    /// Whether the character wants to crouch. It stays crouched while there is no room to stand.
+    pub crouching: bool,
+    /// Height of the collider right now.
+    current_height: f32,
+}
+
+impl Default for CharacterDimensions {
+    fn default() -> Self {
+        Self::new(0.45, 1.8, 1.1)
+    }
+}
+
+impl CharacterDimensions {
+    pub fn new(radius: f32, height: f32, crouch_height: f32) -> Self {
+        Self {
+            radius,
+            height,
+            crouch_height,
+            crouching: false,
+            current_height: height,
+        }
+    }
+
+    /// The height the character is resized to: crouching or standing.
+    pub fn target_height(&self) -> f32 {
+        if self.crouching {
+            self.crouch_height
+        } else {
+            self.height
+        }
+    }
+
+    /// The height of the collider right now, which lags behind [`Self::target_height`] while
+    /// there is no room to stand.
+    pub fn current_height(&self) -> f32 {
+        self.current_height
+    }
+
+    /// Height of the eyes above the character's center.
+    pub fn eye_height(&self) -> f32 {
+        self.current_height / 2.0 - EYE_BELOW_TOP
+    }
+
+    /// A capsule `height` tall, centered on the character.
+    pub fn collider(&self, height: f32) -> Collider {
+        Collider::capsule_y(self.half_segment(height), self.radius)
+    }
+
+    pub fn mesh(&self, height: f32) -> Mesh {
+        shape::Capsule {
+            radius: self.radius,
+            depth: self.half_segment(height) * 2.0,
+            ..default()
+        }
+        .into()
+    }
+
+    fn half_segment(&self, height: f32) -> f32 {
+        (height / 2.0 - self.radius).max(0.0)
+    }
+}
+
+fn spawn_player(
+    mut commands: Commands,
+    models: Res<Models>,
+    gltfs: Res<Assets<Gltf>>,
+    settings: Res<Settings>,
+) {
+    let dimensions = CharacterDimensions::default();
+    let mut player = commands.spawn(SpatialBundle::from_transform(Transform::from_xyz(
+        1.5, 2.0, 1.0,
+    )));
+    if let Some(scene) = character_scene(&models, &gltfs) {
+        player.with_children(|parent| {
+            parent.spawn(CharacterModel::bundle(scene));
+        });
+    }
+    player
+        .insert(dimensions.collider(dimensions.height))
+        .insert(dimensions)
+        .insert(KinematicCharacterController {
+            offset: CharacterLength::Absolute(settings.physics.character_offset),
+            // Walk through sensors, such as melee hitboxes, rather than into them.
+            filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            ..default()
+        })
+        .insert(ColliderMassProperties::Density(199.0))
+        .insert(Health::new(100.0))
+        .insert(Surface::Wood)
+        .insert(Player);
+}
+
+fn read_character_input(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut input: ResMut<CharacterInput>,
+) {
+    *input = CharacterInput {
+        sequence: input.sequence.wrapping_add(1),
+        ..CharacterInput::read(&keys, time.delta_seconds())
+    };
+}
+
+fn movement(
+    input: Res<CharacterInput>,
+    mut query: Query<(&mut KinematicCharacterController, &mut CharacterDimensions), With<Player>>,
+) {
+    let (mut player, mut dimensions) = query.single_mut();
+    player.translation = Some(input.translation());
+    if dimensions.crouching != input.crouch {
+        dimensions.crouching = input.crouch;
+    }
+}
+
+/// Resizes characters whose [`CharacterDimensions`] changed or who are waiting for room to stand
+/// up, keeping their feet in place.
+fn resize_characters(
+    mut meshes: ResMut<Assets<Mesh>>,
+    rapier_context: Res<RapierContext>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut CharacterDimensions,
+            &mut Transform,
+            &mut Collider,
+            Option<&mut Handle<Mesh>>,
+        ),
+        With<KinematicCharacterController>,
+    >,
+) {
+    for (entity, mut dimensions, mut transform, mut collider, mesh) in &mut characters {
+        let (from, to) = (dimensions.current_height, dimensions.target_height());
+        if from == to && !dimensions.is_changed() {
+            continue;
+        }
+        let center = transform.translation + Vec3::Y * (to - from) / 2.0;
+        if to > from {
+            let headroom = Collider::capsule_y(
+                dimensions.half_segment(to),
+                dimensions.radius * HEADROOM_RADIUS,
+            );
+            let blocked = rapier_context.intersection_with_shape(
+                center,
+                transform.rotation,
+                &headroom,
+                QueryFilter::default()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            );
+            if blocked.is_some() {
+                continue;
+            }
+        }
+        transform.translation = center;
+        *collider = dimensions.collider(to);
+        if let Some(mut mesh) = mesh {
+            *mesh = meshes.add(dimensions.mesh(to));
+        }
+        dimensions.bypass_change_detection().current_height = to;
+    }
+}
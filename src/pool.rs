@@ -0,0 +1,105 @@
+//! Entity pools for things spawned and despawned all the time, like projectiles.
+//!
+//! A [`Pool<T>`] holds released entities marked with the component `T`. Releasing an entity hides
+//! it and disables its Rapier body and collider instead of despawning it, and acquiring one
+//! re-enables a released entity before spawning a new one, so the physics world doesn't rebuild
+//! colliders for every shot. Pools are added with [`PoolAppExt::add_pool`], and release their
+//! entities whose [`Lifetime`] is over rather than letting them be despawned.
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::lifetime::Lifetime;
+
+/// Released entities of kind `T`, ready for reuse.
+#[derive(Resource)]
+pub struct Pool<T: Component> {
+    free: Vec<Entity>,
+    /// Released entities kept at most; more are despawned.
+    capacity: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Marks a pooled entity that is released and waiting for reuse. Gameplay systems exclude these
+/// with `Without<Pooled>`.
+#[derive(Component)]
+pub struct Pooled;
+
+impl<T: Component> Pool<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            free: Vec::with_capacity(capacity),
+            capacity,
+            marker: PhantomData,
+        }
+    }
+
+    /// A released entity with `bundle` inserted, or a new one spawned with it. The bundle should
+    /// set everything a previous use may have changed and must include `T`.
+    pub fn acquire(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        let Some(entity) = self.free.pop() else {
+            return commands.spawn(bundle).id();
+        };
+        commands
+            .entity(entity)
+            .remove::<(Pooled, ColliderDisabled, RigidBodyDisabled)>()
+            .insert(bundle)
+            .insert(Visibility::Inherited);
+        entity
+    }
+
+    /// Hides `entity` and stops its physics until it is acquired again.
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if self.free.len() >= self.capacity {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+        commands.entity(entity).insert((
+            Pooled,
+            Visibility::Hidden,
+            ColliderDisabled,
+            RigidBodyDisabled,
+        ));
+        self.free.push(entity);
+    }
+
+    /// Number of released entities waiting for reuse.
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+}
+
+pub trait PoolAppExt {
+    /// Adds a [`Pool<T>`] keeping up to `capacity` released entities.
+    fn add_pool<T: Component>(&mut self, capacity: usize) -> &mut Self;
+}
+
+impl PoolAppExt for App {
+    fn add_pool<T: Component>(&mut self, capacity: usize) -> &mut Self {
+        self.insert_resource(Pool::<T>::new(capacity))
+            .add_systems(PostUpdate, release_expired::<T>)
+            .add_systems(Last, forget_despawned::<T>)
+    }
+}
+
+/// Releases entities of kind `T` whose lifetime is over, before they would be despawned.
+fn release_expired<T: Component>(
+    mut commands: Commands,
+    mut pool: ResMut<Pool<T>>,
+    entities: Query<(Entity, &Lifetime), (With<T>, Without<Pooled>)>,
+) {
+    for (entity, lifetime) in &entities {
+        if lifetime.expired() {
+            pool.release(&mut commands, entity);
+        }
+    }
+}
+
+/// Drops released entities that were despawned by something else, such as a level reload.
+fn forget_despawned<T: Component>(mut removed: RemovedComponents<T>, mut pool: ResMut<Pool<T>>) {
+    for entity in removed.read() {
+        pool.free.retain(|free| *free != entity);
+    }
+}
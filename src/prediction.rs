@@ -0,0 +1,266 @@
+//! Client-side prediction for the networked character.
+//!
+//! A client moves its own character immediately and sends every [`CharacterInput`] to the server,
+//! repeating those not yet acknowledged in case datagrams are lost. The server drives a
+//! [`RemoteCharacter`] per client with those inputs, no more of them per frame than its own frame
+//! time allows, and answers with the character's position and the last input applied. The client then drops the acknowledged inputs and corrects towards the
+//! server's position plus the walking of the inputs still in flight.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy_rapier3d::prelude::*;
+
+use crate::character_input::CharacterInput;
+use crate::game_set::GameSet;
+use crate::materials::MaterialLibrary;
+use crate::network::{is_client, is_server, NetMessage, NetReceived, Network};
+use crate::player::CharacterDimensions;
+use crate::surface::Surface;
+use crate::Player;
+
+/// Inputs kept for replay; older ones are dropped if the server stops answering.
+const MAX_PENDING_INPUTS: usize = 120;
+/// Prediction errors above this many meters are snapped instead of smoothed.
+const SNAP_DISTANCE: f32 = 2.0;
+/// Fraction of the remaining prediction error corrected per second.
+const CORRECTION_RATE: f32 = 10.0;
+/// Longest frame time, in seconds, the server lets a client's input move its character over.
+const MAX_REMOTE_DT: f32 = 1.0 / 20.0;
+/// Most input time, in seconds, a client's character can catch up on in one server frame.
+const MAX_REMOTE_BUDGET: f32 = 0.25;
+
+pub struct PredictionPlugin;
+
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        // Also added by the player, but needed here when the game is built without it.
+        app.init_resource::<CharacterInput>()
+            .init_resource::<PendingInputs>()
+            .add_systems(
+                Update,
+                (
+                    send_inputs.run_if(resource_changed::<CharacterInput>()),
+                    reconcile,
+                )
+                    .chain()
+                    .in_set(GameSet::Movement)
+                    .run_if(is_client),
+            )
+            .add_systems(
+                Update,
+                (
+                    apply_remote_inputs.in_set(GameSet::Movement),
+                    remove_disconnected_characters,
+                )
+                    .run_if(is_server),
+            )
+            .add_systems(
+                PostUpdate,
+                send_character_states
+                    .in_set(GameSet::PhysicsWriteback)
+                    .run_if(is_server),
+            );
+    }
+}
+
+/// Inputs a client has applied but the server hasn't acknowledged, oldest first.
+#[derive(Resource, Default)]
+struct PendingInputs(VecDeque<CharacterInput>);
+
+/// A client's character on the server.
+#[derive(Component)]
+pub struct RemoteCharacter {
+    client: SocketAddr,
+    /// Sequence number of the last input applied.
+    last_sequence: u32,
+    /// Seconds of input the server still applies, refilled by its own frame time so a client
+    /// can't move faster by sending more inputs. Inputs over it are applied once the client
+    /// repeats them.
+    budget: f32,
+}
+
+fn send_inputs(
+    input: Res<CharacterInput>,
+    network: Res<Network>,
+    mut pending: ResMut<PendingInputs>,
+) {
+    pending.0.push_back(*input);
+    if pending.0.len() > MAX_PENDING_INPUTS {
+        pending.0.pop_front();
+    }
+    network.broadcast(&NetMessage::Inputs(pending.0.iter().copied().collect()));
+}
+
+fn reconcile(
+    time: Res<Time<Real>>,
+    mut received: EventReader<NetReceived>,
+    mut pending: ResMut<PendingInputs>,
+    mut player: Query<&mut Transform, With<Player>>,
+) {
+    let Some((sequence, server_translation)) = received
+        .read()
+        .filter_map(|event| match event.message {
+            NetMessage::CharacterState {
+                sequence,
+                translation,
+            } => Some((sequence, translation)),
+            _ => None,
+        })
+        .max_by_key(|(sequence, _)| *sequence)
+    else {
+        return;
+    };
+    pending.0.retain(|input| input.sequence > sequence);
+    let Ok(mut transform) = player.get_single_mut() else {
+        return;
+    };
+
+    // Only the walking is replayed: the controller resolves the vertical pull against the floor,
+    // which the replay can't, so the server's height is taken as is.
+    let predicted = server_translation + pending.0.iter().map(CharacterInput::walk).sum::<Vec3>();
+    let error = predicted - transform.translation;
+    if error.length() > SNAP_DISTANCE {
+        transform.translation = predicted;
+    } else {
+        transform.translation += error * (CORRECTION_RATE * time.delta_seconds()).min(1.0);
+    }
+}
+
+/// A client's input with its frame time and direction kept in range, so a client can't move its
+/// character further than a slow frame would.
+fn bounded(input: &CharacterInput) -> CharacterInput {
+    if input.dt.is_nan() || input.direction.is_nan() || input.vertical.is_nan() {
+        return CharacterInput {
+            dt: 0.0,
+            direction: Vec2::ZERO,
+            vertical: 0.0,
+            ..*input
+        };
+    }
+    CharacterInput {
+        dt: input.dt.clamp(0.0, MAX_REMOTE_DT),
+        direction: input.direction.clamp(Vec2::NEG_ONE, Vec2::ONE),
+        vertical: input.vertical.clamp(-1.0, 1.0),
+        ..*input
+    }
+}
+
+fn apply_remote_inputs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut received: EventReader<NetReceived>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    library: Option<Res<MaterialLibrary>>,
+    mut characters: Query<(
+        &mut RemoteCharacter,
+        &mut KinematicCharacterController,
+        &mut CharacterDimensions,
+    )>,
+) {
+    for (mut character, ..) in &mut characters {
+        character.budget = (character.budget + time.delta_seconds()).min(MAX_REMOTE_BUDGET);
+    }
+
+    for event in received.read() {
+        let NetMessage::Inputs(inputs) = &event.message else {
+            continue;
+        };
+        let existing = characters
+            .iter_mut()
+            .find(|(character, _, _)| character.client == event.from);
+        let last_sequence = existing
+            .as_ref()
+            .map_or(0, |(character, _, _)| character.last_sequence);
+        let mut new_inputs: Vec<_> = inputs
+            .iter()
+            .filter(|input| input.sequence > last_sequence)
+            .map(bounded)
+            .collect();
+        new_inputs.sort_by_key(|input| input.sequence);
+
+        match existing {
+            Some((mut character, mut controller, mut dimensions)) => {
+                let mut translation = Vec3::ZERO;
+                let mut newest = None;
+                for input in new_inputs {
+                    if input.dt > character.budget {
+                        break;
+                    }
+                    character.budget -= input.dt;
+                    translation += input.translation();
+                    newest = Some(input);
+                }
+                let Some(newest) = newest else {
+                    continue;
+                };
+                character.last_sequence = newest.sequence;
+                controller.translation =
+                    Some(controller.translation.unwrap_or_default() + translation);
+                if dimensions.crouching != newest.crouch {
+                    dimensions.crouching = newest.crouch;
+                }
+            }
+            None => {
+                let Some(newest) = new_inputs.last() else {
+                    continue;
+                };
+                info!("spawning a character for {}", event.from);
+                let dimensions = CharacterDimensions::default();
+                commands.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(dimensions.mesh(dimensions.height)),
+                        material: library
+                            .as_ref()
+                            .map(|library| library.wood_crate.clone())
+                            .unwrap_or_default(),
+                        transform: Transform::from_xyz(1.5, 2.0, 1.0),
+                        ..default()
+                    },
+                    dimensions.collider(dimensions.height),
+                    dimensions,
+                    KinematicCharacterController {
+                        offset: CharacterLength::Absolute(0.1),
+                        filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                        ..default()
+                    },
+                    Surface::Wood,
+                    RemoteCharacter {
+                        client: event.from,
+                        last_sequence: newest.sequence,
+                        budget: 0.0,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn remove_disconnected_characters(
+    mut commands: Commands,
+    network: Res<Network>,
+    characters: Query<(Entity, &RemoteCharacter)>,
+) {
+    for (entity, character) in &characters {
+        if !network.has_client(character.client) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn send_character_states(
+    network: Res<Network>,
+    characters: Query<(&RemoteCharacter, &GlobalTransform)>,
+) {
+    for (character, transform) in &characters {
+        network.send(
+            character.client,
+            &NetMessage::CharacterState {
+                sequence: character.last_sequence,
+                translation: transform.translation(),
+            },
+        );
+    }
+}
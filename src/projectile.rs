@@ -0,0 +1,145 @@
+//! Balls thrown from the camera with the middle mouse button while aiming. They come from a
+//! [`Pool`] and go back to it once their [`Lifetime`] of [`PROJECTILE_LIFETIME`] is over. Where one
+//! first hits the level, it leaves a mark, and a short [`Trail`] follows it while it flies fast.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::{aiming, MainCamera};
+use crate::conditions::{in_gameplay, player_alive, text_input_closed};
+use crate::decals::{DecalKind, PlaceDecal};
+use crate::lifetime::Lifetime;
+use crate::pool::{Pool, PoolAppExt, Pooled};
+use crate::surface::Surface;
+use crate::trails::Trail;
+
+/// Seconds before a projectile is released.
+const PROJECTILE_LIFETIME: f32 = 3.0;
+/// Launch speed in meters per second.
+const PROJECTILE_SPEED: f32 = 20.0;
+const PROJECTILE_RADIUS: f32 = 0.15;
+/// Released projectiles kept for reuse.
+const POOL_CAPACITY: usize = 64;
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_pool::<Projectile>(POOL_CAPACITY)
+            .init_resource::<ProjectileAssets>()
+            .add_systems(
+                Update,
+                (
+                    throw_projectile.run_if(
+                        in_gameplay()
+                            .and_then(aiming)
+                            .and_then(player_alive)
+                            .and_then(text_input_closed()),
+                    ),
+                    mark_surfaces,
+                ),
+            );
+    }
+}
+
+/// A thrown ball, and whether it has left its mark.
+#[derive(Component, Default)]
+pub struct Projectile {
+    marked: bool,
+}
+
+#[derive(Resource)]
+struct ProjectileAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ProjectileAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::UVSphere {
+                radius: PROJECTILE_RADIUS,
+                ..default()
+            }));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::ORANGE.into());
+        Self { mesh, material }
+    }
+}
+
+fn throw_projectile(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    assets: Res<ProjectileAssets>,
+    mut pool: ResMut<Pool<Projectile>>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+) {
+    if !mouse.just_pressed(MouseButton::Middle) {
+        return;
+    }
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let forward = camera.forward();
+    pool.acquire(
+        &mut commands,
+        (
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(camera.translation() + forward),
+                ..default()
+            },
+            RigidBody::Dynamic,
+            Collider::ball(PROJECTILE_RADIUS),
+            Velocity::linear(forward * PROJECTILE_SPEED),
+            Ccd::enabled(),
+            Surface::Rubber,
+            Projectile::default(),
+            Lifetime::from_seconds(PROJECTILE_LIFETIME),
+            Trail::new(PROJECTILE_RADIUS, 0.25, Color::rgba(1.0, 0.6, 0.2, 0.8)),
+            Name::new("Projectile"),
+        ),
+    );
+}
+
+/// Marks the level where projectiles are about to hit it for the first time, from a ray along
+/// their flight.
+fn mark_surfaces(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut decals: EventWriter<PlaceDecal>,
+    mut projectiles: Query<(Entity, &mut Projectile, &GlobalTransform, &Velocity), Without<Pooled>>,
+) {
+    for (entity, mut projectile, transform, velocity) in &mut projectiles {
+        if projectile.marked {
+            continue;
+        }
+        let speed = velocity.linvel.length();
+        let Some(direction) = velocity.linvel.try_normalize() else {
+            continue;
+        };
+        // Two frames ahead, so a bounce between frames isn't missed.
+        let reach = speed * time.delta_seconds() * 2.0 + PROJECTILE_RADIUS;
+        let hit = rapier_context.cast_ray_and_get_normal(
+            transform.translation(),
+            direction,
+            reach,
+            true,
+            QueryFilter::only_fixed()
+                .exclude_sensors()
+                .exclude_collider(entity),
+        );
+        if let Some((_, intersection)) = hit {
+            decals.send(PlaceDecal {
+                kind: DecalKind::Mark,
+                point: intersection.point,
+                normal: intersection.normal,
+                strength: 1.0,
+            });
+            projectile.marked = true;
+        }
+    }
+}
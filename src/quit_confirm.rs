@@ -0,0 +1,216 @@
+//! Quitting: the confirmation dialog opened by a [`RequestQuit`] from the pause menu's Quit button,
+//! and saving when the window is closed.
+//!
+//! While playing, the game is paused and the dialog offers to save before quitting, quit without
+//! saving, or go back. Outside of play there is nothing to lose, so the game quits right away.
+//! Closing the window doesn't ask: the game is written to [`AUTOSAVE_PATH`] and settings being
+//! edited are saved before quitting. [`crate::stats`] are saved on any exit.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+
+use crate::menu::{self, MenuInput, BUTTON_COLOR, FOCUSED_BUTTON_COLOR};
+use crate::pause_menu::PauseState;
+use crate::save::{SaveGame, AUTOSAVE_PATH, SAVE_PATH};
+use crate::settings::Settings;
+use crate::text_theme::TextTheme;
+use crate::MyStates;
+
+pub struct QuitConfirmPlugin;
+
+impl Plugin for QuitConfirmPlugin {
+    fn build(&self, app: &mut App) {
+        // In `PostUpdate`, after the pause menu has handled this frame's input, so the key that
+        // opens or closes the dialog doesn't reach the pause menu as well.
+        app.add_event::<RequestQuit>()
+            .init_resource::<QuitDialog>()
+            .add_systems(
+                PostUpdate,
+                (
+                    (navigate_quit_dialog, highlight_quit_focus)
+                        .chain()
+                        .run_if(quit_dialog_open),
+                    save_and_quit_on_close,
+                    open_quit_dialog,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Asks to quit the game, confirming first while playing.
+#[derive(Event)]
+pub struct RequestQuit;
+
+/// Whether the dialog is shown, and its focused button as an index into [`QuitButton::ALL`].
+#[derive(Resource, Default)]
+pub struct QuitDialog {
+    open: bool,
+    focus: usize,
+}
+
+pub fn quit_dialog_closed(dialog: Res<QuitDialog>) -> bool {
+    !dialog.open
+}
+
+fn quit_dialog_open(dialog: Res<QuitDialog>) -> bool {
+    dialog.open
+}
+
+#[derive(Component)]
+struct QuitDialogRoot;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum QuitButton {
+    SaveAndQuit,
+    Quit,
+    Cancel,
+}
+
+impl QuitButton {
+    const ALL: [QuitButton; 3] = [
+        QuitButton::SaveAndQuit,
+        QuitButton::Quit,
+        QuitButton::Cancel,
+    ];
+
+    /// Localization key of the button's label.
+    fn label(self) -> &'static str {
+        match self {
+            QuitButton::SaveAndQuit => "quit.save_and_quit",
+            QuitButton::Quit => "quit.quit",
+            QuitButton::Cancel => "quit.cancel",
+        }
+    }
+}
+
+/// The window isn't closed when requested (see [`crate::GameBuilder::build`]), so that this
+/// can save before the app exits.
+fn save_and_quit_on_close(
+    mut close_requests: EventReader<WindowCloseRequested>,
+    state: Res<State<MyStates>>,
+    pause_state: Res<State<PauseState>>,
+    settings: Res<Settings>,
+    mut save: EventWriter<SaveGame>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if close_requests.read().count() == 0 {
+        return;
+    }
+    if matches!(state.get(), MyStates::Next | MyStates::InGame) {
+        save.send(SaveGame {
+            path: AUTOSAVE_PATH,
+        });
+    }
+    // The settings menu saves when it's closed, which won't happen now.
+    if *pause_state.get() == PauseState::Settings {
+        settings.save();
+    }
+    exit.send(AppExit);
+}
+
+fn open_quit_dialog(
+    mut commands: Commands,
+    mut requests: EventReader<RequestQuit>,
+    mut dialog: ResMut<QuitDialog>,
+    theme: Res<TextTheme>,
+    state: Res<State<MyStates>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if requests.read().count() == 0 || dialog.open {
+        return;
+    }
+    if !matches!(state.get(), MyStates::Next | MyStates::InGame) {
+        exit.send(AppExit);
+        return;
+    }
+    if *pause_state.get() == PauseState::Running {
+        next_pause_state.set(PauseState::Paused);
+    }
+
+    *dialog = QuitDialog {
+        open: true,
+        focus: 0,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                z_index: ZIndex::Global(10),
+                ..menu::overlay()
+            },
+            QuitDialogRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(menu::localized_label("quit.title", theme.title()));
+            parent.spawn(menu::localized_label("quit.warning", theme.body()));
+            for button in QuitButton::ALL {
+                parent
+                    .spawn((menu::button(), button))
+                    .with_children(|parent| {
+                        parent.spawn(menu::localized_label(button.label(), theme.heading()));
+                    });
+            }
+        });
+}
+
+fn navigate_quit_dialog(
+    mut commands: Commands,
+    menu_input: MenuInput,
+    mut dialog: ResMut<QuitDialog>,
+    buttons: Query<(&Interaction, &QuitButton), Changed<Interaction>>,
+    roots: Query<Entity, With<QuitDialogRoot>>,
+    mut save: EventWriter<SaveGame>,
+    mut exit: EventWriter<AppExit>,
+) {
+    menu_input.navigate(&mut dialog.focus, QuitButton::ALL.len());
+    let mut activated = menu_input
+        .activate()
+        .then_some(QuitButton::ALL[dialog.focus]);
+    if menu_input.pause() || menu_input.back() {
+        activated = Some(QuitButton::Cancel);
+    }
+
+    for (interaction, button) in &buttons {
+        match interaction {
+            Interaction::Pressed => activated = Some(*button),
+            Interaction::Hovered => {
+                dialog.focus = QuitButton::ALL
+                    .iter()
+                    .position(|candidate| candidate == button)
+                    .unwrap_or(dialog.focus);
+            }
+            Interaction::None => {}
+        }
+    }
+
+    match activated {
+        Some(QuitButton::SaveAndQuit) => {
+            save.send(SaveGame { path: SAVE_PATH });
+            exit.send(AppExit);
+        }
+        Some(QuitButton::Quit) => exit.send(AppExit),
+        Some(QuitButton::Cancel) => {
+            dialog.open = false;
+            for entity in &roots {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        None => {}
+    }
+}
+
+fn highlight_quit_focus(
+    dialog: Res<QuitDialog>,
+    mut buttons: Query<(&QuitButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in &mut buttons {
+        color.0 = if *button == QuitButton::ALL[dialog.focus] {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
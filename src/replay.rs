@@ -0,0 +1,390 @@
+//! Recording and playback of rigid body motion, for debugging collision anomalies.
+//!
+//! F6 starts and stops recording the transform of every dynamic body after each physics step; the
+//! recording is written to [`REPLAY_PATH`] when it stops. F7 plays the file back with the solver
+//! disabled: P pauses, and `,` and `.` step backwards and forwards through the recording.
+//!
+//! Bodies are recorded by their [`SaveId`], given in spawn order, so a replay saved in an earlier
+//! run plays back as long as the level spawns its bodies in the same order.
+//!
+//! The file is little-endian binary: the magic `RPLY`, a `u16` version and the frame count as a
+//! `u32`, then for each frame its body count as a `u32` followed by each body's save id (`u32`),
+//! translation (3 × `f32`) and rotation (4 × `f32`).
+
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_set::GameSet;
+use crate::localization::Localization;
+use crate::pause_menu::PauseState;
+use crate::save::SaveId;
+use crate::text_theme::TextTheme;
+
+pub const REPLAY_PATH: &str = "replays/last.replay";
+const MAGIC: &[u8; 4] = b"RPLY";
+const VERSION: u16 = 2;
+/// Bytes of a body's pose in the file, and of a frame's body count.
+const POSE_LEN: usize = 4 + 7 * 4;
+const COUNT_LEN: usize = 4;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<ReplayState>()
+            .init_resource::<Recording>()
+            .add_systems(Startup, spawn_replay_status)
+            .add_systems(OnEnter(ReplayState::Recording), start_recording)
+            .add_systems(OnExit(ReplayState::Recording), save_recording)
+            .add_systems(OnEnter(ReplayState::Playing), start_playback)
+            .add_systems(OnExit(ReplayState::Playing), stop_playback)
+            .add_systems(
+                Update,
+                (
+                    toggle_replay,
+                    (scrub_replay, play_replay)
+                        .chain()
+                        .run_if(in_state(ReplayState::Playing)),
+                    update_replay_status,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                record_step
+                    .in_set(GameSet::PhysicsWriteback)
+                    .run_if(in_state(ReplayState::Recording))
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ReplayState {
+    #[default]
+    Off,
+    Recording,
+    Playing,
+}
+
+/// A body's pose in one recorded step.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct BodyPose {
+    id: u32,
+    translation: Vec3,
+    rotation: Quat,
+}
+
+/// The steps recorded so far.
+#[derive(Resource, Default)]
+struct Recording(Vec<Vec<BodyPose>>);
+
+/// A recording being played back.
+#[derive(Resource)]
+struct Playback {
+    frames: Vec<Vec<BodyPose>>,
+    cursor: usize,
+    paused: bool,
+}
+
+#[derive(Component)]
+struct ReplayStatus;
+
+fn toggle_replay(
+    input: Res<Input<KeyCode>>,
+    state: Res<State<ReplayState>>,
+    mut next_state: ResMut<NextState<ReplayState>>,
+) {
+    let next = match state.get() {
+        ReplayState::Off if input.just_pressed(KeyCode::F6) => ReplayState::Recording,
+        ReplayState::Off if input.just_pressed(KeyCode::F7) => ReplayState::Playing,
+        ReplayState::Recording if input.just_pressed(KeyCode::F6) => ReplayState::Off,
+        ReplayState::Playing if input.just_pressed(KeyCode::F7) => ReplayState::Off,
+        _ => return,
+    };
+    next_state.set(next);
+}
+
+fn start_recording(mut recording: ResMut<Recording>) {
+    recording.0.clear();
+}
+
+fn record_step(mut recording: ResMut<Recording>, bodies: Query<(&SaveId, &Transform)>) {
+    recording.0.push(
+        bodies
+            .iter()
+            .map(|(id, transform)| BodyPose {
+                id: id.0,
+                translation: transform.translation,
+                rotation: transform.rotation,
+            })
+            .collect(),
+    );
+}
+
+fn save_recording(mut recording: ResMut<Recording>) {
+    let frames = std::mem::take(&mut recording.0);
+    let result = Path::new(REPLAY_PATH)
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(REPLAY_PATH, encode(&frames)));
+    match result {
+        Ok(()) => info!("saved {} replay frames to {REPLAY_PATH}", frames.len()),
+        Err(error) => error!("could not save {REPLAY_PATH}: {error}"),
+    }
+}
+
+fn encode(frames: &[Vec<BodyPose>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+    for frame in frames {
+        bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        for pose in frame {
+            bytes.extend_from_slice(&pose.id.to_le_bytes());
+            for value in pose.translation.to_array() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            for value in pose.rotation.to_array() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    bytes
+}
+
+/// Reads little-endian values from the front of a byte slice.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        if self.0.len() < N {
+            return Err("unexpected end of file".to_string());
+        }
+        let (head, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(head.try_into().expect("split_at returns N bytes"))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        self.take().map(f32::from_le_bytes)
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<Vec<Vec<BodyPose>>, String> {
+    let mut reader = Reader(bytes);
+    if &reader.take::<4>()? != MAGIC {
+        return Err("not a replay file".to_string());
+    }
+    let version = u16::from_le_bytes(reader.take()?);
+    if version != VERSION {
+        return Err(format!("unsupported replay version {version}"));
+    }
+    // The counts are only trusted as far as the bytes left could hold them.
+    let frame_count = reader.u32()?;
+    let mut frames = Vec::with_capacity((frame_count as usize).min(reader.0.len() / COUNT_LEN));
+    for _ in 0..frame_count {
+        let body_count = reader.u32()?;
+        let mut frame = Vec::with_capacity((body_count as usize).min(reader.0.len() / POSE_LEN));
+        for _ in 0..body_count {
+            let id = reader.u32()?;
+            let translation = Vec3::new(reader.f32()?, reader.f32()?, reader.f32()?);
+            let rotation =
+                Quat::from_xyzw(reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?);
+            frame.push(BodyPose {
+                id,
+                translation,
+                rotation,
+            });
+        }
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+fn start_playback(
+    mut commands: Commands,
+    mut rapier: ResMut<RapierConfiguration>,
+    mut next_state: ResMut<NextState<ReplayState>>,
+) {
+    let frames = match fs::read(REPLAY_PATH)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| decode(&bytes))
+    {
+        Ok(frames) => frames,
+        Err(error) => {
+            warn!("could not play {REPLAY_PATH}: {error}");
+            next_state.set(ReplayState::Off);
+            return;
+        }
+    };
+    rapier.physics_pipeline_active = false;
+    commands.insert_resource(Playback {
+        frames,
+        cursor: 0,
+        paused: false,
+    });
+}
+
+fn stop_playback(
+    mut commands: Commands,
+    mut rapier: ResMut<RapierConfiguration>,
+    pause: Res<State<PauseState>>,
+) {
+    commands.remove_resource::<Playback>();
+    rapier.physics_pipeline_active = *pause.get() == PauseState::Running;
+}
+
+fn scrub_replay(input: Res<Input<KeyCode>>, playback: Option<ResMut<Playback>>) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if input.just_pressed(KeyCode::P) {
+        playback.paused = !playback.paused;
+    }
+    let last = playback.frames.len().saturating_sub(1);
+    if input.just_pressed(KeyCode::Comma) {
+        playback.paused = true;
+        playback.cursor = playback.cursor.saturating_sub(1);
+    }
+    if input.just_pressed(KeyCode::Period) {
+        playback.paused = true;
+        playback.cursor = (playback.cursor + 1).min(last);
+    }
+}
+
+/// Poses the recorded bodies at the current frame, advancing one frame per update.
+fn play_replay(
+    playback: Option<ResMut<Playback>>,
+    pause: Res<State<PauseState>>,
+    mut rapier: ResMut<RapierConfiguration>,
+    mut bodies: Query<(&SaveId, &mut Transform)>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    // Closing the pause menu turns the solver back on.
+    if rapier.physics_pipeline_active {
+        rapier.physics_pipeline_active = false;
+    }
+    let Some(frame) = playback.frames.get(playback.cursor) else {
+        return;
+    };
+    let poses: HashMap<u32, BodyPose> = frame.iter().map(|pose| (pose.id, *pose)).collect();
+    for (id, mut transform) in &mut bodies {
+        if let Some(pose) = poses.get(&id.0) {
+            transform.translation = pose.translation;
+            transform.rotation = pose.rotation;
+        }
+    }
+    if !playback.paused && *pause.get() == PauseState::Running {
+        let last = playback.frames.len().saturating_sub(1);
+        playback.cursor = (playback.cursor + 1).min(last);
+    }
+}
+
+fn spawn_replay_status(mut commands: Commands, theme: Res<TextTheme>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(15.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((TextBundle::from_section("", theme.body()), ReplayStatus));
+        });
+}
+
+fn update_replay_status(
+    state: Res<State<ReplayState>>,
+    recording: Res<Recording>,
+    playback: Option<Res<Playback>>,
+    localization: Res<Localization>,
+    mut texts: Query<&mut Text, With<ReplayStatus>>,
+) {
+    let status = match (state.get(), &playback) {
+        (ReplayState::Recording, _) => {
+            localization.format("replay.recording", &[("frames", &recording.0.len())])
+        }
+        (ReplayState::Playing, Some(playback)) => localization.format(
+            "replay.playing",
+            &[
+                ("frame", &(playback.cursor + 1)),
+                ("frames", &playback.frames.len()),
+            ],
+        ),
+        _ => String::new(),
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<Vec<BodyPose>> {
+        vec![
+            vec![
+                BodyPose {
+                    id: 0,
+                    translation: Vec3::new(1.0, 2.0, 3.0),
+                    rotation: Quat::IDENTITY,
+                },
+                BodyPose {
+                    id: 7,
+                    translation: Vec3::new(-4.0, 0.5, 9.0),
+                    rotation: Quat::from_rotation_y(1.0),
+                },
+            ],
+            Vec::new(),
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(decode(&encode(&frames())), Ok(frames()));
+    }
+
+    #[test]
+    fn truncated_file_is_an_error() {
+        let bytes = encode(&frames());
+        for len in [0, 3, 6, 10, 14, bytes.len() - 1] {
+            assert!(decode(&bytes[..len]).is_err(), "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn huge_counts_are_an_error() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        assert!(decode(b"NOPE\x02\x00\x00\x00\x00\x00").is_err());
+        let mut bytes = encode(&[]);
+        bytes[4] = 1;
+        assert!(decode(&bytes).is_err());
+    }
+}
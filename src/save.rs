@@ -0,0 +1,208 @@
+//! Quicksave (F5) and quickload (F9) of the running game to [`SAVE_PATH`]. Other systems save with
+//! a [`SaveGame`] event, to the quicksave or to [`AUTOSAVE_PATH`]; it is handled in `Last` so it's
+//! written before an `AppExit` sent in the same frame ends the app.
+//!
+//! A [`Snapshot`] holds the game state, the player's transform and health, and the transform and
+//! velocity of every dynamic body. Bodies are matched by the [`SaveId`] they get in spawn order,
+//! so loading restores the bodies of the current session; bodies spawned after the save are
+//! removed.
+
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::conditions::text_input_closed;
+use crate::health::Health;
+use crate::{MyStates, Player};
+
+pub const SAVE_PATH: &str = "saves/quicksave.ron";
+/// Written when the window is closed, so the quicksave isn't overwritten.
+pub const AUTOSAVE_PATH: &str = "saves/autosave.ron";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGame>()
+            .init_resource::<NextSaveId>()
+            .add_systems(
+                Update,
+                (
+                    assign_save_ids,
+                    quicksave.run_if(text_input_closed()),
+                    quickload.run_if(text_input_closed()),
+                )
+                    .chain(),
+            )
+            .add_systems(Last, save_game.run_if(on_event::<SaveGame>()));
+    }
+}
+
+/// Writes the running game to `path`.
+#[derive(Event)]
+pub struct SaveGame {
+    pub path: &'static str,
+}
+
+/// Identifies a dynamic body across a save and a load.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SaveId(pub u32);
+
+#[derive(Resource, Default)]
+struct NextSaveId(u32);
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Snapshot {
+    state: MyStates,
+    player: Option<PlayerSnapshot>,
+    bodies: Vec<BodySnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct PlayerSnapshot {
+    translation: Vec3,
+    rotation: Quat,
+    health: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct BodySnapshot {
+    id: u32,
+    translation: Vec3,
+    rotation: Quat,
+    linvel: Vec3,
+    angvel: Vec3,
+}
+
+fn assign_save_ids(
+    mut commands: Commands,
+    mut next_id: ResMut<NextSaveId>,
+    bodies: Query<(Entity, &RigidBody), Added<RigidBody>>,
+) {
+    for (entity, body) in &bodies {
+        if *body == RigidBody::Dynamic {
+            commands.entity(entity).insert(SaveId(next_id.0));
+            next_id.0 += 1;
+        }
+    }
+}
+
+fn quicksave(input: Res<Input<KeyCode>>, mut save: EventWriter<SaveGame>) {
+    if input.just_pressed(KeyCode::F5) {
+        save.send(SaveGame { path: SAVE_PATH });
+    }
+}
+
+fn save_game(
+    mut requests: EventReader<SaveGame>,
+    state: Res<State<MyStates>>,
+    player: Query<(&Transform, Option<&Health>), With<Player>>,
+    bodies: Query<(&SaveId, &Transform, Option<&Velocity>)>,
+) {
+    let mut paths: Vec<&str> = requests.read().map(|request| request.path).collect();
+    paths.sort_unstable();
+    paths.dedup();
+    let snapshot = Snapshot {
+        state: state.get().clone(),
+        player: player
+            .get_single()
+            .ok()
+            .map(|(transform, health)| PlayerSnapshot {
+                translation: transform.translation,
+                rotation: transform.rotation,
+                health: health.map(|health| health.current),
+            }),
+        bodies: bodies
+            .iter()
+            .map(|(id, transform, velocity)| {
+                let velocity = velocity.copied().unwrap_or_default();
+                BodySnapshot {
+                    id: id.0,
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    linvel: velocity.linvel,
+                    angvel: velocity.angvel,
+                }
+            })
+            .collect(),
+    };
+
+    for path in paths {
+        let result = ron::ser::to_string_pretty(&snapshot, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = Path::new(path).parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                fs::write(path, contents).map_err(|error| error.to_string())
+            });
+        match result {
+            Ok(()) => info!("saved {} bodies to {path}", snapshot.bodies.len()),
+            Err(error) => error!("could not save {path}: {error}"),
+        }
+    }
+}
+
+fn quickload(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    state: Res<State<MyStates>>,
+    mut next_state: ResMut<NextState<MyStates>>,
+    mut player: Query<(&mut Transform, Option<&mut Health>), With<Player>>,
+    mut bodies: Query<(Entity, &SaveId, &mut Transform), Without<Player>>,
+) {
+    if !input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let snapshot: Snapshot = match fs::read_to_string(SAVE_PATH)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|error| error.to_string()))
+    {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            warn!("could not load {SAVE_PATH}: {error}");
+            return;
+        }
+    };
+
+    if *state.get() != snapshot.state {
+        next_state.set(snapshot.state.clone());
+    }
+
+    if let (Some(saved), Ok((mut transform, health))) = (&snapshot.player, player.get_single_mut())
+    {
+        transform.translation = saved.translation;
+        transform.rotation = saved.rotation;
+        // Written directly, so loading doesn't show up as damage dealt or healed.
+        if let (Some(saved_health), Some(mut health)) = (saved.health, health) {
+            health.current = saved_health.min(health.max);
+        }
+    }
+
+    let mut saved_bodies: HashMap<u32, &BodySnapshot> =
+        snapshot.bodies.iter().map(|body| (body.id, body)).collect();
+    for (entity, id, mut transform) in &mut bodies {
+        match saved_bodies.remove(&id.0) {
+            Some(saved) => {
+                transform.translation = saved.translation;
+                transform.rotation = saved.rotation;
+                commands.entity(entity).insert(Velocity {
+                    linvel: saved.linvel,
+                    angvel: saved.angvel,
+                });
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+    if !saved_bodies.is_empty() {
+        warn!(
+            "{} saved bodies no longer exist and were not restored",
+            saved_bodies.len()
+        );
+    }
+    info!("loaded {SAVE_PATH}");
+}
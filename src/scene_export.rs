@@ -0,0 +1,237 @@
+//! Export and import of physics bodies as Bevy [`DynamicScene`]s.
+//!
+//! Rapier's [`Collider`] isn't reflectable, so bodies are written through [`SerializedBody`], a
+//! reflected description of the body type, collider shape, density and velocity. The `export`
+//! console command adds it to every body with a supported shape and saves the scene to
+//! `assets/scenes`; `import` spawns a saved scene, and [`rebuild_bodies`] turns the descriptions
+//! back into Rapier components and meshes.
+
+use std::fs;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::console::ConsoleAppExt;
+use crate::materials::MaterialLibrary;
+
+/// Directory of exported scenes, inside the asset folder so they can be loaded back.
+const SCENES_DIR: &str = "scenes";
+
+pub struct SceneExportPlugin;
+
+impl Plugin for SceneExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SerializedBody>()
+            .register_type::<ColliderShape>()
+            .add_console_command(
+                "export",
+                "export <name> - save all physics bodies to assets/scenes/<name>.scn.ron",
+                export_scene,
+            )
+            .add_console_command(
+                "import",
+                "import <name> - spawn the bodies saved in assets/scenes/<name>.scn.ron",
+                import_scene,
+            )
+            .add_systems(Update, rebuild_bodies);
+    }
+}
+
+/// Reflectable description of a physics body, used while exporting and importing scenes.
+#[derive(Component, Reflect, Default, Clone, Debug)]
+#[reflect(Component)]
+pub struct SerializedBody {
+    pub body: RigidBody,
+    pub shape: ColliderShape,
+    pub density: f32,
+    pub velocity: Velocity,
+}
+
+/// The collider shapes that can be exported.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ColliderShape {
+    Ball { radius: f32 },
+    Cuboid { half_extents: Vec3 },
+    Capsule { half_height: f32, radius: f32 },
+    Cylinder { half_height: f32, radius: f32 },
+}
+
+impl Default for ColliderShape {
+    fn default() -> Self {
+        ColliderShape::Ball { radius: 0.5 }
+    }
+}
+
+impl ColliderShape {
+    /// Describes `collider`, or returns `None` for shapes such as meshes and compounds.
+    pub fn from_collider(collider: &Collider) -> Option<Self> {
+        let scale = collider.scale();
+        match collider.as_unscaled_typed_shape() {
+            ColliderView::Ball(ball) => Some(ColliderShape::Ball {
+                radius: ball.radius() * scale.max_element(),
+            }),
+            ColliderView::Cuboid(cuboid) => Some(ColliderShape::Cuboid {
+                half_extents: cuboid.half_extents() * scale,
+            }),
+            ColliderView::Capsule(capsule) => Some(ColliderShape::Capsule {
+                half_height: capsule.half_height() * scale.y,
+                radius: capsule.radius() * scale.x.max(scale.z),
+            }),
+            ColliderView::Cylinder(cylinder) => Some(ColliderShape::Cylinder {
+                half_height: cylinder.half_height() * scale.y,
+                radius: cylinder.radius() * scale.x.max(scale.z),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn collider(self) -> Collider {
+        match self {
+            ColliderShape::Ball { radius } => Collider::ball(radius),
+            ColliderShape::Cuboid { half_extents } => {
+                Collider::cuboid(half_extents.x, half_extents.y, half_extents.z)
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => Collider::capsule_y(half_height, radius),
+            ColliderShape::Cylinder {
+                half_height,
+                radius,
+            } => Collider::cylinder(half_height, radius),
+        }
+    }
+
+    pub fn mesh(self) -> Mesh {
+        match self {
+            ColliderShape::Ball { radius } => shape::UVSphere {
+                radius,
+                ..default()
+            }
+            .into(),
+            ColliderShape::Cuboid { half_extents } => {
+                let size = half_extents * 2.0;
+                shape::Box::new(size.x, size.y, size.z).into()
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => shape::Capsule {
+                radius,
+                depth: half_height * 2.0,
+                ..default()
+            }
+            .into(),
+            ColliderShape::Cylinder {
+                half_height,
+                radius,
+            } => shape::Cylinder {
+                radius,
+                height: half_height * 2.0,
+                ..default()
+            }
+            .into(),
+        }
+    }
+}
+
+fn scene_path(name: &str) -> String {
+    format!("{SCENES_DIR}/{name}.scn.ron")
+}
+
+fn export_scene(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = *args.first().ok_or("missing argument <name>")?;
+
+    let mut bodies = world.query::<(
+        Entity,
+        &RigidBody,
+        &Collider,
+        Option<&ColliderMassProperties>,
+        Option<&Velocity>,
+    )>();
+    let mut descriptions = Vec::new();
+    let mut skipped = 0;
+    for (entity, body, collider, mass, velocity) in bodies.iter(world) {
+        let Some(shape) = ColliderShape::from_collider(collider) else {
+            skipped += 1;
+            continue;
+        };
+        let density = match mass {
+            Some(ColliderMassProperties::Density(density)) => *density,
+            _ => 1.0,
+        };
+        descriptions.push((
+            entity,
+            SerializedBody {
+                body: *body,
+                shape,
+                density,
+                velocity: velocity.copied().unwrap_or_default(),
+            },
+        ));
+    }
+
+    let entities: Vec<Entity> = descriptions.iter().map(|(entity, _)| *entity).collect();
+    for (entity, description) in descriptions {
+        world.entity_mut(entity).insert(description);
+    }
+    let scene = DynamicSceneBuilder::from_world(world)
+        .deny_all()
+        .allow::<Transform>()
+        .allow::<Name>()
+        .allow::<SerializedBody>()
+        .extract_entities(entities.iter().copied())
+        .build();
+    for entity in &entities {
+        world.entity_mut(*entity).remove::<SerializedBody>();
+    }
+
+    let contents = scene
+        .serialize_ron(world.resource::<AppTypeRegistry>())
+        .map_err(|error| error.to_string())?;
+    let path = format!("assets/{}", scene_path(name));
+    fs::create_dir_all(format!("assets/{SCENES_DIR}")).map_err(|error| error.to_string())?;
+    fs::write(&path, contents).map_err(|error| format!("could not write {path}: {error}"))?;
+    Ok(format!(
+        "exported {} bodies to {path} ({skipped} with unsupported shapes skipped)",
+        entities.len()
+    ))
+}
+
+fn import_scene(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = *args.first().ok_or("missing argument <name>")?;
+    let path = scene_path(name);
+    let scene = world.resource::<AssetServer>().load(&path);
+    world.spawn(DynamicSceneBundle { scene, ..default() });
+    Ok(format!("importing {path}"))
+}
+
+/// Replaces imported [`SerializedBody`] descriptions with Rapier components and a mesh.
+fn rebuild_bodies(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    library: Option<Res<MaterialLibrary>>,
+    bodies: Query<(Entity, &SerializedBody), Added<SerializedBody>>,
+) {
+    for (entity, description) in &bodies {
+        let material = match &library {
+            Some(library) => library.prototype.clone(),
+            None => materials.add(Color::SILVER.into()),
+        };
+        commands
+            .entity(entity)
+            .insert((
+                description.body,
+                description.shape.collider(),
+                ColliderMassProperties::Density(description.density),
+                description.velocity,
+                meshes.add(description.shape.mesh()),
+                material,
+                GlobalTransform::default(),
+                VisibilityBundle::default(),
+            ))
+            .remove::<SerializedBody>();
+    }
+}
@@ -0,0 +1,80 @@
+//! F12 saves the current frame to a timestamped PNG in [`SCREENSHOT_DIR`].
+
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+
+use crate::localization::Localization;
+use crate::toast::ToastEvent;
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, take_screenshot);
+    }
+}
+
+fn take_screenshot(
+    input: Res<Input<KeyCode>>,
+    localization: Res<Localization>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut toasts: EventWriter<ToastEvent>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    if let Err(error) = fs::create_dir_all(SCREENSHOT_DIR) {
+        error!("could not create {SCREENSHOT_DIR}: {error}");
+        return;
+    }
+    let path = format!("{SCREENSHOT_DIR}/screenshot-{}.png", timestamp());
+    // Fails only if a screenshot of this frame is already being taken.
+    if screenshots.save_screenshot_to_disk(window, &path).is_ok() {
+        toasts.send(ToastEvent(
+            localization.format("screenshot.saved", &[("path", &path)]),
+        ));
+    }
+}
+
+/// The current UTC time as `YYYYMMDD-HHMMSS-mmm`.
+pub fn timestamp() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, time_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Days since the epoch to a civil date, after Howard Hinnant's `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}-{:03}",
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
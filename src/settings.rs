@@ -0,0 +1,357 @@
+//! User settings, persisted to [`SETTINGS_PATH`] and applied to the camera, window, lighting,
+//! physics and debug renderer whenever they change. Volumes are applied through
+//! [`crate::audio_mixer`].
+//!
+//! Files from before the settings were split into sections are still read: their flat fields are
+//! moved into the sections they belong to.
+
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow};
+use bevy_rapier3d::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::MainCamera;
+use crate::difficulty::Difficulty;
+use crate::launch_options::LaunchOptions;
+use crate::physics_setup::DebugRenderSetting;
+
+pub const SETTINGS_PATH: &str = "config/settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut settings = Settings::load();
+        // Forced on for this run only; it is saved only if another setting is changed.
+        if app
+            .world
+            .get_resource::<LaunchOptions>()
+            .is_some_and(|options| options.debug.render)
+        {
+            settings.graphics.debug_render = true;
+        }
+        app.insert_resource(settings).add_systems(
+            Update,
+            (
+                apply_settings.run_if(resource_changed::<Settings>()),
+                apply_camera_fov,
+            ),
+        );
+    }
+}
+
+/// Everything stored in the settings file, one section per subsystem.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+    pub input: InputSettings,
+    pub physics: PhysicsSettings,
+    pub player: PlayerSettings,
+    pub hitstop: HitstopSettings,
+    pub difficulty: Difficulty,
+    /// Code of the UI language, see [`crate::localization::LANGUAGES`].
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            graphics: GraphicsSettings::default(),
+            audio: AudioSettings::default(),
+            input: InputSettings::default(),
+            physics: PhysicsSettings::default(),
+            player: PlayerSettings::default(),
+            hitstop: HitstopSettings::default(),
+            difficulty: Difficulty::default(),
+            language: "en".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Vertical field of view, in degrees.
+    pub fov: f32,
+    pub vsync: bool,
+    pub debug_render: bool,
+    pub ambient_brightness: f32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            fov: 45.0,
+            vsync: true,
+            debug_render: true,
+            ambient_brightness: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Volume of all sounds, between 0 and 1.
+    #[serde(alias = "volume")]
+    pub master_volume: f32,
+    /// Volume of the music channel, between 0 and 1.
+    pub music_volume: f32,
+    /// Volume of the sound effects channel, between 0 and 1.
+    pub sfx_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct InputSettings {
+    /// Radians of camera rotation per pixel of mouse motion.
+    pub mouse_sensitivity: f32,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 0.003,
+        }
+    }
+}
+
+/// Tuning of the simulation and of the bodies spawned with the sandbox.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct PhysicsSettings {
+    /// Vertical gravity, in meters per second squared.
+    pub gravity: f32,
+    /// Gravity scale of the rolling ball.
+    pub ball_gravity_scale: f32,
+    /// Gap the character controller keeps between the player and obstacles, in meters.
+    pub character_offset: f32,
+    /// Bodies farther than this from the player, in meters, are taken out of the simulation; see
+    /// [`crate::activation`].
+    pub activation_radius: f32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            gravity: -9.81,
+            ball_gravity_scale: 0.5,
+            character_offset: 0.1,
+            activation_radius: 80.0,
+        }
+    }
+}
+
+/// How the player's model animates, see [`crate::character_animation`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct PlayerSettings {
+    /// Horizontal speeds, in meters per second, at which the walk and the run clips play on their
+    /// own. In between, and below walking, the idle, walk and run clips blend by speed.
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    /// How long clips take to blend into each other, in seconds.
+    pub blend_time: f32,
+}
+
+impl Default for PlayerSettings {
+    fn default() -> Self {
+        Self {
+            walk_speed: 2.5,
+            run_speed: 5.0,
+            blend_time: 0.2,
+        }
+    }
+}
+
+/// How hard hits freeze and slow the game, see [`crate::hitstop`]. The durations are for a hit of
+/// strength 1.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct HitstopSettings {
+    /// Frames the animations freeze for.
+    pub freeze_frames: u32,
+    /// Seconds the game takes to get back to full speed after a hit, and the share of full speed
+    /// it slows down to.
+    pub slow_motion_duration: f32,
+    pub slow_motion_speed: f32,
+}
+
+impl Default for HitstopSettings {
+    fn default() -> Self {
+        Self {
+            freeze_frames: 4,
+            slow_motion_duration: 0.25,
+            slow_motion_speed: 0.3,
+        }
+    }
+}
+
+/// The fields of the settings file from before it had sections.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct FlatSettings {
+    mouse_sensitivity: Option<f32>,
+    #[serde(alias = "volume")]
+    master_volume: Option<f32>,
+    music_volume: Option<f32>,
+    sfx_volume: Option<f32>,
+    fov: Option<f32>,
+    debug_render: Option<bool>,
+    vsync: Option<bool>,
+}
+
+impl FlatSettings {
+    /// Moves the fields found into their sections of `settings`.
+    fn apply(self, settings: &mut Settings) {
+        fn set<T>(value: Option<T>, field: &mut T) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        set(
+            self.mouse_sensitivity,
+            &mut settings.input.mouse_sensitivity,
+        );
+        set(self.master_volume, &mut settings.audio.master_volume);
+        set(self.music_volume, &mut settings.audio.music_volume);
+        set(self.sfx_volume, &mut settings.audio.sfx_volume);
+        set(self.fov, &mut settings.graphics.fov);
+        set(self.debug_render, &mut settings.graphics.debug_render);
+        set(self.vsync, &mut settings.graphics.vsync);
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, falling back to the defaults if it is invalid. A missing file is
+    /// created with the defaults, so they can be edited by hand.
+    pub fn load() -> Self {
+        match fs::read_to_string(SETTINGS_PATH) {
+            Ok(contents) => Self::parse(&contents).unwrap_or_else(|error| {
+                warn!("ignoring invalid {SETTINGS_PATH}: {error}");
+                Self::default()
+            }),
+            Err(_) => {
+                let settings = Self::default();
+                settings.save();
+                settings
+            }
+        }
+    }
+
+    /// Parses a settings file in either layout.
+    fn parse(contents: &str) -> Result<Self, ron::error::SpannedError> {
+        let mut settings: Self = ron::from_str(contents)?;
+        // The old fields were written as plain values, not as `Some(…)`.
+        ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str::<FlatSettings>(contents)?
+            .apply(&mut settings);
+        Ok(settings)
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = Path::new(SETTINGS_PATH).parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                fs::write(SETTINGS_PATH, contents).map_err(|error| error.to_string())
+            });
+        match result {
+            Ok(()) => info!("saved settings to {SETTINGS_PATH}"),
+            Err(error) => error!("could not save {SETTINGS_PATH}: {error}"),
+        }
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut debug_render: ResMut<DebugRenderSetting>,
+    mut rapier: ResMut<RapierConfiguration>,
+    ambient_light: Option<ResMut<AmbientLight>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if debug_render.enabled != settings.graphics.debug_render {
+        debug_render.enabled = settings.graphics.debug_render;
+    }
+    rapier.gravity = Vec3::Y * settings.physics.gravity;
+    if let Some(mut ambient_light) = ambient_light {
+        ambient_light.brightness = settings.graphics.ambient_brightness;
+    }
+
+    let present_mode = if settings.graphics.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    for mut window in &mut windows {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
+
+/// Applies the field of view when it changes or when the main camera is spawned.
+fn apply_camera_fov(
+    settings: Res<Settings>,
+    mut cameras: Query<(&mut Projection, Ref<MainCamera>)>,
+) {
+    for (mut projection, camera) in &mut cameras {
+        if !settings.is_changed() && !camera.is_added() {
+            continue;
+        }
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.graphics.fov.to_radians();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_flat_settings_into_sections() {
+        let settings = Settings::parse(
+            "(mouse_sensitivity: 0.01, volume: 0.5, music_volume: 0.2, sfx_volume: 0.7, fov: 60.0, \
+             debug_render: false, vsync: false, language: \"de\")",
+        )
+        .unwrap();
+        assert_eq!(settings.input.mouse_sensitivity, 0.01);
+        assert_eq!(settings.audio.master_volume, 0.5);
+        assert_eq!(settings.audio.music_volume, 0.2);
+        assert_eq!(settings.audio.sfx_volume, 0.7);
+        assert_eq!(settings.graphics.fov, 60.0);
+        assert!(!settings.graphics.debug_render);
+        assert!(!settings.graphics.vsync);
+        assert_eq!(settings.language, "de");
+        assert_eq!(settings.physics, PhysicsSettings::default());
+    }
+
+    #[test]
+    fn reads_sectioned_settings() {
+        let mut expected = Settings::default();
+        expected.audio.master_volume = 0.25;
+        expected.graphics.fov = 70.0;
+        let contents = ron::to_string(&expected).unwrap();
+        assert_eq!(Settings::parse(&contents).unwrap(), expected);
+    }
+}
@@ -0,0 +1,366 @@
+//! The settings screen, opened from the pause menu.
+//!
+//! Sliders and toggles write straight into [`Settings`], so changes apply immediately; the file
+//! is saved when the screen is closed.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::localization::{Localization, LANGUAGES};
+use crate::menu::{self, MenuInput, BUTTON_COLOR, FOCUSED_BUTTON_COLOR};
+use crate::pause_menu::{OpenSettings, PauseState};
+use crate::settings::Settings;
+use crate::text_theme::TextTheme;
+
+const ROW_WIDTH: f32 = 520.0;
+const TRACK_WIDTH: f32 = 200.0;
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsFocus>()
+            .add_systems(OnEnter(PauseState::Settings), spawn_settings_menu)
+            .add_systems(
+                OnExit(PauseState::Settings),
+                (despawn_settings_menu, save_settings),
+            )
+            .add_systems(
+                Update,
+                (
+                    open_settings,
+                    (navigate_settings_menu, drag_sliders, update_settings_menu)
+                        .chain()
+                        .run_if(in_state(PauseState::Settings)),
+                ),
+            );
+    }
+}
+
+/// A row of the settings screen.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum SettingEntry {
+    MouseSensitivity,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Fov,
+    DebugRender,
+    Vsync,
+    Difficulty,
+    Language,
+    Back,
+}
+
+/// Range and keyboard step of a slider.
+struct SliderRange {
+    min: f32,
+    max: f32,
+    step: f32,
+}
+
+impl SettingEntry {
+    const ALL: [SettingEntry; 10] = [
+        SettingEntry::MouseSensitivity,
+        SettingEntry::MasterVolume,
+        SettingEntry::MusicVolume,
+        SettingEntry::SfxVolume,
+        SettingEntry::Fov,
+        SettingEntry::DebugRender,
+        SettingEntry::Vsync,
+        SettingEntry::Difficulty,
+        SettingEntry::Language,
+        SettingEntry::Back,
+    ];
+
+    /// Localization key of the row's label.
+    fn label(self) -> &'static str {
+        match self {
+            SettingEntry::MouseSensitivity => "settings.mouse_sensitivity",
+            SettingEntry::MasterVolume => "settings.master_volume",
+            SettingEntry::MusicVolume => "settings.music_volume",
+            SettingEntry::SfxVolume => "settings.sfx_volume",
+            SettingEntry::Fov => "settings.fov",
+            SettingEntry::DebugRender => "settings.debug_render",
+            SettingEntry::Vsync => "settings.vsync",
+            SettingEntry::Difficulty => "settings.difficulty",
+            SettingEntry::Language => "settings.language",
+            SettingEntry::Back => "menu.back",
+        }
+    }
+
+    fn slider(self) -> Option<SliderRange> {
+        match self {
+            SettingEntry::MouseSensitivity => Some(SliderRange {
+                min: 0.0005,
+                max: 0.01,
+                step: 0.0005,
+            }),
+            SettingEntry::MasterVolume | SettingEntry::MusicVolume | SettingEntry::SfxVolume => {
+                Some(SliderRange {
+                    min: 0.0,
+                    max: 1.0,
+                    step: 0.05,
+                })
+            }
+            SettingEntry::Fov => Some(SliderRange {
+                min: 30.0,
+                max: 110.0,
+                step: 5.0,
+            }),
+            _ => None,
+        }
+    }
+
+    fn value(self, settings: &Settings) -> f32 {
+        match self {
+            SettingEntry::MouseSensitivity => settings.input.mouse_sensitivity,
+            SettingEntry::MasterVolume => settings.audio.master_volume,
+            SettingEntry::MusicVolume => settings.audio.music_volume,
+            SettingEntry::SfxVolume => settings.audio.sfx_volume,
+            SettingEntry::Fov => settings.graphics.fov,
+            _ => 0.0,
+        }
+    }
+
+    fn set_value(self, settings: &mut Settings, value: f32) {
+        let Some(range) = self.slider() else {
+            return;
+        };
+        let value = value.clamp(range.min, range.max);
+        match self {
+            SettingEntry::MouseSensitivity => settings.input.mouse_sensitivity = value,
+            SettingEntry::MasterVolume => settings.audio.master_volume = value,
+            SettingEntry::MusicVolume => settings.audio.music_volume = value,
+            SettingEntry::SfxVolume => settings.audio.sfx_volume = value,
+            SettingEntry::Fov => settings.graphics.fov = value,
+            _ => {}
+        }
+    }
+
+    fn toggle(self, settings: &mut Settings) -> Option<&mut bool> {
+        match self {
+            SettingEntry::DebugRender => Some(&mut settings.graphics.debug_render),
+            SettingEntry::Vsync => Some(&mut settings.graphics.vsync),
+            _ => None,
+        }
+    }
+
+    fn display(self, settings: &Settings, localization: &Localization) -> String {
+        let on_off = |value| localization.get(if value { "common.on" } else { "common.off" });
+        match self {
+            SettingEntry::MouseSensitivity => format!("{:.4}", settings.input.mouse_sensitivity),
+            SettingEntry::MasterVolume | SettingEntry::MusicVolume | SettingEntry::SfxVolume => {
+                format!("{:.0}%", self.value(settings) * 100.0)
+            }
+            SettingEntry::Fov => format!("{:.0}°", settings.graphics.fov),
+            SettingEntry::DebugRender => on_off(settings.graphics.debug_render).to_string(),
+            SettingEntry::Vsync => on_off(settings.graphics.vsync).to_string(),
+            SettingEntry::Difficulty => localization.get(settings.difficulty.label()).to_string(),
+            SettingEntry::Language => localization.get("language.name").to_string(),
+            SettingEntry::Back => String::new(),
+        }
+    }
+}
+
+/// Moves `language` `offset` places through [`LANGUAGES`], wrapping around.
+fn cycle_language(language: &mut String, offset: isize) {
+    let count = LANGUAGES.len() as isize;
+    let current = LANGUAGES
+        .iter()
+        .position(|code| code == language)
+        .unwrap_or(0) as isize;
+    *language = LANGUAGES[(current + offset).rem_euclid(count) as usize].to_string();
+}
+
+/// Index into [`SettingEntry::ALL`] of the focused row.
+#[derive(Resource, Default)]
+struct SettingsFocus(usize);
+
+#[derive(Component)]
+struct SettingsMenu;
+
+/// Clickable track of a slider.
+#[derive(Component)]
+struct SliderTrack(SettingEntry);
+
+/// Filled part of a slider's track.
+#[derive(Component)]
+struct SliderFill(SettingEntry);
+
+/// Text showing a row's current value.
+#[derive(Component)]
+struct SettingValue(SettingEntry);
+
+fn open_settings(
+    mut events: EventReader<OpenSettings>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if events.read().count() > 0 {
+        next_state.set(PauseState::Settings);
+    }
+}
+
+fn spawn_settings_menu(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    mut focus: ResMut<SettingsFocus>,
+) {
+    focus.0 = 0;
+    commands
+        .spawn((menu::overlay(), SettingsMenu))
+        .with_children(|parent| {
+            parent.spawn(menu::localized_label("settings.title", theme.title()));
+            for entry in SettingEntry::ALL {
+                let mut row = menu::button();
+                row.style.width = Val::Px(ROW_WIDTH);
+                row.style.justify_content = JustifyContent::SpaceBetween;
+                row.style.padding = UiRect::horizontal(Val::Px(16.0));
+
+                parent.spawn((row, entry)).with_children(|parent| {
+                    parent.spawn(menu::localized_label(entry.label(), theme.body()));
+                    if entry.slider().is_some() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(TRACK_WIDTH),
+                                        height: Val::Px(10.0),
+                                        ..default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..default()
+                                },
+                                SliderTrack(entry),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        background_color: Color::ALICE_BLUE.into(),
+                                        ..default()
+                                    },
+                                    SliderFill(entry),
+                                ));
+                            });
+                    }
+                    parent.spawn((menu::label("", theme.body()), SettingValue(entry)));
+                });
+            }
+        });
+}
+
+fn despawn_settings_menu(mut commands: Commands, menus: Query<Entity, With<SettingsMenu>>) {
+    for entity in &menus {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+fn navigate_settings_menu(
+    menu_input: MenuInput,
+    mut focus: ResMut<SettingsFocus>,
+    rows: Query<(&Interaction, &SettingEntry), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    menu_input.navigate(&mut focus.0, SettingEntry::ALL.len());
+    let focused = SettingEntry::ALL[focus.0];
+    let mut activated = menu_input.activate().then_some(focused);
+
+    for (interaction, entry) in &rows {
+        match interaction {
+            Interaction::Pressed => activated = Some(*entry),
+            Interaction::Hovered => {
+                focus.0 = SettingEntry::ALL
+                    .iter()
+                    .position(|candidate| candidate == entry)
+                    .unwrap_or(focus.0);
+            }
+            Interaction::None => {}
+        }
+    }
+
+    let direction = menu_input.right() as i32 - menu_input.left() as i32;
+    if direction != 0 {
+        if let Some(range) = focused.slider() {
+            let value = focused.value(&settings) + direction as f32 * range.step;
+            focused.set_value(&mut settings, value);
+        } else if focused == SettingEntry::Difficulty {
+            settings.difficulty = settings.difficulty.cycle(direction as isize);
+        } else if focused == SettingEntry::Language {
+            cycle_language(&mut settings.language, direction as isize);
+        }
+    }
+
+    match activated {
+        Some(SettingEntry::Back) => next_state.set(PauseState::Paused),
+        Some(SettingEntry::Difficulty) => settings.difficulty = settings.difficulty.cycle(1),
+        Some(SettingEntry::Language) => cycle_language(&mut settings.language, 1),
+        Some(entry) => {
+            if let Some(toggle) = entry.toggle(&mut settings) {
+                *toggle = !*toggle;
+            }
+        }
+        None => {}
+    }
+    if menu_input.back() {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+/// Sets slider values from the cursor while a track is held down.
+fn drag_sliders(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    tracks: Query<(&Interaction, &SliderTrack, &Node, &GlobalTransform)>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    for (interaction, track, node, transform) in &tracks {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(range) = track.0.slider() else {
+            continue;
+        };
+        let rect = node.logical_rect(transform);
+        let t = ((cursor.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+        track
+            .0
+            .set_value(&mut settings, range.min + t * (range.max - range.min));
+    }
+}
+
+fn update_settings_menu(
+    focus: Res<SettingsFocus>,
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    mut rows: Query<(&SettingEntry, &mut BackgroundColor)>,
+    mut fills: Query<(&SliderFill, &mut Style)>,
+    mut values: Query<(&SettingValue, &mut Text)>,
+) {
+    for (entry, mut color) in &mut rows {
+        color.0 = if *entry == SettingEntry::ALL[focus.0] {
+            FOCUSED_BUTTON_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+    for (fill, mut style) in &mut fills {
+        if let Some(range) = fill.0.slider() {
+            let t = (fill.0.value(&settings) - range.min) / (range.max - range.min);
+            style.width = Val::Percent(t * 100.0);
+        }
+    }
+    for (value, mut text) in &mut values {
+        text.sections[0].value = value.0.display(&settings, &localization);
+    }
+}
@@ -0,0 +1,245 @@
+//! Generated shapes for building test courses: ramps, wedges, stairs, arches, cylinders and tori,
+//! each with a mesh and a matching collider. Level entities and prefabs get one through their
+//! `shape` field, as the `ramp`, `stairs` and other shape prefabs do.
+//!
+//! Shapes are centered on their entity like [`shape::Box`], and ramps, stairs and arches face +x:
+//! ramps and stairs rise towards it, and arches span along it.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Segments of the curved part of an arch.
+const ARCH_SEGMENTS: usize = 12;
+/// Capsules along the ring of a torus collider.
+const TORUS_SEGMENTS: usize = 16;
+/// Sides of a cylinder mesh.
+const CYLINDER_RESOLUTION: u32 = 32;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Shape {
+    /// A slope rising over `size.x` to `size.y`, `size.z` wide.
+    Ramp(Vec3),
+    /// A wedge peaking in the middle, like a roof or a speed bump, `size` across.
+    Wedge(Vec3),
+    /// Stairs of `steps` equal steps climbing `size.y` over `size.x`, `size.z` wide.
+    Stairs { size: Vec3, steps: u32 },
+    /// A semicircular arch `width` across and `height` tall on two pillars, `thickness` thick and
+    /// `depth` deep. The height is at least half the width.
+    Arch {
+        width: f32,
+        height: f32,
+        depth: f32,
+        thickness: f32,
+    },
+    /// An upright cylinder.
+    Cylinder { radius: f32, height: f32 },
+    /// A flat ring of `radius` around its middle, with a tube of `tube` radius.
+    Torus { radius: f32, tube: f32 },
+}
+
+impl Shape {
+    pub fn mesh(self) -> Mesh {
+        match self {
+            Shape::Cylinder { radius, height } => shape::Cylinder {
+                radius,
+                height,
+                resolution: CYLINDER_RESOLUTION,
+                segments: 1,
+            }
+            .into(),
+            Shape::Torus { radius, tube } => shape::Torus {
+                radius,
+                ring_radius: tube,
+                ..default()
+            }
+            .into(),
+            _ => {
+                let triangles = self
+                    .prisms()
+                    .iter()
+                    .flat_map(|(profile, depth)| prism_triangles(profile, *depth))
+                    .collect();
+                flat_mesh(triangles)
+            }
+        }
+    }
+
+    pub fn collider(self) -> Collider {
+        match self {
+            Shape::Cylinder { radius, height } => Collider::cylinder(height / 2.0, radius),
+            Shape::Torus { radius, tube } => Collider::compound(
+                (0..TORUS_SEGMENTS)
+                    .map(|index| {
+                        let angle = |index: usize| index as f32 / TORUS_SEGMENTS as f32 * 2.0 * PI;
+                        let point = |angle: f32| Vec3::new(angle.cos(), 0.0, angle.sin()) * radius;
+                        (
+                            Vec3::ZERO,
+                            Quat::IDENTITY,
+                            Collider::capsule(point(angle(index)), point(angle(index + 1)), tube),
+                        )
+                    })
+                    .collect(),
+            ),
+            _ => {
+                let mut pieces: Vec<Collider> = self
+                    .prisms()
+                    .iter()
+                    .filter_map(|(profile, depth)| {
+                        Collider::convex_hull(&prism_points(profile, *depth))
+                    })
+                    .collect();
+                if pieces.len() == 1 {
+                    pieces.remove(0)
+                } else {
+                    Collider::compound(
+                        pieces
+                            .into_iter()
+                            .map(|piece| (Vec3::ZERO, Quat::IDENTITY, piece))
+                            .collect(),
+                    )
+                }
+            }
+        }
+    }
+
+    /// The convex prisms the shape is made of: counterclockwise profiles in the xy plane, and the
+    /// depth they're extruded to along z.
+    fn prisms(self) -> Vec<(Vec<Vec2>, f32)> {
+        match self {
+            Shape::Ramp(size) => {
+                let half = size / 2.0;
+                vec![(
+                    vec![
+                        Vec2::new(-half.x, -half.y),
+                        Vec2::new(half.x, -half.y),
+                        Vec2::new(half.x, half.y),
+                    ],
+                    size.z,
+                )]
+            }
+            Shape::Wedge(size) => {
+                let half = size / 2.0;
+                vec![(
+                    vec![
+                        Vec2::new(-half.x, -half.y),
+                        Vec2::new(half.x, -half.y),
+                        Vec2::new(0.0, half.y),
+                    ],
+                    size.z,
+                )]
+            }
+            Shape::Stairs { size, steps } => {
+                let steps = steps.max(1);
+                let (run, rise) = (size.x / steps as f32, size.y / steps as f32);
+                let bottom = -size.y / 2.0;
+                // Each step is a block from the ground up, so the stairs are solid underneath.
+                (0..steps)
+                    .map(|step| {
+                        let left = -size.x / 2.0 + step as f32 * run;
+                        let top = bottom + (step + 1) as f32 * rise;
+                        (rectangle(left, bottom, left + run, top), size.z)
+                    })
+                    .collect()
+            }
+            Shape::Arch {
+                width,
+                height,
+                depth,
+                thickness,
+            } => {
+                let outer = width / 2.0;
+                let inner = (outer - thickness).max(0.0);
+                let height = height.max(outer);
+                let bottom = -height / 2.0;
+                let spring = height / 2.0 - outer;
+                let mut prisms = Vec::with_capacity(ARCH_SEGMENTS + 2);
+                if spring > bottom {
+                    prisms.push((rectangle(-outer, bottom, -inner, spring), depth));
+                    prisms.push((rectangle(inner, bottom, outer, spring), depth));
+                }
+                let at = |radius: f32, segment: usize| {
+                    let angle = segment as f32 / ARCH_SEGMENTS as f32 * PI;
+                    Vec2::new(angle.cos() * radius, spring + angle.sin() * radius)
+                };
+                prisms.extend((0..ARCH_SEGMENTS).map(|segment| {
+                    (
+                        vec![
+                            at(inner, segment),
+                            at(outer, segment),
+                            at(outer, segment + 1),
+                            at(inner, segment + 1),
+                        ],
+                        depth,
+                    )
+                }));
+                prisms
+            }
+            Shape::Cylinder { .. } | Shape::Torus { .. } => Vec::new(),
+        }
+    }
+}
+
+/// A counterclockwise rectangle from `(left, bottom)` to `(right, top)`.
+fn rectangle(left: f32, bottom: f32, right: f32, top: f32) -> Vec<Vec2> {
+    vec![
+        Vec2::new(left, bottom),
+        Vec2::new(right, bottom),
+        Vec2::new(right, top),
+        Vec2::new(left, top),
+    ]
+}
+
+/// The corners of `profile` extruded `depth` along z, centered on z = 0.
+fn prism_points(profile: &[Vec2], depth: f32) -> Vec<Vec3> {
+    [depth / 2.0, -depth / 2.0]
+        .into_iter()
+        .flat_map(|z| profile.iter().map(move |point| point.extend(z)))
+        .collect()
+}
+
+/// The triangles of `profile` extruded `depth` along z, facing outwards.
+fn prism_triangles(profile: &[Vec2], depth: f32) -> Vec<[Vec3; 3]> {
+    let front = |point: Vec2| point.extend(depth / 2.0);
+    let back = |point: Vec2| point.extend(-depth / 2.0);
+    let mut triangles = Vec::new();
+    for pair in profile[1..].windows(2) {
+        triangles.push([front(profile[0]), front(pair[0]), front(pair[1])]);
+        triangles.push([back(profile[0]), back(pair[1]), back(pair[0])]);
+    }
+    for (index, &start) in profile.iter().enumerate() {
+        let end = profile[(index + 1) % profile.len()];
+        triangles.push([back(start), back(end), front(end)]);
+        triangles.push([back(start), front(end), front(start)]);
+    }
+    triangles
+}
+
+/// A mesh of separate triangles, with flat normals and box-projected UVs.
+fn flat_mesh(triangles: Vec<[Vec3; 3]>) -> Mesh {
+    let mut uvs = Vec::with_capacity(triangles.len() * 3);
+    for triangle in &triangles {
+        let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+        let normal = normal.abs();
+        for corner in triangle {
+            // Project on the plane the face is closest to, one texture repeat per meter.
+            let uv = if normal.x >= normal.y && normal.x >= normal.z {
+                Vec2::new(corner.z, corner.y)
+            } else if normal.y >= normal.z {
+                Vec2::new(corner.x, corner.z)
+            } else {
+                Vec2::new(corner.x, corner.y)
+            };
+            uvs.push(uv.to_array());
+        }
+    }
+    let positions: Vec<Vec3> = triangles.into_iter().flatten().collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.compute_flat_normals();
+    mesh
+}
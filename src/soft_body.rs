@@ -0,0 +1,234 @@
+//! Soft bodies approximated with stock Rapier: a lattice of small balls held in shape by spring
+//! joints, wrapped in a mesh that deforms with it. Level entities and prefabs get one through
+//! their `soft_body` field, as the `jelly` prefab does.
+//!
+//! The balls can't turn, so each spring pulls a ball back to where it sits next to its neighbor
+//! in the rest shape, resisting shearing as well as stretching. The mesh is an ellipsoid skinned
+//! to the lattice: each vertex follows the lattice cell it started in, blending its eight corners.
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy::render::view::NoFrustumCulling;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_set::GameSet;
+use crate::surface::Surface;
+
+pub struct SoftBodyPlugin;
+
+impl Plugin for SoftBodyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            update_soft_body_meshes.in_set(GameSet::PhysicsWriteback),
+        );
+    }
+}
+
+/// A soft body's lattice and springs.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct SoftBodyData {
+    /// Balls along x, y and z, at least 2 each way.
+    pub size: UVec3,
+    /// Distance between neighboring balls at rest, in meters.
+    pub spacing: f32,
+    pub radius: f32,
+    /// Mass of each ball, in kilograms.
+    pub mass: f32,
+    /// How hard the springs pull, and how quickly they stop wobbling.
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+/// The skinned mesh of a soft body, following its lattice. The balls are children of the soft
+/// body, so their transforms are relative to it like the mesh.
+#[derive(Component)]
+pub struct SoftBody {
+    /// Balls of the lattice, along x first, then y, then z.
+    pub nodes: Vec<Entity>,
+    /// The balls each vertex follows, with their weights.
+    skin: Vec<[(usize, f32); 8]>,
+}
+
+impl SoftBodyData {
+    fn size(&self) -> UVec3 {
+        self.size.max(UVec3::splat(2))
+    }
+
+    fn index(&self, node: UVec3) -> usize {
+        let size = self.size();
+        (node.x + node.y * size.x + node.z * size.x * size.y) as usize
+    }
+
+    /// Where the ball `node` sits at rest, relative to the soft body.
+    fn rest_position(&self, node: UVec3) -> Vec3 {
+        (node.as_vec3() - (self.size() - 1).as_vec3() / 2.0) * self.spacing
+    }
+
+    /// Makes `entity` a soft body: spawns its lattice as children, and gives it the skinned mesh
+    /// with `material`. The balls get `surface`, if any.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        entity: Entity,
+        material: Handle<StandardMaterial>,
+        surface: Option<Surface>,
+    ) {
+        let size = self.size();
+        let lattice = || {
+            (0..size.z).flat_map(move |z| {
+                (0..size.y).flat_map(move |y| (0..size.x).map(move |x| UVec3::new(x, y, z)))
+            })
+        };
+        let nodes: Vec<Entity> = lattice()
+            .map(|node| {
+                let mut ball = commands.spawn((
+                    TransformBundle::from_transform(Transform::from_translation(
+                        self.rest_position(node),
+                    )),
+                    RigidBody::Dynamic,
+                    Collider::ball(self.radius),
+                    ColliderMassProperties::Mass(self.mass),
+                    LockedAxes::ROTATION_LOCKED,
+                    Velocity::default(),
+                    Name::new("Soft body node"),
+                ));
+                if let Some(surface) = surface {
+                    ball.insert(surface);
+                }
+                ball.id()
+            })
+            .collect();
+
+        // A spring to each neighbor along +x, +y and +z, on a child of the neighbor since an
+        // entity carries one joint.
+        for node in lattice() {
+            for axis in [UVec3::X, UVec3::Y, UVec3::Z] {
+                let neighbor = node + axis;
+                if neighbor.cmpge(size).any() {
+                    continue;
+                }
+                let spring = [JointAxis::X, JointAxis::Y, JointAxis::Z].into_iter().fold(
+                    GenericJointBuilder::new(JointAxesMask::empty())
+                        .local_anchor1(axis.as_vec3() * self.spacing)
+                        .local_anchor2(Vec3::ZERO),
+                    |spring, joint_axis| {
+                        spring.motor_position(joint_axis, 0.0, self.stiffness, self.damping)
+                    },
+                );
+                let joint = commands
+                    .spawn(ImpulseJoint::new(nodes[self.index(node)], spring))
+                    .id();
+                commands
+                    .entity(nodes[self.index(neighbor)])
+                    .add_child(joint);
+            }
+        }
+
+        // An ellipsoid reaching the outside of the outer balls.
+        let half_extents = self.rest_position(size - 1) + self.radius;
+        let mut mesh: Mesh = shape::UVSphere {
+            radius: 1.0,
+            sectors: 24,
+            stacks: 16,
+        }
+        .into();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return;
+        };
+        let skin = positions
+            .iter()
+            .map(|&position| {
+                let position = Vec3::from(position) * half_extents;
+                // Vertices past the outer balls extrapolate from the outermost cell.
+                let lattice_position = position / self.spacing + (size - 1).as_vec3() / 2.0;
+                let cell = lattice_position
+                    .floor()
+                    .clamp(Vec3::ZERO, (size - 2).as_vec3());
+                let t = lattice_position - cell;
+                let cell = cell.as_uvec3();
+                let mut weights = [(0, 0.0); 8];
+                for (corner, weight) in weights.iter_mut().enumerate() {
+                    let offset = UVec3::new(
+                        corner as u32 & 1,
+                        (corner as u32 >> 1) & 1,
+                        (corner as u32 >> 2) & 1,
+                    );
+                    let blend = Vec3::select(offset.cmpeq(UVec3::ONE), t, 1.0 - t);
+                    *weight = (self.index(cell + offset), blend.x * blend.y * blend.z);
+                }
+                weights
+            })
+            .collect();
+        let soft_body = SoftBody { nodes, skin };
+        let rest_positions: Vec<Vec3> = lattice().map(|node| self.rest_position(node)).collect();
+        deform(&mut mesh, &soft_body, |node| rest_positions[node]);
+
+        commands
+            .entity(entity)
+            .insert((
+                meshes.add(mesh),
+                material,
+                VisibilityBundle::default(),
+                // The mesh deforms without its entity moving, so its bounds can't be trusted.
+                NoFrustumCulling,
+            ))
+            .push_children(&soft_body.nodes)
+            .insert(soft_body);
+    }
+}
+
+/// Moves the vertices of `mesh` to follow `soft_body`, with the balls at `node_position`, and
+/// recomputes its smooth normals.
+fn deform(mesh: &mut Mesh, soft_body: &SoftBody, node_position: impl Fn(usize) -> Vec3) {
+    let positions: Vec<Vec3> = soft_body
+        .skin
+        .iter()
+        .map(|weights| {
+            weights
+                .iter()
+                .map(|&(node, weight)| node_position(node) * weight)
+                .sum()
+        })
+        .collect();
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    if let Some(Indices::U32(indices)) = mesh.indices() {
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| triangle[corner] as usize);
+            // Unnormalized, so larger triangles weigh more.
+            let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+            for vertex in [a, b, c] {
+                normals[vertex] += normal;
+            }
+        }
+    }
+    let normals: Vec<Vec3> = normals.into_iter().map(Vec3::normalize_or_zero).collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+}
+
+fn update_soft_body_meshes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    soft_bodies: Query<(&SoftBody, &Handle<Mesh>)>,
+    nodes: Query<&Transform>,
+) {
+    for (soft_body, handle) in &soft_bodies {
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+        // Transforms rather than global transforms, which aren't propagated yet.
+        let Ok(positions) = soft_body
+            .nodes
+            .iter()
+            .map(|&node| nodes.get(node).map(|transform| transform.translation))
+            .collect::<Result<Vec<Vec3>, _>>()
+        else {
+            continue;
+        };
+        deform(mesh, soft_body, |node| positions[node]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Positional audio: sounds in the world pan and fade with their position relative to the
+//! [`MainCamera`].
+//!
+//! One-shot sounds are played at a point with [`play_at`]. Moving bodies carry a [`MotionSound`],
+//! a loop that follows the body and gets louder the faster it moves.
+
+use bevy::audio::{PlaybackMode, Volume};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::audio_mixer::{AudioChannel, AudioMixer};
+use crate::camera::MainCamera;
+use crate::pause_menu::PauseState;
+
+/// Distance between the listener's ears, in meters.
+const EAR_GAP: f32 = 0.3;
+/// Speed (m/s) at which a motion sound reaches full volume.
+const FULL_VOLUME_SPEED: f32 = 8.0;
+
+pub struct SpatialAudioPlugin;
+
+impl Plugin for SpatialAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (add_listener, attach_motion_sounds, update_motion_sounds),
+        );
+    }
+}
+
+/// A looping sound following a moving body, louder and higher the faster the body moves.
+#[derive(Component)]
+pub struct MotionSound(pub Handle<AudioSource>);
+
+/// The child entity a [`MotionSound`] plays from.
+#[derive(Component)]
+struct MotionEmitter;
+
+/// Plays a one-shot sound at `position`.
+pub fn play_at(
+    commands: &mut Commands,
+    position: Vec3,
+    source: Handle<AudioSource>,
+    settings: PlaybackSettings,
+) {
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings {
+                spatial: true,
+                ..settings
+            },
+        },
+        TransformBundle::from_transform(Transform::from_translation(position)),
+    ));
+}
+
+fn add_listener(mut commands: Commands, cameras: Query<Entity, Added<MainCamera>>) {
+    for camera in &cameras {
+        commands
+            .entity(camera)
+            .insert(SpatialListener::new(EAR_GAP));
+    }
+}
+
+fn attach_motion_sounds(
+    mut commands: Commands,
+    bodies: Query<(Entity, &MotionSound), Added<MotionSound>>,
+) {
+    for (entity, sound) in &bodies {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                AudioBundle {
+                    source: sound.0.clone(),
+                    settings: PlaybackSettings {
+                        mode: PlaybackMode::Loop,
+                        volume: Volume::new_relative(0.0),
+                        spatial: true,
+                        ..default()
+                    },
+                },
+                SpatialBundle::default(),
+                MotionEmitter,
+            ));
+        });
+    }
+}
+
+/// Follows each body's speed; bodies keep their velocity while paused, so the loops go silent.
+fn update_motion_sounds(
+    mixer: Res<AudioMixer>,
+    pause: Res<State<PauseState>>,
+    bodies: Query<&Velocity, With<MotionSound>>,
+    emitters: Query<(&Parent, &SpatialAudioSink), With<MotionEmitter>>,
+) {
+    for (parent, sink) in &emitters {
+        let speed = match pause.get() {
+            PauseState::Running => bodies
+                .get(parent.get())
+                .map_or(0.0, |velocity| velocity.linvel.length()),
+            _ => 0.0,
+        };
+        let loudness = (speed / FULL_VOLUME_SPEED).clamp(0.0, 1.0);
+        sink.set_volume(mixer.volume(AudioChannel::Sfx, loudness));
+        sink.set_speed(0.8 + 0.4 * loudness);
+    }
+}
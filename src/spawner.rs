@@ -0,0 +1,99 @@
+//! Spawners keep dropping a prefab into the level, like crates or balls for the sandbox. Level
+//! entities get one through their `spawner` field.
+//!
+//! A [`Spawner`] spawns its prefab every interval at a random point of its area, and stops while
+//! as many of its entities as its cap are alive, starting again as soon as one goes away. Spawned
+//! entities can be given a [`Lifetime`], so a spawner left running keeps replacing them instead of
+//! filling up and stopping for good. Spawn points and sizes come from the [`WorldRng`], and the
+//! [`Difficulty`] shortens or lengthens the intervals.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::conditions::in_gameplay;
+use crate::difficulty::Difficulty;
+use crate::level_data::spawn_prefab;
+use crate::lifetime::Lifetime;
+use crate::materials::MaterialLibrary;
+use crate::world_rng::WorldRng;
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            run_spawners.run_if(in_gameplay().and_then(resource_exists::<MaterialLibrary>())),
+        );
+    }
+}
+
+/// Spawns the [`Prefabs`](crate::level_data::Prefabs) entry `prefab` around this entity every
+/// `interval` seconds, while fewer than `max_alive` of its entities are alive.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Spawner {
+    pub prefab: String,
+    pub interval: f32,
+    pub max_alive: usize,
+    /// Half the size of the box entities are spawned in, around the spawner and turned with it.
+    #[serde(default)]
+    pub area: Vec3,
+    /// Smallest and largest scale of spawned entities, picked at random, or 1.
+    #[serde(default)]
+    pub scale: Option<(f32, f32)>,
+    /// Seconds each spawned entity lasts before it's despawned, or forever.
+    #[serde(default)]
+    pub lifetime: Option<f32>,
+    /// Seconds since the last spawn, up to the interval.
+    #[serde(skip)]
+    elapsed: f32,
+}
+
+/// An entity spawned by the [`Spawner`] entity.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SpawnedBy(pub Entity);
+
+fn run_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<WorldRng>,
+    mut spawners: Query<(Entity, &mut Spawner, &GlobalTransform)>,
+    spawned: Query<&SpawnedBy>,
+) {
+    for (entity, mut spawner, transform) in &mut spawners {
+        // Held at the interval while at the cap, so the next one comes as soon as there's room.
+        let interval = spawner.interval / difficulty.spawn_rate();
+        spawner.elapsed = (spawner.elapsed + time.delta_seconds()).min(interval);
+        if spawner.elapsed < interval {
+            continue;
+        }
+        let alive = spawned.iter().filter(|by| by.0 == entity).count();
+        if alive >= spawner.max_alive {
+            continue;
+        }
+        spawner.elapsed = 0.0;
+
+        let scale = spawner
+            .scale
+            .map_or(1.0, |(smallest, largest)| rng.range(smallest, largest));
+        let at = Transform::from_translation(transform.transform_point(rng.in_box(spawner.area)))
+            .with_scale(Vec3::splat(scale));
+        let prefab = spawner.prefab.clone();
+        let lifetime = spawner.lifetime;
+        commands.add(move |world: &mut World| {
+            let spawned = match spawn_prefab(world, &prefab, at) {
+                Ok(spawned) => spawned,
+                Err(error) => {
+                    warn!("spawner could not spawn `{prefab}`: {error}");
+                    return;
+                }
+            };
+            let mut spawned = world.entity_mut(spawned);
+            spawned.insert(SpawnedBy(entity));
+            if let Some(lifetime) = lifetime {
+                spawned.insert(Lifetime::from_seconds(lifetime));
+            }
+        });
+    }
+}
@@ -0,0 +1,47 @@
+//! The game's top-level [`MyStates`] and the loading state that fills the asset collections.
+
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::{AudioAssets, Models, TextureAssets, UiAssets, MANIFEST_PATH};
+use crate::materials::MaterialLibrary;
+
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<MyStates>().add_loading_state(
+            LoadingState::new(MyStates::AssetLoading)
+                .continue_to_state(MyStates::Next)
+                .on_failure_continue_to_state(MyStates::FailedLoading)
+                .with_dynamic_assets_file::<StandardDynamicAssetCollection>(MANIFEST_PATH)
+                .load_collection::<Models>()
+                .load_collection::<AudioAssets>()
+                .load_collection::<TextureAssets>()
+                .load_collection::<UiAssets>()
+                .init_resource::<MaterialLibrary>(),
+        );
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States, Serialize, Deserialize)]
+pub enum MyStates {
+    #[default]
+    AssetLoading,
+    /// The asset manifest or an asset collection failed to load; see [`crate::load_failure`].
+    FailedLoading,
+    Next,
+    /// Hosting or joining a networked game; see [`crate::lobby`].
+    Lobby,
+    /// Waiting for the server to answer.
+    Connecting,
+    /// Connecting failed or the connection was lost.
+    ConnectionFailed,
+    InGame,
+    GameOver,
+    /// Placing prefabs in the sandbox; see [`crate::editor`].
+    Editor,
+    /// A system panicked; see [`crate::crash`].
+    Crashed,
+}
@@ -0,0 +1,81 @@
+//! Lifetime statistics, persisted to [`STATS_PATH`] when the game exits: sessions started, time
+//! played and deaths.
+
+use std::fs;
+use std::path::Path;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::conditions::in_gameplay;
+use crate::health::PlayerDied;
+
+pub const STATS_PATH: &str = "saves/stats.ron";
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut stats = Stats::load();
+        stats.sessions += 1;
+        app.insert_resource(stats)
+            .add_systems(
+                Update,
+                (
+                    count_play_time.run_if(in_gameplay()),
+                    count_deaths.run_if(on_event::<PlayerDied>()),
+                ),
+            )
+            .add_systems(Last, save_stats.run_if(on_event::<AppExit>()));
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Stats {
+    pub sessions: u32,
+    /// Seconds spent playing, not counting menus and pauses.
+    pub play_time: f64,
+    pub deaths: u32,
+}
+
+impl Stats {
+    /// Reads the stats file, starting over if it is missing or invalid.
+    pub fn load() -> Self {
+        match fs::read_to_string(STATS_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("ignoring invalid {STATS_PATH}: {error}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = Path::new(STATS_PATH).parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                fs::write(STATS_PATH, contents).map_err(|error| error.to_string())
+            });
+        match result {
+            Ok(()) => info!("saved stats to {STATS_PATH}"),
+            Err(error) => error!("could not save {STATS_PATH}: {error}"),
+        }
+    }
+}
+
+fn count_play_time(time: Res<Time>, mut stats: ResMut<Stats>) {
+    stats.play_time += time.delta_seconds_f64();
+}
+
+fn count_deaths(mut died: EventReader<PlayerDied>, mut stats: ResMut<Stats>) {
+    stats.deaths += died.read().count() as u32;
+}
+
+fn save_stats(stats: Res<Stats>) {
+    stats.save();
+}
@@ -0,0 +1,227 @@
+//! Chunked level streaming, used when `--level` names a `.chunks.ron` file instead of a glTF
+//! scene.
+//!
+//! The file is a [`ChunkManifest`] asset listing the level's chunks, square cells of
+//! [`ChunkManifest::chunk_size`] meters on the ground plane, each with its own scene authored in
+//! level coordinates. Chunks are loaded through the asset server and spawned as [`Level`] scenes,
+//! which gives them colliders and the floor material like the rest of the level, once the player
+//! is within [`LOAD_MARGIN`] beyond the
+//! [`PhysicsSettings::activation_radius`](crate::settings::PhysicsSettings::activation_radius).
+//! Bodies are only woken inside that radius, so a chunk's colliders have time to appear before the
+//! props on it start to fall. Chunks are despawned only once [`UNLOAD_MARGIN`] farther away again,
+//! so walking along a chunk border doesn't load and unload the same chunk over and over.
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadState};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::launch_options::LaunchOptions;
+use crate::level::Level;
+use crate::level_data::LevelDataError;
+use crate::settings::Settings;
+use crate::{MyStates, Player};
+
+/// Suffix of level paths that are streamed.
+pub const CHUNKS_SUFFIX: &str = ".chunks.ron";
+/// Chunks are loaded this many meters beyond the physics activation radius.
+pub const LOAD_MARGIN: f32 = 20.0;
+/// Loaded chunks are unloaded this many meters beyond the distance they are loaded at.
+pub const UNLOAD_MARGIN: f32 = 20.0;
+
+pub struct LevelStreamingPlugin;
+
+impl Plugin for LevelStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ChunkManifest>()
+            .register_asset_loader(ChunkManifestLoader)
+            .add_systems(
+                OnEnter(MyStates::Next),
+                load_chunk_manifest.run_if(run_once()),
+            )
+            .add_systems(
+                Update,
+                (
+                    start_streaming.run_if(resource_exists::<PendingChunkManifest>()),
+                    stream_chunks.run_if(resource_exists::<StreamedLevel>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Whether `--level` asks for a streamed level rather than a single scene.
+pub fn is_streamed(level: &str) -> bool {
+    level.ends_with(CHUNKS_SUFFIX)
+}
+
+/// Contents of a `.chunks.ron` file.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct ChunkManifest {
+    /// Side of a chunk, in meters.
+    pub chunk_size: f32,
+    pub chunks: Vec<ChunkEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ChunkEntry {
+    /// Cell of the chunk: it covers `x * chunk_size` to `(x + 1) * chunk_size` and the same on z.
+    pub cell: (i32, i32),
+    /// Asset path of the chunk's scene.
+    pub scene: String,
+}
+
+/// The chunks of the streamed level and the entities of those loaded.
+#[derive(Resource)]
+pub struct StreamedLevel {
+    chunk_size: f32,
+    scenes: HashMap<(i32, i32), String>,
+    loaded: HashMap<(i32, i32), Entity>,
+}
+
+impl StreamedLevel {
+    /// Number of chunks currently spawned.
+    pub fn loaded(&self) -> usize {
+        self.loaded.len()
+    }
+
+    /// Distance on the ground plane from `position` to the nearest point of chunk `cell`.
+    fn distance(&self, cell: (i32, i32), position: Vec3) -> f32 {
+        let min = Vec2::new(cell.0 as f32, cell.1 as f32) * self.chunk_size;
+        let max = min + Vec2::splat(self.chunk_size);
+        let point = position.xz();
+        point.clamp(min, max).distance(point)
+    }
+}
+
+/// Marks the root of a streamed chunk.
+#[derive(Component)]
+pub struct LevelChunk {
+    pub cell: (i32, i32),
+}
+
+/// The chunk manifest being loaded; streaming starts once it is ready.
+#[derive(Resource)]
+struct PendingChunkManifest(Handle<ChunkManifest>);
+
+#[derive(Default)]
+struct ChunkManifestLoader;
+
+impl AssetLoader for ChunkManifestLoader {
+    type Asset = ChunkManifest;
+    type Settings = ();
+    type Error = LevelDataError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<ChunkManifest, LevelDataError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(LevelDataError::Io)?;
+            ron::de::from_bytes(&bytes).map_err(LevelDataError::Ron)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["chunks.ron"]
+    }
+}
+
+fn load_chunk_manifest(
+    mut commands: Commands,
+    options: Res<LaunchOptions>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Some(level) = options.level.as_deref().filter(|level| is_streamed(level)) {
+        commands.insert_resource(PendingChunkManifest(asset_server.load(level.to_string())));
+    }
+}
+
+fn start_streaming(
+    mut commands: Commands,
+    pending: Res<PendingChunkManifest>,
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<ChunkManifest>>,
+) {
+    let Some(manifest) = manifests.get(&pending.0) else {
+        if asset_server.get_load_state(&pending.0) == Some(LoadState::Failed) {
+            error!("could not load the chunk manifest");
+            commands.remove_resource::<PendingChunkManifest>();
+        }
+        return;
+    };
+    commands.remove_resource::<PendingChunkManifest>();
+    info!("streaming {} chunks", manifest.chunks.len());
+    commands.insert_resource(StreamedLevel {
+        chunk_size: manifest.chunk_size,
+        scenes: manifest
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.cell, chunk.scene.clone()))
+            .collect(),
+        loaded: HashMap::new(),
+    });
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    mut level: ResMut<StreamedLevel>,
+    player: Query<&GlobalTransform, With<Player>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let position = player.translation();
+    // Beyond where bodies are woken, so the ground under them exists when they start to move.
+    let load_distance = settings.physics.activation_radius + LOAD_MARGIN;
+    let unload_distance = load_distance + UNLOAD_MARGIN;
+
+    let to_unload: Vec<(i32, i32)> = level
+        .loaded
+        .keys()
+        .copied()
+        .filter(|cell| level.distance(*cell, position) > unload_distance)
+        .collect();
+    for cell in to_unload {
+        if let Some(entity) = level.loaded.remove(&cell) {
+            debug!("unloading chunk {cell:?}");
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let to_load: Vec<(i32, i32)> = level
+        .scenes
+        .keys()
+        .copied()
+        .filter(|cell| {
+            !level.loaded.contains_key(cell) && level.distance(*cell, position) < load_distance
+        })
+        .collect();
+    for cell in to_load {
+        debug!("loading chunk {cell:?}");
+        // Loads in the background; the scene spawns once the asset is ready.
+        let entity = commands
+            .spawn((
+                SceneBundle {
+                    scene: asset_server.load(&level.scenes[&cell]),
+                    ..default()
+                },
+                RigidBody::Fixed,
+                Level,
+                LevelChunk { cell },
+                Name::new(format!("Chunk {} {}", cell.0, cell.1)),
+            ))
+            .id();
+        level.loaded.insert(cell, entity);
+    }
+}
@@ -0,0 +1,68 @@
+//! What bodies are made of, for picking impact and footstep sounds.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::AudioAssets;
+
+/// The physical material of a collider; colliders without one count as [`Surface::Concrete`].
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Surface {
+    #[default]
+    Concrete,
+    Wood,
+    Rubber,
+    Glass,
+}
+
+impl Surface {
+    pub const ALL: [Surface; 4] = [
+        Surface::Concrete,
+        Surface::Wood,
+        Surface::Rubber,
+        Surface::Glass,
+    ];
+
+    /// Higher is harder; the harder surface of a pair decides what an impact sounds like.
+    pub fn hardness(self) -> u8 {
+        match self {
+            Surface::Rubber => 0,
+            Surface::Wood => 1,
+            Surface::Concrete => 2,
+            Surface::Glass => 3,
+        }
+    }
+
+    /// Playback speed of sounds involving this surface.
+    pub fn pitch(self) -> f32 {
+        match self {
+            Surface::Rubber => 0.85,
+            Surface::Wood => 1.0,
+            Surface::Concrete => 0.95,
+            Surface::Glass => 1.15,
+        }
+    }
+
+    /// Footstep variations for walking on this surface.
+    pub fn footstep_sounds(self, audio: &AudioAssets) -> &[Handle<AudioSource>] {
+        match self {
+            Surface::Wood => &audio.footsteps_wood,
+            // Rubber and glass only differ in pitch.
+            Surface::Concrete | Surface::Rubber | Surface::Glass => &audio.footsteps_concrete,
+        }
+    }
+
+    /// Whether characters kick up dust landing or running on it.
+    pub fn dusty(self) -> bool {
+        matches!(self, Surface::Concrete | Surface::Wood)
+    }
+
+    pub fn impact_sound(self, audio: &AudioAssets) -> Handle<AudioSource> {
+        match self {
+            Surface::Concrete => audio.impact_concrete.clone(),
+            Surface::Wood => audio.impact_wood.clone(),
+            Surface::Rubber => audio.impact_rubber.clone(),
+            Surface::Glass => audio.impact_glass.clone(),
+        }
+    }
+}
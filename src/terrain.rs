@@ -0,0 +1,211 @@
+//! Terrain built from a grayscale heightmap at runtime: `terrain <image.png> <scale> [height]` in
+//! the console spawns a heightfield collider and a matching displaced mesh, centered under the
+//! camera.
+//!
+//! Each pixel is a vertex `scale` meters from its neighbors, black at the bottom and white at
+//! `height` meters. Colored images use their red channel; 16-bit images keep their precision.
+
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy_rapier3d::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::materials::MaterialLibrary;
+use crate::surface::Surface;
+
+/// Height of white pixels when the command doesn't give one, in meters.
+const DEFAULT_HEIGHT: f32 = 10.0;
+/// Widest and longest heightmap accepted, in pixels.
+const MAX_SIZE: u32 = 1024;
+
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command(
+            "terrain",
+            "terrain <image.png> <scale> [height] - build terrain from a heightmap, scale meters apart",
+            terrain_command,
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct Terrain;
+
+/// Heights from 0 to 1, one per pixel, row by row.
+pub struct Heightmap {
+    pub width: usize,
+    pub depth: usize,
+    pub heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Decodes the image file at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|error| format!("could not read {path:?}: {error}"))?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("png");
+        // Not sRGB, so the bytes are the stored values.
+        let image = Image::from_buffer(
+            &bytes,
+            ImageType::Extension(extension),
+            CompressedImageFormats::NONE,
+            false,
+            ImageSampler::Default,
+        )
+        .map_err(|error| format!("could not decode {path:?}: {error}"))?;
+        Self::from_image(&image)
+    }
+
+    pub fn from_image(image: &Image) -> Result<Self, String> {
+        let size = image.size();
+        if size.x < 2 || size.y < 2 || size.x > MAX_SIZE || size.y > MAX_SIZE {
+            return Err(format!(
+                "the image is {}x{}, it has to be from 2x2 to {MAX_SIZE}x{MAX_SIZE}",
+                size.x, size.y
+            ));
+        }
+        let u16_at = |pixel: &[u8]| u16::from_le_bytes([pixel[0], pixel[1]]) as f32 / 65535.0;
+        let heights = match image.texture_descriptor.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => image
+                .data
+                .chunks_exact(4)
+                .map(|pixel| pixel[0] as f32 / 255.0)
+                .collect(),
+            TextureFormat::R16Uint => image.data.chunks_exact(2).map(u16_at).collect(),
+            TextureFormat::Rg16Uint => image.data.chunks_exact(4).map(u16_at).collect(),
+            TextureFormat::Rgba16Unorm => image.data.chunks_exact(8).map(u16_at).collect(),
+            format => return Err(format!("unsupported pixel format {format:?}")),
+        };
+        Ok(Self {
+            width: size.x as usize,
+            depth: size.y as usize,
+            heights,
+        })
+    }
+
+    fn height(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.width + x]
+    }
+
+    /// The terrain collider, `size` meters across with white at `size.y`.
+    pub fn collider(&self, size: Vec3) -> Collider {
+        // Rapier wants rows along z, in column-major order.
+        let heights = (0..self.width)
+            .flat_map(|x| (0..self.depth).map(move |z| (x, z)))
+            .map(|(x, z)| self.height(x, z))
+            .collect();
+        Collider::heightfield(heights, self.depth, self.width, size)
+    }
+
+    /// The terrain mesh, matching [`Heightmap::collider`] triangle for triangle.
+    pub fn mesh(&self, size: Vec3) -> Mesh {
+        let (width, depth) = (self.width, self.depth);
+        let position = |x: usize, z: usize| {
+            Vec3::new(
+                (x as f32 / (width - 1) as f32 - 0.5) * size.x,
+                self.height(x, z) * size.y,
+                (z as f32 / (depth - 1) as f32 - 0.5) * size.z,
+            )
+        };
+        let grid = || (0..depth).flat_map(|z| (0..width).map(move |x| (x, z)));
+
+        let positions: Vec<Vec3> = grid().map(|(x, z)| position(x, z)).collect();
+        // Smooth normals from the neighbors on either side, clamped at the edges.
+        let normals: Vec<Vec3> = grid()
+            .map(|(x, z)| {
+                let along_x =
+                    position((x + 1).min(width - 1), z) - position(x.saturating_sub(1), z);
+                let along_z =
+                    position(x, (z + 1).min(depth - 1)) - position(x, z.saturating_sub(1));
+                along_z.cross(along_x).normalize_or_zero()
+            })
+            .collect();
+        let uvs: Vec<[f32; 2]> = grid()
+            .map(|(x, z)| [x as f32 / (width - 1) as f32, z as f32 / (depth - 1) as f32])
+            .collect();
+        // Split along the same diagonal as Rapier's heightfield cells.
+        let index = |x: usize, z: usize| (z * width + x) as u32;
+        let indices: Vec<u32> = (0..depth - 1)
+            .flat_map(|z| (0..width - 1).map(move |x| (x, z)))
+            .flat_map(|(x, z)| {
+                [
+                    index(x, z),
+                    index(x, z + 1),
+                    index(x + 1, z),
+                    index(x, z + 1),
+                    index(x + 1, z + 1),
+                    index(x + 1, z),
+                ]
+            })
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+fn terrain_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let path = *args.first().ok_or("missing argument <image.png>")?;
+    let scale: f32 = parse_arg(args, 1, "scale")?;
+    let height: f32 = if args.len() > 2 {
+        parse_arg(args, 2, "height")?
+    } else {
+        DEFAULT_HEIGHT
+    };
+    if scale <= 0.0 || height < 0.0 {
+        return Err("the scale has to be positive and the height not negative".to_string());
+    }
+
+    let heightmap = Heightmap::load(Path::new(path))?;
+    let size = Vec3::new(
+        (heightmap.width - 1) as f32 * scale,
+        height,
+        (heightmap.depth - 1) as f32 * scale,
+    );
+    let center = world
+        .query_filtered::<&GlobalTransform, With<MainCamera>>()
+        .get_single(world)
+        .map_or(Vec3::ZERO, |camera| {
+            camera.translation() * Vec3::new(1.0, 0.0, 1.0)
+        });
+    let mesh = world
+        .resource_mut::<Assets<Mesh>>()
+        .add(heightmap.mesh(size));
+    let material = match world.get_resource::<MaterialLibrary>() {
+        Some(library) => library.floor.clone(),
+        None => world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::DARK_GREEN.into()),
+    };
+    world.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            transform: Transform::from_translation(center),
+            ..default()
+        },
+        RigidBody::Fixed,
+        heightmap.collider(size),
+        Surface::Concrete,
+        Terrain,
+        Name::new("Terrain"),
+    ));
+    Ok(format!(
+        "built {}x{} m of terrain from {path}",
+        size.x, size.z
+    ))
+}
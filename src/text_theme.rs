@@ -0,0 +1,109 @@
+//! The UI font and shared text styles.
+//!
+//! The font is loaded with the other assets in [`UiAssets`]; until then [`TextTheme`] uses Bevy's
+//! built-in font, and text spawned before loading finished is switched over afterwards.
+
+use bevy::prelude::*;
+
+use crate::assets::UiAssets;
+use crate::MyStates;
+
+pub struct TextThemePlugin;
+
+impl Plugin for TextThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextTheme>()
+            .add_systems(OnEnter(MyStates::Next), use_ui_font)
+            .add_systems(Update, apply_theme_font);
+    }
+}
+
+/// Font, sizes and colors used by all UI text.
+#[derive(Resource, Clone, Debug)]
+pub struct TextTheme {
+    pub font: Handle<Font>,
+    pub title_size: f32,
+    pub heading_size: f32,
+    pub body_size: f32,
+    pub small_size: f32,
+    pub color: Color,
+    /// Color of secondary text such as labels and history.
+    pub muted_color: Color,
+}
+
+impl Default for TextTheme {
+    fn default() -> Self {
+        Self {
+            font: Handle::default(),
+            title_size: 48.0,
+            heading_size: 24.0,
+            body_size: 20.0,
+            small_size: 16.0,
+            color: Color::WHITE,
+            muted_color: Color::GRAY,
+        }
+    }
+}
+
+impl TextTheme {
+    pub fn style(&self, font_size: f32) -> TextStyle {
+        TextStyle {
+            font: self.font.clone(),
+            font_size,
+            color: self.color,
+        }
+    }
+
+    pub fn title(&self) -> TextStyle {
+        self.style(self.title_size)
+    }
+
+    pub fn heading(&self) -> TextStyle {
+        self.style(self.heading_size)
+    }
+
+    pub fn body(&self) -> TextStyle {
+        self.style(self.body_size)
+    }
+
+    pub fn small(&self) -> TextStyle {
+        self.style(self.small_size)
+    }
+
+    /// [`TextTheme::small`] in the muted color.
+    pub fn muted(&self) -> TextStyle {
+        TextStyle {
+            color: self.muted_color,
+            ..self.small()
+        }
+    }
+}
+
+fn use_ui_font(ui_assets: Res<UiAssets>, mut theme: ResMut<TextTheme>) {
+    theme.font = ui_assets.font.clone();
+}
+
+/// Replaces Bevy's built-in font with the theme's font in new text, and in all text once the
+/// theme's font is loaded.
+fn apply_theme_font(theme: Res<TextTheme>, mut texts: Query<&mut Text>) {
+    let default_font = Handle::<Font>::default();
+    if theme.font == default_font {
+        return;
+    }
+    for mut text in &mut texts {
+        if !theme.is_changed() && !text.is_added() {
+            continue;
+        }
+        if text
+            .sections
+            .iter()
+            .any(|section| section.style.font == default_font)
+        {
+            for section in &mut text.sections {
+                if section.style.font == default_font {
+                    section.style.font = theme.font.clone();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Short confirmation messages shown at the bottom of the screen, sent as [`ToastEvent`]s.
+
+use bevy::prelude::*;
+
+use crate::text_theme::TextTheme;
+
+/// Seconds a toast stays on screen, including its fade-out.
+const TOAST_SECONDS: f32 = 2.5;
+const FADE_SECONDS: f32 = 0.5;
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToastEvent>()
+            .add_systems(Startup, spawn_toast_area)
+            .add_systems(Update, (show_toasts, expire_toasts).chain());
+    }
+}
+
+/// Shows its text as a toast.
+#[derive(Event, Clone, Debug)]
+pub struct ToastEvent(pub String);
+
+/// Column the toasts are stacked in.
+#[derive(Component)]
+struct ToastArea;
+
+/// Seconds left before the toast disappears.
+#[derive(Component)]
+struct Toast(f32);
+
+fn spawn_toast_area(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(120.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(8),
+            ..default()
+        },
+        ToastArea,
+    ));
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    theme: Res<TextTheme>,
+    mut events: EventReader<ToastEvent>,
+    areas: Query<Entity, With<ToastArea>>,
+) {
+    let Ok(area) = areas.get_single() else {
+        return;
+    };
+    for event in events.read() {
+        let toast = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                    ..default()
+                },
+                Toast(TOAST_SECONDS),
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(event.0.clone(), theme.body()));
+            })
+            .id();
+        commands.entity(area).add_child(toast);
+    }
+}
+
+/// Fades toasts out over their last [`FADE_SECONDS`] and removes them.
+fn expire_toasts(
+    mut commands: Commands,
+    // Real time, so toasts also disappear while the game is paused.
+    time: Res<Time<Real>>,
+    mut toasts: Query<(Entity, &mut Toast, &mut BackgroundColor, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, mut toast, mut background, children) in &mut toasts {
+        toast.0 -= time.delta_seconds();
+        if toast.0 <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = (toast.0 / FADE_SECONDS).min(1.0);
+        background.0.set_a(0.6 * alpha);
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                for section in &mut text.sections {
+                    section.style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
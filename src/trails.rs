@@ -0,0 +1,231 @@
+//! Trails behind fast-moving bodies, like thrown projectiles: a [`Trail`] records where its entity
+//! has been and draws a ribbon through those points, turned to face the camera.
+//!
+//! Points are only recorded while the entity moves faster than the trail's minimum speed, and each
+//! one lasts the trail's lifetime, so the ribbon narrows and fades out towards its tail and shrinks
+//! away once the body slows down. The ribbon is a mesh of its own, rebuilt every frame after the
+//! physics step, and goes away with the trail.
+
+use std::collections::VecDeque;
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::view::NoFrustumCulling;
+
+use crate::camera::MainCamera;
+use crate::game_set::GameSet;
+use crate::pool::Pooled;
+
+/// Points closer than this to the last one, in meters, aren't recorded.
+const MIN_SEGMENT: f32 = 0.05;
+
+pub struct TrailsPlugin;
+
+impl Plugin for TrailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RibbonMaterial>().add_systems(
+            PostUpdate,
+            (record_trails, update_ribbons, despawn_orphaned_ribbons)
+                .chain()
+                .in_set(GameSet::PhysicsWriteback),
+        );
+    }
+}
+
+/// Draws a ribbon behind its entity, which should sit at the root of the hierarchy.
+#[derive(Component, Clone, Debug)]
+pub struct Trail {
+    /// Width of the ribbon at its head, in meters.
+    pub width: f32,
+    /// Seconds each point lasts.
+    pub lifetime: f32,
+    /// Below this speed, in meters per second, no points are recorded.
+    pub min_speed: f32,
+    pub color: Color,
+    /// Recorded positions, oldest first, and their age in seconds.
+    points: VecDeque<(Vec3, f32)>,
+    /// Where the entity was last frame.
+    last_position: Option<Vec3>,
+    /// The entity with the ribbon's mesh, once spawned.
+    ribbon: Option<Entity>,
+}
+
+impl Trail {
+    pub fn new(width: f32, lifetime: f32, color: Color) -> Self {
+        Self {
+            width,
+            lifetime,
+            min_speed: 3.0,
+            color,
+            points: VecDeque::new(),
+            last_position: None,
+            ribbon: None,
+        }
+    }
+}
+
+/// The mesh drawing the [`Trail`] of `owner`.
+#[derive(Component)]
+struct TrailRibbon {
+    owner: Entity,
+}
+
+/// Unlit and blended, taking its color from the ribbon's vertices.
+#[derive(Resource)]
+struct RibbonMaterial(Handle<StandardMaterial>);
+
+impl FromWorld for RibbonMaterial {
+    fn from_world(world: &mut World) -> Self {
+        Self(
+            world
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    cull_mode: None,
+                    ..default()
+                }),
+        )
+    }
+}
+
+/// Ages the trails' points and records where their entities are now. Released pooled entities
+/// drop their trail, so it doesn't stretch to where they are reused.
+fn record_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    material: Res<RibbonMaterial>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trails: Query<(Entity, &mut Trail, &Transform, Has<Pooled>)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut trail, transform, pooled) in &mut trails {
+        if trail.ribbon.is_none() {
+            let ribbon = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::new(PrimitiveTopology::TriangleList)),
+                        material: material.0.clone(),
+                        ..default()
+                    },
+                    // The mesh moves with the trail, not the entity's bounds.
+                    NoFrustumCulling,
+                    NotShadowCaster,
+                    TrailRibbon { owner: entity },
+                    Name::new("Trail"),
+                ))
+                .id();
+            trail.ribbon = Some(ribbon);
+        }
+        if pooled {
+            trail.points.clear();
+            trail.last_position = None;
+            continue;
+        }
+
+        let lifetime = trail.lifetime;
+        for (_, age) in &mut trail.points {
+            *age += dt;
+        }
+        while trail
+            .points
+            .front()
+            .is_some_and(|&(_, age)| age >= lifetime)
+        {
+            trail.points.pop_front();
+        }
+        let position = transform.translation;
+        let speed = trail
+            .last_position
+            .map_or(0.0, |last| last.distance(position) / dt.max(f32::EPSILON));
+        trail.last_position = Some(position);
+        let too_close = trail
+            .points
+            .back()
+            .is_some_and(|&(last, _)| last.distance(position) < MIN_SEGMENT);
+        if speed >= trail.min_speed && !too_close {
+            trail.points.push_back((position, 0.0));
+        }
+    }
+}
+
+/// Rebuilds each ribbon through its trail's points and the entity's position, as a strip facing
+/// the camera, narrowing and fading towards the oldest point.
+fn update_ribbons(
+    mut meshes: ResMut<Assets<Mesh>>,
+    trails: Query<(&Trail, &Transform)>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    mut ribbons: Query<(&TrailRibbon, &Handle<Mesh>, &mut Visibility)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    for (ribbon, handle, mut visibility) in &mut ribbons {
+        let Ok((trail, transform)) = trails.get(ribbon.owner) else {
+            continue;
+        };
+        let mut points: Vec<(Vec3, f32)> = trail.points.iter().copied().collect();
+        if points
+            .last()
+            .is_some_and(|&(point, _)| point != transform.translation)
+        {
+            points.push((transform.translation, 0.0));
+        }
+        if points.len() < 2 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+
+        let mut positions = Vec::with_capacity(points.len() * 2);
+        let mut normals = Vec::with_capacity(points.len() * 2);
+        let mut colors = Vec::with_capacity(points.len() * 2);
+        for (index, &(point, age)) in points.iter().enumerate() {
+            let previous = points[index.saturating_sub(1)].0;
+            let next = points[(index + 1).min(points.len() - 1)].0;
+            let to_camera = (camera.translation() - point).normalize_or_zero();
+            let left = 1.0 - (age / trail.lifetime).min(1.0);
+            let side = (next - previous).cross(to_camera).normalize_or_zero() * trail.width / 2.0;
+            let color = trail
+                .color
+                .with_a(trail.color.a() * left)
+                .as_linear_rgba_f32();
+            for offset in [side * left, -side * left] {
+                positions.push(point + offset);
+                normals.push(to_camera);
+                colors.push(color);
+            }
+        }
+        let indices: Vec<u32> = (0..points.len() as u32 - 1)
+            .flat_map(|segment| {
+                let a = segment * 2;
+                [a, a + 1, a + 2, a + 1, a + 3, a + 2]
+            })
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32(indices)));
+    }
+}
+
+/// Despawns ribbons whose entity is gone or has a new trail, such as a pooled entity reused.
+fn despawn_orphaned_ribbons(
+    mut commands: Commands,
+    trails: Query<&Trail>,
+    ribbons: Query<(Entity, &TrailRibbon)>,
+) {
+    for (ribbon, owner) in &ribbons {
+        let owned = trails
+            .get(owner.owner)
+            .is_ok_and(|trail| trail.ribbon == Some(ribbon));
+        if !owned {
+            commands.entity(ribbon).despawn_recursive();
+        }
+    }
+}
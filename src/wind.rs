@@ -0,0 +1,87 @@
+//! Wind: a global [`Wind`] velocity with gusts, pushing on bodies that have a [`WindDrag`].
+//! `wind <x> <y> <z> [gustiness]` in the console changes it.
+//!
+//! Each body is pulled towards the wind's velocity, in proportion to how fast it moves relative to
+//! the air. Gusts vary the strength over time and from place to place, so neighboring bodies don't
+//! all move in step.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::console::{parse_arg, ConsoleAppExt};
+
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>()
+            .add_console_command(
+                "wind",
+                "wind <x> <y> <z> [gustiness] - set the wind velocity, in m/s, and how gusty it is",
+                wind_command,
+            )
+            .add_systems(Update, apply_wind);
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Wind {
+    /// Velocity of the air, in meters per second.
+    pub velocity: Vec3,
+    /// How much gusts vary the wind's strength, from 0 for a steady wind to 1 for gusts that drop
+    /// it to nothing and double it.
+    pub gustiness: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            velocity: Vec3::new(3.0, 0.0, 1.0),
+            gustiness: 0.5,
+        }
+    }
+}
+
+impl Wind {
+    /// The wind's velocity at `position` and `time` seconds, gusts included.
+    pub fn velocity_at(&self, position: Vec3, time: f32) -> Vec3 {
+        // Two waves travelling along the wind, out of step, as a cheap stand-in for noise.
+        let along = position.dot(self.velocity.normalize_or_zero());
+        let gust = ((time * 1.3 - along * 0.4).sin() + (time * 3.1 - along * 0.9).sin()) / 2.0;
+        self.velocity * (1.0 + gust * self.gustiness.clamp(0.0, 1.0))
+    }
+}
+
+/// Makes the wind push the body: the force is the drag times the wind's velocity relative to the
+/// body, in newtons per meter per second.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WindDrag(pub f32);
+
+fn apply_wind(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut bodies: Query<(&WindDrag, &GlobalTransform, &Velocity, &mut ExternalForce)>,
+) {
+    let seconds = time.elapsed_seconds();
+    for (drag, transform, velocity, mut force) in &mut bodies {
+        let relative = wind.velocity_at(transform.translation(), seconds) - velocity.linvel;
+        force.force = relative * drag.0;
+    }
+}
+
+fn wind_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let velocity = Vec3::new(
+        parse_arg(args, 0, "x")?,
+        parse_arg(args, 1, "y")?,
+        parse_arg(args, 2, "z")?,
+    );
+    let mut wind = world.resource_mut::<Wind>();
+    wind.velocity = velocity;
+    if args.len() > 3 {
+        wind.gustiness = parse_arg(args, 3, "gustiness")?;
+    }
+    Ok(format!(
+        "wind set to {velocity} m/s, gustiness {}",
+        wind.gustiness
+    ))
+}
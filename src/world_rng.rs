@@ -0,0 +1,124 @@
+//! [`WorldRng`]: the random numbers that decide what a scene holds, like where spawners drop their
+//! props, how big those are and how enemies differ, seeded from the [`LaunchOptions`] so that
+//! running again with the same `--seed` fills the scene the same way.
+//!
+//! Only what changes the scene draws from it. Effects like particles keep their own numbers, so
+//! how many of them a frame rate or a setting lets through doesn't shift everything after.
+//!
+//! `seed` in the console shows the seed, and `seed <n>` starts the numbers over from another one.
+
+use bevy::prelude::*;
+
+use crate::console::{parse_arg, ConsoleAppExt};
+use crate::launch_options::LaunchOptions;
+
+pub struct WorldRngPlugin;
+
+impl Plugin for WorldRngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldRng>().add_console_command(
+            "seed",
+            "seed [n] - show the world's random seed, or start over from another",
+            seed_command,
+        );
+    }
+}
+
+/// Random numbers for filling the scene, the same sequence for the same seed.
+#[derive(Resource, Debug)]
+pub struct WorldRng {
+    seed: u64,
+    state: u64,
+}
+
+impl FromWorld for WorldRng {
+    fn from_world(world: &mut World) -> Self {
+        let seed = world
+            .get_resource::<LaunchOptions>()
+            .map_or(0, |options| options.seed);
+        Self::new(seed)
+    }
+}
+
+impl WorldRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            // Xorshift never leaves zero.
+            state: splitmix64(seed).max(1),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Between 0 and 1.
+    pub fn next_f32(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Between `min` and `max`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// A point in the box of these half extents around the origin.
+    pub fn in_box(&mut self, half_extents: Vec3) -> Vec3 {
+        Vec3::new(
+            self.range(-1.0, 1.0),
+            self.range(-1.0, 1.0),
+            self.range(-1.0, 1.0),
+        ) * half_extents
+    }
+}
+
+/// Scrambles `seed` into a xorshift state, so nearby seeds start far apart and the first numbers
+/// aren't close to zero.
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn seed_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if args.is_empty() {
+        return Ok(format!("seed {}", world.resource::<WorldRng>().seed()));
+    }
+    let seed = parse_arg::<u64>(args, 0, "seed")?;
+    world.insert_resource(WorldRng::new(seed));
+    Ok(format!("starting over from seed {seed}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(seed: u64) -> Vec<f32> {
+        let mut rng = WorldRng::new(seed);
+        (0..8).map(|_| rng.next_f32()).collect()
+    }
+
+    #[test]
+    fn same_seed_repeats() {
+        assert_eq!(draws(42), draws(42));
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        assert_ne!(draws(2), draws(3));
+        assert_ne!(draws(0), draws(1));
+    }
+
+    #[test]
+    fn small_seeds_start_away_from_zero() {
+        for seed in 0..4 {
+            assert!(draws(seed)[0] > 0.001);
+            assert_ne!(WorldRng::new(seed).state, 0);
+        }
+    }
+}